@@ -0,0 +1,12 @@
+// Generates the server of proto/solver.proto for the grpc feature
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().unwrap();
+        std::env::set_var("PROTOC", protoc);
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_protos(&["../../proto/solver.proto"], &["../../proto"])
+            .unwrap();
+    }
+}
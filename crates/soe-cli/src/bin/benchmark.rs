@@ -0,0 +1,508 @@
+#![warn(clippy::pedantic)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::convert::TryFrom;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::{crate_authors, crate_version, App, Arg, ArgMatches, SubCommand};
+
+use sigils_of_elohim_solver::puzzles::{self, PuzzleRecord};
+use sigils_of_elohim_solver::{
+    solve_first_canonical, solve_one, EngineRegistry, LabelOrder, Outcome, Position, PuzzleSpec,
+    SearchStats, SolveCache, SolveOptions,
+};
+use soe_cli::history::{self, Metric, Record, Run};
+use soe_cli::{exit_with, get_matches, json_errors, json_errors_arg, CliError};
+
+// Counts the heap allocations of the program, for --check-allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// The allocations a solve may make: the vector of squares of the solution.
+// The search itself must not allocate.
+const SOLVE_ALLOCATIONS: usize = 1;
+
+fn main() {
+    let matches = get_matches(app());
+
+    let quiet = matches.is_present("quiet");
+    let pretty = matches.is_present("pretty");
+
+    let records = match matches.value_of("file") {
+        Some(path) => puzzles::load(path).unwrap_or_else(|err| exit_with(err, 1)),
+        None => puzzles::all().iter().map(PuzzleRecord::from).collect(),
+    };
+
+    if matches.is_present("check-allocations") {
+        check_allocations(&records);
+    }
+
+    let history = matches.subcommand_matches("history");
+    if let Some(name) = matches.value_of("engine") {
+        if history.is_some() {
+            exit_with(
+                CliError::new("usage", "--engine can't be used with history"),
+                1,
+            );
+        }
+        run_engines(&records, name, quiet);
+        return;
+    }
+    if matches.is_present("cache") {
+        if history.is_some() {
+            exit_with(
+                CliError::new("usage", "--cache can't be used with history"),
+                1,
+            );
+        }
+        run_cache(&records, quiet);
+        return;
+    }
+    if history.is_some_and(|history| history.is_present("no-run")) {
+        chart_history(history.unwrap(), quiet);
+        return;
+    }
+    // The chart takes the place of the solutions
+    let results = run(&records, quiet || history.is_some(), pretty);
+    if let Some(history) = history {
+        append_history(history, results);
+        chart_history(history, quiet);
+    }
+}
+
+fn app() -> App<'static, 'static> {
+    App::new("Sigils of Elohim Solver - Benchmark")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Benchmark tool for the Sigils of Elohim Solver utility.")
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("No output printed to stdout"),
+        )
+        .arg(
+            Arg::with_name("pretty")
+                .long("pretty")
+                .help("Print the solution with box drawing characters")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("file")
+                .long("file")
+                .value_name("PATH")
+                .help("Solve the puzzles in a puzzle file instead of the puzzles in the game")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("check-allocations")
+                .long("check-allocations")
+                .help("Check that the search makes no heap allocations")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("engine")
+                .long("engine")
+                .value_name("NAME")
+                .help(
+                    "Solve the puzzles with a solver engine, or with each of them for 'all', \
+                     and check the solutions are valid instead of comparing them with the \
+                     known ones",
+                )
+                .possible_values(&["all", "backtrack", "dlx", "smallboards"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("cache")
+                .long("cache")
+                .help(
+                    "Time each puzzle solved without the solve cache, solved through an \
+                     empty cache and looked up in the cache, instead of comparing the \
+                     solutions with the known ones",
+                )
+                .conflicts_with("engine")
+                .takes_value(false),
+        )
+        .arg(json_errors_arg())
+        .subcommand(
+            SubCommand::with_name("history")
+                .about(
+                    "Runs the benchmark, appends the results to a history file and prints \
+                     a chart of each puzzle across the runs in it",
+                )
+                .arg(
+                    Arg::with_name("history")
+                        .help("The history file")
+                        .default_value("benchmark-history.txt")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .value_name("LABEL")
+                        .help(
+                            "The label of the run in the chart [default: the output of \
+                             'git describe --always --dirty']",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("metric")
+                        .long("metric")
+                        .value_name("METRIC")
+                        .help("What to chart")
+                        .possible_values(&["microseconds", "nodes"])
+                        .default_value("microseconds"),
+                )
+                .arg(
+                    Arg::with_name("svg")
+                        .long("svg")
+                        .value_name("PATH")
+                        .help("Also write the chart as an SVG image")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("no-run")
+                        .long("no-run")
+                        .help("Chart the runs in the history file without running")
+                        .conflicts_with("label")
+                        .takes_value(false),
+                ),
+        )
+}
+
+// Solve the puzzles, checking the solutions against the known ones, and
+// return the search statistics and time of each
+fn run(records: &[PuzzleRecord], quiet: bool, pretty: bool) -> Vec<Record> {
+    let mut results = vec![];
+    let mut total_nodes = 0;
+    let mut total_backtracks = 0;
+    for puzzle in records {
+        let start = Instant::now();
+        let result = puzzle
+            .search()
+            .run()
+            .unwrap_or_else(|err| exit_with(err, 1));
+        results.push(Record {
+            puzzle: puzzle.to_string(),
+            nodes: result.stats.nodes,
+            backtracks: result.stats.backtracks,
+            microseconds: u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX),
+        });
+        total_nodes += result.stats.nodes;
+        total_backtracks += result.stats.backtracks;
+        let Some(solution) = result.solutions.into_iter().next() else {
+            exit_with(
+                CliError::new("no-solution", format!("{puzzle} has no solution")),
+                1,
+            );
+        };
+
+        // Puzzles without a known solution only check that there is one
+        let expected = puzzle.solutions.first();
+        let is_correct = expected.is_none_or(|expected| *expected == solution);
+        if !quiet {
+            print_outcome(&mut io::stdout(), puzzle, &result.stats, &solution, pretty)
+                .unwrap_or_else(|err| exit_with(err, 1));
+        }
+
+        if !is_correct {
+            // The full solutions don't fit in a JSON error
+            if !json_errors() {
+                if quiet {
+                    print_outcome(&mut io::stderr(), puzzle, &result.stats, &solution, pretty)
+                        .unwrap_or_else(|err| exit_with(err, 1));
+                }
+                // Labelled by position so that only the tilings differ
+                eprintln!("{:?}", solution.relabel(LabelOrder::ByPosition).to_string());
+                eprintln!();
+                eprintln!("Expected solution:");
+                eprintln!("{}", expected.unwrap().relabel(LabelOrder::ByPosition));
+            }
+            exit_with(
+                CliError::new(
+                    "wrong-solution",
+                    format!("The solution of {puzzle} is incorrect."),
+                ),
+                1,
+            );
+        }
+    }
+
+    if !quiet {
+        println!(
+            "{} puzzles. Nodes: {total_nodes}, backtracks: {total_backtracks}",
+            records.len()
+        );
+    }
+    results
+}
+
+// Solve the puzzles with the engine `name`, or with every engine for "all",
+// checking that each solution is a tiling of the puzzle. Puzzles with blocked
+// squares and puzzles an engine doesn't support are skipped.
+fn run_engines(records: &[PuzzleRecord], name: &str, quiet: bool) {
+    let registry = EngineRegistry::default();
+    for engine in registry
+        .engines()
+        .filter(|engine| name == "all" || engine.name() == name)
+    {
+        let start = Instant::now();
+        let mut solved = 0;
+        for puzzle in records {
+            let spec = PuzzleSpec::new(
+                puzzle.board.row_count(),
+                puzzle.board.column_count(),
+                puzzle.pieces(),
+            );
+            if !puzzle.board.blocked_squares().is_empty() || !engine.supports(&spec) {
+                continue;
+            }
+            let puzzle_start = Instant::now();
+            let outcome = engine
+                .solve(&spec, &SolveOptions::default())
+                .unwrap_or_else(|err| exit_with(err, 1));
+            let Outcome::Solved(solution) = outcome else {
+                exit_with(
+                    CliError::new(
+                        "no-solution",
+                        format!("{} found no solution of {puzzle}", engine.name()),
+                    ),
+                    1,
+                );
+            };
+            if let Err(err) = solution.verify(spec.row_count, spec.column_count, &spec.pieces) {
+                exit_with(
+                    CliError::new(
+                        "wrong-solution",
+                        format!(
+                            "The solution of {puzzle} found by {} is incorrect. {err}",
+                            engine.name()
+                        ),
+                    ),
+                    1,
+                );
+            }
+            solved += 1;
+            if !quiet {
+                println!(
+                    "{}: {puzzle} in {} us",
+                    engine.name(),
+                    puzzle_start.elapsed().as_micros()
+                );
+            }
+        }
+        if !quiet {
+            println!(
+                "{}: {solved} puzzles in {} ms",
+                engine.name(),
+                start.elapsed().as_millis()
+            );
+        }
+    }
+}
+
+// Time each puzzle without the cache, through an empty cache, which solves it
+// and writes the entry, and through the cache with the entry, which only looks
+// it up. Puzzles with blocked squares aren't cached and are skipped.
+fn run_cache(records: &[PuzzleRecord], quiet: bool) {
+    let path = std::env::temp_dir().join(format!("soe_benchmark_cache_{}", std::process::id()));
+    let (mut total_solve, mut total_cold, mut total_warm) =
+        (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    let mut count = 0;
+    for puzzle in records {
+        if !puzzle.board.blocked_squares().is_empty() {
+            continue;
+        }
+        let (row_count, column_count) = (puzzle.board.row_count(), puzzle.board.column_count());
+
+        let start = Instant::now();
+        let expected = solve_one(row_count, column_count, puzzle.pieces())
+            .unwrap_or_else(|err| exit_with(err, 1));
+        let solve = start.elapsed();
+
+        // Each puzzle gets a cache of its own, so that puzzles with the same
+        // canonical form don't hit the entries of each other
+        if path.exists() {
+            fs::remove_file(&path).unwrap_or_else(|err| exit_with(err, 1));
+        }
+        let mut cache = SolveCache::open(&path).unwrap_or_else(|err| exit_with(err, 1));
+        let mut timed_solve = || {
+            let start = Instant::now();
+            let solution = cache
+                .solve_one(row_count, column_count, puzzle.pieces())
+                .unwrap_or_else(|err| exit_with(err, 1));
+            if solution != expected {
+                exit_with(
+                    CliError::new(
+                        "wrong-solution",
+                        format!("The cached solution of {puzzle} is incorrect."),
+                    ),
+                    1,
+                );
+            }
+            start.elapsed()
+        };
+        let cold = timed_solve();
+        let warm = timed_solve();
+
+        if !quiet {
+            println!(
+                "{puzzle}: solve {} us, cold cache {} us, warm cache {} us",
+                solve.as_micros(),
+                cold.as_micros(),
+                warm.as_micros()
+            );
+        }
+        total_solve += solve;
+        total_cold += cold;
+        total_warm += warm;
+        count += 1;
+    }
+    if path.exists() {
+        fs::remove_file(&path).unwrap_or_else(|err| exit_with(err, 1));
+    }
+
+    if !quiet {
+        println!(
+            "{count} puzzles. Solve: {} us, cold cache: {} us ({} us), warm cache: {} us",
+            total_solve.as_micros(),
+            total_cold.as_micros(),
+            // The overhead of the cache on a miss
+            match total_cold.checked_sub(total_solve) {
+                Some(overhead) => format!("+{}", overhead.as_micros()),
+                None => format!("-{}", total_solve.saturating_sub(total_cold).as_micros()),
+            },
+            total_warm.as_micros()
+        );
+    }
+}
+
+// Append a run with `results` to the history file
+fn append_history(matches: &ArgMatches, results: Vec<Record>) {
+    let path = matches.value_of("history").unwrap();
+    let label = matches
+        .value_of("label")
+        .map_or_else(describe_commit, str::to_string);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let run = Run {
+        timestamp,
+        label,
+        records: results,
+    };
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(run.to_lines().as_bytes()))
+        .unwrap_or_else(|err| exit_with(err, 1));
+}
+
+// The commit checked out, or "unknown" outside a git repository
+fn describe_commit() -> String {
+    Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map_or_else(|| "unknown".to_string(), |label| label.trim().to_string())
+}
+
+// Print the chart of the runs in the history file and write it as SVG if asked
+fn chart_history(matches: &ArgMatches, quiet: bool) {
+    let path = matches.value_of("history").unwrap();
+    let text = fs::read_to_string(path).unwrap_or_else(|err| exit_with(err, 1));
+    let runs = history::parse(&text).unwrap_or_else(|err| exit_with(err, 1));
+    let metric = match matches.value_of("metric").unwrap() {
+        "nodes" => Metric::Nodes,
+        _ => Metric::Microseconds,
+    };
+    if !quiet {
+        print!("{}", history::ascii_chart(&runs, metric));
+        if let (Some(first), Some(last)) = (runs.first(), runs.last()) {
+            println!("From {} to {}", first.label, last.label);
+        }
+    }
+    if let Some(svg_path) = matches.value_of("svg") {
+        fs::write(svg_path, history::svg_chart(&runs, metric))
+            .unwrap_or_else(|err| exit_with(err, 1));
+    }
+}
+
+// Solve each puzzle on a rectangular board and exit with an error if the
+// solve allocates more than the solution. Puzzles with blocked squares are
+// skipped. The first solve is repeated so that the tables initialized on
+// first use aren't counted.
+fn check_allocations(records: &[PuzzleRecord]) {
+    for (index, puzzle) in records.iter().enumerate() {
+        if !puzzle.board.blocked_squares().is_empty() {
+            continue;
+        }
+        let (row_count, column_count) = (puzzle.board.row_count(), puzzle.board.column_count());
+        let runs = if index == 0 { 2 } else { 1 };
+        for run in 0..runs {
+            let pieces = puzzle.pieces();
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            let solution = solve_first_canonical(row_count, column_count, pieces);
+            let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+            drop(solution);
+            if run == runs - 1 && allocations > SOLVE_ALLOCATIONS {
+                let message = format!(
+                    "The solve of {puzzle} made {allocations} heap allocations. At most \
+                     {SOLVE_ALLOCATIONS} are expected."
+                );
+                exit_with(CliError::new("allocations", message), 1);
+            }
+        }
+    }
+}
+
+fn print_outcome<T: Write>(
+    write: &mut T,
+    puzzle: &PuzzleRecord,
+    stats: &SearchStats,
+    solution: &Position,
+    pretty: bool,
+) -> Result<(), std::io::Error> {
+    writeln!(write, "{puzzle}")?;
+    // The search statistics don't depend on the speed of the machine
+    writeln!(
+        write,
+        "Nodes: {}, backtracks: {}, effective branching factor: {:.3}",
+        stats.nodes,
+        stats.backtracks,
+        stats.effective_branching_factor()
+    )?;
+    if pretty {
+        writeln!(write, "{solution:#}")
+    } else {
+        writeln!(write, "{solution}")
+    }
+}
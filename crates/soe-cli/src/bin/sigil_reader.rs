@@ -0,0 +1,451 @@
+#![warn(clippy::all)]
+#![warn(clippy::pedantic)]
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use clap::{crate_authors, crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
+use image::RgbImage;
+
+use sigils_of_elohim_solver::puzzles::{self, PuzzleRecord};
+use sigils_of_elohim_solver::vision::{self, ReadError, Reading};
+use sigils_of_elohim_solver::{
+    solve_batch, solve_first_canonical, BoardShape, PieceCollection, PuzzleSpec,
+};
+use soe_cli::{exit_with, get_matches, json_errors, json_errors_arg, report, CliError};
+
+// The exit code when the image isn't a screenshot of a level, as opposed to 1
+// when it can't be opened
+const NO_PUZZLE_EXIT_CODE: i32 = 2;
+
+fn main() {
+    let matches = get_matches(app());
+
+    match matches.subcommand() {
+        ("classify-sprite", Some(matches)) => {
+            classify_sprite(matches);
+            return;
+        }
+        ("db", Some(matches)) => {
+            if let ("ingest", Some(matches)) = matches.subcommand() {
+                ingest(matches);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let path = matches.value_of_os("path").unwrap();
+    let img = vision::open(path).unwrap_or_else(|err| exit_with_read_error(err));
+    if matches.is_present("all") {
+        print_all(&img);
+        return;
+    }
+
+    let mut reading = read(&img, matches.value_of_os("debug"));
+    if matches.is_present("candidates") {
+        for (pieces, solvable) in solve_candidates(&reading) {
+            let outcome = if solvable { "solvable" } else { "no solution" };
+            println!("\"{pieces}\", {outcome}");
+        }
+        return;
+    }
+
+    settle_candidates(&mut reading);
+
+    // A section has fewer levels of some colors than others
+    if let Some(section) = matches.value_of("section") {
+        let level_count = puzzles::section(&format!("{section} {}", reading.color)).len();
+        if level_count > 0
+            && reading
+                .number
+                .is_some_and(|number| number as usize > level_count)
+        {
+            reading.number = None;
+        }
+    }
+
+    match matches.value_of("format") {
+        Some("json") => println!("{}", reading.to_json()),
+        Some("args") => println!(
+            "{} {} \"{}\"",
+            reading.row_count, reading.column_count, reading.pieces
+        ),
+        Some("rust") => {
+            let section = matches.value_of("section").unwrap();
+            let Some(number) = reading.number else {
+                exit_with(
+                    CliError::new(
+                        "unknown-level-number",
+                        format!(
+                            "The level number can't be read from the {} progress dots.",
+                            reading.progress_dot_count
+                        ),
+                    ),
+                    1,
+                );
+            };
+            let solution = if matches.is_present("solve") {
+                solution(&reading)
+            } else {
+                String::new()
+            };
+            println!(
+                "Puzzle::new({:?}, {:?}, {}, {}, {}, {:?}, {:?}),",
+                section,
+                reading.color,
+                number,
+                reading.row_count,
+                reading.column_count,
+                reading.pieces,
+                solution
+            );
+        }
+        // An unknown level number is written as '?'
+        _ => println!(
+            "\"{}\", {}, {}, {}, \"{}\"",
+            reading.color,
+            reading
+                .number
+                .map_or_else(|| "?".to_string(), |number| number.to_string()),
+            reading.row_count,
+            reading.column_count,
+            reading.pieces
+        ),
+    }
+}
+
+// Print the position and the puzzle of every board in the image, one per line
+fn print_all(img: &RgbImage) {
+    match vision::read_all(img) {
+        Ok(readings) => {
+            for reading in readings {
+                let board = reading.board;
+                println!(
+                    "{}, {}, {}, {}, \"{}\", {}, {}, \"{}\"",
+                    board.x,
+                    board.y,
+                    board.width,
+                    board.height,
+                    reading.color,
+                    reading.row_count,
+                    reading.column_count,
+                    reading.pieces
+                );
+            }
+        }
+        Err(err) => exit_with_read_error(err),
+    }
+}
+
+// The arguments and subcommands, one after the other
+#[allow(clippy::too_many_lines)]
+fn app() -> App<'static, 'static> {
+    App::new("Sigils of Elohim Solver Reader")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Outputs the puzzle data from screenshots of the video game 'Sigils of Elohim'")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(
+            Arg::with_name("path")
+                .help("Path to the screenshot")
+                .required(true),
+        )
+        .arg(Arg::with_name("all").long("all").help(
+            "Reads every board of a screenshot with several, like the level select screen. \
+         Prints the position and size of each board on the screen before the puzzle data.",
+        ))
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["text", "json", "rust", "args"])
+                .default_value("text")
+                .conflicts_with("all")
+                .help(
+                    "The output format. 'json' writes the level with the position of the \
+                 board and each of its squares on the screen, 'rust' writes a \
+                 `Puzzle::new` line for the list of puzzles in \
+                 crates/soe-core/src/puzzles.rs and 'args' writes the rows, columns and \
+                 pieces as arguments of soe_solver",
+                ),
+        )
+        .arg(
+            Arg::with_name("section")
+                .long("section")
+                .value_name("SECTION")
+                .required_if("format", "rust")
+                .help("The section of the level for --format rust, e.g. 'A'"),
+        )
+        .arg(
+            Arg::with_name("solve")
+                .long("solve")
+                .requires("section")
+                .help("Fills in the solution of the level for --format rust"),
+        )
+        .arg(
+            Arg::with_name("candidates")
+                .long("candidates")
+                .conflicts_with_all(&["all", "format"])
+                .help(
+                    "Prints every piece list the pieces may be, most likely first, and \
+                     whether the level has a solution with them",
+                ),
+        )
+        .arg(
+            Arg::with_name("debug")
+                .long("debug")
+                .value_name("DIR")
+                .conflicts_with("all")
+                .help(
+                    "Writes the images the reader looked at, with the objects it found \
+                     outlined, and a log of its decisions to DIR, for bug reports",
+                ),
+        )
+        .arg(json_errors_arg())
+        .subcommand(
+            SubCommand::with_name("classify-sprite")
+                .about(
+                    "Prints the kind, orientation and confidence of the piece in an image \
+                     cropped to a single sprite",
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the image of the sprite")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("The output format"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("db")
+                .about("Manages a database of levels read from screenshots")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("ingest")
+                        .about(
+                            "Reads every screenshot in a folder and adds the levels that \
+                             aren't in the database yet, each with a solution and the \
+                             screenshot it was read from",
+                        )
+                        .arg(
+                            Arg::with_name("dir")
+                                .help("The folder of screenshots")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("out")
+                                .long("out")
+                                .value_name("FILE")
+                                .required(true)
+                                .help(
+                                    "The database, a puzzle file or a JSON export if FILE \
+                                     ends in .json. It's created if it doesn't exist.",
+                                ),
+                        ),
+                ),
+        )
+}
+
+// Sprites that could be more than one shape are settled by which of the piece
+// lists they may be give a level with a solution
+fn settle_candidates(reading: &mut Reading) {
+    if reading.candidates.len() > 1 {
+        let solvable = solve_candidates(reading)
+            .into_iter()
+            .find(|&(_, solvable)| solvable);
+        if let Some((pieces, _)) = solvable {
+            reading.pieces = pieces;
+        }
+    }
+}
+
+// Read the screenshots in <dir> in the order of their names and append the
+// levels with a solution that aren't in the database at <out> yet. Levels are
+// the same when their fingerprints are. Screenshots that can't be read and
+// levels without a solution are reported and skipped.
+fn ingest(matches: &ArgMatches) {
+    let dir = Path::new(matches.value_of_os("dir").unwrap());
+    let out = Path::new(matches.value_of_os("out").unwrap());
+    let mut records = if out.exists() {
+        puzzles::load(out).unwrap_or_else(|err| exit_with(err, 1))
+    } else {
+        vec![]
+    };
+    let mut fingerprints: HashSet<u64> = records
+        .iter()
+        .filter_map(PuzzleRecord::fingerprint)
+        .collect();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect()
+        })
+        .unwrap_or_else(|err| exit_with(err, 1));
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let (mut added, mut duplicates, mut skipped) = (0, 0, 0);
+    for path in paths {
+        let mut reading = match vision::open(&path).and_then(|img| vision::read(&img)) {
+            Ok(reading) => reading,
+            Err(err) => {
+                report(CliError::new(
+                    "unreadable-screenshot",
+                    format!("{}: {err}", path.display()),
+                ));
+                skipped += 1;
+                continue;
+            }
+        };
+        settle_candidates(&mut reading);
+        let mut record = PuzzleRecord {
+            name: None,
+            section: None,
+            color: Some(reading.color.to_string()),
+            number: reading.number,
+            source: Some(path.display().to_string()),
+            board: BoardShape::rectangle(reading.row_count, reading.column_count),
+            // The reader only writes piece letters
+            pieces: reading.pieces.parse::<PieceCollection>().unwrap(),
+            solutions: vec![],
+        };
+        let fingerprint = record.fingerprint().unwrap();
+        if fingerprints.contains(&fingerprint) {
+            duplicates += 1;
+            continue;
+        }
+        match record.solve_first_canonical() {
+            Ok(Some(solution)) => record.solutions.push(solution),
+            Ok(None) => {
+                report(CliError::new(
+                    "no-solution",
+                    format!("{}: The level has no solution.", path.display()),
+                ));
+                skipped += 1;
+                continue;
+            }
+            Err(err) => {
+                report(CliError::new(
+                    "invalid-puzzle",
+                    format!("{}: {err}", path.display()),
+                ));
+                skipped += 1;
+                continue;
+            }
+        }
+        fingerprints.insert(fingerprint);
+        records.push(record);
+        added += 1;
+    }
+
+    puzzles::save(out, &records).unwrap_or_else(|err| exit_with(err, 1));
+    println!("{added} added, {duplicates} already in the database, {skipped} skipped");
+}
+
+// Classify the sprite in the image at <path>. The text output is the kind,
+// the orientation, the color and the confidence, followed by a line for each
+// alternative shape with how much worse it matches.
+fn classify_sprite(matches: &ArgMatches) {
+    let path = matches.value_of_os("path").unwrap();
+    let img = vision::open(path).unwrap_or_else(|err| exit_with(err, 1));
+    let class = vision::classify_sprite(&img).unwrap_or_else(|err| exit_with(err, 1));
+    if matches.value_of("format") == Some("json") {
+        println!("{}", class.to_json());
+        return;
+    }
+    println!(
+        "\"{:?}\", {}, \"{}\", {:.2}",
+        class.fixed_piece.piece(),
+        class.orientation(),
+        class.color,
+        class.confidence
+    );
+    for (fixed_piece, extra) in &class.alternatives {
+        println!("or {fixed_piece}, {extra:+.2}");
+    }
+}
+
+// Read the level, writing what the reader saw to `debug_dir` if given
+fn read(img: &RgbImage, debug_dir: Option<&OsStr>) -> Reading {
+    let reading = match debug_dir {
+        Some(dir) => {
+            let (reading, debug) = vision::read_debug(img);
+            debug.write(dir).unwrap_or_else(|err| {
+                exit_with(
+                    CliError::new(
+                        "io",
+                        format!(
+                            "Failed to write the debug output to {}. {err}",
+                            dir.to_string_lossy()
+                        ),
+                    ),
+                    1,
+                )
+            });
+            reading
+        }
+        None => vision::read(img),
+    };
+    reading.unwrap_or_else(|err| exit_with_read_error(err))
+}
+
+// Solve the level with each candidate piece list of `reading`, in order.
+// Lists that don't fit the board have no solution.
+fn solve_candidates(reading: &Reading) -> Vec<(String, bool)> {
+    let specs: Vec<_> = reading
+        .candidates
+        .iter()
+        .map(|pieces| {
+            // The reader only writes piece letters
+            let pieces = pieces.parse().unwrap();
+            let (row_count, column_count) = reading.dimensions();
+            PuzzleSpec::from_dimensions(row_count, column_count, pieces)
+        })
+        .collect();
+    let results = solve_batch(&specs);
+    reading
+        .candidates
+        .iter()
+        .cloned()
+        .zip(results)
+        .map(|(pieces, result)| (pieces, matches!(result, Ok(Some(_)))))
+        .collect()
+}
+
+// The first solution in canonical order, labelled as in the list of puzzles
+fn solution(reading: &Reading) -> String {
+    let pieces = reading
+        .pieces
+        .parse()
+        .unwrap_or_else(|err| exit_with(CliError::from(err), 1));
+    match solve_first_canonical(reading.row_count, reading.column_count, pieces) {
+        Ok(Some(solution)) => solution.to_string(),
+        Ok(None) => exit_with(
+            CliError::new("no-solution", "The level has no solution."),
+            1,
+        ),
+        Err(err) => exit_with(err, 1),
+    }
+}
+
+fn exit_with_read_error(err: ReadError) -> ! {
+    if let ReadError::NoBoard | ReadError::NotAPuzzle { .. } = err {
+        if !json_errors() {
+            eprintln!("No Sigils of Elohim puzzle detected. {err}");
+            exit(NO_PUZZLE_EXIT_CODE);
+        }
+        exit_with(err, NO_PUZZLE_EXIT_CODE);
+    }
+    exit_with(err, 1);
+}
@@ -0,0 +1,73 @@
+#![warn(clippy::pedantic)]
+
+// The solver service of proto/solver.proto over gRPC, for clients that can't
+// link the crate, such as the game overlay. See `soe_cli::grpc`. Built with
+// the grpc feature:
+//
+//     cargo run -p soe-cli --features soe-cli/grpc --bin soe_grpc -- --address 0.0.0.0:50051
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use clap::{crate_authors, crate_version, App, Arg};
+use tonic::transport::Server;
+
+use sigils_of_elohim_solver::QueueOptions;
+use soe_cli::grpc::SolverService;
+use soe_cli::{exit_with, get_matches, json_errors_arg};
+
+// The most requests a connection may have in flight at once
+const REQUESTS_PER_CONNECTION: usize = 8;
+
+// A request that hasn't been answered after this long fails. Solves are
+// abandoned well before, after the timeout of the queue.
+const REQUEST_TIMEOUT: Duration = Duration::from_mins(1);
+
+#[tokio::main]
+async fn main() {
+    let matches = get_matches(
+        App::new("Sigils of Elohim Solver - gRPC Server")
+            .version(crate_version!())
+            .author(crate_authors!())
+            .about("Serves the solver over gRPC")
+            .arg(
+                Arg::with_name("address")
+                    .long("address")
+                    .value_name("ADDRESS")
+                    .default_value("127.0.0.1:50051")
+                    .help("The address to listen on"),
+            )
+            .arg(
+                Arg::with_name("workers")
+                    .long("workers")
+                    .value_name("N")
+                    .default_value("2")
+                    .help("The number of searches to run at once"),
+            )
+            .arg(json_errors_arg()),
+    );
+
+    let address: SocketAddr = matches
+        .value_of("address")
+        .unwrap()
+        .parse()
+        .unwrap_or_else(|err| exit_with(format!("Invalid address. {err}"), 1));
+    let workers = matches
+        .value_of("workers")
+        .unwrap()
+        .parse()
+        .ok()
+        .filter(|&workers: &usize| workers > 0)
+        .unwrap_or_else(|| exit_with("value of --workers must be a positive integer", 1));
+    let service = SolverService::new(QueueOptions {
+        workers,
+        ..QueueOptions::default()
+    });
+    Server::builder()
+        .concurrency_limit_per_connection(REQUESTS_PER_CONNECTION)
+        .timeout(REQUEST_TIMEOUT)
+        .add_service(service.into_server())
+        .serve(address)
+        .await
+        .unwrap_or_else(|err| exit_with(err.to_string(), 1));
+}
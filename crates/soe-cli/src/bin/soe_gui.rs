@@ -0,0 +1,591 @@
+#![warn(clippy::pedantic)]
+
+// A window for solving puzzles without the terminal. Set the size of the
+// board, click squares to block them and set how many of each piece there
+// are. In Solve mode, press Solve and the solution is drawn in the colors of
+// the pieces and can be stepped through one placement at a time. In Play
+// mode, drag the pieces onto the board yourself, with undo, and let the
+// solver complete the board from the pieces placed so far. Built with the gui
+// feature:
+//
+//     cargo run -p soe-cli --features soe-cli/gui --bin soe_gui
+
+use std::collections::BTreeSet;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use eframe::egui::{
+    self, Color32, DragValue, Id, Painter, Pos2, Rect, Response, Sense, Slider, Stroke, StrokeKind,
+    Ui, Vec2,
+};
+
+use sigils_of_elohim_solver::geometry::{Cell, Shape};
+use sigils_of_elohim_solver::{
+    BoardShape, FixedPiece, Game, Palette, Piece, PieceCollection, Placement, Position, Solutions,
+    Solve, SolveError,
+};
+
+// The letters of the pieces in `Piece` order
+const LETTERS: [char; Piece::count()] = ['I', 'O', 'T', 'J', 'L', 'S', 'Z'];
+
+// Searches are abandoned after this long
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// The size of a square of the board on the screen, in points
+const SQUARE_SIZE: f32 = 36.0;
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "Sigils of Elohim Solver",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Ok(Box::new(App::default()))),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Solve,
+    Play,
+}
+
+struct App {
+    mode: Mode,
+    row_count: u32,
+    column_count: u32,
+    // The blocked squares as (row, column). Squares off the board after it
+    // shrinks are kept in case it grows back, and ignored.
+    blocked: BTreeSet<(u32, u32)>,
+    // The number of each piece, indexed by `Piece`
+    counts: [u32; Piece::count()],
+    // The search running in the background, if any
+    search: Option<Receiver<Outcome>>,
+    solution: Option<Steps>,
+    // The game of Play mode, started when the mode is shown
+    game: Option<Game>,
+    // The way each piece is turned in Play mode, indexed by `Piece`
+    orientations: [FixedPiece; Piece::count()],
+    // What happened to the last search or move
+    message: String,
+}
+
+// The result of a search in the background
+enum Outcome {
+    Solved(Result<Solutions, SolveError>),
+    // The board of the game completed by the solver
+    Completed(Option<Position>),
+}
+
+// A solution being stepped through
+struct Steps {
+    placements: Vec<Placement>,
+    replay: String,
+    // The number of placements shown
+    shown: usize,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Solve,
+            row_count: 4,
+            column_count: 4,
+            blocked: BTreeSet::new(),
+            // LLZZ
+            counts: [0, 0, 0, 0, 2, 0, 2],
+            search: None,
+            solution: None,
+            game: None,
+            orientations: Piece::array().map(|piece| {
+                FixedPiece::array()
+                    .iter()
+                    .copied()
+                    .find(|fixed_piece| fixed_piece.piece() == piece)
+                    .unwrap()
+            }),
+            message: String::new(),
+        }
+    }
+}
+
+impl eframe::App for App {
+    fn ui(&mut self, ui: &mut Ui, _frame: &mut eframe::Frame) {
+        self.receive();
+        egui::Panel::left("puzzle").show(ui, |ui| self.puzzle_ui(ui));
+        egui::CentralPanel::default_margins().show(ui, |ui| match self.mode {
+            Mode::Solve => self.solve_ui(ui),
+            Mode::Play => self.play_ui(ui),
+        });
+    }
+}
+
+impl App {
+    fn puzzle_ui(&mut self, ui: &mut Ui) {
+        let mode = self.mode;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, Mode::Solve, "Solve");
+            ui.selectable_value(&mut self.mode, Mode::Play, "Play");
+        });
+        if mode != self.mode {
+            self.message.clear();
+        }
+
+        ui.separator();
+        ui.heading("Board");
+        let (row_count, column_count) = (self.row_count, self.column_count);
+        ui.horizontal(|ui| {
+            ui.label("Rows");
+            ui.add(DragValue::new(&mut self.row_count).range(1..=20));
+            ui.label("Columns");
+            ui.add(DragValue::new(&mut self.column_count).range(1..=20));
+        });
+        if (row_count, column_count) != (self.row_count, self.column_count) {
+            self.changed();
+        }
+        ui.label("In Solve mode, click squares to block them.");
+        if ui.button("Clear blocked squares").clicked() {
+            self.blocked.clear();
+            self.changed();
+        }
+        ui.label(format!("Mask: {}", self.shape().to_mask_str()));
+
+        ui.separator();
+        ui.heading("Pieces");
+        egui::Grid::new("pieces").show(ui, |ui| {
+            for piece in Piece::array() {
+                ui.label(LETTERS[piece as usize].to_string());
+                paint_piece(ui, self.orientations[piece as usize], SQUARE_SIZE / 3.0);
+                if ui
+                    .add(DragValue::new(&mut self.counts[piece as usize]).range(0..=20))
+                    .changed()
+                {
+                    self.changed();
+                }
+                ui.end_row();
+            }
+        });
+        let square_count =
+            (self.row_count * self.column_count) as usize - self.blocked_squares().len();
+        ui.label(format!(
+            "{} squares in pieces for {square_count} open squares",
+            4 * self.counts.iter().sum::<u32>()
+        ));
+
+        ui.separator();
+        let searching = self.search.is_some();
+        if self.mode == Mode::Solve {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!searching, egui::Button::new("Solve"))
+                    .clicked()
+                {
+                    self.solve(ui.ctx());
+                }
+                if searching {
+                    ui.spinner();
+                }
+            });
+        }
+        ui.label(&self.message);
+    }
+
+    fn solve_ui(&mut self, ui: &mut Ui) {
+        let shown = self
+            .solution
+            .as_ref()
+            .map_or(vec![], |steps| steps.placements[..steps.shown].to_vec());
+        self.board_ui(ui, &shown);
+
+        if let Some(steps) = &mut self.solution {
+            ui.add_space(8.0);
+            let count = steps.placements.len();
+            ui.horizontal(|ui| {
+                if ui.button("⏮").clicked() {
+                    steps.shown = 0;
+                }
+                if ui.button("◀").clicked() {
+                    steps.shown = steps.shown.saturating_sub(1);
+                }
+                ui.add(Slider::new(&mut steps.shown, 0..=count).text("placements"));
+                if ui.button("▶").clicked() {
+                    steps.shown = (steps.shown + 1).min(count);
+                }
+                if ui.button("⏭").clicked() {
+                    steps.shown = count;
+                }
+            });
+            if let Some(placement) = steps.shown.checked_sub(1).map(|i| steps.placements[i]) {
+                ui.label(format!(
+                    "{} at row {}, column {}",
+                    placement.piece, placement.row, placement.column
+                ));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Replay:");
+                ui.code(&steps.replay);
+            });
+        }
+    }
+
+    fn play_ui(&mut self, ui: &mut Ui) {
+        if self.game.is_none() {
+            match Game::with_board_shape(&self.shape(), self.pieces()) {
+                Ok(game) => self.game = Some(game),
+                Err(err) => {
+                    self.board_ui(ui, &[]);
+                    ui.label(err.to_string());
+                    return;
+                }
+            }
+        }
+        let searching = self.search.is_some();
+        let moves = self
+            .game
+            .as_ref()
+            .map_or(vec![], |game| game.moves().to_vec());
+        let (response, painter) = self.board_ui(ui, &moves);
+
+        // The piece being dragged is centred on the pointer, and is shown
+        // where it would be placed
+        let square = |piece| {
+            ui.ctx()
+                .pointer_latest_pos()
+                .and_then(|pos| anchor_at(response.rect, piece, pos))
+        };
+        if let Some(piece) = response.dnd_hover_payload::<FixedPiece>() {
+            if let Some((row, column)) = square(*piece) {
+                let placement = Placement {
+                    piece: *piece,
+                    row,
+                    column,
+                };
+                let fill = color(Palette::default().piece(piece.piece())).gamma_multiply(0.5);
+                paint_placement(
+                    &painter.with_clip_rect(response.rect),
+                    response.rect,
+                    placement,
+                    fill,
+                );
+            }
+        }
+        if let Some(piece) = response.dnd_release_payload::<FixedPiece>() {
+            if let Some((row, column)) = square(*piece) {
+                self.place(*piece, row, column);
+            }
+        }
+
+        let orientations = &mut self.orientations;
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        ui.add_space(8.0);
+        ui.label("Drag the pieces onto the board.");
+        ui.horizontal(|ui| {
+            for piece in Piece::array() {
+                let orientation = &mut orientations[piece as usize];
+                let count = game.remaining().count(piece);
+                ui.vertical(|ui| {
+                    if count > 0 && !searching {
+                        let id = Id::new(("piece", piece as usize));
+                        ui.dnd_drag_source(id, *orientation, |ui| {
+                            paint_piece(ui, *orientation, SQUARE_SIZE / 2.0);
+                        });
+                    } else {
+                        paint_piece(ui, *orientation, SQUARE_SIZE / 2.0);
+                    }
+                    ui.label(format!("{} × {count}", LETTERS[piece as usize]));
+                    if ui.button("⟳").on_hover_text("Rotate").clicked() {
+                        *orientation = Shape::from(*orientation).rotate().fixed_piece().unwrap();
+                    }
+                });
+            }
+        });
+
+        ui.add_space(8.0);
+        let (mut undo, mut complete, mut reset) = (false, false, false);
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!searching, |ui| {
+                undo = ui.button("Undo").clicked();
+                complete = ui.button("Complete").clicked();
+                reset = ui.button("Reset").clicked();
+            });
+            if searching {
+                ui.spinner();
+            }
+        });
+        if undo {
+            game.undo();
+            self.message.clear();
+        }
+        if complete {
+            self.complete(ui.ctx());
+        }
+        if reset {
+            self.game = None;
+            self.message.clear();
+        }
+    }
+
+    // Draw the board with the `placements` on it. In Solve mode clicking a
+    // square blocks or unblocks it. In Play mode clicks are ignored, since
+    // changing the board would end the game.
+    #[allow(clippy::cast_precision_loss)]
+    fn board_ui(&mut self, ui: &mut Ui, placements: &[Placement]) -> (Response, Painter) {
+        let size = Vec2::new(
+            self.column_count as f32 * SQUARE_SIZE,
+            self.row_count as f32 * SQUARE_SIZE,
+        );
+        let (response, painter) = ui.allocate_painter(size, Sense::click());
+        paint_board(&painter, response.rect, &self.shape(), placements);
+        if self.mode == Mode::Solve && response.clicked() {
+            if let Some(square) = response
+                .interact_pointer_pos()
+                .and_then(|pos| square_at(response.rect, pos))
+            {
+                if !self.blocked.remove(&square) {
+                    self.blocked.insert(square);
+                }
+                self.changed();
+            }
+        }
+        (response, painter)
+    }
+
+    // Forget the solution and the game after the puzzle changes
+    fn changed(&mut self) {
+        self.search = None;
+        self.solution = None;
+        self.game = None;
+        self.message.clear();
+    }
+
+    // The blocked squares on the board
+    fn blocked_squares(&self) -> Vec<(u32, u32)> {
+        self.blocked
+            .iter()
+            .copied()
+            .filter(|&(row, column)| row < self.row_count && column < self.column_count)
+            .collect()
+    }
+
+    fn shape(&self) -> BoardShape {
+        let rows: Vec<String> = (0..self.row_count)
+            .map(|row| {
+                (0..self.column_count)
+                    .map(|column| {
+                        if self.blocked.contains(&(row, column)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.join("/").parse().unwrap()
+    }
+
+    fn pieces(&self) -> PieceCollection {
+        let pieces: String = LETTERS
+            .iter()
+            .zip(&self.counts)
+            .flat_map(|(&letter, &count)| std::iter::repeat_n(letter, count as usize))
+            .collect();
+        pieces.parse().unwrap()
+    }
+
+    fn place(&mut self, piece: FixedPiece, row: u32, column: u32) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        self.message = match game.place(piece, row, column) {
+            Ok(()) if game.is_solved() => "Solved!".to_string(),
+            Ok(()) => String::new(),
+            Err(err) => err.to_string(),
+        };
+    }
+
+    // Start searching for a solution in the background
+    fn solve(&mut self, ctx: &egui::Context) {
+        let solve = Solve::board(self.row_count, self.column_count)
+            .piece_collection(self.pieces())
+            .blocked(&self.blocked_squares())
+            .timeout(TIMEOUT);
+        self.solution = None;
+        self.search(ctx, move || Outcome::Solved(solve.run()));
+    }
+
+    // Start completing the game in the background
+    fn complete(&mut self, ctx: &egui::Context) {
+        let Some(game) = self.game.clone() else {
+            return;
+        };
+        self.search(ctx, move || Outcome::Completed(game.complete()));
+    }
+
+    fn search(&mut self, ctx: &egui::Context, search: impl FnOnce() -> Outcome + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(search());
+            ctx.request_repaint();
+        });
+        self.search = Some(receiver);
+        self.message = "Searching…".to_string();
+    }
+
+    // Take the result of the search if it's done
+    fn receive(&mut self) {
+        let Some(outcome) = self
+            .search
+            .as_ref()
+            .and_then(|search| search.try_recv().ok())
+        else {
+            return;
+        };
+        self.search = None;
+        match outcome {
+            Outcome::Solved(Ok(solutions)) => match solutions.solutions.into_iter().next() {
+                Some(solution) => {
+                    let placements = solution.placements();
+                    self.message =
+                        format!("Solved after visiting {} boards.", solutions.stats.nodes);
+                    self.solution = Some(Steps {
+                        shown: placements.len(),
+                        replay: solution.to_replay(),
+                        placements,
+                    });
+                }
+                None if solutions.abandoned => {
+                    self.message = "The search took too long and was abandoned.".to_string();
+                }
+                None => self.message = "The puzzle has no solution.".to_string(),
+            },
+            Outcome::Solved(Err(err)) => self.message = err.to_string(),
+            Outcome::Completed(position) => {
+                let Some(game) = &mut self.game else {
+                    return;
+                };
+                let Some(position) = position else {
+                    self.message =
+                        "The pieces on the board aren't part of any solution.".to_string();
+                    return;
+                };
+                // The solution keeps the moves, so only the rest are played
+                for placement in position.placements() {
+                    if !game.moves().contains(&placement) {
+                        game.place(placement.piece, placement.row, placement.column)
+                            .unwrap();
+                    }
+                }
+                self.message = "Completed.".to_string();
+            }
+        }
+    }
+}
+
+fn color([red, green, blue]: [u8; 3]) -> Color32 {
+    Color32::from_rgb(red, green, blue)
+}
+
+// The square of the board under `pos`, if any
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn square_at(rect: Rect, pos: Pos2) -> Option<(u32, u32)> {
+    if !rect.contains(pos) {
+        return None;
+    }
+    let offset = (pos - rect.min) / SQUARE_SIZE;
+    Some((offset.y as u32, offset.x as u32))
+}
+
+// The square of the board under the top-left square of `piece` when the piece
+// is centred on `pos`, if any
+#[allow(clippy::cast_precision_loss)]
+fn anchor_at(rect: Rect, piece: FixedPiece, pos: Pos2) -> Option<(u32, u32)> {
+    let (cells, size) = piece_squares(piece);
+    // The top-left square relative to the smallest rectangle around the piece
+    let Cell(row, column) = cells[0];
+    let offset = Vec2::new(column as f32 + 0.5, row as f32 + 0.5) - size / 2.0;
+    square_at(rect, pos + offset * SQUARE_SIZE)
+}
+
+// The rectangle of a square of the board on the screen
+#[allow(clippy::cast_precision_loss)]
+fn square_rect(rect: Rect, row: i64, column: i64) -> Rect {
+    Rect::from_min_size(
+        rect.min + Vec2::new(column as f32, row as f32) * SQUARE_SIZE,
+        Vec2::splat(SQUARE_SIZE),
+    )
+}
+
+// The squares of a piece from the top-left corner of the smallest rectangle
+// around it, top-left square first, and the size of the rectangle in squares
+#[allow(clippy::cast_precision_loss)]
+fn piece_squares(piece: FixedPiece) -> ([Cell; 4], Vec2) {
+    let Shape(cells) = Shape::from(piece);
+    let rows = cells.map(|Cell(row, _)| row);
+    let columns = cells.map(|Cell(_, column)| column);
+    let (top, left) = (*rows.iter().min().unwrap(), *columns.iter().min().unwrap());
+    let (bottom, right) = (*rows.iter().max().unwrap(), *columns.iter().max().unwrap());
+    (
+        cells.map(|Cell(row, column)| Cell(row - top, column - left)),
+        Vec2::new((right - left + 1) as f32, (bottom - top + 1) as f32),
+    )
+}
+
+// Fill the squares of a placed piece
+fn paint_placement(painter: &Painter, rect: Rect, placement: Placement, fill: Color32) {
+    let Shape(cells) = Shape::from(placement.piece);
+    for Cell(row, column) in cells {
+        let square = square_rect(
+            rect,
+            i64::from(placement.row) + i64::from(row),
+            i64::from(placement.column) + i64::from(column),
+        );
+        painter.rect(
+            square,
+            0.0,
+            fill,
+            Stroke::new(1.0, color(Palette::default().border)),
+            StrokeKind::Inside,
+        );
+    }
+}
+
+// Draw the board with its blocked squares and the `placements`
+fn paint_board(painter: &Painter, rect: Rect, shape: &BoardShape, placements: &[Placement]) {
+    let palette = Palette::default();
+    for row in 0..shape.row_count() {
+        for column in 0..shape.column_count() {
+            let fill = if shape.is_blocked(row, column) {
+                palette.blocked
+            } else {
+                palette.background
+            };
+            let square = square_rect(rect, i64::from(row), i64::from(column));
+            painter.rect(
+                square,
+                0.0,
+                color(fill),
+                Stroke::new(1.0, Color32::GRAY),
+                StrokeKind::Inside,
+            );
+        }
+    }
+    for &placement in placements {
+        let fill = color(palette.piece(placement.piece.piece()));
+        paint_placement(painter, rect, placement, fill);
+    }
+}
+
+// A drawing of a piece with squares of `size`
+fn paint_piece(ui: &mut Ui, piece: FixedPiece, size: f32) {
+    let (cells, rectangle) = piece_squares(piece);
+    let (response, painter) = ui.allocate_painter(rectangle * size, Sense::hover());
+    let fill = color(Palette::default().piece(piece.piece()));
+    for Cell(row, column) in cells {
+        #[allow(clippy::cast_precision_loss)]
+        let min = response.rect.min + Vec2::new(column as f32, row as f32) * size;
+        painter.rect_filled(Rect::from_min_size(min, Vec2::splat(size - 1.0)), 0.0, fill);
+    }
+}
@@ -0,0 +1,205 @@
+#![warn(clippy::pedantic)]
+
+// A server that streams the search for a solution to browsers over a
+// WebSocket. Connect to
+//
+//     ws://HOST:PORT/solve?rows=4&columns=4&pieces=LLZZ
+//
+// to receive every step of the search as a message in the format of
+// `soe_solver --trace`, followed by a "done" message with the solution in the
+// replay format, or null if there is none:
+//
+//     {"step":"done","solution":"L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3"}
+//
+// An invalid puzzle gets an "error" message instead, as does a search that
+// is abandoned after the time limit or when the server is too busy. The
+// connection is closed after the last message.
+//
+// The searches run on a small pool of workers, see `JobQueue`, and only so
+// many connections are served at once. Further connections get a "503
+// Service Unavailable" response.
+//
+// Connecting to ws://HOST:PORT/capabilities instead receives one message with
+// what the server was built with, in the format of `Capabilities::to_json`.
+
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{crate_authors, crate_version, App, Arg};
+
+use sigils_of_elohim_solver::{
+    capabilities, json_string, JobError, JobQueue, Outcome, PieceCollection, PuzzleSpec,
+    QueueOptions,
+};
+use soe_cli::websocket::{self, Handshake, LineMessages};
+use soe_cli::{exit_with, get_matches, json_errors_arg, report};
+
+fn main() {
+    let matches = get_matches(
+        App::new("Sigils of Elohim Solver - Server")
+            .version(crate_version!())
+            .author(crate_authors!())
+            .about("Streams the search for solutions to browsers over WebSockets")
+            .arg(
+                Arg::with_name("address")
+                    .long("address")
+                    .value_name("ADDRESS")
+                    .default_value("127.0.0.1:8080")
+                    .help("The address to listen on"),
+            )
+            .arg(
+                Arg::with_name("max-connections")
+                    .long("max-connections")
+                    .value_name("N")
+                    .default_value("32")
+                    .help("The most connections to serve at once"),
+            )
+            .arg(
+                Arg::with_name("workers")
+                    .long("workers")
+                    .value_name("N")
+                    .default_value("2")
+                    .help("The number of searches to run at once"),
+            )
+            .arg(json_errors_arg()),
+    );
+
+    let address = matches.value_of("address").unwrap();
+    let max_connections = positive_number(matches.value_of("max-connections").unwrap())
+        .unwrap_or_else(|| exit_with("value of --max-connections must be a positive integer", 1));
+    let workers = positive_number(matches.value_of("workers").unwrap())
+        .unwrap_or_else(|| exit_with("value of --workers must be a positive integer", 1));
+    let listener = TcpListener::bind(address).unwrap_or_else(|err| exit_with(err, 1));
+    let queue = Arc::new(JobQueue::new(QueueOptions {
+        workers,
+        ..QueueOptions::default()
+    }));
+    let connections = Arc::new(AtomicUsize::new(0));
+    for mut stream in listener.incoming().flatten() {
+        if let Err(err) = stream
+            .set_read_timeout(Some(SOCKET_TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(SOCKET_TIMEOUT)))
+        {
+            report(err);
+            continue;
+        }
+        if connections.fetch_add(1, Ordering::SeqCst) >= max_connections {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let _ = respond(
+                &mut stream,
+                "503 Service Unavailable",
+                "Too many connections. Try again later.",
+            );
+            continue;
+        }
+        let connection = Connection(Arc::clone(&connections));
+        let queue = Arc::clone(&queue);
+        thread::spawn(move || {
+            if let Err(err) = serve(stream, &queue) {
+                report(err);
+            }
+            drop(connection);
+        });
+    }
+}
+
+// Reading the request or writing a message fails after this long, so that a
+// stalled client doesn't hold its connection forever
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Counts a connection being served until it's dropped
+struct Connection(Arc<AtomicUsize>);
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn positive_number(input: &str) -> Option<usize> {
+    input.parse().ok().filter(|&value| value > 0)
+}
+
+fn serve(mut stream: TcpStream, queue: &JobQueue) -> io::Result<()> {
+    let handshake = match Handshake::read(BufReader::new(&stream)) {
+        Ok(handshake) => handshake,
+        Err(err) => return respond(&mut stream, "400 Bad Request", &err.to_string()),
+    };
+    if handshake.path == "/capabilities" {
+        handshake.accept(&mut stream)?;
+        websocket::write_text(&stream, &capabilities().to_json())?;
+        return websocket::write_close(&stream);
+    }
+    if handshake.path != "/solve" {
+        return respond(&mut stream, "404 Not Found", "Not found.");
+    }
+    let puzzle = (|| {
+        let row_count = handshake.query.get("rows")?.parse().ok()?;
+        let column_count = handshake.query.get("columns")?.parse().ok()?;
+        let pieces: PieceCollection = handshake.query.get("pieces")?.parse().ok()?;
+        Some((row_count, column_count, pieces))
+    })();
+    let Some((row_count, column_count, pieces)) = puzzle else {
+        return respond(
+            &mut stream,
+            "400 Bad Request",
+            "The query must have rows, columns and pieces.",
+        );
+    };
+
+    handshake.accept(&mut stream)?;
+    // The client is identified by its address so that it can't take every
+    // worker by opening several connections
+    let client = stream.peer_addr()?.ip().to_string();
+    let messages = LineMessages::new(stream.try_clone()?);
+    let puzzle = PuzzleSpec::new(row_count, column_count, pieces);
+    let outcome = match queue.submit_traced(&client, puzzle, Box::new(messages)) {
+        Ok(job) => job.wait(),
+        Err(err) => {
+            write_error(&stream, &err.to_string())?;
+            return websocket::write_close(&stream);
+        }
+    };
+    match outcome {
+        Ok(Outcome::Undetermined) => {
+            write_error(&stream, "The search took too long and was abandoned.")?;
+        }
+        Ok(outcome) => {
+            let solution = outcome.solution().map_or_else(
+                || "null".to_string(),
+                |solution| json_string(&solution.to_replay()),
+            );
+            let done = format!("{{\"step\":\"done\",\"solution\":{solution}}}");
+            websocket::write_text(&stream, &done)?;
+        }
+        Err(JobError::Io(err)) => return Err(err),
+        Err(err) => write_error(&stream, &err.to_string())?,
+    }
+    websocket::write_close(&stream)
+}
+
+// Send an "error" message with `message`
+fn write_error(stream: &TcpStream, message: &str) -> io::Result<()> {
+    let error = format!(
+        "{{\"step\":\"error\",\"message\":{}}}",
+        json_string(message)
+    );
+    websocket::write_text(stream, &error)
+}
+
+// Answer a request that isn't accepted with a plain text response
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n\
+         {body}",
+        body.len()
+    )
+}
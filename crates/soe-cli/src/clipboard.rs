@@ -0,0 +1,89 @@
+// Copying output to the system clipboard, so a solution can be pasted into a
+// chat without the terminal mangling the box drawing characters. The text is
+// piped to the clipboard program of the platform: pbcopy on macOS, clip on
+// Windows and the first of wl-copy, xclip and xsel that's installed
+// elsewhere. Without the `clipboard` feature copying is an error.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::CliError;
+
+// The clipboard programs to try, in order, with their arguments
+#[cfg(target_os = "macos")]
+const PROGRAMS: &[(&str, &[&str])] = &[("pbcopy", &[])];
+#[cfg(windows)]
+const PROGRAMS: &[(&str, &[&str])] = &[("clip", &[])];
+#[cfg(not(any(target_os = "macos", windows)))]
+const PROGRAMS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+// Put `text` on the clipboard
+pub fn copy(text: &str) -> Result<(), CliError> {
+    if cfg!(feature = "clipboard") {
+        copy_with(PROGRAMS, text)
+    } else {
+        Err(CliError::new(
+            "clipboard-unsupported",
+            "This build can't copy to the clipboard. Build soe-cli with the clipboard feature.",
+        ))
+    }
+}
+
+// Pipe `text` to the first of `programs` that can be started
+fn copy_with(programs: &[(&str, &[&str])], text: &str) -> Result<(), CliError> {
+    for &(program, args) in programs {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(failed(program, &err)),
+        };
+        let written = child.stdin.take().unwrap().write_all(text.as_bytes());
+        let status = child.wait().map_err(|err| failed(program, &err))?;
+        written.map_err(|err| failed(program, &err))?;
+        if !status.success() {
+            return Err(failed(program, &status));
+        }
+        return Ok(());
+    }
+
+    let names: Vec<_> = programs.iter().map(|&(program, _)| program).collect();
+    let hint = format!("Install one of {}.", names.join(", "));
+    Err(CliError::new(
+        "clipboard",
+        format!("Failed to copy to the clipboard. No clipboard program was found. {hint}"),
+    )
+    .with_hint(hint))
+}
+
+fn failed(program: &str, err: &dyn std::fmt::Display) -> CliError {
+    CliError::new(
+        "clipboard",
+        format!("Failed to copy to the clipboard with {program}. {err}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::copy_with;
+
+    #[test]
+    fn programs() {
+        let err = copy_with(&[("soe-no-such-clipboard", &[])], "AB\n").unwrap_err();
+        assert_eq!(err.code, "clipboard");
+        assert_eq!(err.hints, ["Install one of soe-no-such-clipboard."]);
+        if cfg!(unix) {
+            assert!(copy_with(&[("soe-no-such-clipboard", &[]), ("cat", &[])], "AB\n").is_ok());
+            assert!(copy_with(&[("false", &[])], "AB\n").is_err());
+        }
+    }
+}
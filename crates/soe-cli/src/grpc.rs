@@ -0,0 +1,432 @@
+// The solver service of proto/solver.proto, served by soe_grpc. Solves run
+// on a `JobQueue` and stream a progress message every so often while the
+// search runs, followed by one with the outcome. Screenshots are read on as
+// many threads as the queue has workers and further requests are refused
+// until one is done.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use sigils_of_elohim_solver::vision;
+use sigils_of_elohim_solver::{
+    board_fits, BoardShape, JobError, JobQueue, Outcome, PieceCollection, Position, Progress,
+    QueueOptions, Solve, SolveOneError,
+};
+
+use proto::solve_progress::Outcome as Stage;
+use proto::solver_server::{Solver, SolverServer};
+use proto::{
+    DetectRequest, DetectResponse, Puzzle, SolveProgress, SolveRequest, VerifyRequest,
+    VerifyResponse,
+};
+
+#[allow(clippy::all, clippy::pedantic)]
+pub mod proto {
+    tonic::include_proto!("sigils_of_elohim_solver.v1");
+}
+
+// The least time between two progress messages
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// The progress messages a client may fall behind by. It misses later ones
+// until it catches up.
+const PROGRESS_BUFFER: usize = 16;
+
+// The largest request, a screenshot for Detect, in bytes
+const MAX_REQUEST_SIZE: usize = 16 << 20;
+
+// The most pixels of a screenshot for Detect, checked before it's decoded,
+// since a small compressed file can claim a huge image
+const MAX_IMAGE_PIXELS: u64 = 64 << 20;
+
+pub struct SolverService {
+    queue: Arc<JobQueue>,
+    // A permit for each screenshot that may be read at once
+    readers: Arc<Semaphore>,
+}
+
+impl SolverService {
+    #[must_use]
+    pub fn new(options: QueueOptions) -> Self {
+        let readers = Arc::new(Semaphore::new(options.workers.max(1)));
+        Self {
+            queue: Arc::new(JobQueue::new(options)),
+            readers,
+        }
+    }
+
+    // The service for `tonic::transport::Server::add_service`
+    #[must_use]
+    pub fn into_server(self) -> SolverServer<Self> {
+        SolverServer::new(self).max_decoding_message_size(MAX_REQUEST_SIZE)
+    }
+}
+
+#[tonic::async_trait]
+impl Solver for SolverService {
+    type SolveStream = ReceiverStream<Result<SolveProgress, Status>>;
+
+    async fn solve(
+        &self,
+        request: Request<SolveRequest>,
+    ) -> Result<Response<Self::SolveStream>, Status> {
+        // The client is identified by its address so that it can't take every
+        // worker with several requests
+        let client = request
+            .remote_addr()
+            .map_or_else(String::new, |address| address.ip().to_string());
+        let request = request.into_inner();
+        let (sender, receiver) = mpsc::channel(PROGRESS_BUFFER);
+        let stream = Response::new(ReceiverStream::new(receiver));
+        let (solve, piece_count) = match parse_solve(&request) {
+            Ok(solve) => solve,
+            Err(error) => {
+                let _ = sender.try_send(Ok(SolveProgress {
+                    outcome: Stage::InvalidPuzzle.into(),
+                    error,
+                    ..SolveProgress::default()
+                }));
+                return Ok(stream);
+            }
+        };
+
+        let start = Instant::now();
+        // The progress at the last report, for the message with the outcome
+        let latest = Arc::new(Mutex::new(Progress::default()));
+        let report = {
+            let (sender, latest) = (sender.clone(), Arc::clone(&latest));
+            let mut sent = start;
+            Box::new(move |progress: Progress| {
+                *latest.lock().unwrap() = progress;
+                if sent.elapsed() >= PROGRESS_INTERVAL {
+                    sent = Instant::now();
+                    let _ = sender.try_send(Ok(progress_message(
+                        Stage::Running,
+                        progress,
+                        start.elapsed(),
+                    )));
+                }
+            })
+        };
+        let job = self
+            .queue
+            .submit_solve(&client, solve, report)
+            .map_err(|err| Status::resource_exhausted(err.to_string()))?;
+
+        tokio::task::spawn_blocking(move || {
+            let outcome = job.wait();
+            let mut progress = *latest.lock().unwrap();
+            let message = match outcome {
+                Ok(Outcome::Solved(solution)) => {
+                    progress.depth = piece_count;
+                    Ok(SolveProgress {
+                        solution: solution.to_replay(),
+                        ..progress_message(Stage::Solved, progress, start.elapsed())
+                    })
+                }
+                Ok(Outcome::NoSolution) => Ok(progress_message(
+                    Stage::NoSolution,
+                    progress,
+                    start.elapsed(),
+                )),
+                Ok(Outcome::Undetermined) => Ok(progress_message(
+                    Stage::Undetermined,
+                    progress,
+                    start.elapsed(),
+                )),
+                Err(JobError::InvalidPuzzle(err)) => Ok(SolveProgress {
+                    error: err.to_string(),
+                    ..progress_message(Stage::InvalidPuzzle, progress, start.elapsed())
+                }),
+                Err(err) => Err(Status::internal(err.to_string())),
+            };
+            // The client may have gone
+            let _ = sender.blocking_send(message);
+        });
+        Ok(stream)
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let request = request.into_inner();
+        let result = parse_puzzle(request.puzzle.as_ref()).and_then(|(shape, pieces)| {
+            Position::from_replay_on(&shape, &request.solution)
+                .map_err(|err| err.to_string())?
+                .verify_shape(&shape, &pieces)
+                .map_err(|err| err.to_string())
+        });
+        Ok(Response::new(VerifyResponse {
+            valid: result.is_ok(),
+            error: result.err().unwrap_or_default(),
+        }))
+    }
+
+    async fn detect(
+        &self,
+        request: Request<DetectRequest>,
+    ) -> Result<Response<DetectResponse>, Status> {
+        let permit = Arc::clone(&self.readers)
+            .try_acquire_owned()
+            .map_err(|_| Status::resource_exhausted("Too many screenshots are being read."))?;
+        let image = request.into_inner().image;
+        let reading = tokio::task::spawn_blocking(move || {
+            let reading = vision::load_from_memory_within(&image, MAX_IMAGE_PIXELS)
+                .and_then(|image| vision::read(&image));
+            drop(permit);
+            reading
+        })
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?;
+        Ok(Response::new(match reading {
+            Ok(reading) => DetectResponse {
+                puzzle: Some(Puzzle {
+                    rows: reading.row_count,
+                    columns: reading.column_count,
+                    board: String::new(),
+                    pieces: reading.pieces,
+                }),
+                error: String::new(),
+            },
+            Err(err) => DetectResponse {
+                puzzle: None,
+                error: err.to_string(),
+            },
+        }))
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn progress_message(stage: Stage, progress: Progress, elapsed: Duration) -> SolveProgress {
+    SolveProgress {
+        nodes: progress.nodes,
+        depth: progress.depth,
+        elapsed_ms: elapsed.as_millis() as u64,
+        outcome: stage.into(),
+        ..SolveProgress::default()
+    }
+}
+
+// The search a Solve request asks for and the number of its pieces
+fn parse_solve(request: &SolveRequest) -> Result<(Solve, u32), String> {
+    let (shape, pieces) = parse_puzzle(request.puzzle.as_ref())?;
+    let piece_count = pieces.count_all();
+    let mut solve = Solve::board(shape.row_count(), shape.column_count())
+        .piece_collection(pieces)
+        .blocked(&shape.blocked_squares());
+    if request.timeout_ms > 0 {
+        solve = solve.timeout(Duration::from_millis(request.timeout_ms));
+    }
+    if request.max_nodes > 0 {
+        solve = solve.max_nodes(request.max_nodes);
+    }
+    Ok((solve, piece_count))
+}
+
+// The board and the pieces of a puzzle message
+fn parse_puzzle(puzzle: Option<&Puzzle>) -> Result<(BoardShape, PieceCollection), String> {
+    let puzzle = puzzle.ok_or("The request has no puzzle.")?;
+    let (row_count, column_count) = (puzzle.rows, puzzle.columns);
+    // Checked before the board is built so that a huge one isn't
+    if !board_fits(row_count, column_count) && !board_fits(column_count, row_count) {
+        return Err(SolveOneError::BoardTooLarge {
+            row_count,
+            column_count,
+        }
+        .to_string());
+    }
+    let shape = if puzzle.board.is_empty() {
+        BoardShape::rectangle(row_count, column_count)
+    } else {
+        let shape: BoardShape = puzzle.board.parse().map_err(|err| format!("{err}"))?;
+        if (shape.row_count(), shape.column_count()) != (row_count, column_count) {
+            return Err(format!(
+                "The board is {} by {} but the puzzle is {row_count} by {column_count}.",
+                shape.row_count(),
+                shape.column_count()
+            ));
+        }
+        shape
+    };
+    let pieces = puzzle.pieces.parse().map_err(|err| format!("{err}"))?;
+    Ok((shape, pieces))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use image::{DynamicImage, ImageOutputFormat, RgbImage};
+    use tokio_stream::StreamExt;
+    use tonic::Request;
+
+    use super::proto::solver_server::Solver;
+    use super::proto::{DetectRequest, Puzzle, SolveProgress, SolveRequest, VerifyRequest};
+    use super::{SolverService, Stage};
+    use sigils_of_elohim_solver::QueueOptions;
+
+    // The field of a request
+    #[allow(clippy::unnecessary_wraps)]
+    fn puzzle(row_count: u32, column_count: u32, board: &str, pieces: &str) -> Option<Puzzle> {
+        Some(Puzzle {
+            rows: row_count,
+            columns: column_count,
+            board: board.to_string(),
+            pieces: pieces.to_string(),
+        })
+    }
+
+    async fn solve(service: &SolverService, request: SolveRequest) -> Vec<SolveProgress> {
+        let stream = service.solve(Request::new(request)).await.unwrap();
+        stream
+            .into_inner()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await
+    }
+
+    #[tokio::test]
+    async fn solve_puzzles() {
+        let service = SolverService::new(QueueOptions {
+            timeout: Duration::from_millis(500),
+            ..QueueOptions::default()
+        });
+        let messages = solve(
+            &service,
+            SolveRequest {
+                puzzle: puzzle(4, 4, "", "LLZZ"),
+                ..SolveRequest::default()
+            },
+        )
+        .await;
+        let last = messages.last().unwrap();
+        assert_eq!(last.outcome(), Stage::Solved);
+        assert_eq!(last.solution, "L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3");
+        assert_eq!(last.depth, 4);
+
+        let messages = solve(
+            &service,
+            SolveRequest {
+                puzzle: puzzle(3, 4, "4./4#/4.", "II"),
+                ..SolveRequest::default()
+            },
+        )
+        .await;
+        assert_eq!(messages.last().unwrap().solution, "I2@r0c0; I2@r2c0");
+
+        // A puzzle with no solution that takes longer than the node limit
+        let messages = solve(
+            &service,
+            SolveRequest {
+                puzzle: puzzle(4, 12, "", "IITTTLLLJJSZ"),
+                max_nodes: 100_000,
+                ..SolveRequest::default()
+            },
+        )
+        .await;
+        let last = messages.last().unwrap();
+        assert_eq!(last.outcome(), Stage::Undetermined);
+        assert!(last.nodes >= 99_000);
+        assert!(messages[..messages.len() - 1]
+            .iter()
+            .all(|message| message.outcome() == Stage::Running));
+
+        for (puzzle, error) in [
+            (puzzle(4, 4, "", "LLZ"), "There are 3 pieces for 16 squares"),
+            (puzzle(4, 4, "", "LLZX"), "unrecognized characters"),
+            (puzzle(3, 4, "4./4.", "II"), "The board is 2 by 4"),
+            (puzzle(100, 100, "", "I"), "too large"),
+            (None, "no puzzle"),
+        ] {
+            let messages = solve(
+                &service,
+                SolveRequest {
+                    puzzle,
+                    ..SolveRequest::default()
+                },
+            )
+            .await;
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].outcome(), Stage::InvalidPuzzle);
+            assert!(messages[0].error.contains(error), "{}", messages[0].error);
+        }
+    }
+
+    #[tokio::test]
+    async fn verify() {
+        let service = SolverService::new(QueueOptions::default());
+        let verify = |puzzle, solution: &str| {
+            let request = Request::new(VerifyRequest {
+                puzzle,
+                solution: solution.to_string(),
+            });
+            let service = &service;
+            async move { service.verify(request).await.unwrap().into_inner() }
+        };
+
+        let response = verify(
+            puzzle(4, 4, "", "LLZZ"),
+            "L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3",
+        )
+        .await;
+        assert!(response.valid && response.error.is_empty());
+        let response = verify(puzzle(3, 4, "4./4#/4.", "II"), "I2@r0c0; I2@r2c0").await;
+        assert!(response.valid);
+
+        let response = verify(puzzle(3, 4, "4./4#/4.", "II"), "I2@r0c0; I2@r1c0").await;
+        assert!(!response.valid);
+        assert!(response.error.contains("illegal placement"));
+        let response = verify(puzzle(4, 4, "", "LLZZ"), "L2@r0c0").await;
+        assert!(!response.valid);
+        assert!(response.error.contains("isn't covered"));
+        let response = verify(
+            puzzle(4, 4, "", "LLZZ"),
+            "I2@r0c0; I2@r1c0; I2@r2c0; I2@r3c0",
+        )
+        .await;
+        assert!(!response.valid);
+        assert!(response.error.contains("has the pieces"));
+    }
+
+    #[tokio::test]
+    async fn detect() {
+        let service = SolverService::new(QueueOptions::default());
+        let detect = |image: Vec<u8>| {
+            let service = &service;
+            async move {
+                service
+                    .detect(Request::new(DetectRequest { image }))
+                    .await
+                    .unwrap()
+                    .into_inner()
+            }
+        };
+
+        let response = detect(b"not an image".to_vec()).await;
+        assert!(response.puzzle.is_none());
+        assert!(response.error.starts_with("Unable to decode the image."));
+
+        let mut png = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(50, 50))
+            .write_to(&mut png, ImageOutputFormat::PNG)
+            .unwrap();
+        let response = detect(png).await;
+        assert_eq!(response.error, "Unable to find the board.");
+
+        // A PNG whose header claims 60000 by 60000 pixels, with no pixel data
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(b"\0\0\xea\x60\0\0\xea\x60\x08\x02\0\0\0\x0f\xb0\xe2\x15");
+        png.extend(b"\0\0\0\0IDAT\x35\xaf\x06\x1e\0\0\0\0IEND\xae\x42\x60\x82");
+        let response = detect(png).await;
+        assert!(response.puzzle.is_none());
+        assert_eq!(
+            response.error,
+            "The image of 60000 by 60000 pixels is too large."
+        );
+    }
+}
@@ -0,0 +1,343 @@
+// The benchmark history, a text file with one line per puzzle per run:
+//
+//     1760000000	a1b2c3d	A yellow 1	12	3	41
+//
+// with the Unix time of the run, its label (usually the commit), the puzzle,
+// the nodes, the backtracks and the microseconds of the search separated by
+// tabs. Consecutive lines with the same time and label are one run. Lines
+// starting with '#' are comments. Runs are only ever appended, so the file
+// can be kept across checkouts and shared by copying.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter, Write as _};
+
+// The most recent runs shown in a chart
+pub const MAX_CHART_RUNS: usize = 60;
+
+// The characters of the ASCII chart, from the lowest value to the highest
+const RAMP: &[u8] = b"_.:-=+*#%@";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    pub puzzle: String,
+    pub nodes: u64,
+    pub backtracks: u64,
+    pub microseconds: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Run {
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub label: String,
+    pub records: Vec<Record>,
+}
+
+impl Run {
+    // The lines to append to the history file. Tabs and newlines in the label
+    // and the puzzle names are replaced with spaces.
+    #[must_use]
+    pub fn to_lines(&self) -> String {
+        let clean = |s: &str| s.replace(['\t', '\n', '\r'], " ");
+        let mut lines = String::new();
+        for record in &self.records {
+            writeln!(
+                lines,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                self.timestamp,
+                clean(&self.label),
+                clean(&record.puzzle),
+                record.nodes,
+                record.backtracks,
+                record.microseconds
+            )
+            .unwrap();
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseHistoryError {
+    // Lines are numbered from 1
+    pub line: usize,
+}
+
+impl Display for ParseHistoryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Line {} of the history isn't TIME, LABEL, PUZZLE, NODES, BACKTRACKS and \
+             MICROSECONDS separated by tabs.",
+            self.line
+        )
+    }
+}
+
+impl Error for ParseHistoryError {}
+
+// Read the runs of a history file, oldest first
+pub fn parse(text: &str) -> Result<Vec<Run>, ParseHistoryError> {
+    let mut runs: Vec<Run> = vec![];
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = || ParseHistoryError { line: index + 1 };
+        let fields: Vec<&str> = line.split('\t').collect();
+        let &[timestamp, label, puzzle, nodes, backtracks, microseconds] = fields.as_slice() else {
+            return Err(err());
+        };
+        let number = |field: &str| field.parse::<u64>().map_err(|_| err());
+        let timestamp = number(timestamp)?;
+        let record = Record {
+            puzzle: puzzle.to_string(),
+            nodes: number(nodes)?,
+            backtracks: number(backtracks)?,
+            microseconds: number(microseconds)?,
+        };
+        match runs.last_mut() {
+            Some(run) if run.timestamp == timestamp && run.label == label => {
+                run.records.push(record);
+            }
+            _ => runs.push(Run {
+                timestamp,
+                label: label.to_string(),
+                records: vec![record],
+            }),
+        }
+    }
+    Ok(runs)
+}
+
+// What a chart shows
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Nodes,
+    Microseconds,
+}
+
+impl Metric {
+    fn value(self, record: &Record) -> u64 {
+        match self {
+            Metric::Nodes => record.nodes,
+            Metric::Microseconds => record.microseconds,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Nodes => "nodes",
+            Metric::Microseconds => "microseconds",
+        }
+    }
+}
+
+// The value of each puzzle in each of the last `MAX_CHART_RUNS` runs, with
+// the puzzles in the order they first appear. A puzzle missing from a run has
+// no value for it.
+fn series(runs: &[Run], metric: Metric) -> Vec<(&str, Vec<Option<u64>>)> {
+    let runs = &runs[runs.len().saturating_sub(MAX_CHART_RUNS)..];
+    let mut series: Vec<(&str, Vec<Option<u64>>)> = vec![];
+    let mut indices = HashMap::new();
+    for (run_index, run) in runs.iter().enumerate() {
+        for record in &run.records {
+            let index = *indices.entry(record.puzzle.as_str()).or_insert_with(|| {
+                series.push((record.puzzle.as_str(), vec![None; runs.len()]));
+                series.len() - 1
+            });
+            series[index].1[run_index] = Some(metric.value(record));
+        }
+    }
+    series
+}
+
+// The range of the values of a series, or `None` if it has none
+fn range(values: &[Option<u64>]) -> Option<(u64, u64)> {
+    let min = values.iter().flatten().min()?;
+    let max = values.iter().flatten().max()?;
+    Some((*min, *max))
+}
+
+// A chart with a row for each puzzle and a column for each run, oldest
+// first. Each value is drawn with a character from '_' for the lowest value
+// of the puzzle to '@' for the highest, or ' ' if the puzzle wasn't in the
+// run. The last value and the range follow.
+//
+//     A yellow 1  __.__@:__  41 (35..97)
+#[must_use]
+pub fn ascii_chart(runs: &[Run], metric: Metric) -> String {
+    let series = series(runs, metric);
+    let width = series
+        .iter()
+        .map(|(puzzle, _)| puzzle.len())
+        .max()
+        .unwrap_or(0);
+    let run_count = runs.len().min(MAX_CHART_RUNS);
+    let mut chart = String::new();
+    writeln!(
+        chart,
+        "The {} of each puzzle in the last {run_count} runs, oldest first",
+        metric.name()
+    )
+    .unwrap();
+    for (puzzle, values) in &series {
+        let Some((min, max)) = range(values) else {
+            continue;
+        };
+        let bars: String = values
+            .iter()
+            .map(|value| match value {
+                None => ' ',
+                Some(_) if max == min => char::from(RAMP[0]),
+                Some(value) => {
+                    let level = (value - min) * (RAMP.len() as u64 - 1) / (max - min);
+                    char::from(RAMP[usize::try_from(level).unwrap()])
+                }
+            })
+            .collect();
+        let last = values.iter().rev().flatten().next().unwrap();
+        writeln!(chart, "{puzzle:width$}  {bars}  {last} ({min}..{max})").unwrap();
+    }
+    chart
+}
+
+// The same chart as an SVG image, a line for each puzzle scaled to its own
+// range
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn svg_chart(runs: &[Run], metric: Metric) -> String {
+    const ROW_HEIGHT: usize = 40;
+    const LABEL_WIDTH: usize = 160;
+    const STEP: usize = 8;
+    let series = series(runs, metric);
+    let run_count = runs.len().min(MAX_CHART_RUNS);
+    let width = LABEL_WIDTH + STEP * run_count.max(2) + 120;
+    let height = ROW_HEIGHT * (series.len() + 1);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         font-family=\"sans-serif\" font-size=\"12\">"
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<text x=\"4\" y=\"20\">The {} of each puzzle in the last {run_count} runs, oldest \
+         first</text>",
+        metric.name()
+    )
+    .unwrap();
+    for (row, (puzzle, values)) in series.iter().enumerate() {
+        let Some((min, max)) = range(values) else {
+            continue;
+        };
+        let top = ROW_HEIGHT * (row + 1);
+        let baseline = top + ROW_HEIGHT - 8;
+        writeln!(
+            svg,
+            "<text x=\"4\" y=\"{}\">{}</text>",
+            top + ROW_HEIGHT / 2 + 4,
+            escape(puzzle)
+        )
+        .unwrap();
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let value = (*value)?;
+                let fraction = if max == min {
+                    0.0
+                } else {
+                    (value - min) as f64 / (max - min) as f64
+                };
+                let x = LABEL_WIDTH + STEP * index;
+                let y = baseline as f64 - fraction * (ROW_HEIGHT - 16) as f64;
+                Some(format!("{x},{y:.1}"))
+            })
+            .collect();
+        writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"steelblue\" stroke-width=\"1.5\" points=\"{}\"/>",
+            points.join(" ")
+        )
+        .unwrap();
+        let last = values.iter().rev().flatten().next().unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\">{last} ({min}..{max})</text>",
+            LABEL_WIDTH + STEP * run_count.max(2) + 8,
+            top + ROW_HEIGHT / 2 + 4
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ascii_chart, parse, svg_chart, Metric, ParseHistoryError, Record, Run};
+
+    fn run(timestamp: u64, label: &str, microseconds: &[u64]) -> Run {
+        Run {
+            timestamp,
+            label: label.to_string(),
+            records: microseconds
+                .iter()
+                .enumerate()
+                .map(|(index, &microseconds)| Record {
+                    puzzle: format!("A yellow {}", index + 1),
+                    nodes: 10,
+                    backtracks: 2,
+                    microseconds,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let runs = vec![run(1, "abc", &[5, 7]), run(2, "def", &[6, 9])];
+        let text = format!("# history\n{}\n{}", runs[0].to_lines(), runs[1].to_lines());
+        assert_eq!(parse(&text), Ok(runs));
+        assert_eq!(
+            parse("1\tabc\tA yellow 1\t10\t2\n"),
+            Err(ParseHistoryError { line: 1 })
+        );
+        assert_eq!(
+            parse("\n1\tabc\tA yellow 1\t10\tx\t5\n"),
+            Err(ParseHistoryError { line: 2 })
+        );
+    }
+
+    #[test]
+    fn charts() {
+        let runs = vec![
+            run(1, "abc", &[10, 7]),
+            run(2, "def", &[20, 7]),
+            run(3, "ghi", &[15]),
+        ];
+        let chart = ascii_chart(&runs, Metric::Microseconds);
+        assert_eq!(
+            chart,
+            "The microseconds of each puzzle in the last 3 runs, oldest first\n\
+             A yellow 1  _@=  15 (10..20)\n\
+             A yellow 2  __   7 (7..7)\n"
+        );
+
+        let svg = svg_chart(&runs, Metric::Nodes);
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,318 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+
+// Error reporting shared by the programs. Errors are printed as
+//
+//     error: The total number of squares on the board is not a multiple of four.
+//
+// or, with --json-errors, as one JSON object per line for wrapping tools:
+//
+//     {"code":"invalid-board-size","message":"The total number ...","hints":[]}
+//
+// Codes are stable; messages and hints are for people and may change.
+
+use std::fmt::Display;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::{App, Arg, ArgMatches, ErrorKind};
+
+use image::ImageError;
+use sigils_of_elohim_solver::puzzles::{ImportCsvError, LoadPuzzlesError};
+use sigils_of_elohim_solver::trace::TraceError;
+use sigils_of_elohim_solver::vision::ReadError;
+use sigils_of_elohim_solver::{
+    json_string, ParseBoardShapeError, ParseOrientationWeightsError, ParsePaletteError,
+    ParsePieceCollectionError, ParsePositionError, ParseReplayError, SolveCacheError, SolveError,
+    SolveOneError, VerifyError,
+};
+
+use crate::history::ParseHistoryError;
+
+pub mod clipboard;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod history;
+pub mod websocket;
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug)]
+pub struct CliError {
+    // A short kebab-case name for the kind of error, e.g. "io"
+    pub code: &'static str,
+    pub message: String,
+    // Ways to fix the error
+    pub hints: Vec<String>,
+}
+
+impl CliError {
+    pub fn new<T: Display>(code: &'static str, message: T) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            hints: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn with_hint<T: Display>(mut self, hint: T) -> Self {
+        self.hints.push(hint.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let hints: Vec<_> = self.hints.iter().map(|hint| json_string(hint)).collect();
+        format!(
+            "{{\"code\":{},\"message\":{},\"hints\":[{}]}}",
+            json_string(self.code),
+            json_string(&self.message),
+            hints.join(",")
+        )
+    }
+}
+
+// Plain messages are about the command line arguments
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        Self::new("invalid-argument", message)
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::new("invalid-argument", message)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        Self::new("io", err)
+    }
+}
+
+impl From<SolveOneError> for CliError {
+    fn from(err: SolveOneError) -> Self {
+        match &err {
+            SolveOneError::InvalidBoardSize => Self::new("invalid-board-size", &err),
+            SolveOneError::InconsistentPieceCount { suggestions, .. } => {
+                let mut cli_error = Self::new("inconsistent-piece-count", &err);
+                for suggestion in suggestions {
+                    cli_error = cli_error.with_hint(suggestion);
+                }
+                cli_error
+            }
+            SolveOneError::PieceCountOverLimit => Self::new("too-many-pieces", &err),
+            SolveOneError::BoardTooLarge { .. } => Self::new("board-too-large", &err),
+            SolveOneError::PieceDoesntFit { .. } => Self::new("piece-doesnt-fit", &err),
+        }
+    }
+}
+
+impl From<SolveError> for CliError {
+    fn from(err: SolveError) -> Self {
+        match err {
+            SolveError::InvalidPieces(err) => err.into(),
+            SolveError::InvalidBlockedSquare(_) => Self::new("invalid-board", err),
+            SolveError::InvalidPuzzle(err) => err.into(),
+        }
+    }
+}
+
+impl From<ParsePieceCollectionError> for CliError {
+    fn from(err: ParsePieceCollectionError) -> Self {
+        Self::new("invalid-pieces", err)
+    }
+}
+
+impl From<ParseBoardShapeError> for CliError {
+    fn from(err: ParseBoardShapeError) -> Self {
+        Self::new("invalid-board", err)
+    }
+}
+
+impl From<ParseOrientationWeightsError> for CliError {
+    fn from(err: ParseOrientationWeightsError) -> Self {
+        Self::new("invalid-argument", err)
+    }
+}
+
+impl From<ParsePaletteError> for CliError {
+    fn from(err: ParsePaletteError) -> Self {
+        Self::new("invalid-palette", err)
+    }
+}
+
+impl From<ParseHistoryError> for CliError {
+    fn from(err: ParseHistoryError) -> Self {
+        Self::new("invalid-history", err)
+    }
+}
+
+impl From<ParseReplayError> for CliError {
+    fn from(err: ParseReplayError) -> Self {
+        match err {
+            ParseReplayError::BoardTooLarge { .. } => Self::new("board-too-large", err),
+            _ => Self::new("invalid-replay", err),
+        }
+    }
+}
+
+impl From<ParsePositionError> for CliError {
+    fn from(err: ParsePositionError) -> Self {
+        Self::new("invalid-solution-format", err)
+    }
+}
+
+impl From<VerifyError> for CliError {
+    fn from(err: VerifyError) -> Self {
+        Self::new("invalid-solution", err)
+    }
+}
+
+impl From<SolveCacheError> for CliError {
+    fn from(err: SolveCacheError) -> Self {
+        match err {
+            SolveCacheError::InvalidPuzzle(err) => err.into(),
+            SolveCacheError::Io(_) => Self::new("io", err),
+        }
+    }
+}
+
+impl From<TraceError> for CliError {
+    fn from(err: TraceError) -> Self {
+        match err {
+            TraceError::InvalidPuzzle(err) => err.into(),
+            TraceError::Io(err) => err.into(),
+        }
+    }
+}
+
+impl From<LoadPuzzlesError> for CliError {
+    fn from(err: LoadPuzzlesError) -> Self {
+        match err {
+            LoadPuzzlesError::Io(err) => err.into(),
+            LoadPuzzlesError::BoardTooLarge { .. } => Self::new("board-too-large", err),
+            _ => Self::new("invalid-puzzle-file", err),
+        }
+    }
+}
+
+impl From<ImportCsvError> for CliError {
+    fn from(err: ImportCsvError) -> Self {
+        Self::new("invalid-csv", err)
+    }
+}
+
+impl From<ImageError> for CliError {
+    fn from(err: ImageError) -> Self {
+        Self::new("image", err)
+    }
+}
+
+impl From<ReadError> for CliError {
+    fn from(err: ReadError) -> Self {
+        let code = match err {
+            ReadError::NoBoard | ReadError::NotAPuzzle { .. } => "no-puzzle",
+            ReadError::UnknownColor => "unknown-color",
+            ReadError::Io(_) => "io",
+            ReadError::Image(_) => "image",
+            ReadError::UnsupportedFormat(_) => "unsupported-format",
+            ReadError::NoSprite => "no-sprite",
+            ReadError::ImageTooLarge { .. } => "image-too-large",
+        };
+        Self::new(code, err)
+    }
+}
+
+// Whether errors are printed as JSON
+#[must_use]
+pub fn json_errors() -> bool {
+    JSON_ERRORS.load(Ordering::Relaxed)
+}
+
+// The --json-errors flag, which every program takes
+#[must_use]
+pub fn json_errors_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("json-errors")
+        .long("json-errors")
+        .global(true)
+        .help("Print errors as JSON objects with a code, a message and hints")
+}
+
+// Parse the arguments like `App::get_matches`, but print usage errors as JSON
+// when --json-errors is given
+#[must_use]
+pub fn get_matches<'a>(app: App<'a, '_>) -> ArgMatches<'a> {
+    match app.get_matches_safe() {
+        Ok(matches) => {
+            JSON_ERRORS.store(matches.is_present("json-errors"), Ordering::Relaxed);
+            matches
+        }
+        Err(err) => match err.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => err.exit(),
+            _ if std::env::args().any(|arg| arg == "--json-errors") => {
+                let message = err.message.lines().next().unwrap_or_default();
+                let message = message.trim_start_matches("error: ");
+                let usage = CliError::new("usage", message).with_hint("Run with --help for usage");
+                eprintln!("{}", usage.to_json());
+                process::exit(1);
+            }
+            _ => err.exit(),
+        },
+    }
+}
+
+// Print `err` to stderr. The hints are only printed in JSON because the
+// messages already include them.
+pub fn report<T: Into<CliError>>(err: T) {
+    let err = err.into();
+    if json_errors() {
+        eprintln!("{}", err.to_json());
+    } else {
+        eprintln!("error: {}", err.message);
+    }
+}
+
+// Print `err` and exit with `exit_code`
+pub fn exit_with<T: Into<CliError>>(err: T, exit_code: i32) -> ! {
+    report(err);
+    process::exit(exit_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_error_to_json() {
+        let err = CliError::new("io", "No \"file\"\n").with_hint("Check\\the path");
+        assert_eq!(
+            err.to_json(),
+            r#"{"code":"io","message":"No \"file\"\u000a","hints":["Check\\the path"]}"#
+        );
+    }
+
+    #[test]
+    fn inconsistent_piece_count_hints() {
+        let result = sigils_of_elohim_solver::solve_one(2, 4, "I".parse().unwrap());
+        let err: CliError = result.err().unwrap().into();
+        assert_eq!(err.code, "inconsistent-piece-count");
+        assert!(!err.hints.is_empty());
+    }
+
+    #[test]
+    fn board_too_large() {
+        let result = sigils_of_elohim_solver::puzzles::from_toml(
+            "[[puzzle]]\nrows = 30\ncolumns = 30\npieces = \"I\"",
+        );
+        let err: CliError = result.err().unwrap().into();
+        assert_eq!(err.code, "board-too-large");
+        let result = sigils_of_elohim_solver::Position::from_replay(30, 30, "I1@r0c0");
+        let err: CliError = result.err().unwrap().into();
+        assert_eq!(err.code, "board-too-large");
+    }
+}
@@ -0,0 +1,1394 @@
+#![warn(clippy::pedantic)]
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{crate_authors, crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
+use image::png::PNGEncoder;
+use image::{ColorType, ImageError};
+
+use sigils_of_elohim_solver::board::transform;
+use sigils_of_elohim_solver::geometry::Symmetry;
+use sigils_of_elohim_solver::puzzles::BookletFormat;
+use sigils_of_elohim_solver::{
+    animation, capabilities, explain, hints, puzzles, sample_solutions, side_by_side, solve_all,
+    solve_anytime, solve_constrained, solve_one_with_options, trace, AnnotatedPosition, BoardShape,
+    FillStrategy, Game, LabelOrder, OrientationWeights, Outcome, Palette, PieceCollection,
+    PieceConstraints, Placement, Position, PuzzleSpec, RasterOptions, Solve, SolveCache,
+    SolveOneError, SolveOptions,
+};
+use soe_cli::{clipboard, exit_with, get_matches, json_errors_arg, CliError};
+
+fn main() {
+    let matches = get_matches(app());
+    if run_subcommand(&matches) {
+        return;
+    }
+
+    if matches.is_present("copy")
+        && matches!(matches.value_of("format"), Some("bin" | "png" | "gif"))
+    {
+        exit_with_error("--copy can't be used with --format bin, png or gif");
+    }
+    let solutions = if CONSTRAINT_ARGS.iter().any(|&name| matches.is_present(name)) {
+        if matches.value_of("format") == Some("gif") {
+            exit_with_error("--format gif can't be used with piece constraints");
+        }
+        solve_with_constraints(&matches).map(|solution| solution.into_iter().collect())
+    } else {
+        let puzzle = puzzle_spec(&matches);
+        if matches.value_of("format") == Some("gif") {
+            if matches.is_present("sample") {
+                exit_with_error("--format gif can't be used with --sample");
+            }
+            animate(&matches, puzzle);
+            return;
+        }
+        if let Some(mask) = matches.value_of("board") {
+            Ok(solve_board_shape(&matches, puzzle, mask))
+        } else if let Some(path) = matches.value_of("trace") {
+            Ok(solve_traced(&matches, puzzle, path))
+        } else if matches.is_present("all") {
+            solve_all(puzzle.row_count, puzzle.column_count, puzzle.pieces)
+        } else if let Some(path) = matches.value_of("cache") {
+            Ok(solve_cached(puzzle, path))
+        } else if let Some(budget) = matches.value_of("anytime") {
+            Ok(vec![solve_within(puzzle, budget)])
+        } else if let Some(count) = matches.value_of("sample") {
+            Ok(sample(&matches, &puzzle, count))
+        } else {
+            let options = solve_options(&matches);
+            let outcome = solve_one_with_options(
+                puzzle.row_count,
+                puzzle.column_count,
+                puzzle.pieces,
+                &options,
+            );
+            if outcome.as_ref().is_ok_and(Outcome::is_undetermined) {
+                println!("Undetermined");
+                return;
+            }
+            outcome.map(|outcome| outcome.into_solution().into_iter().collect())
+        }
+    };
+    let mut solutions = solutions.unwrap_or_else(|err| {
+        exit_with_error(err);
+    });
+    if let Some(order) = matches.value_of("labels") {
+        let order: LabelOrder = order.parse().unwrap();
+        for solution in &mut solutions {
+            *solution = solution.relabel(order);
+        }
+    }
+
+    if matches.value_of("format") == Some("bin") {
+        write_binary(&solutions).unwrap_or_else(|err| exit_with_error(err));
+        return;
+    }
+    if solutions.is_empty() {
+        println!("No solution");
+    }
+    if matches.value_of("format") == Some("png") {
+        let options = raster_options(&matches);
+        write_png(&solutions, matches.value_of("out").unwrap(), &options)
+            .unwrap_or_else(|err| exit_with_error(err));
+        return;
+    }
+    let output = format_solutions(&matches, &solutions);
+    print!("{output}");
+    if matches.is_present("copy") {
+        let output = if solutions.is_empty() {
+            "No solution\n"
+        } else {
+            &output
+        };
+        clipboard::copy(output).unwrap_or_else(|err| exit_with_error(err));
+    }
+}
+
+// The arguments and subcommands, one after the other
+#[allow(clippy::too_many_lines)]
+fn app() -> App<'static, 'static> {
+    App::new("Sigils of Elohim Solver")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Solves puzzles from the video game 'Sigils of Elohim'")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .args(&main_puzzle_args())
+        .arg(
+            Arg::with_name("pretty")
+                .long("pretty")
+                .help("Print the solution with box drawing characters")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("pretty-ascii")
+                .long("pretty-ascii")
+                .help("Print the solution with ASCII box drawing characters")
+                .conflicts_with("pretty")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("grid")
+                .long("grid")
+                .value_name("GRID")
+                .possible_values(&["letters", "kinds", "both"])
+                .conflicts_with_all(&["pretty", "pretty-ascii"])
+                .help(
+                    "What the squares of a piece show. 'letters', the default, labels each \
+                     piece with its own letter, 'kinds' shows the kind of the piece (I, O, T, \
+                     J, L, S or Z), as the game colors them, and 'both' prints the two grids \
+                     side by side",
+                ),
+        )
+        .arg(
+            Arg::with_name("labels")
+                .long("labels")
+                .value_name("ORDER")
+                .possible_values(&["placement", "kind", "position"])
+                .help(
+                    "Label the pieces in the order they were placed, by kind or by their \
+                     top-left square, which labels the same tiling the same whatever the \
+                     search",
+                ),
+        )
+        .arg(
+            Arg::with_name("all")
+                .long("all")
+                .help("Print all the solutions in canonical order")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("layout-columns")
+                .long("columns")
+                .value_name("N")
+                .help("Print up to N solutions side by side"),
+        )
+        .arg(
+            Arg::with_name("max-width")
+                .long("max-width")
+                .value_name("WIDTH")
+                .help(
+                    "Print as many solutions side by side as fit in WIDTH characters, up to \
+                 the number given by --columns",
+                ),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["text", "bin", "html", "svg", "png", "gif", "narrate"])
+                .default_value("text")
+                .help(
+                    "The output format. 'bin' writes the compact binary encoding, 'html' \
+                 writes each solution as an HTML table, 'svg' as an SVG image, 'narrate' \
+                 describes it in words for screen readers and 'png' writes each solution as \
+                 an image to the file given by --out. 'gif' writes an animation of the \
+                 search for the first solution in canonical order to --out",
+                ),
+        )
+        .arg(
+            Arg::with_name("copy")
+                .long("copy")
+                .help(
+                    "Copy the output to the clipboard as well as printing it, for pasting \
+                     into a chat. Not for the 'bin', 'png' and 'gif' formats",
+                )
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("out")
+                .long("out")
+                .value_name("FILE")
+                .required_ifs(&[("format", "png"), ("format", "gif")])
+                .help(
+                    "The file to write images to. With several solutions, the number of the \
+                 solution is appended to the file name",
+                ),
+        )
+        .arg(
+            Arg::with_name("cell-size")
+                .long("cell-size")
+                .value_name("PIXELS")
+                .help("The size of a square in images"),
+        )
+        .arg(
+            Arg::with_name("palette")
+                .long("palette")
+                .value_name("PALETTE")
+                .help(
+                    "The colors of images and HTML: 'default', 'colorblind' or a palette \
+                     file of 'name = color' lines",
+                ),
+        )
+        .arg(
+            Arg::with_name("board")
+                .long("board")
+                .value_name("MASK")
+                .conflicts_with("trace")
+                .help(
+                    "The shape of the board as a mask string, e.g. '3.#/4.', to block \
+                 squares. It must have <rows> rows and <columns> columns",
+                ),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .long("trace")
+                .value_name("FILE")
+                .help(
+                    "Write every step of the search to FILE as JSON lines. The search is \
+                 single threaded and finds solutions in canonical order",
+                ),
+        )
+        .arg(
+            Arg::with_name("backtracks")
+                .long("backtracks")
+                .help("Animate every step of the search, not just the pieces of the solution")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .value_name("N")
+                .help("The number of threads to search with"),
+        )
+        .arg(
+            Arg::with_name("nondeterministic")
+                .long("nondeterministic")
+                .help("With several threads, print the first solution found by any of them")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("max-nodes")
+                .long("max-nodes")
+                .value_name("N")
+                .help("Give up after searching N nodes and print 'Undetermined'"),
+        )
+        .arg(
+            Arg::with_name("anytime")
+                .long("anytime")
+                .value_name("MILLISECONDS")
+                .help(
+                    "Search for at most MILLISECONDS and print the tiling with the most \
+                 pieces placed if no solution was found",
+                )
+                .conflicts_with_all(&[
+                    "all",
+                    "board",
+                    "trace",
+                    "threads",
+                    "nondeterministic",
+                    "max-nodes",
+                    "fill",
+                    "cache",
+                ]),
+        )
+        .arg(
+            Arg::with_name("no-transpose")
+                .long("no-transpose")
+                .help("Don't solve boards with more rows than columns on the transposed board")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("fill")
+                .long("fill")
+                .value_name("STRATEGY")
+                .possible_values(&["row-major", "column-major", "boustrophedon", "spiral"])
+                .help("The order in which the board is filled [default: row-major]")
+                .conflicts_with_all(&["all", "board", "trace", "threads", "cache"]),
+        )
+        .arg(
+            Arg::with_name("orientations")
+                .long("orientations")
+                .value_name("WEIGHTS")
+                .help(
+                    "Prefer or avoid orientations of the pieces, e.g. \"I1=ban,T2=-1\". \
+                 Orientations with higher weights are tried first [default: 0].",
+                )
+                .conflicts_with_all(&["all", "board", "trace", "cache", "anytime"]),
+        )
+        .arg(
+            Arg::with_name("break-symmetry")
+                .long("break-symmetry")
+                .help(
+                    "Search only one of the placements of a piece that the symmetries of \
+                 the board map onto each other. Faster when there's no solution, but the \
+                 solution printed isn't necessarily the first in canonical order",
+                )
+                .takes_value(false)
+                .conflicts_with_all(&["all", "board", "trace", "cache", "anytime"]),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .long("sample")
+                .value_name("N")
+                .help(
+                    "Print N solutions picked about uniformly at random, without \
+                 enumerating them all. The seed is printed to stderr",
+                )
+                .conflicts_with_all(&[
+                    "all",
+                    "board",
+                    "trace",
+                    "threads",
+                    "nondeterministic",
+                    "max-nodes",
+                    "anytime",
+                    "no-transpose",
+                    "fill",
+                    "orientations",
+                    "break-symmetry",
+                    "cache",
+                ]),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .requires("sample")
+                .help("The seed of --sample, to pick the same solutions again [default: random]"),
+        )
+        .arg(
+            Arg::with_name("cache")
+                .long("cache")
+                .value_name("FILE")
+                .help(
+                    "Look the solution up in the cache FILE and add it to the cache if it \
+                 isn't there",
+                )
+                .conflicts_with_all(&[
+                    "all",
+                    "board",
+                    "trace",
+                    "threads",
+                    "nondeterministic",
+                    "max-nodes",
+                    "no-transpose",
+                ]),
+        )
+        .arg(
+            Arg::with_name("forbid")
+                .long("forbid")
+                .value_name("PIECES")
+                .help("Tile the board with any pieces but these, e.g. 'S,Z'")
+                .conflicts_with_all(CONSTRAINT_CONFLICTS),
+        )
+        .arg(
+            Arg::with_name("require-at-least")
+                .long("require-at-least")
+                .value_name("PIECE=N")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Tile the board with any pieces, using at least N of PIECE, e.g. 'T=2'")
+                .conflicts_with_all(CONSTRAINT_CONFLICTS),
+        )
+        .arg(
+            Arg::with_name("require-at-most")
+                .long("require-at-most")
+                .value_name("PIECE=N")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Tile the board with any pieces, using at most N of PIECE, e.g. 'I=1'")
+                .conflicts_with_all(CONSTRAINT_CONFLICTS),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Prints statistics about the puzzles in the game")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("duplicates")
+                        .about("Lists puzzles that appear more than once"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("hint")
+                .about("Shows the placements that are the same in every solution")
+                .args(&puzzle_args())
+                .arg(
+                    Arg::with_name("pretty")
+                        .long("pretty")
+                        .help("Print the hint with box drawing characters")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("pretty-ascii")
+                        .long("pretty-ascii")
+                        .help("Print the hint with ASCII box drawing characters")
+                        .conflicts_with("pretty")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("numbered")
+                        .long("numbered")
+                        .help(
+                            "Number the top-left square of each placement in the order to \
+                         place them",
+                        )
+                        .conflicts_with_all(&["pretty", "pretty-ascii"])
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .possible_values(&["text", "svg", "png"])
+                        .default_value("text")
+                        .help(
+                            "The output format. 'png' writes an image to the file given by --out",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .required_if("format", "png")
+                        .help("The file to write the image to"),
+                )
+                .arg(
+                    Arg::with_name("cell-size")
+                        .long("cell-size")
+                        .value_name("PIXELS")
+                        .help("The size of a square in images"),
+                )
+                .arg(
+                    Arg::with_name("palette")
+                        .long("palette")
+                        .value_name("PALETTE")
+                        .help(
+                            "The colors of images: 'default', 'colorblind' or a palette \
+                             file of 'name = color' lines",
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("explain")
+                .about("Explains why the puzzle has a solution or not")
+                .args(&puzzle_args())
+                .arg(
+                    Arg::with_name("max-nodes")
+                        .long("max-nodes")
+                        .value_name("N")
+                        .default_value("10000000")
+                        .help("Give up searching for a solution after N nodes"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("capabilities")
+                .about("Prints the version and the features this build supports")
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Print them as a JSON object")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about(
+                    "Checks that the solution read from stdin tiles the board with exactly \
+                     the pieces",
+                )
+                .args(&puzzle_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("transpose")
+                .about(
+                    "Prints the puzzle with the rows and columns swapped. The board is turned \
+                     a quarter turn so the pieces are the same",
+                )
+                .args(&puzzle_args())
+                .arg(
+                    Arg::with_name("mirror")
+                        .long("mirror")
+                        .help(
+                            "Reflect the board in its diagonal instead, which swaps the J and \
+                             L pieces and the S and Z pieces",
+                        )
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("transform")
+                .about("Turns or reflects a solution")
+                .arg(
+                    Arg::with_name("symmetry")
+                        .help("How to transform the solution")
+                        .possible_values(&[
+                            "identity",
+                            "rotate90",
+                            "rotate180",
+                            "rotate270",
+                            "flip-vertical",
+                            "flip-horizontal",
+                            "transpose",
+                            "anti-transpose",
+                        ])
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file with the solution [default: stdin]")
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("pretty")
+                        .long("pretty")
+                        .help("Print the solution with box drawing characters")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about(
+                    "Prints two solutions side by side with the squares where their tilings \
+                     differ in lower case",
+                )
+                .arg(
+                    Arg::with_name("left")
+                        .help("A file with the first solution")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("right")
+                        .help("A file with the second solution")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("pretty")
+                        .long("pretty")
+                        .help(
+                            "Draw the solutions with box drawing characters and shade the \
+                             squares that differ",
+                        )
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("play")
+                .about("Place the pieces yourself, one move per line")
+                .args(&puzzle_args())
+                .arg(
+                    Arg::with_name("pretty")
+                        .long("pretty")
+                        .help("Print the board with box drawing characters")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("file")
+                .about("Solves the puzzles in a puzzle file")
+                .arg(
+                    Arg::with_name("path")
+                        .help("The puzzle file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("pretty")
+                        .long("pretty")
+                        .help("Print the solutions with box drawing characters")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify-archive")
+                .about(
+                    "Checks every solution in a puzzle file or JSON export and prints the \
+                     ones that aren't tilings of their puzzles",
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .help("The puzzle file")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Converts a CSV of levels to a puzzle file, solving each level")
+                .arg(
+                    Arg::with_name("path")
+                        .help("The CSV file with the section, color, number, size and pieces")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .help("Write the puzzle file to FILE instead of stdout")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("puzzles")
+                .about("Works with the puzzles in the game")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("Writes the puzzles in the game with their solutions")
+                        .arg(
+                            Arg::with_name("format")
+                                .long("format")
+                                .value_name("FORMAT")
+                                .possible_values(&["csv", "json", "toml"])
+                                .default_value("toml")
+                                .help(
+                                    "The output format. 'toml' writes a puzzle file, 'json' \
+                                 writes the same data as JSON and 'csv' writes the \
+                                 levels without their solutions.",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("out")
+                                .long("out")
+                                .value_name("FILE")
+                                .help("Write to FILE instead of stdout")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("booklet")
+                        .about("Writes a document with the solutions of a section")
+                        .arg(
+                            Arg::with_name("section")
+                                .value_name("SECTION")
+                                .help("The section, e.g. 'A', or a color in it, e.g. 'A yellow'")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("format")
+                                .long("format")
+                                .value_name("FORMAT")
+                                .possible_values(&["markdown", "html"])
+                                .default_value("markdown")
+                                .help("The output format"),
+                        )
+                        .arg(
+                            Arg::with_name("out")
+                                .long("out")
+                                .value_name("FILE")
+                                .help("Write to FILE instead of stdout")
+                                .takes_value(true),
+                        ),
+                ),
+        )
+        .arg(json_errors_arg())
+}
+
+// Run the subcommand given, if any. Returns whether there was one.
+fn run_subcommand(matches: &ArgMatches) -> bool {
+    match matches.subcommand() {
+        ("stats", Some(matches)) => {
+            stats(matches);
+        }
+        ("hint", Some(matches)) => {
+            hint(matches);
+        }
+        ("explain", Some(matches)) => {
+            let max_nodes = matches
+                .value_of("max-nodes")
+                .unwrap()
+                .parse()
+                .unwrap_or_else(|_| {
+                    exit_with_error("value of --max-nodes must be a non-negative integer")
+                });
+            print!("{}", explain(&puzzle_spec(matches), max_nodes));
+        }
+        ("capabilities", Some(matches)) => {
+            let capabilities = capabilities();
+            if matches.is_present("json") {
+                println!("{}", capabilities.to_json());
+            } else {
+                print!("{capabilities}");
+            }
+        }
+        ("verify", Some(matches)) => {
+            verify(matches);
+        }
+        ("transpose", Some(matches)) => {
+            transpose(matches);
+        }
+        ("transform", Some(matches)) => {
+            transform(matches);
+        }
+        ("diff", Some(matches)) => {
+            diff(matches);
+        }
+        ("play", Some(matches)) => {
+            play(matches);
+        }
+        ("file", Some(matches)) => {
+            solve_file(matches);
+        }
+        ("verify-archive", Some(matches)) => {
+            verify_archive(matches);
+        }
+        ("import", Some(matches)) => {
+            import(matches);
+        }
+        ("puzzles", Some(matches)) => match matches.subcommand() {
+            ("export", Some(matches)) => export(matches),
+            ("booklet", Some(matches)) => booklet(matches),
+            _ => {}
+        },
+        _ => return false,
+    }
+    true
+}
+
+// The options of the search for one solution
+fn solve_options(matches: &ArgMatches) -> SolveOptions {
+    let threads = matches.value_of("threads").map_or(1, |threads| {
+        parse_positive_number(threads)
+            .unwrap_or_else(|_| exit_with_error("value of --threads must be a positive integer"))
+    });
+    let max_nodes = matches.value_of("max-nodes").map(|max_nodes| {
+        max_nodes.parse().unwrap_or_else(|_| {
+            exit_with_error("value of --max-nodes must be a non-negative integer")
+        })
+    });
+    SolveOptions {
+        threads: threads as usize,
+        deterministic: !matches.is_present("nondeterministic"),
+        transpose: !matches.is_present("no-transpose"),
+        max_nodes,
+        fill: matches
+            .value_of("fill")
+            .map_or_else(FillStrategy::default, |fill| fill.parse().unwrap()),
+        orientations: matches
+            .value_of("orientations")
+            .map_or_else(OrientationWeights::default, |weights| {
+                weights.parse().unwrap_or_else(|err| exit_with_error(err))
+            }),
+        break_symmetry: matches.is_present("break-symmetry"),
+        ..SolveOptions::default()
+    }
+}
+
+// The solutions in the text, HTML, SVG or narrated format
+fn format_solutions(matches: &ArgMatches, solutions: &[Position]) -> String {
+    if let Some(format @ ("html" | "svg" | "narrate")) = matches.value_of("format") {
+        let palette = palette(matches);
+        let formatted: Vec<_> = solutions
+            .iter()
+            .map(|solution| match format {
+                "html" => solution.to_html_with_palette(&palette),
+                "svg" => solution.to_svg_with_palette(&palette),
+                _ => solution.narrate(),
+            })
+            .collect();
+        return formatted.join("\n");
+    }
+    let pretty = matches.is_present("pretty");
+    let pretty_ascii = matches.is_present("pretty-ascii");
+    let blocks: Vec<_> = solutions
+        .iter()
+        .map(|solution| {
+            if pretty {
+                format!("{solution:#}")
+            } else if pretty_ascii {
+                solution.to_ascii_art()
+            } else {
+                match matches.value_of("grid") {
+                    Some("kinds") => solution.to_kind_string(),
+                    Some("both") => {
+                        side_by_side(&[solution.to_string(), solution.to_kind_string()], 2, None)
+                    }
+                    _ => solution.to_string(),
+                }
+            }
+        })
+        .collect();
+    let max_width = matches.value_of("max-width").map(|width| {
+        parse_positive_number(width)
+            .unwrap_or_else(|_| exit_with_error("value of --max-width must be a positive integer"))
+            as usize
+    });
+    let columns = match matches.value_of("layout-columns") {
+        Some(columns) => parse_positive_number(columns)
+            .unwrap_or_else(|_| exit_with_error("value of --columns must be a positive integer"))
+            as usize,
+        // Fill the width
+        None if max_width.is_some() => usize::MAX,
+        None => 1,
+    };
+    side_by_side(&blocks, columns, max_width)
+}
+
+// Solve the puzzle within `budget` milliseconds, or else return the tiling
+// with the most pieces placed. A partial tiling is noted on stderr.
+fn solve_within(puzzle: PuzzleSpec, budget: &str) -> Position {
+    let budget = budget
+        .parse()
+        .unwrap_or_else(|_| exit_with_error("value of --anytime must be a non-negative integer"));
+    let piece_count = puzzle.pieces.count_all();
+    let result = solve_anytime(
+        puzzle.row_count,
+        puzzle.column_count,
+        puzzle.pieces,
+        Duration::from_millis(budget),
+    )
+    .unwrap_or_else(|err| exit_with_error(err));
+    if !result.complete {
+        let reason = if result.abandoned {
+            "No solution found in time"
+        } else {
+            "No solution"
+        };
+        eprintln!(
+            "{reason}. The best partial tiling has {} of {piece_count} pieces.",
+            result.piece_count()
+        );
+    }
+    result.position
+}
+
+// Solve the puzzle on the board given by the mask string `mask`
+fn solve_board_shape(matches: &ArgMatches, puzzle: PuzzleSpec, mask: &str) -> Vec<Position> {
+    let shape: BoardShape = mask.parse().unwrap_or_else(|err| exit_with_error(err));
+    if (shape.row_count(), shape.column_count()) != (puzzle.row_count, puzzle.column_count) {
+        exit_with_error("value of --board must have <rows> rows and <columns> columns");
+    }
+    let solve = Solve::board(puzzle.row_count, puzzle.column_count)
+        .piece_collection(puzzle.pieces)
+        .blocked(&shape.blocked_squares())
+        .split_regions(true);
+    let solve = if matches.is_present("all") {
+        solve.all()
+    } else {
+        solve
+    };
+    solve
+        .run()
+        .unwrap_or_else(|err| exit_with_error(err))
+        .solutions
+}
+
+// Solve the puzzle, writing the trace of the search to `path`
+fn solve_traced(matches: &ArgMatches, puzzle: PuzzleSpec, path: &str) -> Vec<Position> {
+    let max_solutions = if matches.is_present("all") {
+        u64::MAX
+    } else {
+        1
+    };
+    let file = File::create(path).unwrap_or_else(|err| exit_with_error(err));
+    trace::solve_traced(
+        puzzle.row_count,
+        puzzle.column_count,
+        puzzle.pieces,
+        max_solutions,
+        BufWriter::new(file),
+    )
+    .unwrap_or_else(|err| exit_with_error(err))
+}
+
+fn raster_options(matches: &ArgMatches) -> RasterOptions {
+    let mut options = RasterOptions::default();
+    if let Some(cell_size) = matches.value_of("cell-size") {
+        options.cell_size = parse_positive_number(cell_size)
+            .unwrap_or_else(|_| exit_with_error("value of --cell-size must be a positive integer"));
+    }
+    options.palette = palette(matches);
+    options
+}
+
+// The palette named by --palette or read from the file it gives
+fn palette(matches: &ArgMatches) -> Palette {
+    let Some(name) = matches.value_of("palette") else {
+        return Palette::default();
+    };
+    Palette::named(name).unwrap_or_else(|| {
+        std::fs::read_to_string(name)
+            .map_err(|err| {
+                CliError::new(
+                    "io",
+                    format!("Failed to read the palette file {name}. {err}"),
+                )
+            })
+            .and_then(|s| s.parse().map_err(CliError::from))
+            .unwrap_or_else(|err| exit_with_error(err))
+    })
+}
+
+// Write an animation of the search to the file given by --out
+fn animate(matches: &ArgMatches, puzzle: PuzzleSpec) {
+    // Enough for a minute at the frame delay
+    const MAX_FRAMES: usize = 1200;
+    const FRAME_DELAY: u16 = 5;
+
+    let backtracks = matches.is_present("backtracks");
+    let frames = animation::solve_frames(
+        puzzle.row_count,
+        puzzle.column_count,
+        puzzle.pieces,
+        backtracks,
+        MAX_FRAMES,
+    )
+    .unwrap_or_else(|err| exit_with_error(err));
+    let delay = if backtracks {
+        FRAME_DELAY
+    } else {
+        10 * FRAME_DELAY
+    };
+    File::create(matches.value_of("out").unwrap())
+        .map_err(ImageError::from)
+        .and_then(|file| animation::write_gif(&frames, &raster_options(matches), delay, file))
+        .unwrap_or_else(|err| exit_with_error(err));
+}
+
+// Write the solutions as PNG images to `path`, or to `path` with "-1", "-2"...
+// inserted before the extension if there are several
+fn write_png(solutions: &[Position], path: &str, options: &RasterOptions) -> io::Result<()> {
+    for (index, solution) in solutions.iter().enumerate() {
+        let path = if solutions.len() == 1 {
+            PathBuf::from(path)
+        } else {
+            let path = Path::new(path);
+            let mut name = path.file_stem().unwrap_or_default().to_os_string();
+            name.push(format!("-{}", index + 1));
+            if let Some(extension) = path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            path.with_file_name(name)
+        };
+        let image = solution.to_image(options);
+        PNGEncoder::new(File::create(path)?).encode(
+            &image,
+            image.width(),
+            image.height(),
+            ColorType::RGB(8),
+        )?;
+    }
+    Ok(())
+}
+
+// Write the solutions to stdout one after the other in the binary encoding
+fn write_binary(solutions: &[Position]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for solution in solutions {
+        stdout.write_all(&solution.encode())?;
+    }
+    stdout.flush()
+}
+
+// The flags that tile the board with any pieces within limits
+const CONSTRAINT_ARGS: &[&str] = &["forbid", "require-at-least", "require-at-most"];
+
+// The arguments that can't be combined with piece constraints
+const CONSTRAINT_CONFLICTS: &[&str] = &[
+    "tetrominoes",
+    "all",
+    "board",
+    "trace",
+    "threads",
+    "nondeterministic",
+    "max-nodes",
+    "anytime",
+    "no-transpose",
+    "cache",
+    "fill",
+    "orientations",
+    "break-symmetry",
+    "sample",
+];
+
+// `puzzle_args` with the tetrominoes optional when piece constraints are
+// given instead
+fn main_puzzle_args<'a, 'b>() -> [Arg<'a, 'b>; 3] {
+    let [rows, columns, tetrominoes] = puzzle_args();
+    [
+        rows,
+        columns,
+        tetrominoes.required_unless_one(CONSTRAINT_ARGS),
+    ]
+}
+
+// Tile the board given by <rows> and <columns> with pieces that satisfy the
+// piece constraint flags
+fn solve_with_constraints(matches: &ArgMatches) -> Result<Option<Position>, SolveOneError> {
+    let row_count = parse_positive_number(matches.value_of("rows").unwrap())
+        .unwrap_or_else(|_| exit_with_error("value of <rows> must be a positive integer"));
+    let column_count = parse_positive_number(matches.value_of("columns").unwrap())
+        .unwrap_or_else(|_| exit_with_error("value of <columns> must be a positive integer"));
+
+    let mut constraints = PieceConstraints::default();
+    if let Some(pieces) = matches.value_of("forbid") {
+        constraints
+            .forbid_all(pieces)
+            .unwrap_or_else(|err| exit_with_error(format!("value of --forbid is invalid. {err}")));
+    }
+    for value in matches.values_of("require-at-least").into_iter().flatten() {
+        let (piece, count) = PieceConstraints::parse_count(value).unwrap_or_else(|err| {
+            exit_with_error(format!("value of --require-at-least is invalid. {err}"))
+        });
+        constraints.require_at_least(piece, count);
+    }
+    for value in matches.values_of("require-at-most").into_iter().flatten() {
+        let (piece, count) = PieceConstraints::parse_count(value).unwrap_or_else(|err| {
+            exit_with_error(format!("value of --require-at-most is invalid. {err}"))
+        });
+        constraints.require_at_most(piece, count);
+    }
+    solve_constrained(row_count, column_count, &constraints)
+}
+
+// The arguments that define a puzzle
+fn puzzle_args<'a, 'b>() -> [Arg<'a, 'b>; 3] {
+    [
+        Arg::with_name("rows")
+            .help("The number of grid rows")
+            .required(true),
+        Arg::with_name("columns")
+            .help("The number of grid columns")
+            .required(true),
+        Arg::with_name("tetrominoes")
+            .help("The set tetrominoes to tile.")
+            .long_help(
+                "A string consisting the names of the one-sided tetrominoes to tile.\n\
+                 For example, 'IIOL' means two I tetrominoes, one O and one L tetromino.\n\
+                 See https://en.wikipedia.org/wiki/Tetromino#One-sided_tetrominoes\n\
+                 for images of the one-sided tetrominoes with names.",
+            )
+            .required(true),
+    ]
+}
+
+// Parse the arguments added by `puzzle_args`
+fn puzzle_spec(matches: &ArgMatches) -> PuzzleSpec {
+    let row_count = matches.value_of("rows").unwrap();
+    let row_count = parse_positive_number(row_count)
+        .unwrap_or_else(|_| exit_with_error("value of <rows> must be a positive integer"));
+
+    let col_count = matches.value_of("columns").unwrap();
+    let col_count = parse_positive_number(col_count)
+        .unwrap_or_else(|_| exit_with_error("value of <columns> must be a positive integer"));
+
+    let tetrominoes = matches.value_of("tetrominoes").unwrap();
+    let pieces: PieceCollection = tetrominoes.parse().unwrap_or_else(|_| {
+        exit_with_error(
+            "value of <tetrominoes> must be consist of letters I, O, T, J, L, S or Z only",
+        )
+    });
+
+    PuzzleSpec::new(row_count, col_count, pieces)
+}
+
+fn hint(matches: &ArgMatches) {
+    let puzzle = puzzle_spec(matches);
+    let hint = if matches.is_present("numbered") {
+        hints::numbered_position(&puzzle)
+    } else {
+        hints::forced_position(&puzzle).map(|hint| hint.map(AnnotatedPosition::from))
+    };
+    let Some(hint) = hint.unwrap_or_else(|err| exit_with_error(err)) else {
+        println!("No solution");
+        return;
+    };
+    match matches.value_of("format") {
+        Some("svg") => print!("{}", hint.to_svg_with_palette(&palette(matches))),
+        Some("png") => {
+            let image = hint.to_image(&raster_options(matches));
+            image
+                .save(matches.value_of("out").unwrap())
+                .unwrap_or_else(|err| exit_with_error(err));
+        }
+        _ if matches.is_present("pretty") => print!("{:#}", hint.position),
+        _ if matches.is_present("pretty-ascii") => print!("{}", hint.position.to_ascii_art()),
+        _ => print!("{hint}"),
+    }
+}
+
+// About uniformly random solutions of the puzzle, with the seed printed so
+// that they can be picked again with --seed
+fn sample(matches: &ArgMatches, puzzle: &PuzzleSpec, count: &str) -> Vec<Position> {
+    let count = parse_positive_number(count)
+        .unwrap_or_else(|_| exit_with_error("value of --sample must be a positive integer"));
+    let seed = matches.value_of("seed").map_or_else(random_seed, |seed| {
+        seed.parse()
+            .unwrap_or_else(|_| exit_with_error("value of --seed must be a non-negative integer"))
+    });
+    eprintln!("Seed: {seed}");
+    sample_solutions(
+        puzzle.row_count,
+        puzzle.column_count,
+        &puzzle.pieces,
+        count as usize,
+        seed,
+    )
+    .unwrap_or_else(|err| exit_with_error(err))
+}
+
+// A seed that differs from run to run. Only the low bits of the time matter.
+#[allow(clippy::cast_possible_truncation)]
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
+
+fn solve_cached(puzzle: PuzzleSpec, path: &str) -> Vec<Position> {
+    let mut cache = SolveCache::open(path).unwrap_or_else(|err| exit_with_error(err));
+    cache
+        .solve_one(puzzle.row_count, puzzle.column_count, puzzle.pieces)
+        .unwrap_or_else(|err| exit_with_error(err))
+        .into_iter()
+        .collect()
+}
+
+// Check the solution on stdin and print the first rule it breaks
+fn verify(matches: &ArgMatches) {
+    let puzzle = puzzle_spec(matches);
+    let solution = io::read_to_string(io::stdin()).unwrap_or_else(|err| exit_with_error(err));
+    let solution: Position = solution.parse().unwrap_or_else(|err| exit_with_error(err));
+    solution
+        .verify(puzzle.row_count, puzzle.column_count, &puzzle.pieces)
+        .unwrap_or_else(|err| exit_with_error(err));
+    println!("The solution is valid.");
+}
+
+// Print the puzzle on the board turned a quarter turn, or reflected in its
+// diagonal with --mirror
+fn transpose(matches: &ArgMatches) {
+    let symmetry = if matches.is_present("mirror") {
+        Symmetry::Transpose
+    } else {
+        Symmetry::Rotate90
+    };
+    let puzzle = puzzle_spec(matches).transformed(symmetry);
+    println!(
+        "{} {} {}",
+        puzzle.row_count,
+        puzzle.column_count,
+        puzzle.pieces.to_canonical_string()
+    );
+}
+
+// Print the solution in <file>, or read from stdin, transformed by <symmetry>
+fn transform(matches: &ArgMatches) {
+    let symmetry: Symmetry = matches.value_of("symmetry").unwrap().parse().unwrap();
+    let solution = match matches.value_of("file") {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_else(|err| {
+            exit_with_error(CliError::new("io", format!("Failed to read {path}. {err}")))
+        }),
+        None => io::read_to_string(io::stdin()).unwrap_or_else(|err| exit_with_error(err)),
+    };
+    let solution: Position = solution.parse().unwrap_or_else(|err| exit_with_error(err));
+    // Relabelled so the labels read top-left first again
+    let solution = transform::position(&solution, symmetry).relabel(LabelOrder::ByPosition);
+    if matches.is_present("pretty") {
+        print!("{solution:#}");
+    } else {
+        print!("{solution}");
+    }
+}
+
+// Compare the solutions in the files given by <left> and <right>
+fn diff(matches: &ArgMatches) {
+    let read = |name| -> Position {
+        let path = matches.value_of(name).unwrap();
+        let solution = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            exit_with_error(CliError::new("io", format!("Failed to read {path}. {err}")))
+        });
+        solution.parse().unwrap_or_else(|err| {
+            exit_with_error(CliError::new(
+                "invalid-solution-format",
+                format!("The solution in {path} is invalid. {err}"),
+            ))
+        })
+    };
+    // The same pieces get the same labels on both sides
+    let (left, right) = (
+        read("left").relabel(LabelOrder::ByPosition),
+        read("right").relabel(LabelOrder::ByPosition),
+    );
+    if (left.row_count(), left.column_count()) != (right.row_count(), right.column_count()) {
+        exit_with_error(CliError::new(
+            "invalid-argument",
+            "The solutions aren't the same size.",
+        ));
+    }
+
+    let diff = left.diff(&right);
+    if matches.is_present("pretty") {
+        print!("{diff:#}");
+    } else {
+        print!("{diff}");
+    }
+    match diff.count() {
+        0 => println!("The tilings are the same."),
+        1 => println!("1 square differs."),
+        count => println!("{count} squares differ."),
+    }
+}
+
+// Play the puzzle in the terminal. Each line is a placement in the replay
+// format, e.g. "T3@r1c2", or one of the commands "undo", "complete" and
+// "quit".
+fn play(matches: &ArgMatches) {
+    let puzzle = puzzle_spec(matches);
+    let mut game = Game::new(puzzle.row_count, puzzle.column_count, puzzle.pieces)
+        .unwrap_or_else(|err| exit_with_error(err));
+    let print = |position: &Position| {
+        if matches.is_present("pretty") {
+            print!("{position:#}");
+        } else {
+            print!("{position}");
+        }
+    };
+    print(&game.position());
+    for line in io::stdin().lines() {
+        let line = line.unwrap_or_else(|err| exit_with_error(err));
+        match line.trim() {
+            "" => continue,
+            "quit" => return,
+            "undo" => {
+                if game.undo().is_none() {
+                    println!("Nothing to undo");
+                    continue;
+                }
+            }
+            "complete" => {
+                match game.complete() {
+                    Some(solution) => print(&solution),
+                    None => println!("The pieces placed so far aren't part of a solution"),
+                }
+                continue;
+            }
+            placement => match placement.parse::<Placement>() {
+                Ok(placement) => {
+                    if let Err(err) = game.place(placement.piece, placement.row, placement.column) {
+                        println!("{err}");
+                        continue;
+                    }
+                }
+                Err(err) => {
+                    println!("{err}");
+                    continue;
+                }
+            },
+        }
+        print(&game.position());
+        if game.is_solved() {
+            println!("Solved");
+            return;
+        }
+    }
+}
+
+// Solve each puzzle in a puzzle file, printing its name and its first solution
+// in canonical order
+fn solve_file(matches: &ArgMatches) {
+    let records =
+        puzzles::load(matches.value_of("path").unwrap()).unwrap_or_else(|err| exit_with_error(err));
+    for (index, record) in records.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{record}");
+        match record.solve_first_canonical() {
+            Ok(Some(solution)) if matches.is_present("pretty") => print!("{solution:#}"),
+            Ok(Some(solution)) => print!("{solution}"),
+            Ok(None) => println!("No solution"),
+            Err(err) => println!("error: {err}"),
+        }
+    }
+}
+
+// Print the failures and totals of the archive, and exit with an error if
+// anything failed
+fn verify_archive(matches: &ArgMatches) {
+    let report = puzzles::verify_archive(matches.value_of("path").unwrap())
+        .unwrap_or_else(|err| exit_with_error(err));
+    print!("{report}");
+    if !report.is_valid() {
+        exit_with_error(CliError::new(
+            "invalid-archive",
+            format!(
+                "The archive has {} invalid puzzles or solutions.",
+                report.failures.len()
+            ),
+        ));
+    }
+}
+
+fn import(matches: &ArgMatches) {
+    let csv = std::fs::read_to_string(matches.value_of("path").unwrap())
+        .unwrap_or_else(|err| exit_with_error(err));
+    let records = puzzles::import_csv(&csv).unwrap_or_else(|err| exit_with_error(err));
+    match matches.value_of("out") {
+        Some(path) => puzzles::save(path, &records).unwrap_or_else(|err| exit_with_error(err)),
+        None => print!("{}", puzzles::to_toml(&records)),
+    }
+}
+
+fn export(matches: &ArgMatches) {
+    let records: Vec<_> = puzzles::all()
+        .iter()
+        .map(puzzles::PuzzleRecord::from)
+        .collect();
+    let output = match matches.value_of("format") {
+        Some("csv") => puzzles::to_csv(&records),
+        Some("json") => puzzles::to_json(&records),
+        _ => puzzles::to_toml(&records),
+    };
+    match matches.value_of("out") {
+        Some(path) => std::fs::write(path, output).unwrap_or_else(|err| exit_with_error(err)),
+        None => print!("{output}"),
+    }
+}
+
+fn booklet(matches: &ArgMatches) {
+    let name = matches.value_of("section").unwrap();
+    let section = puzzles::section(name);
+    if section.is_empty() {
+        exit_with_error(format!("there is no section '{name}'"));
+    }
+    let format = match matches.value_of("format") {
+        Some("html") => BookletFormat::Html,
+        _ => BookletFormat::Markdown,
+    };
+    // Title the booklet with the names as they are in the game
+    let first = section[0];
+    let title = if name.split_whitespace().count() > 1 {
+        format!("{} {}", first.section, first.color)
+    } else {
+        first.section.to_string()
+    };
+    let output = puzzles::booklet(&title, &section, format);
+    match matches.value_of("out") {
+        Some(path) => std::fs::write(path, output).unwrap_or_else(|err| exit_with_error(err)),
+        None => print!("{output}"),
+    }
+}
+
+fn stats(matches: &ArgMatches) {
+    if let ("duplicates", _) = matches.subcommand() {
+        for group in puzzles::duplicates() {
+            let names: Vec<_> = group.iter().map(ToString::to_string).collect();
+            println!("{}", names.join(" = "));
+        }
+    }
+}
+
+// A number argument that isn't a whole number greater than zero
+#[derive(Debug)]
+struct ParsePositiveNumberError;
+
+fn parse_positive_number(input: &str) -> Result<u32, ParsePositiveNumberError> {
+    match input.parse() {
+        Ok(0) | Err(_) => Err(ParsePositiveNumberError),
+        Ok(value) => Ok(value),
+    }
+}
+
+fn exit_with_error<T: Into<CliError>>(err: T) -> ! {
+    exit_with(err, 1)
+}
@@ -0,0 +1,352 @@
+// The parts of the WebSocket protocol (RFC 6455) needed to stream the search
+// to a browser: the opening handshake and unmasked text frames from the
+// server. Messages from the client other than the handshake are ignored.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, Read, Write};
+
+// Appended to the client's key to compute the accept key
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Limits on the handshake so that a client can't make the server buffer an
+// endless request
+const MAX_LINE_LENGTH: u64 = 8192;
+const MAX_HEADER_COUNT: usize = 64;
+
+// The opening request of a WebSocket connection
+pub struct Handshake {
+    // The path without the query, e.g. "/solve"
+    pub path: String,
+    // The query parameters. Values aren't percent-decoded.
+    pub query: HashMap<String, String>,
+    key: String,
+}
+
+#[derive(Debug)]
+pub enum HandshakeError {
+    Io(io::Error),
+    // The request isn't an HTTP GET request, or a line of it or the number of
+    // headers is over the limit
+    InvalidRequest,
+    // The request doesn't ask to upgrade to a WebSocket
+    NotWebSocket,
+}
+
+#[allow(clippy::enum_glob_use)]
+impl Display for HandshakeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use HandshakeError::*;
+        match self {
+            Io(err) => write!(f, "Failed to read the request. {err}"),
+            InvalidRequest => write!(f, "The request is not a valid HTTP GET request."),
+            NotWebSocket => write!(f, "The request is not a WebSocket handshake."),
+        }
+    }
+}
+
+impl Error for HandshakeError {}
+
+impl From<io::Error> for HandshakeError {
+    fn from(err: io::Error) -> Self {
+        HandshakeError::Io(err)
+    }
+}
+
+impl Handshake {
+    // Read the request line and the headers of the handshake
+    pub fn read<R: BufRead>(mut reader: R) -> Result<Self, HandshakeError> {
+        let mut line = String::new();
+        read_line(&mut reader, &mut line)?;
+        let mut parts = line.split_whitespace();
+        let (Some("GET"), Some(target), Some(_)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(HandshakeError::InvalidRequest);
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+
+        let mut upgrade = false;
+        let mut key = None;
+        for header_count in 0.. {
+            let mut line = String::new();
+            if read_line(&mut reader, &mut line)? == 0 {
+                return Err(HandshakeError::InvalidRequest);
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if header_count == MAX_HEADER_COUNT {
+                return Err(HandshakeError::InvalidRequest);
+            }
+            let (name, value) = line.split_once(':').ok_or(HandshakeError::InvalidRequest)?;
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("upgrade") {
+                upgrade = value.eq_ignore_ascii_case("websocket");
+            } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.to_string());
+            }
+        }
+        match key {
+            Some(key) if upgrade => Ok(Self {
+                path: path.to_string(),
+                query,
+                key,
+            }),
+            _ => Err(HandshakeError::NotWebSocket),
+        }
+    }
+
+    // Accept the connection. Frames may be written after this.
+    pub fn accept<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 101 Switching Protocols\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(&self.key)
+        )?;
+        writer.flush()
+    }
+}
+
+// Read a line of at most `MAX_LINE_LENGTH` bytes, counting the line ending
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> Result<usize, HandshakeError> {
+    let length = reader.by_ref().take(MAX_LINE_LENGTH + 1).read_line(line)?;
+    if length as u64 > MAX_LINE_LENGTH {
+        return Err(HandshakeError::InvalidRequest);
+    }
+    Ok(length)
+}
+
+// The value of the Sec-WebSocket-Accept header for the client's key
+#[must_use]
+pub fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{key}{GUID}").as_bytes()))
+}
+
+// Write `text` as a single text frame
+pub fn write_text<W: Write>(mut writer: W, text: &str) -> io::Result<()> {
+    write_frame(&mut writer, 0x1, text.as_bytes())
+}
+
+// Write a close frame with the normal closure status code
+pub fn write_close<W: Write>(mut writer: W) -> io::Result<()> {
+    write_frame(&mut writer, 0x8, &1000u16.to_be_bytes())
+}
+
+// The match bounds the length for each cast
+#[allow(clippy::cast_possible_truncation)]
+fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut header = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => header.push(len as u8),
+        len @ 126..=0xffff => {
+            header.push(126);
+            header.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            header.push(127);
+            header.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    writer.write_all(&header)?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+// A writer that sends each line written to it as a text message, without the
+// line break. `trace::solve_traced` can write to it directly.
+pub struct LineMessages<W: Write> {
+    writer: W,
+    line: Vec<u8>,
+}
+
+impl<W: Write> LineMessages<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            line: vec![],
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Write for LineMessages<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if byte == b'\n' {
+                let line = String::from_utf8(std::mem::take(&mut self.line))
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                write_text(&mut self.writer, &line)?;
+            } else {
+                self.line.push(byte);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    // An unfinished line is kept until its line break is written
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// The names follow the specification, FIPS 180-4
+#[allow(clippy::many_single_char_names)]
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(x);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, h) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{accept_key, base64, sha1, write_close, Handshake, HandshakeError, LineMessages};
+
+    #[test]
+    fn handshake() {
+        // The example from RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(sha1(b"")[..4], [0xda, 0x39, 0xa3, 0xee]);
+
+        let request = "GET /solve?rows=4&columns=4&pieces=LLZZ HTTP/1.1\r\n\
+                       Host: localhost\r\n\
+                       Upgrade: websocket\r\n\
+                       Connection: Upgrade\r\n\
+                       Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        let handshake = Handshake::read(request.as_bytes()).unwrap();
+        assert_eq!(handshake.path, "/solve");
+        assert_eq!(handshake.query["pieces"], "LLZZ");
+        let mut response = vec![];
+        handshake.accept(&mut response).unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101 "));
+        assert!(response.ends_with("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n"));
+
+        let error = |request: &str| Handshake::read(request.as_bytes()).err().unwrap();
+        assert!(matches!(
+            error("POST / HTTP/1.1\r\n\r\n"),
+            HandshakeError::InvalidRequest
+        ));
+        assert!(matches!(
+            error("GET / HTTP/1.1\r\n\r\n"),
+            HandshakeError::NotWebSocket
+        ));
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(8192));
+        assert!(matches!(error(&long_line), HandshakeError::InvalidRequest));
+        let many_headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(65));
+        assert!(matches!(
+            error(&many_headers),
+            HandshakeError::InvalidRequest
+        ));
+        let headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(64));
+        assert!(matches!(error(&headers), HandshakeError::NotWebSocket));
+    }
+
+    #[test]
+    fn frames() {
+        let mut messages = LineMessages::new(vec![]);
+        write!(messages, "ab\nc").unwrap();
+        writeln!(messages, "d").unwrap();
+        let mut frames = messages.into_inner();
+        assert_eq!(frames, b"\x81\x02ab\x81\x02cd");
+
+        frames.clear();
+        super::write_text(&mut frames, &"x".repeat(200)).unwrap();
+        assert_eq!(frames[..4], [0x81, 126, 0, 200]);
+        frames.clear();
+        write_close(&mut frames).unwrap();
+        assert_eq!(frames, [0x88, 2, 0x03, 0xe8]);
+    }
+}
@@ -0,0 +1,329 @@
+// Analysis of all the solutions of a puzzle.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::{
+    sample_solutions, validate, Board, FixedPiece, Palette, Piece, PieceCollection, SolveOneError,
+    Solver,
+};
+
+// The number of solutions in which each square is covered by each kind of
+// piece.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Heatmap {
+    row_count: u32,
+    column_count: u32,
+    solution_count: u64,
+    // Indexed by square in row-major order and then by `Piece`
+    counts: Vec<[u64; Piece::count()]>,
+}
+
+// Enumerate all the solutions of the puzzle and count how often each square is
+// covered by each kind of piece.
+pub fn heatmap(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<Heatmap, SolveOneError> {
+    solution_stats(row_count, column_count, pieces).map(|stats| stats.heatmap)
+}
+
+// A heatmap of `sample_count` solutions sampled with `sample_solutions`, for
+// puzzles with too many solutions to enumerate. Its solution count is the
+// number of samples.
+pub fn sampled_heatmap(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+    sample_count: usize,
+    seed: u64,
+) -> Result<Heatmap, SolveOneError> {
+    let samples = sample_solutions(row_count, column_count, pieces, sample_count, seed)?;
+    let mut counts = vec![[0; Piece::count()]; (row_count * column_count) as usize];
+    for sample in &samples {
+        let pieces = sample.label_pieces();
+        let labels = sample.squares.iter().filter(|&&label| label != b'\n');
+        for (square, label) in counts.iter_mut().zip(labels) {
+            square[pieces[label] as usize] += 1;
+        }
+    }
+    Ok(Heatmap::from_counts(
+        row_count,
+        column_count,
+        samples.len() as u64,
+        counts,
+    ))
+}
+
+// Counts over all the solutions of a puzzle, collected as they are found
+// without keeping the solutions
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SolutionStats {
+    // Indexed by `FixedPiece`
+    first_pieces: [u64; FixedPiece::count()],
+    heatmap: Heatmap,
+}
+
+// Enumerate all the solutions of the puzzle and collect `SolutionStats`
+pub fn solution_stats(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<SolutionStats, SolveOneError> {
+    validate(row_count, column_count, &pieces)?;
+
+    let mut stats = SolutionStats {
+        first_pieces: [0; FixedPiece::count()],
+        heatmap: Heatmap {
+            row_count,
+            column_count,
+            solution_count: 0,
+            counts: vec![[0; Piece::count()]; (row_count * column_count) as usize],
+        },
+    };
+    let mut solver = Solver::new(Board::new(row_count, column_count), pieces);
+    solver.for_each_solution(&mut |board| {
+        let heatmap = &mut stats.heatmap;
+        heatmap.solution_count += 1;
+        for (row, col, piece) in board.covered_squares() {
+            heatmap.counts[row * column_count as usize + col][piece as usize] += 1;
+        }
+        // The first piece placed covers the top-left square
+        let first = board.placement_of(board.stack[0]);
+        stats.first_pieces[first.piece as usize] += 1;
+    });
+    Ok(stats)
+}
+
+impl SolutionStats {
+    #[must_use]
+    pub fn solution_count(&self) -> u64 {
+        self.heatmap.solution_count
+    }
+
+    // The number of solutions with `fixed_piece` on the top-left square, the
+    // first choice of the search
+    #[must_use]
+    pub fn first_piece_count(&self, fixed_piece: FixedPiece) -> u64 {
+        self.first_pieces[fixed_piece as usize]
+    }
+
+    // The fixed pieces on the top-left square of any solution with their
+    // number of solutions, in `FixedPiece` order
+    #[must_use]
+    pub fn first_pieces(&self) -> Vec<(FixedPiece, u64)> {
+        FixedPiece::array()
+            .iter()
+            .map(|&fixed_piece| (fixed_piece, self.first_piece_count(fixed_piece)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    // How often each square is covered by each kind of piece
+    #[must_use]
+    pub fn heatmap(&self) -> &Heatmap {
+        &self.heatmap
+    }
+}
+
+impl Heatmap {
+    pub(crate) fn from_counts(
+        row_count: u32,
+        column_count: u32,
+        solution_count: u64,
+        counts: Vec<[u64; Piece::count()]>,
+    ) -> Self {
+        Heatmap {
+            row_count,
+            column_count,
+            solution_count,
+            counts,
+        }
+    }
+
+    #[must_use]
+    pub fn solution_count(&self) -> u64 {
+        self.solution_count
+    }
+
+    // The number of solutions in which the square at `row`, `column` is
+    // covered by `piece`.
+    #[must_use]
+    pub fn count(&self, row: u32, column: u32, piece: Piece) -> u64 {
+        self.counts[(row * self.column_count + column) as usize][piece as usize]
+    }
+
+    // The piece that covers the square at `row`, `column` in the most
+    // solutions, the first in `Piece` order if there is a tie. Returns `None`
+    // if there are no solutions.
+    #[must_use]
+    pub fn most_frequent(&self, row: u32, column: u32) -> Option<Piece> {
+        let counts = &self.counts[(row * self.column_count + column) as usize];
+        Piece::array()
+            .iter()
+            .rev()
+            .copied()
+            .filter(|&p| counts[p as usize] > 0)
+            .max_by_key(|&p| counts[p as usize])
+    }
+
+    // Returns true if the square at `row`, `column` is covered by the same
+    // kind of piece in every solution.
+    #[must_use]
+    pub fn is_forced(&self, row: u32, column: u32) -> bool {
+        self.most_frequent(row, column)
+            .is_some_and(|p| self.count(row, column, p) == self.solution_count)
+    }
+
+    // Write the counts as CSV with one line per square.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("row,column");
+        for piece in &Piece::array() {
+            write!(csv, ",{piece:?}").unwrap();
+        }
+        csv.push('\n');
+        for row in 0..self.row_count {
+            for column in 0..self.column_count {
+                write!(csv, "{row},{column}").unwrap();
+                for &piece in &Piece::array() {
+                    write!(csv, ",{}", self.count(row, column, piece)).unwrap();
+                }
+                csv.push('\n');
+            }
+        }
+        csv
+    }
+}
+
+impl Heatmap {
+    // The heatmap as `Display` shows it, with each piece in its color in
+    // `palette` as a 24-bit ANSI escape code
+    #[must_use]
+    pub fn to_ansi(&self, palette: &Palette) -> String {
+        self.grid(Some(palette))
+    }
+
+    fn grid(&self, palette: Option<&Palette>) -> String {
+        let mut grid = String::new();
+        for row in 0..self.row_count {
+            for column in 0..self.column_count {
+                let Some(piece) = self.most_frequent(row, column) else {
+                    grid.push('.');
+                    continue;
+                };
+                let name = format!("{piece:?}");
+                let name = if self.is_forced(row, column) {
+                    name
+                } else {
+                    name.to_ascii_lowercase()
+                };
+                match palette {
+                    Some(palette) => {
+                        let [r, g, b] = palette.piece(piece);
+                        write!(grid, "\x1b[38;2;{r};{g};{b}m{name}\x1b[0m").unwrap();
+                    }
+                    None => grid.push_str(&name),
+                }
+            }
+            grid.push('\n');
+        }
+        grid
+    }
+}
+
+// Displays the most frequent piece on each square, in upper case if the square
+// is forced and lower case otherwise. Squares are shown as '.' if there are no
+// solutions. The alternate format is `to_ansi` with the default palette.
+impl Display for Heatmap {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let palette = Palette::default();
+        write!(f, "{}", self.grid(f.alternate().then_some(&palette)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{heatmap, sampled_heatmap, solution_stats};
+    use crate::Palette;
+    use crate::{solve_all, FixedPiece, Piece, PieceCollection};
+
+    #[test]
+    fn counts() {
+        let heatmap = heatmap(4, 4, "IIII".parse().unwrap()).unwrap();
+
+        assert_eq!(heatmap.solution_count(), 2);
+        assert_eq!(heatmap.count(2, 1, Piece::I), 2);
+        assert_eq!(heatmap.count(2, 1, Piece::O), 0);
+        assert!(heatmap.is_forced(2, 1));
+        assert_eq!(heatmap.to_string(), "IIII\nIIII\nIIII\nIIII\n");
+    }
+
+    #[test]
+    fn display() {
+        let heatmap = heatmap(4, 4, "IIOO".parse().unwrap()).unwrap();
+
+        assert_eq!(heatmap.solution_count(), 6);
+        assert_eq!(heatmap.count(0, 0, Piece::I), 4);
+        assert_eq!(heatmap.count(0, 1, Piece::I), 3);
+        assert_eq!(heatmap.count(0, 1, Piece::O), 3);
+        assert_eq!(heatmap.count(1, 1, Piece::O), 4);
+        assert_eq!(heatmap.to_string(), "iiii\niooi\niooi\niiii\n");
+        assert!(format!("{heatmap:#}").starts_with("\x1b[38;2;0;255;255mi\x1b[0m"));
+        assert!(heatmap
+            .to_ansi(&Palette::colorblind())
+            .contains("\x1b[38;2;240;228;66mo\x1b[0m"));
+    }
+
+    #[test]
+    fn stats() {
+        let stats = solution_stats(4, 4, "IIOO".parse().unwrap()).unwrap();
+        assert_eq!(stats.solution_count(), 6);
+        assert_eq!(
+            stats.first_pieces(),
+            [
+                (FixedPiece::I1, 2),
+                (FixedPiece::I2, 2),
+                (FixedPiece::O1, 2)
+            ]
+        );
+        assert_eq!(
+            stats.heatmap(),
+            &heatmap(4, 4, "IIOO".parse().unwrap()).unwrap()
+        );
+
+        // The same counts as from all the solutions
+        let pieces: PieceCollection = "TTLSZL".parse().unwrap();
+        let stats = solution_stats(4, 6, pieces.clone()).unwrap();
+        let solutions = solve_all(4, 6, pieces).unwrap();
+        assert_eq!(stats.solution_count(), solutions.len() as u64);
+        for &(fixed_piece, count) in &stats.first_pieces() {
+            let expected = solutions
+                .iter()
+                .filter(|solution| solution.placements()[0].piece == fixed_piece)
+                .count();
+            assert_eq!(count, expected as u64);
+        }
+    }
+
+    #[test]
+    fn sampled() {
+        let heatmap = sampled_heatmap(4, 4, &"IIOO".parse().unwrap(), 10, 1).unwrap();
+        assert_eq!(heatmap.solution_count(), 6);
+        assert_eq!(heatmap.to_string(), "iiii\niooi\niooi\niiii\n");
+    }
+
+    #[test]
+    fn csv() {
+        let heatmap = heatmap(1, 4, "I".parse().unwrap()).unwrap();
+
+        assert_eq!(
+            heatmap.to_csv(),
+            "row,column,I,O,T,J,L,S,Z\n\
+             0,0,1,0,0,0,0,0,0\n\
+             0,1,1,0,0,0,0,0,0\n\
+             0,2,1,0,0,0,0,0,0\n\
+             0,3,1,0,0,0,0,0,0\n"
+        );
+    }
+}
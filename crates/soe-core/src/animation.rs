@@ -0,0 +1,139 @@
+// Animations of the search for a solution, rendered as animated GIFs.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use image::gif::{Encoder, Frame};
+use image::ImageResult;
+
+use crate::{
+    validate, Board, PieceCollection, Position, RasterOptions, SearchStep, SolveOneError, Solver,
+};
+
+// The positions of the search for the first solution in canonical order, one
+// per frame of an animation. The first frame is the empty board. Without
+// `backtracks`, the pieces of the solution are placed one frame at a time.
+// With `backtracks`, every placement and backtrack of the search is a frame,
+// up to `max_frames` frames; the last frame is the solution if there is one.
+pub fn solve_frames(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    backtracks: bool,
+    max_frames: usize,
+) -> Result<Vec<Position>, SolveOneError> {
+    validate(row_count, column_count, &pieces)?;
+    let board = Board::new(row_count, column_count);
+    let mut frames = vec![board.position()];
+    let mut solution = None;
+
+    let mut trace = |_: SearchStep, board: &Board| {
+        if backtracks && frames.len() + 1 < max_frames {
+            frames.push(board.position());
+        }
+    };
+    Solver::new(board.clone(), pieces)
+        .with_max_solutions(1)
+        .with_trace(&mut trace)
+        .for_each_solution(&mut |board| solution = Some(board.clone()));
+
+    let Some(solution) = solution else {
+        return Ok(frames);
+    };
+    if backtracks {
+        frames.push(solution.position());
+    } else {
+        let mut board = board;
+        for placement in solution.position().placements() {
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .unwrap();
+            frames.push(board.position());
+        }
+    }
+    Ok(frames)
+}
+
+// Write `frames` as an animated GIF, showing each frame for `delay`
+// hundredths of a second and the last frame for a second longer.
+pub fn write_gif<W: Write>(
+    frames: &[Position],
+    options: &RasterOptions,
+    delay: u16,
+    writer: W,
+) -> ImageResult<()> {
+    let mut encoder = Encoder::new(writer);
+    for (index, position) in frames.iter().enumerate() {
+        let image = position.to_image(options);
+        // The renderer uses a handful of colors, so the frame is indexed
+        // exactly instead of being quantized
+        let mut palette = vec![];
+        let mut indexes = HashMap::new();
+        let pixels: Vec<u8> = image
+            .pixels()
+            .map(|pixel| {
+                *indexes.entry(pixel.data).or_insert_with(|| {
+                    palette.extend_from_slice(&pixel.data);
+                    (palette.len() / 3 - 1) as u8
+                })
+            })
+            .collect();
+        let mut frame = Frame::from_palette_pixels(
+            image.width() as u16,
+            image.height() as u16,
+            &pixels,
+            &palette,
+            None,
+        );
+        frame.delay = if index + 1 == frames.len() {
+            delay + 100
+        } else {
+            delay
+        };
+        encoder.encode(&frame)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_frames, write_gif};
+    use crate::RasterOptions;
+
+    #[test]
+    fn frames() {
+        let frames = solve_frames(2, 4, "IO".parse().unwrap(), false, 0).unwrap();
+        assert_eq!(frames.len(), 1);
+
+        let frames = solve_frames(4, 4, "LLZZ".parse().unwrap(), false, 0).unwrap();
+        let frames: Vec<_> = frames.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            frames,
+            [
+                "....\n....\n....\n....\n",
+                "AAA.\nA...\n....\n....\n",
+                "AAAB\nA.BB\n..B.\n....\n",
+                "AAAB\nACBB\nCCB.\nC...\n",
+                "AAAB\nACBB\nCCBD\nCDDD\n",
+            ]
+        );
+
+        let frames = solve_frames(4, 4, "LLZZ".parse().unwrap(), true, 1000).unwrap();
+        assert!(frames.len() > 5);
+        assert_eq!(frames[0].to_string(), "....\n....\n....\n....\n");
+        assert_eq!(
+            frames.last().unwrap().to_string(),
+            "AAAB\nACBB\nCCBD\nCDDD\n"
+        );
+        let frames = solve_frames(4, 4, "LLZZ".parse().unwrap(), true, 4).unwrap();
+        assert_eq!(frames.len(), 4);
+    }
+
+    #[test]
+    fn gif() {
+        let frames = solve_frames(4, 4, "LLZZ".parse().unwrap(), false, 0).unwrap();
+        let mut gif = vec![];
+        write_gif(&frames, &RasterOptions::default(), 50, &mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+    }
+}
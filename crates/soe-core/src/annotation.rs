@@ -0,0 +1,150 @@
+// Marks drawn on top of the squares of a position, e.g. to show which piece to
+// place next and where in a hint. The text rendering puts the mark in place
+// of the label of the square:
+//
+//     AA1.
+//     AA.>
+//
+// and `to_svg` and `to_image` draw them over the rendered position.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::Position;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Annotation {
+    // A number, e.g. the order in which to place the pieces. Written as `+`
+    // in text when it has more than one digit.
+    Number(u32),
+    Arrow(Arrow),
+    // A dot, written as `*` in text
+    Marker,
+}
+
+// The direction an arrow points in
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Arrow {
+    // The row and column offsets of a step in the direction
+    pub(crate) fn offsets(self) -> (i32, i32) {
+        match self {
+            Arrow::Up => (-1, 0),
+            Arrow::Down => (1, 0),
+            Arrow::Left => (0, -1),
+            Arrow::Right => (0, 1),
+        }
+    }
+}
+
+impl Annotation {
+    // The character the annotation is written as in text
+    #[must_use]
+    pub fn to_char(self) -> char {
+        match self {
+            Annotation::Number(number) => std::char::from_digit(number, 10).unwrap_or('+'),
+            Annotation::Arrow(Arrow::Up) => '^',
+            Annotation::Arrow(Arrow::Down) => 'v',
+            Annotation::Arrow(Arrow::Left) => '<',
+            Annotation::Arrow(Arrow::Right) => '>',
+            Annotation::Marker => '*',
+        }
+    }
+}
+
+// A position with at most one annotation on each square
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedPosition {
+    pub position: Position,
+    // The row, column and annotation of each annotated square, in the order
+    // they were added
+    annotations: Vec<(u32, u32, Annotation)>,
+}
+
+impl From<Position> for AnnotatedPosition {
+    fn from(position: Position) -> Self {
+        Self {
+            position,
+            annotations: vec![],
+        }
+    }
+}
+
+impl AnnotatedPosition {
+    // Put `annotation` on the square at `row`, `column`, replacing the
+    // annotation already there. The square must be on the board.
+    pub fn annotate(&mut self, row: u32, column: u32, annotation: Annotation) {
+        assert!(
+            row < self.position.row_count() && column < self.position.column_count(),
+            "square off the board"
+        );
+        match self
+            .annotations
+            .iter_mut()
+            .find(|(r, c, _)| (*r, *c) == (row, column))
+        {
+            Some(existing) => existing.2 = annotation,
+            None => self.annotations.push((row, column, annotation)),
+        }
+    }
+
+    // The same with `annotation` on the square at `row`, `column`
+    #[must_use]
+    pub fn with(mut self, row: u32, column: u32, annotation: Annotation) -> Self {
+        self.annotate(row, column, annotation);
+        self
+    }
+
+    #[must_use]
+    pub fn annotation(&self, row: u32, column: u32) -> Option<Annotation> {
+        self.annotations
+            .iter()
+            .find(|(r, c, _)| (*r, *c) == (row, column))
+            .map(|&(_, _, annotation)| annotation)
+    }
+
+    // The annotated squares as (row, column, annotation), in the order they
+    // were annotated
+    #[must_use]
+    pub fn annotations(&self) -> &[(u32, u32, Annotation)] {
+        &self.annotations
+    }
+}
+
+impl Display for AnnotatedPosition {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let width = self.position.column_count() as usize + 1;
+        let mut text: Vec<char> = self.position.squares.iter().map(|&s| s as char).collect();
+        for &(row, column, annotation) in &self.annotations {
+            text[row as usize * width + column as usize] = annotation.to_char();
+        }
+        write!(f, "{}", text.into_iter().collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnotatedPosition, Annotation, Arrow};
+    use crate::Position;
+
+    #[test]
+    fn annotate() {
+        let position = Position::from_replay(2, 4, "O1@r0c0").unwrap();
+        let mut annotated = AnnotatedPosition::from(position)
+            .with(0, 2, Annotation::Number(1))
+            .with(1, 3, Annotation::Arrow(Arrow::Right))
+            .with(1, 0, Annotation::Number(12));
+        annotated.annotate(1, 0, Annotation::Marker);
+
+        assert_eq!(annotated.to_string(), "AA1.\n*A.>\n");
+        assert_eq!(annotated.annotation(1, 0), Some(Annotation::Marker));
+        assert_eq!(annotated.annotation(0, 0), None);
+        assert_eq!(annotated.annotations().len(), 3);
+        assert_eq!(Annotation::Number(12).to_char(), '+');
+    }
+}
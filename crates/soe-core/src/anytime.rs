@@ -0,0 +1,148 @@
+// An anytime search, for programs that must show something within a time
+// budget. If no solution is found in time, the tiling with the most pieces
+// placed that the search reached is returned instead.
+//
+//     let result = solve_anytime(8, 8, pieces, Duration::from_millis(200))?;
+//     if !result.complete { ... }
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    validate, Board, FixedPiece, PieceCollection, Position, SolveOneError, Solver, PIECE_MAP,
+};
+
+#[derive(Clone, Debug)]
+pub struct AnytimeSolution {
+    // A solution if `complete`, otherwise the partial tiling with the most
+    // pieces placed, which may be the empty board
+    pub position: Position,
+    // True if `position` covers the whole board
+    pub complete: bool,
+    // True if the budget ran out before the search finished. A search that
+    // finished without a complete tiling shows the puzzle has no solution.
+    pub abandoned: bool,
+}
+
+impl AnytimeSolution {
+    // The number of pieces placed in `position`
+    #[must_use]
+    pub fn piece_count(&self) -> usize {
+        self.position.placements().len()
+    }
+}
+
+// Search for the first solution in canonical order for at most `budget`. The
+// solution is the one `solve_first_canonical` gives if the search completes.
+pub fn solve_anytime(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    budget: Duration,
+) -> Result<AnytimeSolution, SolveOneError> {
+    validate(row_count, column_count, &pieces)?;
+
+    let deadline = Instant::now() + budget;
+    let stop = || Instant::now() >= deadline;
+    let board = Board::new(row_count, column_count);
+    let mut best = (0, board.position());
+    let mut solver = Solver::new(board, pieces).with_stop(&stop);
+    let solution = solver.solve_anytime(0, &mut best);
+
+    Ok(match solution {
+        Some(position) => AnytimeSolution {
+            position,
+            complete: true,
+            abandoned: false,
+        },
+        None => AnytimeSolution {
+            position: best.1,
+            complete: false,
+            abandoned: solver.stopped,
+        },
+    })
+}
+
+impl Solver<'_> {
+    // `solve_one`, keeping the position with the most pieces placed in
+    // `best` along with its piece count. `placed` is the number of pieces on
+    // the board.
+    fn solve_anytime(&mut self, placed: usize, best: &mut (usize, Position)) -> Option<Position> {
+        if self.should_stop() {
+            return None;
+        }
+        if self.board.is_complete() {
+            return Some(self.board.position());
+        }
+        if placed > best.0 {
+            // The best position is replaced often, so its buffer is reused
+            best.0 = placed;
+            self.board.position_into(&mut best.1.squares);
+        }
+
+        let mut candidates = self.board.candidates() & self.pieces.fixed_pieces();
+        while candidates != 0 {
+            let r = FixedPiece::from(candidates.trailing_zeros() as usize);
+            candidates &= candidates - 1;
+            let t = PIECE_MAP[r as usize];
+            if self.board.push(r).is_ok() {
+                self.pieces.remove(t);
+                let solution = self.solve_anytime(placed + 1, best);
+                if solution.is_some() {
+                    return solution;
+                }
+                self.board.pop();
+                self.pieces.add(t);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::solve_anytime;
+    use crate::solve_first_canonical;
+
+    #[test]
+    fn complete() {
+        let pieces = || "TTLSZL".parse().unwrap();
+        let result = solve_anytime(4, 6, pieces(), Duration::from_secs(10)).unwrap();
+        let expected = solve_first_canonical(4, 6, pieces()).unwrap().unwrap();
+
+        assert!(result.complete);
+        assert!(!result.abandoned);
+        assert!(result.position == expected);
+        assert_eq!(result.piece_count(), 6);
+    }
+
+    #[test]
+    fn no_solution() {
+        // T pieces can't cover the corners of a board two rows high
+        let result = solve_anytime(2, 8, "TTTT".parse().unwrap(), Duration::from_secs(10)).unwrap();
+
+        assert!(!result.complete);
+        assert!(!result.abandoned);
+        assert!(result.piece_count() > 0);
+        assert_eq!(result.position.to_string().matches('.').count() % 4, 0);
+    }
+
+    #[test]
+    fn budget_runs_out() {
+        // A puzzle with no solution that takes longer than the budget
+        let pieces = "IITTTLLLJJSZ".parse().unwrap();
+        let result = solve_anytime(4, 12, pieces, Duration::from_millis(10)).unwrap();
+
+        assert!(!result.complete);
+        assert!(result.abandoned);
+        assert!(result.piece_count() > 0);
+    }
+
+    #[test]
+    fn invalid_puzzle() {
+        let result = solve_anytime(3, 3, "LL".parse().unwrap(), Duration::from_secs(1));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,4 @@
+// Operations on the packed bitboard of `Board`. The board itself is defined
+// in the crate root next to the solver.
+
+pub mod transform;
@@ -0,0 +1,207 @@
+// The symmetries of a rectangle applied to the packed bitboard, placements and
+// positions directly. A `row_count` by `column_count` board is packed row by
+// row with a border bit after each row, so square (row, column) is bit
+// `row * (column_count + 1) + column`.
+
+use crate::geometry::{Cell, Shape, Symmetry};
+use crate::{Board, Placement, Position};
+
+// The number of rows and columns of the image of a `row_count` by
+// `column_count` board
+#[must_use]
+pub fn dimensions(symmetry: Symmetry, row_count: u32, column_count: u32) -> (u32, u32) {
+    if symmetry.swaps_dimensions() {
+        (column_count, row_count)
+    } else {
+        (row_count, column_count)
+    }
+}
+
+// The image of the squares set in `bits`. Border bits and bits past the last
+// row are ignored and clear in the result.
+#[must_use]
+pub fn squares(bits: u64, symmetry: Symmetry, row_count: u32, column_count: u32) -> u64 {
+    let (_, image_column_count) = dimensions(symmetry, row_count, column_count);
+    let mut image = 0;
+    for row in 0..row_count {
+        for column in 0..column_count {
+            if bits & 1 << (row * (column_count + 1) + column) == 0 {
+                continue;
+            }
+            let (row, column) = apply(symmetry, row, column, row_count, column_count);
+            image |= 1 << (row * (image_column_count + 1) + column);
+        }
+    }
+    image
+}
+
+// The image of a placement. Reflections turn J pieces into L pieces and S
+// pieces into Z pieces and the reverse.
+#[must_use]
+pub fn placement(
+    placement: Placement,
+    symmetry: Symmetry,
+    row_count: u32,
+    column_count: u32,
+) -> Placement {
+    let shape =
+        Shape::from(placement.piece).translate(placement.row as i32, placement.column as i32);
+    let cells = shape.0.map(|Cell(row, column)| {
+        let (row, column) = apply(symmetry, row as u32, column as u32, row_count, column_count);
+        Cell(row as i32, column as i32)
+    });
+    let shape = Shape(cells);
+    let piece = shape.fixed_piece().unwrap();
+    let Cell(row, column) = cells.iter().copied().min().unwrap();
+    Placement {
+        piece,
+        row: row as u32,
+        column: column as u32,
+    }
+}
+
+// The image of a board with its blocked squares and pieces. The pieces keep
+// their order on the stack.
+#[must_use]
+pub fn board(board: &Board, symmetry: Symmetry) -> Board {
+    let (row_count, column_count) = (board.height as u32, board.width as u32 - 1);
+    let (image_row_count, image_column_count) = dimensions(symmetry, row_count, column_count);
+    let mut image = Board::new(image_row_count, image_column_count);
+
+    let pieces = &board.stack[..board.stack_count];
+    let covered_by_pieces = pieces.iter().fold(0, |bits, &(bitmap, _)| bits | bitmap);
+    image.bits |= squares(
+        board.bits & !covered_by_pieces,
+        symmetry,
+        row_count,
+        column_count,
+    );
+    for &entry in pieces {
+        let Placement { piece, row, column } =
+            placement(board.placement_of(entry), symmetry, row_count, column_count);
+        image.place_at(piece, row, column).unwrap();
+    }
+    image
+}
+
+// The image of a position. Labels are kept.
+#[must_use]
+pub fn position(position: &Position, symmetry: Symmetry) -> Position {
+    let (row_count, column_count) = (position.row_count(), position.column_count());
+    let (image_row_count, image_column_count) = dimensions(symmetry, row_count, column_count);
+    let mut squares = vec![b'\n'; (image_row_count * (image_column_count + 1)) as usize];
+    for row in 0..row_count {
+        for column in 0..column_count {
+            let (image_row, image_column) = apply(symmetry, row, column, row_count, column_count);
+            squares[(image_row * (image_column_count + 1) + image_column) as usize] =
+                position.squares[(row * (column_count + 1) + column) as usize];
+        }
+    }
+    Position { squares }
+}
+
+// The image of the square at `row`, `column`
+fn apply(
+    symmetry: Symmetry,
+    row: u32,
+    column: u32,
+    row_count: u32,
+    column_count: u32,
+) -> (u32, u32) {
+    let cell = Cell(row as i32, column as i32);
+    let Cell(row, column) = symmetry.apply(cell, row_count as i32, column_count as i32);
+    (row as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{board, dimensions, placement, position, squares};
+    use crate::geometry::Symmetry::{self, *};
+    use crate::FixedPiece::*;
+    use crate::{Board, Placement, Position};
+
+    #[test]
+    fn transform_squares() {
+        // The top-left square and the square right of it on a 2 by 3 board
+        let bits = 0b11;
+        assert_eq!(squares(bits, Identity, 2, 3), bits);
+        // 3 by 2: the top-right square and the one below it
+        assert_eq!(squares(bits, Rotate90, 2, 3), 1 << 1 | 1 << 4);
+        assert_eq!(squares(bits, Transpose, 2, 3), 1 | 1 << 3);
+        assert_eq!(squares(bits, FlipVertical, 2, 3), 0b11 << 4);
+        // Border bits are dropped
+        assert_eq!(squares(1 << 3 | u64::MAX << 8, Identity, 2, 3), 0);
+        assert_eq!(dimensions(Rotate270, 2, 3), (3, 2));
+
+        for &symmetry in &Symmetry::array() {
+            let inverse = match symmetry {
+                Rotate90 => Rotate270,
+                Rotate270 => Rotate90,
+                symmetry => symmetry,
+            };
+            let (row_count, column_count) = dimensions(symmetry, 2, 3);
+            let bits = 0b101_0110;
+            let image = squares(bits, symmetry, 2, 3);
+            assert_eq!(image.count_ones(), 4);
+            assert_eq!(squares(image, inverse, row_count, column_count), bits);
+        }
+    }
+
+    #[test]
+    fn transform_placement() {
+        let l2 = Placement {
+            piece: L2,
+            row: 0,
+            column: 0,
+        };
+        assert_eq!(
+            placement(l2, Transpose, 2, 4),
+            Placement {
+                piece: J3,
+                row: 0,
+                column: 0
+            }
+        );
+        assert_eq!(
+            placement(l2, Rotate180, 2, 4),
+            Placement {
+                piece: L4,
+                row: 0,
+                column: 3
+            }
+        );
+        assert_eq!(
+            placement(l2, FlipHorizontal, 2, 4),
+            Placement {
+                piece: J4,
+                row: 0,
+                column: 1
+            }
+        );
+    }
+
+    #[test]
+    fn transform_board_and_position() {
+        let mut original = Board::new(2, 4);
+        original.block(1, 3).unwrap();
+        original.place_at(L2, 0, 0).unwrap();
+        let image = board(&original, Rotate90);
+        assert_eq!(image.position().to_string(), "AA\n.A\n.A\n#.\n");
+        assert!(position(&original.position(), Rotate90) == image.position());
+        let back = board(&image, Rotate270);
+        assert!(back.position() == original.position());
+
+        let solution = Position::from_replay(4, 4, "L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3").unwrap();
+        for &symmetry in &Symmetry::array() {
+            let image = position(&solution, symmetry);
+            let mut board = Board::new(4, 4);
+            for placement in solution.placements() {
+                let placement = super::placement(placement, symmetry, 4, 4);
+                board
+                    .place_at(placement.piece, placement.row, placement.column)
+                    .unwrap();
+            }
+            assert!(board.position() == image);
+        }
+    }
+}
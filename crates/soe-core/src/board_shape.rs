@@ -0,0 +1,251 @@
+// Irregular boards: rectangles with some squares blocked. Shapes are written
+// as mask strings in one of two forms.
+//
+// The run-length form lists the rows from the top, separated by '/'. A row is
+// a sequence of '.' for open squares and '#' for blocked squares, each
+// optionally preceded by a repeat count. "3.#/4." is a 2 by 4 board with the
+// top-right square blocked.
+//
+// The hex form is "<rows>x<columns>:<mask>", where the mask is a hexadecimal
+// number in which bit `row * columns + column` is set if the square is open.
+// "2x4:f7" is the same board as above. It's limited to 64 squares.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BoardShape {
+    row_count: u32,
+    column_count: u32,
+    // Whether each square is blocked, in row-major order
+    blocked: Vec<bool>,
+}
+
+#[derive(Debug)]
+pub enum ParseBoardShapeError {
+    // The string isn't in either form
+    InvalidSyntax,
+    // The rows of the run-length form have different lengths
+    RaggedRows,
+    // The hex form has more than 64 squares or bits set outside the board
+    InvalidMask,
+}
+
+impl Display for ParseBoardShapeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ParseBoardShapeError::*;
+        match self {
+            InvalidSyntax => write!(f, "The board shape is not a valid mask string."),
+            RaggedRows => write!(f, "The rows of the board shape have different lengths."),
+            InvalidMask => write!(f, "The hexadecimal mask doesn't match the board size."),
+        }
+    }
+}
+
+impl Error for ParseBoardShapeError {}
+
+impl BoardShape {
+    // A `row_count` by `column_count` board with no blocked squares
+    #[must_use]
+    pub fn rectangle(row_count: u32, column_count: u32) -> Self {
+        Self {
+            row_count,
+            column_count,
+            blocked: vec![false; (row_count * column_count) as usize],
+        }
+    }
+
+    // Parse either form of mask string
+    pub fn from_mask_str(s: &str) -> Result<Self, ParseBoardShapeError> {
+        match s.split_once(':') {
+            Some((size, mask)) => Self::from_hex(size, mask),
+            None => Self::from_run_length(s),
+        }
+    }
+
+    fn from_run_length(s: &str) -> Result<Self, ParseBoardShapeError> {
+        let mut rows = vec![];
+        for row in s.split('/') {
+            let mut squares = vec![];
+            let mut count = None;
+            for c in row.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    count = Some(count.unwrap_or(0) * 10 + digit as usize);
+                    if count > Some(64) {
+                        return Err(ParseBoardShapeError::InvalidSyntax);
+                    }
+                    continue;
+                }
+                let blocked = match c {
+                    '.' => false,
+                    '#' => true,
+                    _ => return Err(ParseBoardShapeError::InvalidSyntax),
+                };
+                match count.take().unwrap_or(1) {
+                    0 => return Err(ParseBoardShapeError::InvalidSyntax),
+                    n => squares.extend(std::iter::repeat_n(blocked, n)),
+                }
+            }
+            if squares.is_empty() || count.is_some() {
+                return Err(ParseBoardShapeError::InvalidSyntax);
+            }
+            rows.push(squares);
+        }
+        let column_count = rows[0].len();
+        if rows.iter().any(|row| row.len() != column_count) {
+            return Err(ParseBoardShapeError::RaggedRows);
+        }
+        Ok(Self {
+            row_count: rows.len() as u32,
+            column_count: column_count as u32,
+            blocked: rows.concat(),
+        })
+    }
+
+    fn from_hex(size: &str, mask: &str) -> Result<Self, ParseBoardShapeError> {
+        let (row_count, column_count) = size
+            .split_once('x')
+            .and_then(|(rows, columns)| Some((rows.parse().ok()?, columns.parse().ok()?)))
+            .filter(|&(rows, columns): &(u32, u32)| rows > 0 && columns > 0)
+            .ok_or(ParseBoardShapeError::InvalidSyntax)?;
+        let mask =
+            u64::from_str_radix(mask, 16).map_err(|_| ParseBoardShapeError::InvalidSyntax)?;
+        let square_count = row_count
+            .checked_mul(column_count)
+            .filter(|&count| count <= 64)
+            .ok_or(ParseBoardShapeError::InvalidMask)?;
+        if square_count < 64 && mask >> square_count != 0 {
+            return Err(ParseBoardShapeError::InvalidMask);
+        }
+        Ok(Self {
+            row_count,
+            column_count,
+            blocked: (0..square_count).map(|i| mask & 1 << i == 0).collect(),
+        })
+    }
+
+    // The run-length form, with runs of the same square merged
+    #[must_use]
+    pub fn to_mask_str(&self) -> String {
+        let mut s = String::new();
+        for (index, row) in self.blocked.chunks(self.column_count as usize).enumerate() {
+            if index > 0 {
+                s.push('/');
+            }
+            for run in row.chunk_by(|a, b| a == b) {
+                if run.len() > 1 {
+                    s.push_str(&run.len().to_string());
+                }
+                s.push(if run[0] { '#' } else { '.' });
+            }
+        }
+        s
+    }
+
+    // The hex form, or `None` if the board has more than 64 squares
+    #[must_use]
+    pub fn to_hex_str(&self) -> Option<String> {
+        if self.blocked.len() > 64 {
+            return None;
+        }
+        let mask = self
+            .blocked
+            .iter()
+            .enumerate()
+            .filter(|&(_, &blocked)| !blocked)
+            .fold(0u64, |mask, (i, _)| mask | 1 << i);
+        Some(format!(
+            "{}x{}:{:x}",
+            self.row_count, self.column_count, mask
+        ))
+    }
+
+    #[must_use]
+    pub fn row_count(&self) -> u32 {
+        self.row_count
+    }
+
+    #[must_use]
+    pub fn column_count(&self) -> u32 {
+        self.column_count
+    }
+
+    // Panics if the square is off the board
+    #[must_use]
+    pub fn is_blocked(&self, row: u32, column: u32) -> bool {
+        assert!(
+            row < self.row_count && column < self.column_count,
+            "square off the board"
+        );
+        self.blocked[(row * self.column_count + column) as usize]
+    }
+
+    // The (row, column) of each blocked square in row-major order
+    #[must_use]
+    pub fn blocked_squares(&self) -> Vec<(u32, u32)> {
+        (0..self.blocked.len() as u32)
+            .filter(|&i| self.blocked[i as usize])
+            .map(|i| (i / self.column_count, i % self.column_count))
+            .collect()
+    }
+}
+
+impl FromStr for BoardShape {
+    type Err = ParseBoardShapeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_mask_str(s)
+    }
+}
+
+impl Display for BoardShape {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_mask_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoardShape, ParseBoardShapeError};
+
+    #[test]
+    fn run_length() {
+        let shape = BoardShape::from_mask_str("3.#/4.").unwrap();
+        assert_eq!((shape.row_count(), shape.column_count()), (2, 4));
+        assert!(shape.is_blocked(0, 3));
+        assert!(!shape.is_blocked(1, 3));
+        assert_eq!(shape.blocked_squares(), [(0, 3)]);
+        assert_eq!(shape.to_mask_str(), "3.#/4.");
+
+        let shape: BoardShape = "..#./#1.2.".parse().unwrap();
+        assert_eq!(shape.to_string(), "2.#./#3.");
+        let shape: BoardShape = "12./12.".parse().unwrap();
+        assert!(shape == BoardShape::rectangle(2, 12));
+    }
+
+    #[test]
+    fn hex() {
+        let shape = BoardShape::from_mask_str("2x4:f7").unwrap();
+        assert!(shape == "3.#/4.".parse().unwrap());
+        assert_eq!(shape.to_hex_str().unwrap(), "2x4:f7");
+        let shape = BoardShape::from_mask_str("8x8:ffffffffffffffff").unwrap();
+        assert!(shape == BoardShape::rectangle(8, 8));
+        assert_eq!(BoardShape::rectangle(9, 9).to_hex_str(), None);
+    }
+
+    #[test]
+    fn errors() {
+        use ParseBoardShapeError::*;
+        let error = |s: &str| BoardShape::from_mask_str(s).unwrap_err();
+        assert!(matches!(error(""), InvalidSyntax));
+        assert!(matches!(error("3.x"), InvalidSyntax));
+        assert!(matches!(error("3"), InvalidSyntax));
+        assert!(matches!(error("0."), InvalidSyntax));
+        assert!(matches!(error("4./"), InvalidSyntax));
+        assert!(matches!(error("4./3."), RaggedRows));
+        assert!(matches!(error("2x4:fg"), InvalidSyntax));
+        assert!(matches!(error("0x4:f"), InvalidSyntax));
+        assert!(matches!(error("2x4:1ff"), InvalidMask));
+        assert!(matches!(error("9x9:1"), InvalidMask));
+    }
+}
@@ -0,0 +1,245 @@
+// An on-disk cache of solutions, so that solving the same puzzle again
+// returns at once. Puzzles are keyed by the board size and the pieces in
+// canonical order, so "4 4 ZZLL" and "4 4 LLZZ" share an entry, and looked up
+// by the canonical form of the puzzle, so "10 4 LLZZ..." and "4 10 LLZZ..."
+// also share an entry. Solutions are kept turned to the canonical form and
+// turned back when they're looked up.
+//
+// The cache is a text file with one entry per line: the key, a space and the
+// solution in the replay format, or "-" if the puzzle has no solution:
+//
+//     4x4 LLZZ L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3
+//     2x4 SZ -
+//
+// New entries are appended as they're solved. Lines that can't be read are
+// skipped, so a damaged cache only costs the time to solve those puzzles
+// again.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::board::transform;
+use crate::geometry::Symmetry;
+use crate::{board_fits, solve_one, PieceCollection, Position, PuzzleSpec, SolveOneError};
+
+pub struct SolveCache {
+    path: PathBuf,
+    entries: HashMap<PuzzleSpec, Option<Position>>,
+}
+
+#[derive(Debug)]
+pub enum SolveCacheError {
+    InvalidPuzzle(SolveOneError),
+    Io(io::Error),
+}
+
+impl Display for SolveCacheError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use SolveCacheError::*;
+        match self {
+            InvalidPuzzle(err) => write!(f, "{err}"),
+            Io(err) => write!(f, "Failed to write the solution cache. {err}"),
+        }
+    }
+}
+
+impl Error for SolveCacheError {}
+
+impl SolveCache {
+    // Open the cache at `path`. A missing file is an empty cache; the file is
+    // created when the first entry is added.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let entries = contents.lines().filter_map(parse_entry).collect();
+        Ok(Self { path, entries })
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The cached result for the puzzle: `None` if it isn't cached and
+    // `Some(None)` if it's cached as having no solution
+    #[must_use]
+    pub fn get(
+        &self,
+        row_count: u32,
+        column_count: u32,
+        pieces: &PieceCollection,
+    ) -> Option<Option<Position>> {
+        let (key, symmetry) = canonical_key(row_count, column_count, pieces);
+        let solution = self.entries.get(&key)?;
+        Some(
+            solution
+                .as_ref()
+                .map(|solution| transform::position(solution, symmetry.inverse())),
+        )
+    }
+
+    // Add the result for the puzzle to the cache and the file
+    pub fn insert(
+        &mut self,
+        row_count: u32,
+        column_count: u32,
+        pieces: &PieceCollection,
+        solution: Option<&Position>,
+    ) -> io::Result<()> {
+        let replay = solution.map_or_else(|| "-".to_string(), Position::to_replay);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{} {replay}", key(row_count, column_count, pieces))?;
+        let (key, symmetry) = canonical_key(row_count, column_count, pieces);
+        self.entries.insert(
+            key,
+            solution.map(|solution| transform::position(solution, symmetry)),
+        );
+        Ok(())
+    }
+
+    // Like `solve_one`, but return the cached result if there is one and
+    // cache the result otherwise
+    pub fn solve_one(
+        &mut self,
+        row_count: u32,
+        column_count: u32,
+        pieces: PieceCollection,
+    ) -> Result<Option<Position>, SolveCacheError> {
+        if let Some(solution) = self.get(row_count, column_count, &pieces) {
+            return Ok(solution);
+        }
+        let counts = pieces.clone();
+        let solution =
+            solve_one(row_count, column_count, pieces).map_err(SolveCacheError::InvalidPuzzle)?;
+        self.insert(row_count, column_count, &counts, solution.as_ref())
+            .map_err(SolveCacheError::Io)?;
+        Ok(solution)
+    }
+}
+
+fn key(row_count: u32, column_count: u32, pieces: &PieceCollection) -> String {
+    format!(
+        "{row_count}x{column_count} {}",
+        pieces.to_canonical_string()
+    )
+}
+
+// The canonical form of the puzzle and the symmetry that turns the puzzle to
+// it. The whole form is the key, as different puzzles may share a
+// fingerprint.
+fn canonical_key(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+) -> (PuzzleSpec, Symmetry) {
+    PuzzleSpec::new(row_count, column_count, pieces.clone()).canonicalize()
+}
+
+fn parse_entry(line: &str) -> Option<(PuzzleSpec, Option<Position>)> {
+    let mut parts = line.splitn(3, ' ');
+    let (size, pieces, replay) = (parts.next()?, parts.next()?, parts.next()?);
+    let (row_count, column_count) = size.split_once('x')?;
+    let (row_count, column_count) = (row_count.parse().ok()?, column_count.parse().ok()?);
+    if !board_fits(row_count, column_count) {
+        return None;
+    }
+    let pieces: PieceCollection = pieces.parse().ok()?;
+    let solution = match replay {
+        "-" => None,
+        replay => {
+            let solution = Position::from_replay(row_count, column_count, replay).ok()?;
+            if !covers(&solution, &pieces) {
+                return None;
+            }
+            Some(solution)
+        }
+    };
+    let (key, symmetry) = canonical_key(row_count, column_count, &pieces);
+    Some((
+        key,
+        solution.map(|solution| transform::position(&solution, symmetry)),
+    ))
+}
+
+// Whether `solution` is a full board covered by exactly `pieces`
+fn covers(solution: &Position, pieces: &PieceCollection) -> bool {
+    // The fixed pieces are written like "L2", so the first letter is the piece
+    let placed: String = solution
+        .placements()
+        .iter()
+        .map(|placement| placement.piece.to_string().remove(0))
+        .collect();
+    !solution.to_string().contains('.')
+        && placed
+            .parse::<PieceCollection>()
+            .ok()
+            .map(|placed| placed.to_canonical_string())
+            == Some(pieces.to_canonical_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SolveCache;
+    use std::fs;
+
+    #[test]
+    fn cache() {
+        let path = std::env::temp_dir().join(format!("soe_cache_{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut cache = SolveCache::open(&path).unwrap();
+        assert!(cache.is_empty());
+        let solution = cache.solve_one(4, 4, "ZZLL".parse().unwrap()).unwrap();
+        assert!(cache
+            .solve_one(2, 4, "SZ".parse().unwrap())
+            .unwrap()
+            .is_none());
+        assert!(cache.solve_one(4, 4, "LLZ".parse().unwrap()).is_err());
+        assert_eq!(cache.len(), 2);
+
+        // Damaged lines are skipped
+        let mut contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("4x4 LLZZ "));
+        assert!(contents.ends_with("\n2x4 SZ -\n"));
+        contents.push_str("4x4 OOOO O1@r0c0\nnonsense\n30x30 I I1@r0c0\n");
+        fs::write(&path, contents).unwrap();
+
+        let cache = SolveCache::open(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        let cached = cache.get(4, 4, &"LZLZ".parse().unwrap()).unwrap();
+        assert!(cached == solution);
+        assert!(cache.get(2, 4, &"ZS".parse().unwrap()) == Some(None));
+        assert!(cache.get(4, 4, &"OOOO".parse().unwrap()).is_none());
+
+        // The same puzzle turned over shares the entry
+        let turned = cache.get(4, 2, &"SZ".parse().unwrap());
+        assert!(turned == Some(None));
+        let mut cache = cache;
+        let pieces = "TTLSZL".parse().unwrap();
+        let solution = cache.solve_one(6, 4, pieces).unwrap().unwrap();
+        assert!(solution.verify(6, 4, &"TTLSZL".parse().unwrap()).is_ok());
+        for pieces in ["TTLSZL", "TTJZSJ"] {
+            let pieces = pieces.parse().unwrap();
+            let turned = cache.get(4, 6, &pieces).unwrap().unwrap();
+            assert!(turned.verify(4, 6, &pieces).is_ok());
+        }
+        assert_eq!(cache.len(), 3);
+        fs::remove_file(&path).unwrap();
+    }
+}
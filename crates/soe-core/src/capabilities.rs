@@ -0,0 +1,109 @@
+// What the linked build of the library supports, for frontends that adapt to
+// it, e.g. by hiding the thread count when the search can't use threads or
+// limiting the board size that can be entered.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{max_board_area, max_board_column_count, EngineRegistry, MAX_PIECE_COUNT};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    // The version of the crate, e.g. "0.1.0"
+    pub version: &'static str,
+    // Searching with several threads, the `parallel` feature
+    pub parallel: bool,
+    // PNG and GIF output, the `render` feature
+    pub render: bool,
+    // Reading puzzles from screenshots. The reader is in another crate, so
+    // this is only set by `sigils_of_elohim_solver::capabilities`.
+    pub vision: bool,
+    // The names of the engines of `EngineRegistry::default`, e.g. "dlx"
+    pub engines: Vec<String>,
+    // See `max_board_area` and `max_board_column_count`
+    pub max_board_area: u32,
+    pub max_board_column_count: u32,
+    pub max_piece_count: u32,
+}
+
+// The capabilities of this build of `soe-core`
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        parallel: cfg!(feature = "parallel"),
+        render: cfg!(feature = "render"),
+        vision: false,
+        engines: EngineRegistry::default()
+            .names()
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        max_board_area: max_board_area(),
+        max_board_column_count: max_board_column_count(),
+        max_piece_count: MAX_PIECE_COUNT as u32,
+    }
+}
+
+impl Capabilities {
+    // The capabilities as a JSON object with the names of the fields as keys
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let engines: Vec<String> = self
+            .engines
+            .iter()
+            .map(|engine| format!("\"{engine}\""))
+            .collect();
+        format!(
+            "{{\"version\":\"{}\",\"parallel\":{},\"render\":{},\"vision\":{},\"engines\":[{}],\
+             \"max_board_area\":{},\"max_board_column_count\":{},\"max_piece_count\":{}}}",
+            self.version,
+            self.parallel,
+            self.render,
+            self.vision,
+            engines.join(","),
+            self.max_board_area,
+            self.max_board_column_count,
+            self.max_piece_count
+        )
+    }
+}
+
+// One `name: value` line per field
+impl Display for Capabilities {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let yes_no = |supported| if supported { "yes" } else { "no" };
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "parallel: {}", yes_no(self.parallel))?;
+        writeln!(f, "render: {}", yes_no(self.render))?;
+        writeln!(f, "vision: {}", yes_no(self.vision))?;
+        writeln!(f, "engines: {}", self.engines.join(", "))?;
+        writeln!(f, "max board area: {}", self.max_board_area)?;
+        writeln!(f, "max board column count: {}", self.max_board_column_count)?;
+        writeln!(f, "max piece count: {}", self.max_piece_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capabilities;
+
+    #[test]
+    fn capabilities_json() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.engines, ["backtrack", "dlx", "smallboards"]);
+        assert!(!capabilities.vision);
+        let json = capabilities.to_json();
+        assert!(json.starts_with(&format!(
+            "{{\"version\":\"{}\",\"parallel\":{},",
+            env!("CARGO_PKG_VERSION"),
+            cfg!(feature = "parallel")
+        )));
+        assert!(json.ends_with(
+            "\"engines\":[\"backtrack\",\"dlx\",\"smallboards\"],\"max_board_area\":63,\
+             \"max_board_column_count\":20,\"max_piece_count\":12}"
+        ));
+        assert!(capabilities
+            .to_string()
+            .contains("\nmax board column count: 20\n"));
+    }
+}
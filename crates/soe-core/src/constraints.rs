@@ -0,0 +1,215 @@
+// Tiling a board with pieces of any kind, within limits on how many of each
+// kind may be used. This is for experiments like "can this board be tiled
+// without S and Z pieces?" that would otherwise need a piece string for every
+// possible mix.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{validate, Board, Piece, PieceCollection, Position, SolveOneError, Solver};
+
+// The least and the most pieces of each kind a solution may use
+pub struct PieceConstraints {
+    min: [u32; Piece::count()],
+    max: [u32; Piece::count()],
+}
+
+#[derive(Debug)]
+pub enum ParseConstraintError {
+    // The value isn't of the form "T=2"
+    InvalidFormat,
+    InvalidPiece,
+    InvalidCount,
+}
+
+impl Display for ParseConstraintError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ParseConstraintError::*;
+        match self {
+            InvalidFormat => write!(f, "The value must be a piece and a count, e.g. 'T=2'."),
+            InvalidPiece => write!(f, "The piece must be one of I, O, T, J, L, S or Z."),
+            InvalidCount => write!(f, "The count must be a non-negative integer."),
+        }
+    }
+}
+
+impl Error for ParseConstraintError {}
+
+impl Default for PieceConstraints {
+    fn default() -> Self {
+        Self {
+            min: [0; Piece::count()],
+            max: [u32::MAX; Piece::count()],
+        }
+    }
+}
+
+impl PieceConstraints {
+    // No pieces of the kind may be used
+    pub fn forbid(&mut self, piece: Piece) {
+        self.max[piece as usize] = 0;
+    }
+
+    pub fn require_at_least(&mut self, piece: Piece, count: u32) {
+        self.min[piece as usize] = count;
+    }
+
+    pub fn require_at_most(&mut self, piece: Piece, count: u32) {
+        self.max[piece as usize] = count;
+    }
+
+    // Apply a comma separated list of pieces to forbid, e.g. "S,Z"
+    pub fn forbid_all(&mut self, pieces: &str) -> Result<(), ParseConstraintError> {
+        let pieces: PieceCollection = pieces
+            .parse()
+            .map_err(|_| ParseConstraintError::InvalidPiece)?;
+        for &piece in &Piece::array() {
+            if pieces.count(piece) > 0 {
+                self.forbid(piece);
+            }
+        }
+        Ok(())
+    }
+
+    // Parse a constraint of the form "T=2" into the piece and the count
+    pub fn parse_count(input: &str) -> Result<(Piece, u32), ParseConstraintError> {
+        let (piece, count) = input
+            .split_once('=')
+            .ok_or(ParseConstraintError::InvalidFormat)?;
+        let pieces = PieceCollection::from_str(piece.trim())
+            .ok()
+            .filter(|pieces| pieces.count_all() == 1)
+            .ok_or(ParseConstraintError::InvalidPiece)?;
+        let piece = Piece::array()
+            .iter()
+            .copied()
+            .find(|&piece| pieces.count(piece) == 1)
+            .unwrap();
+        let count = count
+            .trim()
+            .parse()
+            .map_err(|_| ParseConstraintError::InvalidCount)?;
+        Ok((piece, count))
+    }
+
+    #[must_use]
+    pub fn min(&self, piece: Piece) -> u32 {
+        self.min[piece as usize]
+    }
+
+    #[must_use]
+    pub fn max(&self, piece: Piece) -> u32 {
+        self.max[piece as usize]
+    }
+}
+
+// Tile the board with pieces of any kind that satisfy `constraints` and
+// return the first solution in canonical order, or `None` if there is none.
+pub fn solve_constrained(
+    row_count: u32,
+    column_count: u32,
+    constraints: &PieceConstraints,
+) -> Result<Option<Position>, SolveOneError> {
+    // Validate the board with a collection of the right size
+    let piece_count = row_count * column_count / 4;
+    let mut counts = [0; Piece::count()];
+    counts[0] = piece_count;
+    validate(row_count, column_count, &PieceCollection { counts })?;
+
+    // Each kind is available up to its maximum, and all but its minimum may
+    // be left over
+    let mut counts = [0; Piece::count()];
+    let mut leftover = [0; Piece::count()];
+    for index in 0..Piece::count() {
+        counts[index] = constraints.max[index].min(piece_count);
+        if constraints.min[index] > counts[index] {
+            return Ok(None);
+        }
+        leftover[index] = counts[index].saturating_sub(constraints.min[index]);
+    }
+    let board = Board::new(row_count, column_count);
+    Ok(Solver::new(board, PieceCollection { counts })
+        .with_leftover(leftover)
+        .solve_one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_constrained, ParseConstraintError, PieceConstraints};
+    use crate::Piece::*;
+    use crate::{Piece, PieceCollection, Position};
+
+    // The pieces used by a solution
+    fn pieces(solution: &Position) -> String {
+        let replay = solution.to_replay();
+        let pieces: String = replay
+            .split("; ")
+            .map(|placement| &placement[..1])
+            .collect();
+        pieces
+            .parse::<PieceCollection>()
+            .unwrap()
+            .to_canonical_string()
+    }
+
+    #[test]
+    fn constrained() {
+        let solve = |constraints| solve_constrained(4, 4, &constraints).unwrap();
+        let solution = solve(PieceConstraints::default()).unwrap();
+        assert_eq!(pieces(&solution), "IIII");
+
+        let mut constraints = PieceConstraints::default();
+        constraints.forbid_all("I,O").unwrap();
+        constraints.require_at_least(T, 2);
+        let solution = solve(constraints).unwrap();
+        let used = pieces(&solution);
+        assert!(!used.contains('I') && !used.contains('O'));
+        assert!(used.matches('T').count() >= 2);
+
+        let mut constraints = PieceConstraints::default();
+        constraints.require_at_least(S, 1);
+        constraints.forbid_all("Z").unwrap();
+        let solution = solve(constraints).unwrap();
+        assert!(pieces(&solution).contains('S'));
+
+        let mut constraints = PieceConstraints::default();
+        for &piece in &Piece::array() {
+            if piece != T {
+                constraints.forbid(piece);
+            }
+        }
+        assert!(solve(constraints).is_some());
+        let mut constraints = PieceConstraints::default();
+        constraints.require_at_least(O, 5);
+        assert!(solve(constraints).is_none());
+        let mut constraints = PieceConstraints::default();
+        constraints.require_at_least(I, 2);
+        constraints.require_at_most(I, 1);
+        assert!(solve(constraints).is_none());
+
+        assert!(solve_constrained(3, 3, &PieceConstraints::default()).is_err());
+    }
+
+    #[test]
+    fn parse() {
+        assert!(matches!(PieceConstraints::parse_count("t = 2"), Ok((T, 2))));
+        assert!(matches!(
+            PieceConstraints::parse_count("T2"),
+            Err(ParseConstraintError::InvalidFormat)
+        ));
+        assert!(matches!(
+            PieceConstraints::parse_count("TT=2"),
+            Err(ParseConstraintError::InvalidPiece)
+        ));
+        assert!(matches!(
+            PieceConstraints::parse_count("T=-1"),
+            Err(ParseConstraintError::InvalidCount)
+        ));
+        let mut constraints = PieceConstraints::default();
+        assert!(constraints.forbid_all("S,X").is_err());
+        constraints.forbid_all("S,Z").unwrap();
+        assert_eq!((constraints.max(S), constraints.max(Z)), (0, 0));
+        assert_eq!(constraints.max(T), u32::MAX);
+    }
+}
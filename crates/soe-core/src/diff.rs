@@ -0,0 +1,173 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::Position;
+
+// The squares on which two positions of the same size differ. A square differs
+// if it is covered by a piece in one position and not in the other, or if the
+// pieces covering it in the two positions don't occupy the same squares.
+// Labels are ignored, so the same tiling found in a different order has no
+// differences.
+pub struct PositionDiff {
+    left: Position,
+    right: Position,
+    differs: Vec<bool>,
+}
+
+impl Position {
+    // Compare this position with `other`. Panics if the positions don't have
+    // the same number of rows and columns.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> PositionDiff {
+        assert!(
+            self.row_count() == other.row_count() && self.column_count() == other.column_count(),
+            "positions of different sizes can't be compared"
+        );
+
+        let a = &self.squares;
+        let b = &other.squares;
+        let differs = (0..a.len())
+            .map(|i| {
+                if a[i] == b'\n' {
+                    return false;
+                }
+                if a[i] == b'.' || b[i] == b'.' {
+                    return a[i] != b[i];
+                }
+                (0..a.len()).any(|j| (a[j] == a[i]) != (b[j] == b[i]))
+            })
+            .collect();
+
+        PositionDiff {
+            left: Position {
+                squares: self.squares.clone(),
+            },
+            right: Position {
+                squares: other.squares.clone(),
+            },
+            differs,
+        }
+    }
+}
+
+impl PositionDiff {
+    // Returns true if the two positions have the same tiling.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.differs.contains(&true)
+    }
+
+    // The number of squares that differ.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.differs.iter().filter(|&&d| d).count()
+    }
+
+    // Returns true if the square at `row`, `column` differs.
+    #[must_use]
+    pub fn differs(&self, row: u32, column: u32) -> bool {
+        let index = row * (self.left.column_count() + 1) + column;
+        self.differs[index as usize]
+    }
+}
+
+// Displays the two positions side by side. The plain format prints the labels
+// of differing squares in lower case. The alternate format draws both positions
+// with box drawing characters and shades the differing squares.
+impl Display for PositionDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if f.alternate() {
+            let left = self.left.pretty_lines(false, |i| self.differs[i]);
+            let right = self.right.pretty_lines(false, |i| self.differs[i]);
+            for (l, r) in left.iter().zip(&right) {
+                writeln!(f, "{l}   {r}")?;
+            }
+            return Ok(());
+        }
+
+        let width = self.left.column_count() as usize + 1;
+        let rows = self
+            .left
+            .squares
+            .chunks(width)
+            .zip(self.right.squares.chunks(width));
+        for (row_index, (l, r)) in rows.enumerate() {
+            let render = |squares: &[u8]| -> String {
+                squares[..width - 1]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &s)| {
+                        if self.differs[row_index * width + i] {
+                            s.to_ascii_lowercase() as char
+                        } else {
+                            s as char
+                        }
+                    })
+                    .collect()
+            };
+            writeln!(f, "{} | {}", render(l), render(r))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Board;
+    use crate::FixedPiece::*;
+
+    #[test]
+    fn identical_tilings() {
+        let mut a = Board::new(2, 4);
+        a.push(I2).unwrap();
+        a.push(I2).unwrap();
+        let diff = a.position().diff(&a.position());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "AAAA | AAAA\nBBBB | BBBB\n");
+    }
+
+    #[test]
+    fn different_tilings() {
+        let mut a = Board::new(4, 3);
+        a.push(I1).unwrap();
+        a.push(O1).unwrap();
+        a.push(O1).unwrap();
+        let mut b = Board::new(4, 3);
+        b.push(I1).unwrap();
+        b.push(I1).unwrap();
+        b.push(I1).unwrap();
+        let diff = a.position().diff(&b.position());
+
+        assert!(!diff.is_empty());
+        assert_eq!(diff.count(), 8);
+        assert!(diff.differs(0, 1));
+        assert!(!diff.differs(1, 0));
+        assert_eq!(
+            diff.to_string(),
+            "Abb | Abc\n\
+             Abb | Abc\n\
+             Acc | Abc\n\
+             Acc | Abc\n"
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        let mut a = Board::new(4, 2);
+        a.push(I1).unwrap();
+        a.push(I1).unwrap();
+        let mut b = Board::new(4, 2);
+        b.push(O1).unwrap();
+        b.push(O1).unwrap();
+        let diff = a.position().diff(&b.position());
+
+        assert_eq!(
+            format!("{diff:#}"),
+            "┌─┬─┐   ┌───┐\n\
+             │░│░│   │░░░│\n\
+             │░│░│   ├───┤\n\
+             │░│░│   │░░░│\n\
+             └─┴─┘   └───┘\n"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// Numbers of rows and columns and indices of squares as distinct types, so a
+// number of columns can't be passed where a number of rows is expected. The
+// functions that take plain `u32` sizes take the rows first, like these
+// types; they're for code that moves between coordinate systems, e.g. screen
+// positions, where x counts columns and y rows, or transposed boards.
+
+use std::convert::TryInto;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Mul;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RowCount(u32);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ColCount(u32);
+
+impl RowCount {
+    // `None` if `row_count` is 0
+    #[must_use]
+    pub const fn new(row_count: u32) -> Option<Self> {
+        if row_count == 0 {
+            None
+        } else {
+            Some(Self(row_count))
+        }
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    // Whether `row` is a row of the board
+    #[must_use]
+    pub const fn contains(self, row: u32) -> bool {
+        row < self.0
+    }
+
+    // The number of columns of the transposed board
+    #[must_use]
+    pub const fn transposed(self) -> ColCount {
+        ColCount(self.0)
+    }
+}
+
+impl ColCount {
+    // `None` if `column_count` is 0
+    #[must_use]
+    pub const fn new(column_count: u32) -> Option<Self> {
+        if column_count == 0 {
+            None
+        } else {
+            Some(Self(column_count))
+        }
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    // Whether `column` is a column of the board
+    #[must_use]
+    pub const fn contains(self, column: u32) -> bool {
+        column < self.0
+    }
+
+    // The number of rows of the transposed board
+    #[must_use]
+    pub const fn transposed(self) -> RowCount {
+        RowCount(self.0)
+    }
+}
+
+impl From<RowCount> for u32 {
+    fn from(row_count: RowCount) -> Self {
+        row_count.0
+    }
+}
+
+impl From<ColCount> for u32 {
+    fn from(column_count: ColCount) -> Self {
+        column_count.0
+    }
+}
+
+// The number of squares of the board, which doesn't overflow
+impl Mul<ColCount> for RowCount {
+    type Output = u64;
+
+    fn mul(self, column_count: ColCount) -> u64 {
+        u64::from(self.0) * u64::from(column_count.0)
+    }
+}
+
+impl Mul<RowCount> for ColCount {
+    type Output = u64;
+
+    fn mul(self, row_count: RowCount) -> u64 {
+        row_count * self
+    }
+}
+
+impl Display for RowCount {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for ColCount {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// The row-major index of a square of a board, the order in which the search
+// fills the board. Finding the row and column again takes the number of
+// columns.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CellIndex(u32);
+
+impl CellIndex {
+    // The index of the square at `row`, `column`, or `None` if it's off the
+    // board
+    #[must_use]
+    pub fn new(row: u32, column: u32, row_count: RowCount, column_count: ColCount) -> Option<Self> {
+        if !row_count.contains(row) || !column_count.contains(column) {
+            return None;
+        }
+        (u64::from(row) * u64::from(column_count.0) + u64::from(column))
+            .try_into()
+            .ok()
+            .map(Self)
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn row(self, column_count: ColCount) -> u32 {
+        self.0 / column_count.0
+    }
+
+    #[must_use]
+    pub const fn column(self, column_count: ColCount) -> u32 {
+        self.0 % column_count.0
+    }
+
+    // The index of the same square on the board reflected in its main
+    // diagonal, which has `column_count` rows and `row_count` columns
+    #[must_use]
+    pub const fn transposed(self, row_count: RowCount, column_count: ColCount) -> Self {
+        Self(self.column(column_count) * row_count.0 + self.row(column_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellIndex, ColCount, RowCount};
+
+    #[test]
+    fn checked() {
+        assert_eq!(RowCount::new(0), None);
+        assert_eq!(ColCount::new(0), None);
+        let rows = RowCount::new(2).unwrap();
+        let columns = ColCount::new(3).unwrap();
+        assert_eq!(rows * columns, 6);
+        assert_eq!(columns * rows, 6);
+        assert_eq!(rows.transposed().get(), 2);
+        assert_eq!(u32::from(columns), 3);
+        assert!(rows.contains(1) && !rows.contains(2));
+        assert_eq!(CellIndex::new(2, 0, rows, columns), None);
+        assert_eq!(CellIndex::new(0, 3, rows, columns), None);
+    }
+
+    #[test]
+    fn cell_index() {
+        let (rows, columns) = (RowCount::new(2).unwrap(), ColCount::new(3).unwrap());
+        let index = CellIndex::new(1, 2, rows, columns).unwrap();
+        assert_eq!(index.get(), 5);
+        assert_eq!((index.row(columns), index.column(columns)), (1, 2));
+        // Square (1, 2) of the 2 by 3 board is (2, 1) of the 3 by 2 board
+        let transposed = index.transposed(rows, columns);
+        assert_eq!(transposed.get(), 5);
+        let index = CellIndex::new(0, 1, rows, columns).unwrap();
+        let transposed = index.transposed(rows, columns);
+        assert_eq!(
+            (
+                transposed.row(rows.transposed()),
+                transposed.column(rows.transposed())
+            ),
+            (1, 0)
+        );
+    }
+}
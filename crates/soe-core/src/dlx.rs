@@ -0,0 +1,257 @@
+// Solving puzzles as exact cover problems with dancing links. Each placement
+// of a fixed piece on the board is a row covering its four squares, and the
+// squares are the columns that must each be covered exactly once. The number
+// of pieces of each kind is checked as rows are chosen rather than with a
+// column per piece, which would find each tiling once for every order of the
+// pieces of a kind. The column with the fewest rows left is covered next.
+
+use crate::geometry::{Cell, Shape};
+use crate::{FixedPiece, LabelOrder, Outcome, Piece, PieceCollection, Position, PIECE_MAP};
+
+// Solve the puzzle, giving up with `Undetermined` after `max_nodes` nodes.
+// The puzzle must be valid.
+pub(crate) fn solve(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+    max_nodes: Option<u64>,
+) -> Outcome {
+    let mut links = Links::new(row_count, column_count, pieces);
+    links.max_nodes = max_nodes.unwrap_or(u64::MAX);
+    let mut chosen = vec![];
+    if links.search(&mut chosen) {
+        Outcome::Solved(links.position(&chosen))
+    } else if links.stopped {
+        Outcome::Undetermined
+    } else {
+        Outcome::NoSolution
+    }
+}
+
+// The toroidal doubly linked lists of the exact cover matrix. Node 0 is the
+// root, nodes 1 to the number of squares are the column headers and the rest
+// are the nodes of the rows, four to a row.
+struct Links {
+    row_count: u32,
+    column_count: u32,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    // The column header of each node
+    column: Vec<usize>,
+    // The number of rows left in each column, indexed by header
+    size: Vec<usize>,
+    // The fixed piece and squares of each row
+    rows: Vec<(FixedPiece, [usize; 4])>,
+    // The pieces of each kind left to place
+    remaining: [u32; Piece::count()],
+    nodes: u64,
+    max_nodes: u64,
+    stopped: bool,
+}
+
+impl Links {
+    fn new(row_count: u32, column_count: u32, pieces: &PieceCollection) -> Self {
+        let square_count = (row_count * column_count) as usize;
+        let mut links = Links {
+            row_count,
+            column_count,
+            left: (0..=square_count)
+                .map(|i| if i == 0 { square_count } else { i - 1 })
+                .collect(),
+            right: (0..=square_count)
+                .map(|i| (i + 1) % (square_count + 1))
+                .collect(),
+            up: (0..=square_count).collect(),
+            down: (0..=square_count).collect(),
+            column: (0..=square_count).collect(),
+            size: vec![0; square_count + 1],
+            rows: vec![],
+            remaining: pieces.counts,
+            nodes: 0,
+            max_nodes: u64::MAX,
+            stopped: false,
+        };
+
+        let in_board = |cell: Cell| {
+            (0..row_count as i32).contains(&cell.0) && (0..column_count as i32).contains(&cell.1)
+        };
+        for &fixed_piece in &FixedPiece::array() {
+            if pieces.counts[PIECE_MAP[fixed_piece as usize] as usize] == 0 {
+                continue;
+            }
+            for row in 0..row_count as i32 {
+                for column in 0..column_count as i32 {
+                    let shape = Shape::from(fixed_piece).translate(row, column);
+                    if shape.0.iter().all(|&cell| in_board(cell)) {
+                        let squares = shape
+                            .0
+                            .map(|cell| (cell.0 * column_count as i32 + cell.1) as usize);
+                        links.add_row(fixed_piece, squares);
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    fn add_row(&mut self, fixed_piece: FixedPiece, squares: [usize; 4]) {
+        let first = self.left.len();
+        for (i, &square) in squares.iter().enumerate() {
+            let node = first + i;
+            let header = square + 1;
+            self.left.push(first + (i + 3) % 4);
+            self.right.push(first + (i + 1) % 4);
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.column.push(header);
+            let last = self.up[header];
+            self.down[last] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+        }
+        self.rows.push((fixed_piece, squares));
+    }
+
+    // The row of a node that isn't a header
+    fn row(&self, node: usize) -> usize {
+        (node - self.size.len()) / 4
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = j;
+                self.up[down] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+
+    // Cover the remaining squares, pushing the rows chosen onto `chosen`.
+    // Returns whether all were covered.
+    fn search(&mut self, chosen: &mut Vec<usize>) -> bool {
+        if self.right[0] == 0 {
+            return true;
+        }
+        if self.nodes == self.max_nodes {
+            self.stopped = true;
+            return false;
+        }
+        self.nodes += 1;
+
+        let mut header = self.right[0];
+        let mut best = header;
+        while header != 0 {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+
+        self.cover(best);
+        let mut i = self.down[best];
+        while i != best {
+            let row = self.row(i);
+            let piece = PIECE_MAP[self.rows[row].0 as usize] as usize;
+            if self.remaining[piece] > 0 {
+                self.remaining[piece] -= 1;
+                chosen.push(row);
+                let mut j = self.right[i];
+                while j != i {
+                    self.cover(self.column[j]);
+                    j = self.right[j];
+                }
+                if self.search(chosen) {
+                    return true;
+                }
+                let mut j = self.left[i];
+                while j != i {
+                    self.uncover(self.column[j]);
+                    j = self.left[j];
+                }
+                chosen.pop();
+                self.remaining[piece] += 1;
+                if self.stopped {
+                    break;
+                }
+            }
+            i = self.down[i];
+        }
+        self.uncover(best);
+        false
+    }
+
+    // The tiling of the chosen rows, labelled by position
+    fn position(&self, chosen: &[usize]) -> Position {
+        let width = self.column_count as usize + 1;
+        let mut squares = vec![b'\n'; self.row_count as usize * width];
+        for (label, &row) in (b'A'..).zip(chosen) {
+            for &square in &self.rows[row].1 {
+                let (row, column) = (
+                    square / self.column_count as usize,
+                    square % self.column_count as usize,
+                );
+                squares[row * width + column] = label;
+            }
+        }
+        Position { squares }.relabel(LabelOrder::ByPosition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve;
+    use crate::{solve_one, Outcome, PieceCollection};
+
+    #[test]
+    fn solve_puzzles() {
+        for (row_count, column_count, pieces) in [
+            (4, 4, "LLZZ"),
+            (4, 6, "TTLSZL"),
+            (5, 8, "IIOTTLLJJS"),
+            (2, 2, "O"),
+        ] {
+            let pieces: PieceCollection = pieces.parse().unwrap();
+            let outcome = solve(row_count, column_count, &pieces, None);
+            let solution = outcome.solution().unwrap();
+            assert!(solution.verify(row_count, column_count, &pieces).is_ok());
+        }
+        let pieces: PieceCollection = "TTTT".parse().unwrap();
+        assert_eq!(solve(2, 8, &pieces, None), Outcome::NoSolution);
+        assert!(solve_one(2, 8, pieces).unwrap().is_none());
+    }
+
+    #[test]
+    fn give_up() {
+        let pieces: PieceCollection = "IITTTLLLJJSZ".parse().unwrap();
+        assert_eq!(solve(4, 12, &pieces, Some(100)), Outcome::Undetermined);
+    }
+}
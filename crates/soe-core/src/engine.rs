@@ -0,0 +1,226 @@
+// Solvers behind a common trait, so that other engines can be tried and
+// compared with the built-in ones. The registry holds the engines by name:
+//
+//     let mut registry = EngineRegistry::default();
+//     registry.register(Box::new(MyEngine));
+//     for engine in registry.engines() {
+//         let outcome = engine.solve(&spec, &SolveOptions::default())?;
+//     }
+//
+// Engines may find different solutions of the same puzzle, so solutions are
+// compared with `Position::verify` rather than with each other.
+
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{
+    dlx, smallboards, solve_one_with_options, Outcome, PuzzleSpec, SolveOneError, SolveOptions,
+};
+
+pub type SolveResult = Result<Outcome, SolveOneError>;
+
+pub trait TilingEngine: Send + Sync {
+    // The name the engine is registered under, e.g. "dlx"
+    fn name(&self) -> &str;
+
+    // Solve the puzzle. The options an engine has no use for are ignored, and
+    // an engine that can't solve the puzzle returns `Undetermined`.
+    fn solve(&self, spec: &PuzzleSpec, options: &SolveOptions) -> SolveResult;
+
+    // Whether the engine can solve the puzzle, so that callers can skip it
+    fn supports(&self, _spec: &PuzzleSpec) -> bool {
+        true
+    }
+}
+
+// The backtracking search of `solve_one_with_options`, with all its options
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Backtracker;
+
+impl TilingEngine for Backtracker {
+    fn name(&self) -> &'static str {
+        "backtrack"
+    }
+
+    fn solve(&self, spec: &PuzzleSpec, options: &SolveOptions) -> SolveResult {
+        solve_one_with_options(
+            spec.row_count,
+            spec.column_count,
+            spec.pieces.clone(),
+            options,
+        )
+    }
+}
+
+// Exact cover with dancing links. Only `max_nodes` is used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Dlx;
+
+impl TilingEngine for Dlx {
+    fn name(&self) -> &'static str {
+        "dlx"
+    }
+
+    fn solve(&self, spec: &PuzzleSpec, options: &SolveOptions) -> SolveResult {
+        spec.validate()?;
+        Ok(dlx::solve(
+            spec.row_count,
+            spec.column_count,
+            &spec.pieces,
+            options.max_nodes,
+        ))
+    }
+}
+
+// The column by column dynamic programming of `smallboards`, for boards at
+// most three squares wide. The options are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmallBoards;
+
+impl TilingEngine for SmallBoards {
+    fn name(&self) -> &'static str {
+        "smallboards"
+    }
+
+    fn solve(&self, spec: &PuzzleSpec, _options: &SolveOptions) -> SolveResult {
+        if !self.supports(spec) {
+            spec.validate()?;
+            return Ok(Outcome::Undetermined);
+        }
+        Ok(
+            match smallboards::solve(spec.row_count, spec.column_count, &spec.pieces)? {
+                Some(solution) => Outcome::Solved(solution),
+                None => Outcome::NoSolution,
+            },
+        )
+    }
+
+    fn supports(&self, spec: &PuzzleSpec) -> bool {
+        smallboards::is_small(spec.row_count, spec.column_count)
+    }
+}
+
+// Engines by name, in the order they were registered. The default registry
+// has the built-in engines.
+pub struct EngineRegistry {
+    engines: Vec<Box<dyn TilingEngine>>,
+}
+
+impl EngineRegistry {
+    // A registry with no engines
+    #[must_use]
+    pub fn empty() -> Self {
+        Self { engines: vec![] }
+    }
+
+    // Add an engine, replacing and returning the engine with the same name if
+    // there is one
+    pub fn register(&mut self, engine: Box<dyn TilingEngine>) -> Option<Box<dyn TilingEngine>> {
+        if let Some(index) = self.engines.iter().position(|e| e.name() == engine.name()) {
+            return Some(std::mem::replace(&mut self.engines[index], engine));
+        }
+        self.engines.push(engine);
+        None
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn TilingEngine> {
+        self.engines().find(|engine| engine.name() == name)
+    }
+
+    pub fn engines(&self) -> impl Iterator<Item = &dyn TilingEngine> {
+        self.engines.iter().map(AsRef::as_ref)
+    }
+
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.engines().map(TilingEngine::name).collect()
+    }
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(Backtracker));
+        registry.register(Box::new(Dlx));
+        registry.register(Box::new(SmallBoards));
+        registry
+    }
+}
+
+// Written as the names of the engines
+impl Debug for EngineRegistry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("EngineRegistry")
+            .field(&self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EngineRegistry, SolveResult, TilingEngine};
+    use crate::{Outcome, PuzzleSpec, SolveOneError, SolveOptions};
+
+    #[test]
+    fn builtin_engines_agree() {
+        let registry = EngineRegistry::default();
+        assert_eq!(registry.names(), ["backtrack", "dlx", "smallboards"]);
+        for (row_count, column_count, pieces) in
+            [(4, 6, "TTLSZL"), (3, 8, "IOLJZT"), (2, 8, "TTTT")]
+        {
+            let spec = PuzzleSpec::new(row_count, column_count, pieces.parse().unwrap());
+            let expected = registry
+                .get("backtrack")
+                .unwrap()
+                .solve(&spec, &SolveOptions::default())
+                .unwrap();
+            for engine in registry.engines().filter(|engine| engine.supports(&spec)) {
+                let outcome = engine.solve(&spec, &SolveOptions::default()).unwrap();
+                match &outcome {
+                    Outcome::Solved(solution) => assert!(solution
+                        .verify(row_count, column_count, &spec.pieces)
+                        .is_ok()),
+                    _ => assert_eq!(outcome, expected),
+                }
+                assert_eq!(outcome.solution().is_some(), expected.solution().is_some());
+            }
+        }
+
+        let spec = PuzzleSpec::new(4, 6, "TTLSZL".parse().unwrap());
+        let small = registry.get("smallboards").unwrap();
+        assert!(!small.supports(&spec));
+        assert_eq!(
+            small.solve(&spec, &SolveOptions::default()).unwrap(),
+            Outcome::Undetermined
+        );
+        for engine in registry.engines() {
+            let spec = PuzzleSpec::new(3, 3, "LL".parse().unwrap());
+            assert!(matches!(
+                engine.solve(&spec, &SolveOptions::default()),
+                Err(SolveOneError::InvalidBoardSize)
+            ));
+        }
+    }
+
+    struct Never;
+
+    impl TilingEngine for Never {
+        fn name(&self) -> &'static str {
+            "dlx"
+        }
+
+        fn solve(&self, _spec: &PuzzleSpec, _options: &SolveOptions) -> SolveResult {
+            Ok(Outcome::Undetermined)
+        }
+    }
+
+    #[test]
+    fn register() {
+        let mut registry = EngineRegistry::empty();
+        assert!(registry.get("dlx").is_none());
+        assert!(registry.register(Box::new(Never)).is_none());
+        let replaced = EngineRegistry::default().register(Box::new(Never));
+        assert_eq!(replaced.unwrap().name(), "dlx");
+        assert_eq!(format!("{registry:?}"), "EngineRegistry([\"dlx\"])");
+    }
+}
@@ -0,0 +1,183 @@
+// Why a puzzle is or isn't solvable, in words: the piece count checks of
+// `validate`, the coloring arguments of `analyze_parity` and, when those
+// don't settle it, a search bounded by a node limit.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::geometry::Symmetry;
+use crate::{
+    analyze_parity, board_fits, validate, ParityReport, Position, PuzzleSpec, Solve, SolveOneError,
+};
+
+#[derive(Debug)]
+pub enum Verdict {
+    // The pieces can't cover the board, e.g. they have too few squares
+    Invalid(SolveOneError),
+    // A coloring argument shows there's no solution without searching
+    RuledOut,
+    Solved(Position),
+    // The search visited every node without finding a solution
+    NoSolution,
+    // The search was abandoned at the node limit
+    Undetermined,
+}
+
+#[derive(Debug)]
+pub struct Explanation {
+    pub parity: ParityReport,
+    pub verdict: Verdict,
+    // The nodes the search visited, 0 if there was no need to search
+    pub nodes: u64,
+}
+
+// Explain whether the puzzle has a solution, searching at most `max_nodes`
+// nodes
+#[must_use]
+pub fn explain(spec: &PuzzleSpec, max_nodes: u64) -> Explanation {
+    let parity = analyze_parity(spec);
+    let explanation = |verdict, nodes| Explanation {
+        parity,
+        verdict,
+        nodes,
+    };
+
+    // Boards that only fit the bitboard transposed are searched transposed
+    let transpose = !board_fits(spec.row_count, spec.column_count)
+        && board_fits(spec.column_count, spec.row_count);
+    let searched = if transpose {
+        spec.transformed(Symmetry::Transpose)
+    } else {
+        spec.clone()
+    };
+    if let Err(err) = validate(searched.row_count, searched.column_count, &searched.pieces) {
+        return explanation(Verdict::Invalid(err), 0);
+    }
+    if parity.rules_out() {
+        return explanation(Verdict::RuledOut, 0);
+    }
+
+    let solutions = Solve::board(searched.row_count, searched.column_count)
+        .piece_collection(searched.pieces)
+        .max_nodes(max_nodes)
+        .run()
+        .unwrap();
+    let verdict = match solutions.solutions.into_iter().next() {
+        Some(solution) if transpose => Verdict::Solved(solution.transposed()),
+        Some(solution) => Verdict::Solved(solution),
+        None if solutions.abandoned => Verdict::Undetermined,
+        None => Verdict::NoSolution,
+    };
+    explanation(verdict, solutions.stats.nodes)
+}
+
+// The names of the coloring arguments that rule the puzzle out
+fn failed_arguments(parity: &ParityReport) -> Vec<&'static str> {
+    [
+        ("the checkerboard", parity.checkerboard_rules_out),
+        ("the T parity", parity.t_parity_rules_out),
+        ("the column stripes", parity.column_stripes.rules_out),
+        ("the row stripes", parity.row_stripes.rules_out),
+    ]
+    .iter()
+    .filter(|&&(_, rules_out)| rules_out)
+    .map(|&(name, _)| name)
+    .collect()
+}
+
+// A sentence with the verdict, then the solution if there is one and the
+// coloring arguments
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let nodes = if self.nodes == 1 { "node" } else { "nodes" };
+        match &self.verdict {
+            Verdict::Invalid(err) => return writeln!(f, "The puzzle has no solution. {err}"),
+            Verdict::RuledOut => {
+                let failed = failed_arguments(&self.parity);
+                writeln!(
+                    f,
+                    "The puzzle has no solution: {} argument{} {} it out.",
+                    failed.join(" and "),
+                    if failed.len() == 1 { "" } else { "s" },
+                    if failed.len() == 1 { "rules" } else { "rule" }
+                )?;
+            }
+            // The solution ends with a newline
+            Verdict::Solved(solution) => {
+                write!(
+                    f,
+                    "The puzzle has a solution, found after searching {} {nodes}:\n\n{solution}",
+                    self.nodes
+                )?;
+            }
+            Verdict::NoSolution => writeln!(
+                f,
+                "The puzzle has no solution. None of the coloring arguments rules it out, but \
+                 a search of all {} {nodes} found no tiling.",
+                self.nodes
+            )?,
+            Verdict::Undetermined => writeln!(
+                f,
+                "It's not known whether the puzzle has a solution. None of the coloring \
+                 arguments rules it out and the search gave up after {} {nodes}.",
+                self.nodes
+            )?,
+        }
+        write!(f, "\n{}", self.parity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explain, Verdict};
+    use crate::{PuzzleSpec, SolveOneError};
+
+    fn spec(row_count: u32, column_count: u32, pieces: &str) -> PuzzleSpec {
+        PuzzleSpec::new(row_count, column_count, pieces.parse().unwrap())
+    }
+
+    #[test]
+    fn verdicts() {
+        let explanation = explain(&spec(4, 4, "OTTT"), 1000);
+        assert!(matches!(explanation.verdict, Verdict::RuledOut));
+        assert_eq!(explanation.nodes, 0);
+        assert!(explanation
+            .to_string()
+            .starts_with("The puzzle has no solution: the T parity argument rules it out.\n\n"));
+
+        let explanation = explain(&spec(4, 5, "OOOSZ"), 1000);
+        assert!(explanation.to_string().starts_with(
+            "The puzzle has no solution: the column stripes argument rules it out.\n"
+        ));
+
+        let explanation = explain(&spec(4, 4, "IIOO"), 1000);
+        let Verdict::Solved(solution) = &explanation.verdict else {
+            panic!("{:?}", explanation.verdict);
+        };
+        assert!(solution.verify(4, 4, &"IIOO".parse().unwrap()).is_ok());
+        assert!(explanation
+            .to_string()
+            .contains(&format!(":\n\n{solution}\nCheckerboard: ")));
+
+        // Passes the coloring arguments but has no tiling: an S on a 2 by 4
+        // board leaves two holes of two squares
+        let explanation = explain(&spec(2, 4, "SZ"), 1000);
+        assert!(matches!(explanation.verdict, Verdict::NoSolution));
+        assert!(explanation.nodes > 0);
+
+        let explanation = explain(&spec(4, 6, "TTLSZL"), 2);
+        assert!(matches!(explanation.verdict, Verdict::Undetermined));
+
+        let explanation = explain(&spec(4, 3, "II"), 1000);
+        assert!(matches!(
+            explanation.verdict,
+            Verdict::Invalid(SolveOneError::InconsistentPieceCount { .. })
+        ));
+
+        // Only fits the bitboard transposed
+        let explanation = explain(&spec(16, 3, "IIIIIIIIIIII"), 1000);
+        let Verdict::Solved(solution) = &explanation.verdict else {
+            panic!("{:?}", explanation.verdict);
+        };
+        assert_eq!((solution.row_count(), solution.column_count()), (16, 3));
+    }
+}
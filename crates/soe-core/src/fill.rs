@@ -0,0 +1,318 @@
+// The order in which the search fills the board. The solver always covers the
+// first empty square next, so the scan order decides which square that is.
+// The row-major order is the fastest, because its candidates are looked up in
+// a table, but other orders can need far fewer nodes on some piece sets, e.g.
+// column-major on tall boards with many I pieces.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    Board, FixedPiece, Outcome, PieceCollection, Position, SolveOptions, Solver, PIECE_MAP,
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum FillStrategy {
+    #[default]
+    RowMajor,
+    ColumnMajor,
+    // Rows alternately left to right and right to left
+    Boustrophedon,
+    // Clockwise from the top-left corner towards the center
+    Spiral,
+}
+
+#[derive(Debug)]
+pub struct ParseFillStrategyError;
+
+impl Display for ParseFillStrategyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The fill strategy must be row-major, column-major, boustrophedon or spiral."
+        )
+    }
+}
+
+impl Error for ParseFillStrategyError {}
+
+impl FromStr for FillStrategy {
+    type Err = ParseFillStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "row-major" => Ok(FillStrategy::RowMajor),
+            "column-major" => Ok(FillStrategy::ColumnMajor),
+            "boustrophedon" => Ok(FillStrategy::Boustrophedon),
+            "spiral" => Ok(FillStrategy::Spiral),
+            _ => Err(ParseFillStrategyError),
+        }
+    }
+}
+
+impl FillStrategy {
+    // The squares of a `row_count` by `column_count` board as (row, column)
+    // in the order they are filled
+    #[must_use]
+    pub fn order(self, row_count: u32, column_count: u32) -> Vec<(u32, u32)> {
+        let rows = 0..row_count;
+        match self {
+            FillStrategy::RowMajor => rows
+                .flat_map(|row| (0..column_count).map(move |column| (row, column)))
+                .collect(),
+            FillStrategy::ColumnMajor => (0..column_count)
+                .flat_map(|column| (0..row_count).map(move |row| (row, column)))
+                .collect(),
+            FillStrategy::Boustrophedon => rows
+                .flat_map(|row| {
+                    (0..column_count).map(move |column| {
+                        if row % 2 == 0 {
+                            (row, column)
+                        } else {
+                            (row, column_count - 1 - column)
+                        }
+                    })
+                })
+                .collect(),
+            FillStrategy::Spiral => spiral(row_count, column_count),
+        }
+    }
+}
+
+fn spiral(row_count: u32, column_count: u32) -> Vec<(u32, u32)> {
+    let mut order = Vec::with_capacity((row_count * column_count) as usize);
+    let (mut top, mut left) = (0, 0);
+    let (mut bottom, mut right) = (row_count, column_count);
+    while top < bottom && left < right {
+        order.extend((left..right).map(|column| (top, column)));
+        order.extend((top + 1..bottom).map(|row| (row, right - 1)));
+        if bottom - top > 1 {
+            order.extend((left..right - 1).rev().map(|column| (bottom - 1, column)));
+        }
+        if right - left > 1 {
+            order.extend((top + 1..bottom - 1).rev().map(|row| (row, left)));
+        }
+        top += 1;
+        left += 1;
+        bottom -= 1;
+        right -= 1;
+    }
+    order
+}
+
+// Solve an empty board in the order of `options.fill`. The search is
+// abandoned once `cancelled` is set.
+pub(crate) fn solve_one(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    options: &SolveOptions,
+    cancelled: &AtomicBool,
+) -> Outcome {
+    let max_nodes = options.max_nodes.unwrap_or(u64::MAX);
+    let stop = || cancelled.load(Ordering::Relaxed);
+    let (solution, stopped) = match options.fill {
+        // The row-major search of the transposed board fills the board in
+        // column-major order
+        FillStrategy::ColumnMajor => {
+            let board = Board::new(column_count, row_count);
+            let mut solver = Solver::new(board, pieces.transposed())
+                .with_max_nodes(max_nodes)
+                .with_stop(&stop);
+            let solution = solver.solve_one().map(|solution| solution.transposed());
+            (solution, solver.stopped)
+        }
+        FillStrategy::RowMajor => {
+            let board = Board::new(row_count, column_count);
+            let mut solver = Solver::new(board, pieces)
+                .with_max_nodes(max_nodes)
+                .with_stop(&stop);
+            (solver.solve_one(), solver.stopped)
+        }
+        strategy => {
+            let board = Board::new(row_count, column_count);
+            let mut solver = OrderedSolver::new(board, pieces, strategy, max_nodes, cancelled);
+            (solver.solve_one(), solver.stopped)
+        }
+    };
+    match solution {
+        Some(solution) => Outcome::Solved(solution),
+        None if stopped => Outcome::Undetermined,
+        None => Outcome::NoSolution,
+    }
+}
+
+// A search that covers the first empty square in any order. It tries every
+// placement over the square instead of looking candidates up.
+struct OrderedSolver<'a> {
+    board: Board,
+    pieces: PieceCollection,
+    // The bit of each square in fill order
+    order: Vec<u64>,
+    // The placements that cover each square in `order` and no square before
+    // it, as the bitmap and the fixed piece
+    placements: Vec<Vec<(u64, FixedPiece)>>,
+    nodes: u64,
+    max_nodes: u64,
+    // Polled every 1024 nodes; the search is abandoned once it's set
+    cancelled: &'a AtomicBool,
+    stopped: bool,
+}
+
+impl<'a> OrderedSolver<'a> {
+    fn new(
+        board: Board,
+        pieces: PieceCollection,
+        strategy: FillStrategy,
+        max_nodes: u64,
+        cancelled: &'a AtomicBool,
+    ) -> Self {
+        let row_count = board.height as u32;
+        let column_count = board.width as u32 - 1;
+        let order: Vec<u64> = strategy
+            .order(row_count, column_count)
+            .into_iter()
+            .map(|(row, column)| 1 << (row as usize * board.width + column as usize))
+            .collect();
+
+        let mut placements = vec![vec![]; order.len()];
+        for &fixed_piece in &FixedPiece::array() {
+            for row in 0..row_count {
+                for column in 0..column_count {
+                    let Some(bitmap) = board.bitmap_at(fixed_piece, row, column) else {
+                        continue;
+                    };
+                    // Leave out placements that cross the border
+                    if bitmap & board.bits != 0 {
+                        continue;
+                    }
+                    let first = order.iter().position(|&bit| bitmap & bit != 0).unwrap();
+                    placements[first].push((bitmap, fixed_piece));
+                }
+            }
+        }
+
+        Self {
+            board,
+            pieces,
+            order,
+            placements,
+            nodes: 0,
+            max_nodes,
+            cancelled,
+            stopped: false,
+        }
+    }
+
+    fn solve_one(&mut self) -> Option<Position> {
+        self.search(0)
+    }
+
+    // Squares before `start` in the order are all covered
+    fn search(&mut self, start: usize) -> Option<Position> {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes
+            || self.nodes.is_multiple_of(0x400) && self.cancelled.load(Ordering::Relaxed)
+        {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return None;
+        }
+        let Some(index) = (start..self.order.len()).find(|&i| self.board.bits & self.order[i] == 0)
+        else {
+            return Some(self.board.position());
+        };
+
+        for placement in 0..self.placements[index].len() {
+            let (bitmap, fixed_piece) = self.placements[index][placement];
+            let piece = PIECE_MAP[fixed_piece as usize];
+            if self.board.bits & bitmap != 0 || self.pieces.count(piece) == 0 {
+                continue;
+            }
+            self.board.add(bitmap, fixed_piece);
+            self.pieces.remove(piece);
+            let solution = self.search(index + 1);
+            if solution.is_some() || self.stopped {
+                return solution;
+            }
+            self.board.pop();
+            self.pieces.add(piece);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FillStrategy::{self, *};
+    use crate::{puzzles, solve_one_with_options, Outcome, SolveOptions};
+
+    #[test]
+    fn order() {
+        assert_eq!(
+            Boustrophedon.order(2, 3),
+            [(0, 0), (0, 1), (0, 2), (1, 2), (1, 1), (1, 0)]
+        );
+        assert_eq!(ColumnMajor.order(2, 2), [(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(
+            Spiral.order(3, 3),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (2, 1),
+                (2, 0),
+                (1, 0),
+                (1, 1)
+            ]
+        );
+        for &(row_count, column_count) in &[(1, 4), (4, 1), (2, 5), (5, 4)] {
+            let mut order = Spiral.order(row_count, column_count);
+            assert_eq!(order.len(), (row_count * column_count) as usize);
+            order.sort_unstable();
+            assert_eq!(order, RowMajor.order(row_count, column_count));
+        }
+        assert_eq!("spiral".parse::<FillStrategy>().unwrap(), Spiral);
+        assert!("diagonal".parse::<FillStrategy>().is_err());
+    }
+
+    #[test]
+    fn solve() {
+        for puzzle in puzzles::all().iter().step_by(7) {
+            for &fill in &[RowMajor, ColumnMajor, Boustrophedon, Spiral] {
+                let options = SolveOptions {
+                    fill,
+                    ..SolveOptions::default()
+                };
+                let pieces = puzzle.tetrominoes.parse().unwrap();
+                let outcome =
+                    solve_one_with_options(puzzle.row_count, puzzle.column_count, pieces, &options)
+                        .unwrap();
+                let solution = outcome.into_solution().unwrap();
+                assert_eq!(
+                    solution.to_replay().split("; ").count(),
+                    puzzle.tetrominoes.len()
+                );
+            }
+        }
+
+        let options = SolveOptions {
+            fill: Spiral,
+            ..SolveOptions::default()
+        };
+        let outcome = solve_one_with_options(4, 4, "IITT".parse().unwrap(), &options);
+        assert!(outcome.unwrap() == Outcome::NoSolution);
+        let options = SolveOptions {
+            fill: Boustrophedon,
+            max_nodes: Some(3),
+            ..SolveOptions::default()
+        };
+        let outcome = solve_one_with_options(4, 8, "IIIIIIIL".parse().unwrap(), &options);
+        assert!(outcome.unwrap().is_undetermined());
+    }
+}
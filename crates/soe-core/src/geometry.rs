@@ -0,0 +1,310 @@
+// The geometry of the tetrominoes. Co-ordinates are (row, column) with rows
+// increasing downwards, so rotations are as seen on the screen.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{piece_shape, FixedPiece};
+
+// A square relative to some origin
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Cell(pub i32, pub i32);
+
+impl Cell {
+    #[must_use]
+    pub const fn translate(self, rows: i32, columns: i32) -> Self {
+        Cell(self.0 + rows, self.1 + columns)
+    }
+
+    // Rotate a quarter turn clockwise around the origin.
+    #[must_use]
+    pub const fn rotate(self) -> Self {
+        Cell(self.1, -self.0)
+    }
+
+    // Reflect in the row axis, swapping left and right.
+    #[must_use]
+    pub const fn reflect(self) -> Self {
+        Cell(self.0, -self.1)
+    }
+}
+
+// The four squares of a tetromino
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Shape(pub [Cell; 4]);
+
+impl Shape {
+    #[must_use]
+    pub fn translate(self, rows: i32, columns: i32) -> Self {
+        Shape(self.0.map(|c| c.translate(rows, columns)))
+    }
+
+    // Rotate a quarter turn clockwise around the origin. The result is not
+    // normalized.
+    #[must_use]
+    pub fn rotate(self) -> Self {
+        Shape(self.0.map(Cell::rotate))
+    }
+
+    // Reflect in the row axis. The result is not normalized.
+    #[must_use]
+    pub fn reflect(self) -> Self {
+        Shape(self.0.map(Cell::reflect))
+    }
+
+    // Sort the cells in row-major order and translate the shape so that the
+    // first cell is at the origin. This is how the shapes of `FixedPiece` are
+    // positioned.
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        let mut cells = self.0;
+        cells.sort_unstable();
+        let Cell(row, column) = cells[0];
+        Shape(cells).translate(-row, -column)
+    }
+
+    // The fixed tetromino with this shape, ignoring position
+    #[must_use]
+    pub fn fixed_piece(self) -> Option<FixedPiece> {
+        let shape = self.normalize();
+        FixedPiece::array()
+            .iter()
+            .copied()
+            .find(|&p| Shape::from(p) == shape)
+    }
+}
+
+// The shape of the fixed tetromino with its first square in row-major order
+// at the origin. Cells are in row-major order.
+impl From<FixedPiece> for Shape {
+    fn from(fixed_piece: FixedPiece) -> Self {
+        let mut cells = [Cell(0, 0); 4];
+        for (cell, &(row, column)) in cells[1..].iter_mut().zip(&piece_shape(fixed_piece)) {
+            *cell = Cell(row as i32, column as i32);
+        }
+        cells.sort_unstable();
+        Shape(cells)
+    }
+}
+
+// The symmetries of a rectangle, the eight transforms of the dihedral group.
+// Rotations are clockwise.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // Swap the top and bottom rows
+    FlipVertical,
+    // Swap the left and right columns
+    FlipHorizontal,
+    // Reflect in the main diagonal
+    Transpose,
+    // Reflect in the anti-diagonal
+    AntiTranspose,
+}
+
+impl Symmetry {
+    #[must_use]
+    pub const fn count() -> usize {
+        8
+    }
+
+    #[must_use]
+    pub const fn array() -> [Self; Self::count()] {
+        use Symmetry::*;
+        [
+            Identity,
+            Rotate90,
+            Rotate180,
+            Rotate270,
+            FlipVertical,
+            FlipHorizontal,
+            Transpose,
+            AntiTranspose,
+        ]
+    }
+
+    // Returns true if the transform turns a `row_count` by `column_count`
+    // board into a `column_count` by `row_count` board.
+    #[must_use]
+    pub const fn swaps_dimensions(self) -> bool {
+        use Symmetry::*;
+        matches!(self, Rotate90 | Rotate270 | Transpose | AntiTranspose)
+    }
+
+    // Returns true if the transform turns the board over, which turns J pieces
+    // into L pieces and S pieces into Z pieces.
+    #[must_use]
+    pub const fn is_reflection(self) -> bool {
+        use Symmetry::*;
+        matches!(
+            self,
+            FlipVertical | FlipHorizontal | Transpose | AntiTranspose
+        )
+    }
+
+    // The transform that undoes this one
+    #[must_use]
+    pub const fn inverse(self) -> Self {
+        use Symmetry::*;
+        match self {
+            Rotate90 => Rotate270,
+            Rotate270 => Rotate90,
+            symmetry => symmetry,
+        }
+    }
+
+    // The image of the square `cell` of a `row_count` by `column_count`
+    // board.
+    #[must_use]
+    pub const fn apply(self, cell: Cell, row_count: i32, column_count: i32) -> Cell {
+        use Symmetry::*;
+        let Cell(row, column) = cell;
+        let (last_row, last_column) = (row_count - 1, column_count - 1);
+        match self {
+            Identity => Cell(row, column),
+            Rotate90 => Cell(column, last_row - row),
+            Rotate180 => Cell(last_row - row, last_column - column),
+            Rotate270 => Cell(last_column - column, row),
+            FlipVertical => Cell(last_row - row, column),
+            FlipHorizontal => Cell(row, last_column - column),
+            Transpose => Cell(column, row),
+            AntiTranspose => Cell(last_column - column, last_row - row),
+        }
+    }
+}
+
+// The names of the symmetries in the order of `Symmetry::array`
+const SYMMETRY_NAMES: [&str; Symmetry::count()] = [
+    "identity",
+    "rotate90",
+    "rotate180",
+    "rotate270",
+    "flip-vertical",
+    "flip-horizontal",
+    "transpose",
+    "anti-transpose",
+];
+
+#[derive(Debug)]
+pub struct ParseSymmetryError;
+
+impl Display for ParseSymmetryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The symmetry must be one of {}.",
+            SYMMETRY_NAMES.join(", ")
+        )
+    }
+}
+
+impl Error for ParseSymmetryError {}
+
+// Parsed from the names written by `Display`, e.g. "rotate90" and
+// "flip-horizontal"
+impl FromStr for Symmetry {
+    type Err = ParseSymmetryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SYMMETRY_NAMES
+            .iter()
+            .position(|&name| name == s)
+            .map(|index| Symmetry::array()[index])
+            .ok_or(ParseSymmetryError)
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let index = Symmetry::array().iter().position(|s| s == self).unwrap();
+        write!(f, "{}", SYMMETRY_NAMES[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, Shape, Symmetry};
+    use crate::FixedPiece::{self, *};
+
+    #[test]
+    fn from_fixed_piece() {
+        assert_eq!(
+            Shape::from(L4),
+            Shape([Cell(0, 0), Cell(1, -2), Cell(1, -1), Cell(1, 0)])
+        );
+        for &fixed_piece in &FixedPiece::array() {
+            let shape = Shape::from(fixed_piece);
+            assert_eq!(shape.normalize(), shape);
+            assert_eq!(shape.translate(3, -2).fixed_piece(), Some(fixed_piece));
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        assert_eq!(Shape::from(I1).rotate().fixed_piece(), Some(I2));
+        assert_eq!(Shape::from(O1).rotate().fixed_piece(), Some(O1));
+        assert_eq!(Shape::from(T1).rotate().fixed_piece(), Some(T2));
+        assert_eq!(Shape::from(T2).rotate().fixed_piece(), Some(T3));
+        assert_eq!(Shape::from(S1).rotate().fixed_piece(), Some(S2));
+        let shape = Shape::from(J1);
+        assert_eq!(shape.rotate().rotate().rotate().rotate(), shape);
+    }
+
+    #[test]
+    fn reflect() {
+        assert_eq!(Shape::from(J1).reflect().fixed_piece(), Some(L1));
+        assert_eq!(Shape::from(S1).reflect().fixed_piece(), Some(Z1));
+        assert_eq!(Shape([Cell(0, 0); 4]).fixed_piece(), None);
+    }
+
+    #[test]
+    fn apply() {
+        // The top-right corner of a 2 by 3 board
+        let corner = Cell(0, 2);
+        let images: Vec<_> = Symmetry::array()
+            .iter()
+            .map(|s| s.apply(corner, 2, 3))
+            .collect();
+        assert_eq!(
+            images,
+            [
+                Cell(0, 2),
+                Cell(2, 1),
+                Cell(1, 0),
+                Cell(0, 0),
+                Cell(1, 2),
+                Cell(0, 0),
+                Cell(2, 0),
+                Cell(0, 1),
+            ]
+        );
+        for symmetry in Symmetry::array() {
+            let image = symmetry.apply(corner, 2, 3);
+            let (row_count, column_count) = if symmetry.swaps_dimensions() {
+                (3, 2)
+            } else {
+                (2, 3)
+            };
+            assert_eq!(
+                symmetry.inverse().apply(image, row_count, column_count),
+                corner
+            );
+        }
+    }
+
+    #[test]
+    fn parse_symmetry() {
+        for symmetry in Symmetry::array() {
+            assert_eq!(
+                symmetry.to_string().parse::<Symmetry>().ok(),
+                Some(symmetry)
+            );
+        }
+        assert_eq!(Symmetry::FlipHorizontal.to_string(), "flip-horizontal");
+        assert!("rotate45".parse::<Symmetry>().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// A puzzle with its options that can be moved to another thread and solved
+// there, and cancelled from any thread:
+//
+//     let handle = SolverHandle::new(spec, SolveOptions::default());
+//     let worker = handle.clone();
+//     let search = thread::spawn(move || worker.solve());
+//     handle.cancel();
+//
+// All the public types of the crate that hold data, e.g. `PieceCollection`,
+// `Position`, `SolveOptions` and `Outcome`, are `Send` and `Sync`, so they can
+// also be kept in shared state without wrappers.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{solve_cancellable, Outcome, PuzzleSpec, SolveOneError, SolveOptions};
+
+// Clones share the cancellation, so a clone can be kept to cancel the search
+// of another.
+#[derive(Clone, Debug)]
+pub struct SolverHandle {
+    spec: PuzzleSpec,
+    options: SolveOptions,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl SolverHandle {
+    #[must_use]
+    pub fn new(spec: PuzzleSpec, options: SolveOptions) -> Self {
+        Self {
+            spec,
+            options,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[must_use]
+    pub fn spec(&self) -> &PuzzleSpec {
+        &self.spec
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &SolveOptions {
+        &self.options
+    }
+
+    // Solve the puzzle as `solve_one_with_options` does. The outcome is
+    // `Undetermined` if the search is cancelled before it completes.
+    pub fn solve(&self) -> Result<Outcome, SolveOneError> {
+        solve_cancellable(
+            self.spec.row_count,
+            self.spec.column_count,
+            self.spec.pieces.clone(),
+            &self.options,
+            &self.cancelled,
+        )
+    }
+
+    // Abandon the searches of this handle and its clones, both running and
+    // future ones. The search notices within about a thousand nodes.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::thread;
+
+    use super::SolverHandle;
+    use crate::{
+        AnytimeSolution, FillStrategy, Outcome, PieceCollection, Position, PuzzleSpec, SearchStats,
+        Solutions, Solve, SolveOptions,
+    };
+
+    fn assert_thread_safe<T: Send + Sync + Clone + Debug>() {}
+
+    #[test]
+    fn thread_safe_types() {
+        assert_thread_safe::<PieceCollection>();
+        assert_thread_safe::<Position>();
+        assert_thread_safe::<PuzzleSpec>();
+        assert_thread_safe::<SolveOptions>();
+        assert_thread_safe::<Outcome>();
+        assert_thread_safe::<SearchStats>();
+        assert_thread_safe::<Solutions>();
+        assert_thread_safe::<Solve>();
+        assert_thread_safe::<AnytimeSolution>();
+        assert_thread_safe::<SolverHandle>();
+    }
+
+    #[test]
+    fn solve_on_another_thread() {
+        let spec = PuzzleSpec::new(4, 6, "TTLSZL".parse().unwrap());
+        let handle = SolverHandle::new(spec, SolveOptions::default());
+        let worker = handle.clone();
+        let outcome = thread::spawn(move || worker.solve()).join().unwrap();
+        assert!(outcome.unwrap().solution().is_some());
+        assert!(!handle.is_cancelled());
+    }
+
+    #[test]
+    fn cancel() {
+        // A puzzle with no solution that takes a long time to show it
+        let spec = PuzzleSpec::new(4, 12, "IITTTLLLJJSZ".parse().unwrap());
+        for (fill, threads) in [
+            (FillStrategy::RowMajor, 1),
+            (FillStrategy::RowMajor, 4),
+            (FillStrategy::Spiral, 1),
+        ] {
+            let options = SolveOptions {
+                threads,
+                fill,
+                ..SolveOptions::default()
+            };
+            let handle = SolverHandle::new(spec.clone(), options);
+            handle.cancel();
+            assert_eq!(handle.solve().unwrap(), Outcome::Undetermined);
+        }
+    }
+
+    #[test]
+    fn debug() {
+        let pieces: PieceCollection = "ZLZL".parse().unwrap();
+        assert_eq!(format!("{pieces:?}"), "PieceCollection(\"LLZZ\")");
+        let position: Position = "AB\nAB\n".parse().unwrap();
+        assert_eq!(format!("{position:?}"), "Position(\"AB\\nAB\\n\")");
+    }
+}
@@ -0,0 +1,125 @@
+// Hints that reveal only the parts of a solution that can be deduced.
+
+use crate::{
+    AnnotatedPosition, Annotation, Board, FixedPiece, Placement, Position, PuzzleSpec,
+    SolveOneError,
+};
+
+// The placements that appear in every solution of the puzzle, in canonical
+// order. Returns `None` if the puzzle has no solution.
+pub fn forced_cells(puzzle: &PuzzleSpec) -> Result<Option<Vec<Placement>>, SolveOneError> {
+    puzzle.validate()?;
+
+    // The stack entries of the placements common to all the solutions so far
+    let mut forced: Option<Vec<(u64, FixedPiece)>> = None;
+    let mut solver = puzzle.solver();
+    solver.for_each_solution(&mut |board| {
+        let entries = &board.stack[..board.stack_count];
+        match &mut forced {
+            None => forced = Some(entries.to_vec()),
+            Some(forced) => forced.retain(|entry| entries.contains(entry)),
+        }
+    });
+
+    let board = &solver.board;
+    Ok(forced.map(|forced| {
+        forced
+            .iter()
+            .map(|&entry| board.placement_of(entry))
+            .collect()
+    }))
+}
+
+// A position showing only the placements that appear in every solution.
+// Returns `None` if the puzzle has no solution.
+pub fn forced_position(puzzle: &PuzzleSpec) -> Result<Option<Position>, SolveOneError> {
+    let Some(forced) = forced_cells(puzzle)? else {
+        return Ok(None);
+    };
+    let mut board = Board::new(puzzle.row_count, puzzle.column_count);
+    for placement in forced {
+        board
+            .place_at(placement.piece, placement.row, placement.column)
+            .unwrap();
+    }
+    Ok(Some(board.position()))
+}
+
+// The forced position with the top-left square of each placement numbered
+// in the order to place them, canonical order. Returns `None` if the puzzle
+// has no solution.
+pub fn numbered_position(puzzle: &PuzzleSpec) -> Result<Option<AnnotatedPosition>, SolveOneError> {
+    let Some(forced) = forced_cells(puzzle)? else {
+        return Ok(None);
+    };
+    let mut board = Board::new(puzzle.row_count, puzzle.column_count);
+    for placement in &forced {
+        board
+            .place_at(placement.piece, placement.row, placement.column)
+            .unwrap();
+    }
+    let mut position = AnnotatedPosition::from(board.position());
+    for (number, placement) in (1..).zip(&forced) {
+        position.annotate(placement.row, placement.column, Annotation::Number(number));
+    }
+    Ok(Some(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{forced_cells, forced_position, numbered_position};
+    use crate::FixedPiece::*;
+    use crate::{Placement, PuzzleSpec};
+
+    #[test]
+    fn forced() {
+        let puzzle = PuzzleSpec::new(4, 4, "IIOO".parse().unwrap());
+        assert_eq!(forced_cells(&puzzle).unwrap(), Some(vec![]));
+
+        let puzzle = PuzzleSpec::new(6, 4, "IOSZJL".parse().unwrap());
+        let forced = forced_cells(&puzzle).unwrap().unwrap();
+        assert_eq!(
+            forced,
+            [Placement {
+                piece: O1,
+                row: 2,
+                column: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn no_solution() {
+        let puzzle = PuzzleSpec::new(4, 4, "IITT".parse().unwrap());
+        assert_eq!(forced_cells(&puzzle).unwrap(), None);
+        assert!(forced_position(&puzzle).unwrap().is_none());
+    }
+
+    #[test]
+    fn position() {
+        let puzzle = PuzzleSpec::new(4, 6, "TTSSZL".parse().unwrap());
+        let position = forced_position(&puzzle).unwrap().unwrap();
+        assert_eq!(
+            position.to_string(),
+            "...AA.\n\
+             ..AA..\n\
+             ..BB..\n\
+             .BB...\n"
+        );
+    }
+
+    #[test]
+    fn numbered() {
+        let puzzle = PuzzleSpec::new(4, 6, "TTSSZL".parse().unwrap());
+        let position = numbered_position(&puzzle).unwrap().unwrap();
+        assert_eq!(
+            position.to_string(),
+            "...1A.\n\
+             ..AA..\n\
+             ..2B..\n\
+             .BB...\n"
+        );
+        let puzzle = PuzzleSpec::new(4, 4, "IITT".parse().unwrap());
+        assert!(numbered_position(&puzzle).unwrap().is_none());
+    }
+}
@@ -0,0 +1,89 @@
+// Rendering of positions as HTML tables with inline styles, so they can be
+// pasted anywhere without a style sheet.
+
+use std::fmt::Write;
+
+use crate::palette::css;
+use crate::{Palette, Position};
+
+impl Position {
+    // Render the position as a `<table>`, one cell per square. Squares are
+    // colored by the kind of piece that covers them and pieces are outlined.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        self.to_html_with_palette(&Palette::default())
+    }
+
+    // Render the position as `to_html` does, in the colors of `palette`
+    #[must_use]
+    pub fn to_html_with_palette(&self, palette: &Palette) -> String {
+        let (row_count, column_count) = (self.row_count() as usize, self.column_count() as usize);
+        let width = column_count + 1;
+        let pieces = self.label_pieces();
+        let label = |row: usize, column: usize| self.squares[row * width + column];
+        // The style of the border between squares with labels `a` and `b`.
+        // Borders of the board have `None` on one side.
+        let solid = format!("2px solid {}", css(palette.border));
+        let border = |a: Option<u8>, b: Option<u8>| if a == b { "none" } else { &solid };
+
+        let mut html = String::from("<table style=\"border-collapse:collapse\">\n");
+        for row in 0..row_count {
+            html.push_str("<tr>");
+            for column in 0..column_count {
+                let this = Some(label(row, column));
+                let up = row.checked_sub(1).map(|row| label(row, column));
+                let down = (row + 1 < row_count).then(|| label(row + 1, column));
+                let left = column.checked_sub(1).map(|column| label(row, column));
+                let right = (column + 1 < column_count).then(|| label(row, column + 1));
+                let background = css(match label(row, column) {
+                    b'.' => palette.background,
+                    b'#' => palette.blocked,
+                    label => pieces
+                        .get(&label)
+                        .map_or(palette.unknown, |&piece| palette.piece(piece)),
+                });
+                write!(
+                    html,
+                    "<td style=\"width:1.5em;height:1.5em;padding:0;background:{};\
+                     border-top:{};border-bottom:{};border-left:{};border-right:{}\"></td>",
+                    background,
+                    border(this, up),
+                    border(this, down),
+                    border(this, left),
+                    border(this, right),
+                )
+                .unwrap();
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Palette, Position};
+
+    #[test]
+    fn to_html() {
+        let position = Position::from_replay(2, 4, "I2@r0c0").unwrap();
+        let html = position.to_html();
+        let rows: Vec<_> = html.lines().collect();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], "<table style=\"border-collapse:collapse\">");
+        assert_eq!(rows[3], "</table>");
+        assert_eq!(rows[1].matches("<td").count(), 4);
+        assert_eq!(rows[1].matches("background:#0ff").count(), 4);
+        assert_eq!(rows[2].matches("background:#fff").count(), 4);
+        assert!(rows[1].starts_with(
+            "<tr><td style=\"width:1.5em;height:1.5em;padding:0;background:#0ff;\
+             border-top:2px solid #000;border-bottom:2px solid #000;\
+             border-left:2px solid #000;border-right:none\"></td>"
+        ));
+
+        let html = position.to_html_with_palette(&Palette::colorblind());
+        assert_eq!(html.matches("background:#56b4e9").count(), 4);
+    }
+}
@@ -0,0 +1,35 @@
+// JSON strings, for the exports and messages that are written by hand rather
+// than with a JSON library
+
+use std::fmt::Write as _;
+
+// `s` in double quotes, with quotes, backslashes and control characters
+// escaped
+#[must_use]
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(json_string("LLZZ"), "\"LLZZ\"");
+        assert_eq!(
+            json_string("a \"b\"\\c\n\u{7f}"),
+            "\"a \\\"b\\\"\\\\c\\u000a\\u007f\""
+        );
+    }
+}
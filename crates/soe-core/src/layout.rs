@@ -0,0 +1,90 @@
+// Laying out several printed boards side by side, so that many small
+// solutions fit on a screen.
+
+// The gap between blocks in a row
+const GAP: usize = 2;
+
+// Lay out `blocks` of text in rows of at most `columns` blocks, with each row
+// at most `max_width` characters wide if given. A block wider than
+// `max_width` gets a row to itself. Rows are separated by an empty line and
+// each block is padded to the width of its widest line.
+#[must_use]
+pub fn side_by_side(blocks: &[String], columns: usize, max_width: Option<usize>) -> String {
+    let width = |block: &String| block.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut rows: Vec<Vec<&String>> = vec![];
+    let mut row_width = 0;
+    for block in blocks {
+        let block_width = width(block);
+        match rows.last_mut() {
+            Some(row)
+                if row.len() < columns
+                    && max_width.is_none_or(|max| row_width + GAP + block_width <= max) =>
+            {
+                row.push(block);
+                row_width += GAP + block_width;
+            }
+            _ => {
+                rows.push(vec![block]);
+                row_width = block_width;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        let height = row
+            .iter()
+            .map(|block| block.lines().count())
+            .max()
+            .unwrap_or(0);
+        for line in 0..height {
+            let mut text = String::new();
+            for (column, block) in row.iter().enumerate() {
+                if column > 0 {
+                    text.push_str(&" ".repeat(GAP));
+                }
+                let part = block.lines().nth(line).unwrap_or("");
+                text.push_str(part);
+                text.push_str(&" ".repeat(width(block) - part.chars().count()));
+            }
+            output.push_str(text.trim_end());
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::side_by_side;
+
+    #[test]
+    fn layout() {
+        let blocks: Vec<String> = ["AB\nAB\n", "C\nC\nC\n", "DDD\n"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            side_by_side(&blocks, 3, None),
+            "AB  C  DDD\n\
+             AB  C\n\
+             \u{20}   C\n"
+        );
+        assert_eq!(
+            side_by_side(&blocks, 2, None),
+            "AB  C\nAB  C\n    C\n\nDDD\n"
+        );
+        assert_eq!(
+            side_by_side(&blocks, 3, Some(6)),
+            "AB  C\nAB  C\n    C\n\nDDD\n"
+        );
+        assert_eq!(
+            side_by_side(&blocks, 3, Some(1)),
+            "AB\nAB\n\nC\nC\nC\n\nDDD\n"
+        );
+        assert_eq!(side_by_side(&blocks[..1], 1, None), blocks[0]);
+    }
+}
@@ -0,0 +1,2933 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::enum_glob_use)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::precedence)]
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::{self, FromStr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+pub mod analysis;
+#[cfg(feature = "render")]
+pub mod animation;
+mod annotation;
+mod anytime;
+pub mod board;
+mod board_shape;
+mod cache;
+mod capabilities;
+mod constraints;
+mod diff;
+mod difficulty;
+mod dimensions;
+mod dlx;
+mod engine;
+mod explain;
+mod fill;
+pub mod geometry;
+mod handle;
+pub mod hints;
+mod html;
+mod json;
+mod layout;
+mod narrate;
+mod orientation;
+mod palette;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parity;
+mod play;
+pub mod puzzles;
+#[cfg(feature = "parallel")]
+mod queue;
+#[cfg(feature = "render")]
+mod raster;
+mod regions;
+mod relabel;
+mod replay;
+mod rng;
+mod sampling;
+#[cfg(feature = "serde")]
+mod serialization;
+mod session;
+pub mod smallboards;
+pub mod soldb;
+mod solutions;
+mod solve;
+mod svg;
+mod symmetry;
+mod symmetry_breaking;
+pub mod trace;
+mod verify;
+
+pub use annotation::{AnnotatedPosition, Annotation, Arrow};
+pub use anytime::{solve_anytime, AnytimeSolution};
+pub use board_shape::{BoardShape, ParseBoardShapeError};
+pub use cache::{SolveCache, SolveCacheError};
+pub use capabilities::{capabilities, Capabilities};
+pub use constraints::{solve_constrained, ParseConstraintError, PieceConstraints};
+pub use diff::PositionDiff;
+pub use difficulty::estimate_difficulty;
+pub use dimensions::{CellIndex, ColCount, RowCount};
+pub use engine::{Backtracker, Dlx, EngineRegistry, SmallBoards, SolveResult, TilingEngine};
+pub use explain::{explain, Explanation, Verdict};
+pub use fill::{FillStrategy, ParseFillStrategyError};
+pub use handle::SolverHandle;
+pub use json::json_string;
+pub use layout::side_by_side;
+pub use orientation::{OrientationWeights, ParseOrientationWeightsError};
+pub use palette::{Palette, ParsePaletteError};
+#[cfg(feature = "parallel")]
+pub use parallel::solve_batch;
+pub use parity::{analyze_parity, ParityReport, StripeCount};
+pub use play::{Game, MoveError};
+#[cfg(feature = "parallel")]
+pub use queue::{JobError, JobHandle, JobQueue, QueueOptions, SubmitError};
+pub use rand_core::{RngCore, SeedableRng};
+#[cfg(feature = "render")]
+pub use raster::RasterOptions;
+pub use relabel::{LabelOrder, ParseLabelOrderError};
+pub use replay::ParseReplayError;
+pub use rng::Rng;
+pub use sampling::sample_solutions;
+pub use session::Session;
+pub use solutions::{solve_all_compact, DecodePositionError, SolutionSet};
+pub use solve::{Progress, SearchStats, Solutions, Solve, SolveError};
+pub use verify::{ParsePositionError, VerifyError};
+
+use FixedPiece::*;
+use Piece::*;
+
+use geometry::{Cell, Shape, Symmetry};
+use symmetry_breaking::RootSymmetry;
+
+// The maximum of the number of pieces that this library can handle
+pub const MAX_PIECE_COUNT: usize = 12;
+
+// The largest board the search can handle, as the number of rows times the
+// number of columns plus one. The board is kept in a 64 bit bitboard with a
+// border column after each row, and the bit past the last square must be
+// free. Boards that don't fit this way round are solved transposed by
+// `solve_one` when the transposed board fits.
+#[must_use]
+pub const fn max_board_area() -> u32 {
+    63
+}
+
+// The most columns a board the search can handle may have, so that a
+// vertical I piece fits in the bitboard
+#[must_use]
+pub const fn max_board_column_count() -> u32 {
+    20
+}
+
+// Whether the search can handle a `row_count` by `column_count` board without
+// transposing it. See `max_board_area` and `max_board_column_count`.
+#[must_use]
+pub fn board_fits(row_count: u32, column_count: u32) -> bool {
+    column_count <= max_board_column_count()
+        && u64::from(row_count) * u64::from(column_count + 1) <= u64::from(max_board_area())
+}
+
+// Whether the search can only handle a `row_count` by `column_count` board by
+// transposing it
+pub(crate) fn fits_only_transposed(row_count: u32, column_count: u32) -> bool {
+    !board_fits(row_count, column_count) && board_fits(column_count, row_count)
+}
+
+#[derive(Debug)]
+pub enum SolveOneError {
+    // The number of squares on the board must be a multiple of 4
+    InvalidBoardSize,
+    // The number of squares must equare 4 * the number of pieces.
+    // `square_count` is the number of open squares on the board.
+    InconsistentPieceCount {
+        square_count: u32,
+        piece_count: u32,
+        suggestions: Vec<Suggestion>,
+    },
+    // The number of pieces is greater than `MAX_PIECE_COUNT`
+    PieceCountOverLimit,
+    // The board is larger than `max_board_area` or wider than
+    // `max_board_column_count`
+    BoardTooLarge {
+        row_count: u32,
+        column_count: u32,
+    },
+    // No orientation of `piece` fits on a board whose shorter side is
+    // `width` squares long, e.g. anything but an I on a board one square wide
+    PieceDoesntFit {
+        piece: Piece,
+        width: u32,
+    },
+}
+
+impl Display for SolveOneError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use SolveOneError::*;
+        match self {
+            InvalidBoardSize => write!(
+                f,
+                "The total number of squares on the board is not a multiple of four."
+            ),
+            InconsistentPieceCount {
+                square_count,
+                piece_count,
+                suggestions,
+            } => {
+                write!(
+                    f,
+                    "The total number of squares on the board and the total number of \
+                     squares in pieces don't match. There are {piece_count} pieces for \
+                     {square_count} squares."
+                )?;
+                for (i, suggestion) in suggestions.iter().enumerate() {
+                    let separator = if i == 0 { " Try to" } else { " or" };
+                    write!(f, "{separator} {suggestion}")?;
+                }
+                if !suggestions.is_empty() {
+                    write!(f, ".")?;
+                }
+                Ok(())
+            }
+            PieceCountOverLimit => write!(
+                f,
+                "This program can handle at most {MAX_PIECE_COUNT} tetrominoes."
+            ),
+            BoardTooLarge {
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "A board of {row_count} by {column_count} squares is too large. The number of \
+                 rows times the number of columns plus one can be at most {} and the number of \
+                 columns at most {}.",
+                max_board_area(),
+                max_board_column_count()
+            ),
+            PieceDoesntFit { piece, width: 1 } => write!(
+                f,
+                "A board one square wide can only be tiled with I pieces, not {piece:?} pieces."
+            ),
+            PieceDoesntFit { piece, width } => write!(
+                f,
+                "The {piece:?} piece doesn't fit on a board {width} squares wide."
+            ),
+        }
+    }
+}
+
+impl Error for SolveOneError {}
+
+// A change to a puzzle that makes the number of squares in pieces equal the
+// number of open squares on the board
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Suggestion {
+    AddPieces(u32),
+    RemovePieces(u32),
+    BlockSquares(u32),
+    UnblockSquares(u32),
+    Resize { row_count: u32, column_count: u32 },
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use Suggestion::*;
+        let plural = |count: u32| if count == 1 { "" } else { "s" };
+        match *self {
+            AddPieces(count) => write!(f, "add {count} piece{}", plural(count)),
+            RemovePieces(count) => write!(f, "remove {count} piece{}", plural(count)),
+            BlockSquares(count) => write!(f, "block {count} square{}", plural(count)),
+            UnblockSquares(count) => write!(f, "unblock {count} square{}", plural(count)),
+            Resize {
+                row_count,
+                column_count,
+            } => write!(f, "use a {row_count}x{column_count} board"),
+        }
+    }
+}
+
+// The suggestions for fixing a puzzle with `piece_count` pieces on a
+// `row_count` by `column_count` board with `blocked_count` blocked squares
+fn suggestions(
+    row_count: u32,
+    column_count: u32,
+    blocked_count: u32,
+    piece_count: u32,
+) -> Vec<Suggestion> {
+    use Suggestion::*;
+    let square_count = row_count * column_count - blocked_count;
+    let piece_square_count = 4 * piece_count;
+    let mut suggestions = vec![];
+    if piece_square_count < square_count {
+        let missing = square_count - piece_square_count;
+        if missing.is_multiple_of(4) && piece_count + missing / 4 <= MAX_PIECE_COUNT as u32 {
+            suggestions.push(AddPieces(missing / 4));
+        }
+        suggestions.push(BlockSquares(missing));
+    } else {
+        let extra = piece_square_count - square_count;
+        if extra.is_multiple_of(4) {
+            suggestions.push(RemovePieces(extra / 4));
+        }
+        if extra <= blocked_count {
+            suggestions.push(UnblockSquares(extra));
+        }
+    }
+    // Keep one of the dimensions. Blocked squares may not be on the resized
+    // board, so only empty boards are resized.
+    if blocked_count == 0 && piece_count != 0 {
+        if piece_square_count.is_multiple_of(row_count) {
+            suggestions.push(Resize {
+                row_count,
+                column_count: piece_square_count / row_count,
+            });
+        }
+        if piece_square_count.is_multiple_of(column_count) && row_count != column_count {
+            suggestions.push(Resize {
+                row_count: piece_square_count / column_count,
+                column_count,
+            });
+        }
+    }
+    suggestions
+}
+
+// A puzzle: the size of the board and the pieces to tile it with.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PuzzleSpec {
+    pub row_count: u32,
+    pub column_count: u32,
+    pub pieces: PieceCollection,
+}
+
+impl PuzzleSpec {
+    #[must_use]
+    pub fn new(row_count: u32, column_count: u32, pieces: PieceCollection) -> Self {
+        Self {
+            row_count,
+            column_count,
+            pieces,
+        }
+    }
+
+    #[must_use]
+    pub fn from_dimensions(
+        row_count: RowCount,
+        column_count: ColCount,
+        pieces: PieceCollection,
+    ) -> Self {
+        Self::new(row_count.get(), column_count.get(), pieces)
+    }
+
+    // The size of the board, `None` if it has no squares
+    #[must_use]
+    pub fn dimensions(&self) -> Option<(RowCount, ColCount)> {
+        Some((
+            RowCount::new(self.row_count)?,
+            ColCount::new(self.column_count)?,
+        ))
+    }
+
+    // A hash of the board size and the pieces that doesn't depend on the order
+    // of the pieces, for keying caches and finding duplicate puzzles. It is
+    // FNV-1a of the sizes and the count of each piece, so it's the same on
+    // every platform and release.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(self.row_count, self.column_count, &self.pieces)
+    }
+
+    // The same puzzle on the board turned by `symmetry`. Reflections swap the
+    // J and L pieces and the S and Z pieces.
+    #[must_use]
+    pub fn transformed(&self, symmetry: Symmetry) -> Self {
+        let (row_count, column_count) =
+            board::transform::dimensions(symmetry, self.row_count, self.column_count);
+        let pieces = if symmetry.is_reflection() {
+            self.pieces.transposed()
+        } else {
+            self.pieces.clone()
+        };
+        Self::new(row_count, column_count, pieces)
+    }
+
+    // The representative of the puzzles that are the same up to turning the
+    // board, with the symmetry that takes this puzzle to it. It has no more
+    // rows than columns and of those the least canonical piece string, so
+    // e.g. 4x10 and 10x4 puzzles with the same pieces have the same
+    // representative. Its solutions turned by the inverse of the symmetry are
+    // solutions of this puzzle.
+    #[must_use]
+    pub fn canonicalize(&self) -> (Self, Symmetry) {
+        Symmetry::array()
+            .iter()
+            .map(|&symmetry| (self.transformed(symmetry), symmetry))
+            .min_by_key(|(spec, _)| {
+                (
+                    spec.row_count,
+                    spec.column_count,
+                    spec.pieces.to_canonical_string(),
+                )
+            })
+            .unwrap()
+    }
+
+    fn validate(&self) -> Result<(), SolveOneError> {
+        validate(self.row_count, self.column_count, &self.pieces)
+    }
+
+    fn solver<'a>(&self) -> Solver<'a> {
+        let pieces = self.pieces.clone();
+        Solver::new(Board::new(self.row_count, self.column_count), pieces)
+    }
+}
+
+fn fingerprint(row_count: u32, column_count: u32, pieces: &PieceCollection) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = OFFSET_BASIS;
+    let sizes = [row_count, column_count];
+    for byte in sizes
+        .iter()
+        .chain(&pieces.counts)
+        .flat_map(|n| n.to_le_bytes())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
+// Solves the puzzle and returns a solution if there is one. Which solution is
+// returned is not specified; use `solve_first_canonical` when the result must
+// be stable. Boards at most three squares wide are solved column by column
+// instead of with the general search, see `smallboards`.
+pub fn solve_one(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<Option<Position>, SolveOneError> {
+    // Whether the board fits is checked by the search, which transposes it
+    // if it doesn't
+    validate_squares(row_count, column_count, 0, &pieces)?;
+    if smallboards::is_small(row_count, column_count) {
+        return smallboards::solve(row_count, column_count, &pieces);
+    }
+    let outcome =
+        solve_one_with_options(row_count, column_count, pieces, &SolveOptions::default())?;
+    Ok(outcome.into_solution())
+}
+
+// Solves the puzzle and returns the first solution in canonical order.
+//
+// A solution is written as the sequence of its placements ordered by the
+// top-left square of each piece. A placement is ranked first by the row-major
+// index of that square and then by the index of its orientation in
+// `FixedPiece` (I1, I2, O1, T1, ..., Z2). Solutions are ordered
+// lexicographically by these sequences and the first one is returned, with
+// its pieces labelled 'A', 'B', ... in sequence order. This order does not
+// depend on the search algorithm, so the result is stable across releases.
+pub fn solve_first_canonical(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<Option<Position>, SolveOneError> {
+    validate(row_count, column_count, &pieces)?;
+
+    // The search fills the first empty square in row-major order and tries
+    // the orientations in `FixedPiece` order, so it visits solutions in
+    // canonical order.
+    let mut solver = Solver::new(Board::new(row_count, column_count), pieces);
+    Ok(solver.solve_one())
+}
+
+// Finds all the solutions of the puzzle in canonical order. See
+// `solve_first_canonical` for the definition of the order. Boards that only
+// fit the bitboard transposed are searched transposed, like `solve_one` does,
+// and their solutions are in the canonical order of the transposed board.
+pub fn solve_all(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<Vec<Position>, SolveOneError> {
+    if fits_only_transposed(row_count, column_count) {
+        let solutions = solve_all(column_count, row_count, pieces.transposed())?;
+        return Ok(solutions.iter().map(Position::transposed).collect());
+    }
+    validate(row_count, column_count, &pieces)?;
+
+    if let Some(root) = RootSymmetry::new(row_count, column_count, &pieces) {
+        return Ok(root.solve_all(&pieces));
+    }
+    let mut solutions = vec![];
+    let mut solver = Solver::new(Board::new(row_count, column_count), pieces);
+    solver.for_each_solution(&mut |board| solutions.push(board.position()));
+    Ok(solutions)
+}
+
+// Solves the puzzle starting from `hint`, a solution of a similar puzzle on a
+// board of the same size. The placements of `hint` whose pieces are still
+// available are kept and only the rest of the board is searched. If that
+// fails, the kept placements are dropped one by one, starting with the last,
+// until a solution is found. `hint` is ignored if its board size differs.
+pub fn solve_warm_start(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    hint: &Position,
+) -> Result<Option<Position>, SolveOneError> {
+    validate(row_count, column_count, &pieces)?;
+
+    if hint.row_count() != row_count || hint.column_count() != column_count {
+        return Ok(Solver::new(Board::new(row_count, column_count), pieces).solve_one());
+    }
+    Ok(warm_start(
+        &Board::new(row_count, column_count),
+        &pieces,
+        hint,
+    ))
+}
+
+// Solve starting from `board` with the placements of `hint` that are
+// available and fit. See `solve_warm_start`.
+fn warm_start(board: &Board, pieces: &PieceCollection, hint: &Position) -> Option<Position> {
+    let mut kept = vec![];
+    let mut available = pieces.clone();
+    let mut scratch = board.clone();
+    for placement in hint.placements() {
+        let piece = PIECE_MAP[placement.piece as usize];
+        if available.count(piece) > 0
+            && scratch
+                .place_at(placement.piece, placement.row, placement.column)
+                .is_ok()
+        {
+            available.remove(piece);
+            kept.push(placement);
+        }
+    }
+
+    for kept_count in (0..=kept.len()).rev() {
+        let mut board = board.clone();
+        let mut remaining = pieces.clone();
+        for placement in &kept[..kept_count] {
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .unwrap();
+            remaining.remove(PIECE_MAP[placement.piece as usize]);
+        }
+
+        let solution = Solver::new(board, remaining).solve_one();
+        if solution.is_some() {
+            return solution;
+        }
+    }
+
+    None
+}
+
+// Options for `solve_one_with_options`
+#[derive(Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SolveOptions {
+    // The number of threads to search with. Without the `parallel` feature
+    // the search is always single threaded.
+    pub threads: usize,
+    // When searching with more than one thread, return the canonical first
+    // solution, as the single threaded search does. Otherwise the solution
+    // found first is returned, which can differ between runs but is faster.
+    pub deterministic: bool,
+    // Solve boards with more rows than columns by solving the transposed
+    // board, which is usually faster, and transposing the solution back. The
+    // solution is then the canonical first solution of the transposed board.
+    pub transpose: bool,
+    // Check at every node that each remaining kind of piece still fits
+    // somewhere on the board and backtrack if one doesn't. This visits fewer
+    // nodes but the check costs more than it saves on most puzzles, so it's
+    // off by default.
+    pub prune_unplaceable: bool,
+    // Abandon the search after visiting this many nodes. The outcome is then
+    // `Undetermined`. With several threads the limit is shared by the
+    // threads and can be overshot by up to 1024 nodes per thread.
+    pub max_nodes: Option<u64>,
+    // The order in which the board is filled. Other orders than `RowMajor`
+    // are searched with a single thread and without transposing, and
+    // `prune_unplaceable` is ignored.
+    pub fill: FillStrategy,
+    // Orientations of the pieces to try first, or not to place at all. Only
+    // the `RowMajor` search uses them, and with any but the neutral weights
+    // it searches with a single thread.
+    pub orientations: OrientationWeights,
+    // When the board has symmetries that map the pieces onto themselves,
+    // search only one of the placements of a piece that they map onto each
+    // other. Proving that there's no solution is then faster, but the
+    // solution found isn't necessarily the canonical first. Only the single
+    // threaded `RowMajor` search with neutral orientation weights breaks
+    // symmetry, and it searches with one thread when it does.
+    pub break_symmetry: bool,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            deterministic: true,
+            transpose: true,
+            prune_unplaceable: false,
+            max_nodes: None,
+            fill: FillStrategy::RowMajor,
+            orientations: OrientationWeights::default(),
+            break_symmetry: false,
+        }
+    }
+}
+
+// The result of a search that may be abandoned before it completes
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Solved(Position),
+    NoSolution,
+    // The search was abandoned before a solution was found or the puzzle was
+    // shown to have none
+    Undetermined,
+}
+
+impl Outcome {
+    #[must_use]
+    pub fn solution(&self) -> Option<&Position> {
+        match self {
+            Outcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    // The solution, or `None` if there is none or the search was abandoned
+    #[must_use]
+    pub fn into_solution(self) -> Option<Position> {
+        match self {
+            Outcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_undetermined(&self) -> bool {
+        *self == Outcome::Undetermined
+    }
+}
+
+// Solves the puzzle with the given options. See `SolveOptions`.
+pub fn solve_one_with_options(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    options: &SolveOptions,
+) -> Result<Outcome, SolveOneError> {
+    solve_cancellable(
+        row_count,
+        column_count,
+        pieces,
+        options,
+        &AtomicBool::new(false),
+    )
+}
+
+// `solve_one_with_options`, abandoned with the outcome `Undetermined` once
+// `cancelled` is set. It's polled every 1024 nodes.
+fn solve_cancellable(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    options: &SolveOptions,
+    cancelled: &AtomicBool,
+) -> Result<Outcome, SolveOneError> {
+    if fits_only_transposed(row_count, column_count) {
+        // The column-major order of the board is the row-major order of the
+        // transposed board
+        let options = SolveOptions {
+            transpose: false,
+            orientations: options.orientations.transposed(),
+            fill: match options.fill {
+                FillStrategy::ColumnMajor => FillStrategy::RowMajor,
+                fill => fill,
+            },
+            ..options.clone()
+        };
+        let outcome = solve_cancellable(
+            column_count,
+            row_count,
+            pieces.transposed(),
+            &options,
+            cancelled,
+        )?;
+        return Ok(match outcome {
+            Outcome::Solved(solution) => Outcome::Solved(solution.transposed()),
+            outcome => outcome,
+        });
+    }
+    validate(row_count, column_count, &pieces)?;
+
+    if options.fill != FillStrategy::RowMajor {
+        return Ok(fill::solve_one(
+            row_count,
+            column_count,
+            pieces,
+            options,
+            cancelled,
+        ));
+    }
+    if options.transpose && row_count > column_count {
+        let options = SolveOptions {
+            transpose: false,
+            orientations: options.orientations.transposed(),
+            ..options.clone()
+        };
+        let outcome = solve_cancellable(
+            column_count,
+            row_count,
+            pieces.transposed(),
+            &options,
+            cancelled,
+        )?;
+        return Ok(match outcome {
+            Outcome::Solved(solution) => Outcome::Solved(solution.transposed()),
+            outcome => outcome,
+        });
+    }
+
+    let stop = || cancelled.load(Ordering::Relaxed);
+    if options.break_symmetry && options.orientations.is_neutral() {
+        if let Some(root) = RootSymmetry::new(row_count, column_count, &pieces) {
+            return Ok(root.solve_one(&pieces, options, &stop));
+        }
+    }
+    let board = Board::new(row_count, column_count);
+    #[cfg(feature = "parallel")]
+    if options.threads > 1 && options.orientations.is_neutral() {
+        return Ok(parallel::solve_one(&board, &pieces, options, cancelled));
+    }
+    let mut solver = Solver::new(board, pieces)
+        .with_stop(&stop)
+        .with_prune_unplaceable(options.prune_unplaceable)
+        .with_max_nodes(options.max_nodes.unwrap_or(u64::MAX))
+        .with_orientations(&options.orientations);
+    Ok(match solver.solve_one() {
+        Some(solution) => Outcome::Solved(solution),
+        None if solver.stopped => Outcome::Undetermined,
+        None => Outcome::NoSolution,
+    })
+}
+
+fn validate(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+) -> Result<(), SolveOneError> {
+    validate_blocked(row_count, column_count, 0, pieces)
+}
+
+// Validate a puzzle with `blocked_count` blocked squares. The board must also
+// fit in the bitboard, which isn't implied by the piece count limit when
+// squares are blocked.
+fn validate_blocked(
+    row_count: u32,
+    column_count: u32,
+    blocked_count: u32,
+    pieces: &PieceCollection,
+) -> Result<(), SolveOneError> {
+    if !board_fits(row_count, column_count) {
+        return Err(SolveOneError::BoardTooLarge {
+            row_count,
+            column_count,
+        });
+    }
+    validate_squares(row_count, column_count, blocked_count, pieces)
+}
+
+// Check that `pieces` can cover the open squares of a board of any size
+fn validate_squares(
+    row_count: u32,
+    column_count: u32,
+    blocked_count: u32,
+    pieces: &PieceCollection,
+) -> Result<(), SolveOneError> {
+    let too_large = || SolveOneError::BoardTooLarge {
+        row_count,
+        column_count,
+    };
+    let area: u32 = (u64::from(row_count) * u64::from(column_count))
+        .try_into()
+        .map_err(|_| too_large())?;
+    let square_count = area - blocked_count;
+    if !square_count.is_multiple_of(4) {
+        return Err(SolveOneError::InvalidBoardSize);
+    }
+    let piece_count = pieces.count_all();
+    if 4 * piece_count != square_count {
+        return Err(SolveOneError::InconsistentPieceCount {
+            square_count,
+            piece_count,
+            suggestions: suggestions(row_count, column_count, blocked_count, piece_count),
+        });
+    }
+    if piece_count > MAX_PIECE_COUNT as u32 {
+        return Err(SolveOneError::PieceCountOverLimit);
+    }
+    if let Some(piece) = Piece::array()
+        .iter()
+        .copied()
+        .find(|&piece| pieces.count(piece) > 0 && !fits(piece, row_count, column_count))
+    {
+        return Err(SolveOneError::PieceDoesntFit {
+            piece,
+            width: row_count.min(column_count),
+        });
+    }
+    Ok(())
+}
+
+// Whether an orientation of `piece` fits in a `row_count` by `column_count`
+// rectangle
+fn fits(piece: Piece, row_count: u32, column_count: u32) -> bool {
+    FixedPiece::array()
+        .iter()
+        .filter(|&&fixed_piece| PIECE_MAP[fixed_piece as usize] == piece)
+        .any(|&fixed_piece| {
+            let Shape(cells) = Shape::from(fixed_piece);
+            let extent = |f: fn(&Cell) -> i32| {
+                let values = cells.iter().map(f);
+                (values.clone().max().unwrap() - values.min().unwrap() + 1) as u32
+            };
+            extent(|cell| cell.0) <= row_count && extent(|cell| cell.1) <= column_count
+        })
+}
+
+#[derive(Debug)]
+pub enum PlaceAtError {
+    // Part of the piece is off the board
+    OffBoard,
+    // The piece overlaps a square that is already occupied
+    Overlap,
+    // The board already holds `MAX_PIECE_COUNT` pieces
+    PieceCountOverLimit,
+}
+
+impl Display for PlaceAtError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use PlaceAtError::*;
+        match self {
+            OffBoard => write!(f, "The piece doesn't fit on the board."),
+            Overlap => write!(f, "The piece overlaps another piece."),
+            PieceCountOverLimit => write!(
+                f,
+                "The board can hold at most {MAX_PIECE_COUNT} tetrominoes."
+            ),
+        }
+    }
+}
+
+impl Error for PlaceAtError {}
+
+// Pieces are one-sided tetrominos.
+// See https://en.wikipedia.org/wiki/Tetromino#One-sided_tetrominoes
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Piece {
+    I,
+    O,
+    T,
+    J,
+    L,
+    S,
+    Z,
+}
+
+impl Piece {
+    // The number of one-sided tetrominos.
+    #[must_use]
+    pub const fn count() -> usize {
+        7
+    }
+
+    // All the one-sided tetrominos in an array
+    #[must_use]
+    pub const fn array() -> [Self; Self::count()] {
+        [I, O, T, J, L, S, Z]
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct PieceCollection {
+    counts: [u32; Piece::count()],
+}
+
+// Written as the canonical string, e.g. `PieceCollection("LLZZ")`
+impl fmt::Debug for PieceCollection {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("PieceCollection")
+            .field(&self.to_canonical_string())
+            .finish()
+    }
+}
+
+impl PieceCollection {
+    // The number of pieces of the kind in the collection
+    #[must_use]
+    pub fn count(&self, piece: Piece) -> u32 {
+        self.counts[piece as usize]
+    }
+
+    fn remove(&mut self, piece: Piece) {
+        self.counts[piece as usize] -= 1;
+    }
+
+    fn add(&mut self, piece: Piece) {
+        self.counts[piece as usize] += 1;
+    }
+
+    // The fixed tetrominoes of the pieces in the collection as a bit set
+    // indexed by `FixedPiece`
+    fn fixed_pieces(&self) -> u32 {
+        self.counts
+            .iter()
+            .zip(&ORIENTATIONS)
+            .filter(|&(&count, _)| count > 0)
+            .fold(0, |fixed_pieces, (_, orientations)| {
+                fixed_pieces | orientations
+            })
+    }
+
+    // The pieces that tile the transposed board. Transposing is a reflection
+    // so it swaps the chiral pieces.
+    fn transposed(&self) -> Self {
+        let mut counts = self.counts;
+        counts.swap(J as usize, L as usize);
+        counts.swap(S as usize, Z as usize);
+        PieceCollection { counts }
+    }
+
+    #[must_use]
+    pub fn count_all(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    // The pieces in the order IOTJLSZ, each repeated by its count, e.g.
+    // "IOOTTZ". Equal collections have the same string, so it can be used as a
+    // key.
+    #[must_use]
+    pub fn to_canonical_string(&self) -> String {
+        Piece::array()
+            .iter()
+            .map(|&piece| format!("{piece:?}").repeat(self.count(piece) as usize))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ParsePieceCollectionError {
+    UnrecognizedCharacter,
+}
+
+impl Display for ParsePieceCollectionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The value contains unrecognized characters.")
+    }
+}
+
+impl Error for ParsePieceCollectionError {}
+
+impl FromStr for PieceCollection {
+    type Err = ParsePieceCollectionError;
+    // Pieces may be in any order and in either case. Whitespace and the
+    // separators `,;+/|` are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut counts = [0; Piece::count()];
+
+        for c in s.chars() {
+            let piece = match c {
+                c if c.is_whitespace() || ",;+/|".contains(c) => continue,
+                'I' | 'i' => Piece::I,
+                'O' | 'o' => Piece::O,
+                'T' | 't' => Piece::T,
+                'J' | 'j' => Piece::J,
+                'L' | 'l' => Piece::L,
+                'S' | 's' => Piece::S,
+                'Z' | 'z' => Piece::Z,
+                _ => return Err(ParsePieceCollectionError::UnrecognizedCharacter),
+            };
+            counts[piece as usize] += 1;
+        }
+
+        Ok(Self { counts })
+    }
+}
+
+// The fixed tetrominos.
+// See https://en.wikipedia.org/wiki/Tetromino#Fixed_tetrominoes
+// x1 is the fixed tetromino x in 'standard' position.
+// x2 is x1 rotated 90° clockwise, x3 is 180° and x4 is 270°.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FixedPiece {
+    I1,
+    I2,
+    O1,
+    T1,
+    T2,
+    T3,
+    T4,
+    J1,
+    J2,
+    J3,
+    J4,
+    L1,
+    L2,
+    L3,
+    L4,
+    S1,
+    S2,
+    Z1,
+    Z2,
+}
+
+impl FixedPiece {
+    // The number of fixed tetrominos.
+    #[must_use]
+    pub const fn count() -> usize {
+        19
+    }
+
+    // All the fixed tetrominos in an array
+    #[must_use]
+    pub const fn array() -> [Self; Self::count()] {
+        [
+            I1, I2, O1, T1, T2, T3, T4, J1, J2, J3, J4, L1, L2, L3, L4, S1, S2, Z1, Z2,
+        ]
+    }
+
+    // The one-sided tetromino this is an orientation of
+    #[must_use]
+    pub const fn piece(self) -> Piece {
+        PIECE_MAP[self as usize]
+    }
+}
+
+impl From<usize> for FixedPiece {
+    fn from(value: usize) -> Self {
+        match value {
+            0 => I1,
+            1 => I2,
+            2 => O1,
+            3 => T1,
+            4 => T2,
+            5 => T3,
+            6 => T4,
+            7 => J1,
+            8 => J2,
+            9 => J3,
+            10 => J4,
+            11 => L1,
+            12 => L2,
+            13 => L3,
+            14 => L4,
+            15 => S1,
+            16 => S2,
+            17 => Z1,
+            18 => Z2,
+            _ => panic!(),
+        }
+    }
+}
+
+impl Display for FixedPiece {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseFixedPieceError {
+    UnrecognizedName,
+}
+
+impl Display for ParseFixedPieceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The value is not the name of a fixed tetromino.")
+    }
+}
+
+impl Error for ParseFixedPieceError {}
+
+// Parses names like "T3", as printed by `Display`. The letter may be lower case.
+impl FromStr for FixedPiece {
+    type Err = ParseFixedPieceError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FixedPiece::array()
+            .iter()
+            .find(|p| p.to_string().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or(ParseFixedPieceError::UnrecognizedName)
+    }
+}
+
+// A fixed tetromino with its top-left square at `row`, `column`. The top-left
+// square is the first square of the piece in row-major order.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placement {
+    pub piece: FixedPiece,
+    pub row: u32,
+    pub column: u32,
+}
+
+// A step of the search, passed to the trace of a `Solver`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SearchStep {
+    // A piece was placed on the first empty square
+    Place(Placement),
+    // The piece placed last was removed to try the next candidate
+    Backtrack(Placement),
+}
+
+// The shape of the fixed tetrominos when the top left corner of the tetromino
+// is positioned at the top left corner of the board. The co-ordinates used
+// are (row_index, column_index). The (0, 0) square is not included.
+// Some shapes hang over the hang over the board, e.g. J1 and L4.
+//
+//        ------------------
+//        | 0,0 | 0,1 | 0,2
+// -------|----------------
+// | 1,-1 | 1,0 | 1,1 | 1,2
+// -------|----------------
+// | 2,-1 | 2,0 | 2,1 | 2,2
+//
+type PieceShape = [(isize, isize); 3];
+
+// An array of all the shapes
+const fn piece_shapes() -> [PieceShape; FixedPiece::count()] {
+    [
+        [(1, 0), (2, 0), (3, 0)],   // I1
+        [(0, 1), (0, 2), (0, 3)],   // I2
+        [(0, 1), (1, 1), (1, 0)],   // O1
+        [(0, 1), (0, 2), (1, 1)],   // T1
+        [(1, 0), (1, -1), (2, 0)],  // T2
+        [(1, -1), (1, 0), (1, 1)],  // T3
+        [(1, 0), (1, 1), (2, 0)],   // T4
+        [(1, 0), (2, -1), (2, 0)],  // J1
+        [(1, 0), (1, 1), (1, 2)],   // J2
+        [(0, 1), (1, 0), (2, 0)],   // J3
+        [(0, 1), (0, 2), (1, 2)],   // J4
+        [(1, 0), (2, 0), (2, 1)],   // L1
+        [(0, 1), (0, 2), (1, 0)],   // L2
+        [(0, 1), (1, 1), (2, 1)],   // L3
+        [(1, -2), (1, -1), (1, 0)], // L4
+        [(0, 1), (1, -1), (1, 0)],  // S1
+        [(1, 0), (1, 1), (2, 1)],   // S2
+        [(0, 1), (1, 1), (1, 2)],   // Z1
+        [(1, -1), (1, 0), (2, -1)], // Z2
+    ]
+}
+
+const fn piece_shape(fixed_piece: FixedPiece) -> PieceShape {
+    piece_shapes()[fixed_piece as usize]
+}
+
+// The orientations of each piece as a bit set indexed by `FixedPiece`
+const ORIENTATIONS: [u32; Piece::count()] = [
+    0b11,
+    0b100,
+    0b111_1000,
+    0b111_1000_0000,
+    0b111_1000_0000_0000,
+    0b1_1000_0000_0000_0000,
+    0b110_0000_0000_0000_0000,
+];
+
+// This array is indexed by the `FixedPiece` enum and maps fixed tetrominoes to tetrominoes
+const PIECE_MAP: [Piece; FixedPiece::count()] =
+    [I, I, O, T, T, T, T, J, J, J, J, L, L, L, L, S, S, Z, Z];
+
+// A position on a board. Contians a row_count * (col_count + 1) long vector representing
+// squares of the board. An empty square is represented by b'.' the end of a row is markef by
+// b'\n'. A square occupied by a piece is respresented by b'A', b'B', ... . Squares with the
+// same character are occupied by the same piece. A blocked square is represented by b'#'.
+//
+// Positions are equal if they have the same tiling, whatever the labels of the
+// pieces, so the same solution found in another order is the same key in a
+// map. The hash is of the squares with the labels numbered in the order they
+// first appear, which is the same for equal positions.
+#[derive(Clone)]
+pub struct Position {
+    squares: Vec<u8>,
+}
+
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.squares.len() == other.squares.len()
+            && self.numbered_labels() == other.numbered_labels()
+    }
+}
+
+impl Eq for Position {}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.numbered_labels().hash(state);
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn row_count(&self) -> u32 {
+        (self.squares.len() / (self.column_count() as usize + 1)) as u32
+    }
+
+    #[must_use]
+    pub fn column_count(&self) -> u32 {
+        self.squares.iter().position(|&s| s == b'\n').unwrap() as u32
+    }
+
+    // The size of the board. A position has at least one row and column.
+    #[must_use]
+    pub fn dimensions(&self) -> (RowCount, ColCount) {
+        (
+            RowCount::new(self.row_count()).unwrap(),
+            ColCount::new(self.column_count()).unwrap(),
+        )
+    }
+
+    // The placements of the pieces in the order of their labels. Squares that
+    // don't form a tetromino are skipped.
+    #[must_use]
+    pub fn placements(&self) -> Vec<Placement> {
+        let width = self.column_count() as usize + 1;
+        let mut labels: Vec<u8> = self
+            .squares
+            .iter()
+            .copied()
+            .filter(u8::is_ascii_alphabetic)
+            .collect();
+        labels.sort_unstable();
+        labels.dedup();
+
+        labels
+            .into_iter()
+            .filter_map(|label| {
+                let cells: Vec<_> = (0..self.squares.len())
+                    .filter(|&i| self.squares[i] == label)
+                    .map(|i| Cell((i / width) as i32, (i % width) as i32))
+                    .collect();
+                let cells: [Cell; 4] = cells.try_into().ok()?;
+                Some(Placement {
+                    piece: Shape(cells).fixed_piece()?,
+                    row: cells[0].0 as u32,
+                    column: cells[0].1 as u32,
+                })
+            })
+            .collect()
+    }
+
+    // The squares with each label replaced by 128 plus the number of labels
+    // before its first appearance, and the other squares kept. There are at
+    // most 52 labels, so the numbers are never ASCII.
+    fn numbered_labels(&self) -> Vec<u8> {
+        let mut numbers = HashMap::new();
+        self.squares
+            .iter()
+            .map(|&square| {
+                if square.is_ascii_alphabetic() {
+                    let next = 128 + numbers.len() as u8;
+                    *numbers.entry(square).or_insert(next)
+                } else {
+                    square
+                }
+            })
+            .collect()
+    }
+
+    // The kind of piece of each label. Labels of squares that don't form a
+    // tetromino are missing.
+    fn label_pieces(&self) -> HashMap<u8, Piece> {
+        let width = self.column_count() as usize + 1;
+        self.placements()
+            .iter()
+            .map(|placement| {
+                let label =
+                    self.squares[placement.row as usize * width + placement.column as usize];
+                (label, PIECE_MAP[placement.piece as usize])
+            })
+            .collect()
+    }
+
+    // The position reflected in its main diagonal. Labels are kept.
+    fn transposed(&self) -> Self {
+        board::transform::position(self, geometry::Symmetry::Transpose)
+    }
+
+    // The position with each square of a piece showing the kind of the piece,
+    // I, O, T, J, L, S or Z, instead of its label. Empty and blocked squares
+    // are kept.
+    //
+    //     AAABB        TTTOO
+    //     .A.BB   ->   .T.OO
+    #[must_use]
+    pub fn to_kind_string(&self) -> String {
+        let pieces = self.label_pieces();
+        self.squares
+            .iter()
+            .map(|square| match pieces.get(square) {
+                Some(piece) => format!("{piece:?}"),
+                None => char::from(*square).to_string(),
+            })
+            .collect()
+    }
+
+    // Render the position with box drawing characters like the alternate
+    // format, but only with the ASCII characters `+-|`. Empty squares are
+    // filled with ':'.
+    #[must_use]
+    pub fn to_ascii_art(&self) -> String {
+        let mut art = String::new();
+        for line in self.pretty_lines(true, |i| self.squares[i] == b'.') {
+            art.push_str(&line);
+            art.push('\n');
+        }
+        art
+    }
+
+    // Render the position with box drawing characters, or their ASCII
+    // approximations if `ascii` is true, one string per line. Squares for
+    // which `shaded` returns true are filled with a shade character. `shaded`
+    // is passed the index of the square in `squares`.
+    fn pretty_lines<F: Fn(usize) -> bool>(&self, ascii: bool, shaded: F) -> Vec<String> {
+        const BOX_CHARS: [char; 16] = [
+            ' ',   // 0000
+            '?',   // 0001 up
+            '?',   // 0010 down
+            '│', // 0011
+            '?',   // 0100 left
+            '┘', // 0101
+            '┐', // 0110
+            '┤', // 0111
+            '?',   // 1000 right
+            '└', // 1001
+            '┌', // 1010
+            '├', // 1011
+            '─', // 1100
+            '┴', // 1101
+            '┬', // 1110
+            '┼', // 1111
+        ];
+        // Indexed like BOX_CHARS
+        const ASCII_BOX_CHARS: [char; 16] = [
+            ' ', '?', '?', '|', '?', '+', '+', '+', '?', '+', '+', '+', '-', '+', '+', '+',
+        ];
+        let (box_chars, shade) = if ascii {
+            (&ASCII_BOX_CHARS, ':')
+        } else {
+            (&BOX_CHARS, '░')
+        };
+
+        let column_count = self.column_count() as usize;
+        let row_count = self.row_count() as usize;
+
+        // Get the index of the element on a `row_count` by `2 * self.column_count`
+        // rescaled version of squares. Returns `None` if the coordinates are off
+        // the board.
+        let index = |row: isize, col: isize| -> Option<usize> {
+            if row < 0 || row >= row_count as isize || col < 0 || col >= 2 * column_count as isize {
+                return None;
+            }
+
+            Some((row * (column_count as isize + 1) + col / 2) as usize)
+        };
+        let get = |row: isize, col: isize| index(row, col).map(|i| self.squares[i]);
+
+        let mut lines = Vec::with_capacity(row_count + 1);
+        for row in 0..=row_count as isize {
+            let mut line = String::new();
+            for col in 0..=2 * column_count as isize {
+                let top_left = get(row - 1, col - 1);
+                let top_right = get(row - 1, col);
+                let bottom_left = get(row, col - 1);
+                let bottom_right = get(row, col);
+
+                let up = top_left != top_right;
+                let down = bottom_left != bottom_right;
+                let left = top_left != bottom_left;
+                let right = top_right != bottom_right;
+
+                let char_index = usize::from(up)
+                    + (if down { 2 } else { 0 })
+                    + (if left { 4 } else { 0 })
+                    + (if right { 8 } else { 0 });
+
+                let c = if char_index > 0 {
+                    box_chars[char_index]
+                } else if index(row, col).is_some_and(&shaded) {
+                    shade
+                } else {
+                    ' '
+                };
+
+                line.push(c);
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+// Written as the plain format, e.g. `Position("AAB\nABB\n")`
+impl fmt::Debug for Position {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("Position")
+            .field(&str::from_utf8(&self.squares).unwrap())
+            .finish()
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if !f.alternate() {
+            return write!(f, "{}", str::from_utf8(&self.squares).unwrap());
+        }
+
+        for line in self.pretty_lines(false, |i| self.squares[i] == b'.') {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+// A placement of the kind of piece with index `piece` on a board with the
+// squares `bits` covered, as a bitmap, or 0 if it fits nowhere. It has the last
+// top-left square of the first orientation that fits. `bitmaps` are the
+// bitmaps of the fixed tetrominoes on the board.
+fn witness(bits: u64, bitmaps: &[u64; FixedPiece::count()], piece: usize) -> u64 {
+    let empty = !bits;
+    let mut orientations = ORIENTATIONS[piece];
+    while orientations != 0 {
+        let bitmap = bitmaps[orientations.trailing_zeros() as usize];
+        orientations &= orientations - 1;
+        // The squares on which the piece could have its top-left square:
+        // every square of the piece must be empty
+        let mut squares = bitmap;
+        let mut anchors = empty;
+        while squares != 0 {
+            anchors &= empty >> squares.trailing_zeros();
+            squares &= squares - 1;
+        }
+        if anchors != 0 {
+            return bitmap << anchors.ilog2();
+        }
+    }
+    0
+}
+
+// A board being filled with pieces. Pieces are normally pushed onto the first
+// empty square, but they can also be placed on any square with `place_at`.
+#[derive(Clone, Debug)]
+pub struct Board {
+    // The "outer" width; col_count + 1 for the border
+    width: usize,
+    height: usize,
+    bits: u64,
+    bitmaps: [u64; FixedPiece::count()],
+    // The bitmap and orientation of each piece in the order they were placed
+    stack: [(u64, FixedPiece); MAX_PIECE_COUNT],
+    stack_count: usize,
+    // Where each kind of piece fits, once `track_placeable` has been called
+    placeable: Option<Box<Placeable>>,
+}
+
+// For each number of pieces on the stack, a placement of each kind of piece
+// as a bitmap, indexed by `Piece`, or 0 if it fits nowhere. The search goes
+// back and forth between boards with the same number of pieces that differ in
+// the last few, so a placement that fit on the previous board usually still
+// doesn't overlap any covered square and saves looking for one. They're taken
+// as far from the first empty square as they can be, so that few pieces
+// cover them.
+type Placeable = [[u64; Piece::count()]; MAX_PIECE_COUNT + 1];
+
+impl Board {
+    // Panics if the board is larger than `max_board_area`
+    #[must_use]
+    pub fn new(row_count: u32, col_count: u32) -> Self {
+        assert!(board_fits(row_count, col_count), "board too large");
+        let mut bits = 0_u64;
+
+        let width = col_count as usize + 1;
+        let height = row_count as usize;
+        let area = width * height;
+        bits |= u64::MAX << area;
+        for b in (0..area).skip(width - 1).step_by(width) {
+            bits |= 1 << b;
+        }
+
+        let mut bitmaps = [1_u64; FixedPiece::count()];
+        for (from, to) in piece_shapes().iter().zip(&mut bitmaps) {
+            for square in from {
+                *to |= 1 << width as isize * square.0 + square.1;
+            }
+        }
+
+        Self {
+            width,
+            height,
+            bits,
+            bitmaps,
+            stack: [(0, I1); MAX_PIECE_COUNT],
+            stack_count: 0,
+            placeable: None,
+        }
+    }
+
+    fn first_empty_square(&self) -> u32 {
+        (self.bits ^ u64::MAX).trailing_zeros()
+    }
+
+    // The squares around the first empty square as a 16 bit neighborhood for
+    // `candidates`. Bit `4 * row + column` is set if the square `row` rows
+    // down and `column - 1` columns right of the first empty square is
+    // covered. Squares outside the bitboard count as covered.
+    fn neighborhood(&self) -> usize {
+        // Shifted left by one so the square before the first empty square can
+        // be reached
+        let bits = u128::from(self.bits) << 1 | 1 | u128::MAX << 65;
+        let offset = self.first_empty_square() as usize;
+        (0..4).fold(0, |neighborhood, row| {
+            let nibble = (bits >> (offset + row * self.width)) as usize & 0xf;
+            neighborhood | nibble << (4 * row)
+        })
+    }
+
+    // Remember where each kind of piece fits from now on, for `all_placeable`
+    fn track_placeable(&mut self) {
+        self.placeable = Some(Box::new([[0; Piece::count()]; MAX_PIECE_COUNT + 1]));
+    }
+
+    // Returns true if every kind of piece in `pieces` fits somewhere on the
+    // board. If one doesn't, the board can't be completed with `pieces`.
+    // After `track_placeable` the placements found are kept for the next
+    // board with as many pieces.
+    fn all_placeable(&mut self, pieces: &PieceCollection) -> bool {
+        let level = self.stack_count;
+        let bits = self.bits;
+        let Some(placeable) = &mut self.placeable else {
+            return (0..Piece::count()).all(|piece| {
+                pieces.counts[piece] == 0 || witness(bits, &self.bitmaps, piece) != 0
+            });
+        };
+        let fits = |witness: u64| witness != 0 && witness & bits == 0;
+        for piece in 0..Piece::count() {
+            if pieces.counts[piece] == 0 || fits(placeable[level][piece]) {
+                continue;
+            }
+            let found = witness(bits, &self.bitmaps, piece);
+            placeable[level][piece] = found;
+            if found == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    // The fixed tetrominoes that might fit on the first empty square, as a
+    // bit set indexed by `FixedPiece`. A piece that's left out certainly
+    // doesn't fit.
+    fn candidates(&self) -> u32 {
+        candidate_table()[self.neighborhood()]
+    }
+
+    // Returns Ok if the push succeeds and Err if the piece doesn't fit
+    fn push(&mut self, fixed_piece: FixedPiece) -> Result<(), ()> {
+        debug_assert!(self.stack_count < MAX_PIECE_COUNT);
+        let offset = self.first_empty_square();
+        let bitmap = self.bitmaps[fixed_piece as usize] << offset;
+        if self.bits & bitmap != 0 {
+            return Err(());
+        }
+        self.add(bitmap, fixed_piece);
+        Ok(())
+    }
+
+    // The bitmap of `fixed_piece` with its top-left square at `row`, `col`.
+    // Returns `None` if that square is off the board or the piece doesn't fit
+    // in the bitboard.
+    fn bitmap_at(&self, fixed_piece: FixedPiece, row: u32, col: u32) -> Option<u64> {
+        if row as usize >= self.height || col as usize >= self.width - 1 {
+            return None;
+        }
+        let offset = row as usize * self.width + col as usize;
+        let bitmap = self.bitmaps[fixed_piece as usize];
+        let shifted = bitmap << offset;
+        if shifted >> offset != bitmap {
+            return None;
+        }
+        Some(shifted)
+    }
+
+    // Returns true if `fixed_piece` fits with its top-left square at `row`,
+    // `col`. The top-left square is the first square of the piece in row-major
+    // order.
+    #[must_use]
+    pub fn can_place(&self, fixed_piece: FixedPiece, row: u32, col: u32) -> bool {
+        self.check_place(fixed_piece, row, col).is_ok()
+    }
+
+    // Places `fixed_piece` with its top-left square at `row`, `col`.
+    pub fn place_at(
+        &mut self,
+        fixed_piece: FixedPiece,
+        row: u32,
+        col: u32,
+    ) -> Result<(), PlaceAtError> {
+        let bitmap = self.check_place(fixed_piece, row, col)?;
+        self.add(bitmap, fixed_piece);
+        Ok(())
+    }
+
+    fn check_place(
+        &self,
+        fixed_piece: FixedPiece,
+        row: u32,
+        col: u32,
+    ) -> Result<u64, PlaceAtError> {
+        if self.stack_count == MAX_PIECE_COUNT {
+            return Err(PlaceAtError::PieceCountOverLimit);
+        }
+        let bitmap = self
+            .bitmap_at(fixed_piece, row, col)
+            .ok_or(PlaceAtError::OffBoard)?;
+        if self.bits & bitmap != 0 {
+            return Err(PlaceAtError::Overlap);
+        }
+        Ok(bitmap)
+    }
+
+    fn add(&mut self, bitmap: u64, fixed_piece: FixedPiece) {
+        #[cfg(any(test, feature = "audit"))]
+        self.audit(bitmap, fixed_piece);
+        self.bits |= bitmap;
+        self.stack[self.stack_count] = (bitmap, fixed_piece);
+        self.stack_count += 1;
+    }
+
+    // Panic unless `bitmap` covers four squares of the board in the shape of
+    // `fixed_piece`. A shape shifted too far left or right wraps around into
+    // the next or previous row, which the border column only catches if the
+    // shape crosses it.
+    #[cfg(any(test, feature = "audit"))]
+    fn audit(&self, bitmap: u64, fixed_piece: FixedPiece) {
+        assert_eq!(
+            bitmap.count_ones(),
+            4,
+            "{fixed_piece:?} doesn't cover four squares"
+        );
+        let cells: Vec<_> = (0..64)
+            .filter(|b| bitmap & 1 << b != 0)
+            .map(|b| {
+                let (row, column) = (b / self.width, b % self.width);
+                assert!(
+                    row < self.height && column < self.width - 1,
+                    "{:?} covers a square off the board",
+                    fixed_piece
+                );
+                Cell(row as i32, column as i32)
+            })
+            .collect();
+        let cells: [Cell; 4] = cells.try_into().unwrap();
+        assert!(
+            Shape(cells).normalize() == Shape::from(fixed_piece),
+            "{:?} wraps around the edge of the board",
+            fixed_piece
+        );
+    }
+
+    fn pop(&mut self) -> Piece {
+        debug_assert!(self.stack_count > 0);
+        self.stack_count -= 1;
+        let (bitmap, fixed_piece) = self.stack[self.stack_count];
+        self.bits &= !bitmap;
+        PIECE_MAP[fixed_piece as usize]
+    }
+
+    // Block the square at `row`, `col` so that no piece can cover it.
+    pub fn block(&mut self, row: u32, col: u32) -> Result<(), PlaceAtError> {
+        if row as usize >= self.height || col as usize >= self.width - 1 {
+            return Err(PlaceAtError::OffBoard);
+        }
+        let bit = 1 << (row as usize * self.width + col as usize);
+        if self.bits & bit != 0 {
+            return Err(PlaceAtError::Overlap);
+        }
+        self.bits |= bit;
+        Ok(())
+    }
+
+    // The row, column and piece of every covered square.
+    fn covered_squares(&self) -> impl Iterator<Item = (usize, usize, Piece)> + '_ {
+        self.stack[0..self.stack_count]
+            .iter()
+            .flat_map(move |&(bitmap, fixed_piece)| {
+                let piece = PIECE_MAP[fixed_piece as usize];
+                (0..64)
+                    .filter(move |b| bitmap & 1 << b != 0)
+                    .map(move |b| (b / self.width, b % self.width, piece))
+            })
+    }
+
+    fn is_complete(&self) -> bool {
+        self.bits == u64::MAX
+    }
+
+    // The placement of the piece placed last
+    fn last_placement(&self) -> Placement {
+        debug_assert!(self.stack_count > 0);
+        self.placement_of(self.stack[self.stack_count - 1])
+    }
+
+    // The placement of a piece on the stack from its entry
+    fn placement_of(&self, (bitmap, piece): (u64, FixedPiece)) -> Placement {
+        let shift = bitmap.trailing_zeros() as usize;
+        Placement {
+            piece,
+            row: (shift / self.width) as u32,
+            column: (shift % self.width) as u32,
+        }
+    }
+
+    #[must_use]
+    pub fn position(&self) -> Position {
+        let mut squares = Vec::with_capacity(self.width * self.height);
+        self.position_into(&mut squares);
+        Position { squares }
+    }
+
+    // Write the squares of `position` to `squares`, replacing its contents
+    // but keeping its allocation, for searches that look at many positions
+    // without keeping them
+    pub fn position_into(&self, squares: &mut Vec<u8>) {
+        // Squares that are covered but not by a piece are blocked
+        squares.clear();
+        squares.extend((0..self.width * self.height).map(|i| {
+            if self.bits & 1 << i != 0 {
+                b'#'
+            } else {
+                b'.'
+            }
+        }));
+
+        for (index, &(bitmap, fixed_piece)) in self.stack[0..self.stack_count].iter().enumerate() {
+            let shift = bitmap.trailing_zeros() as usize;
+            let shape = piece_shape(fixed_piece);
+
+            let marker = (index + 65) as u8;
+            squares[shift] = marker;
+            for offset in &shape {
+                let offset = self.width as isize * offset.0 + offset.1;
+                let index = shift + offset as usize;
+                squares[index] = marker;
+            }
+        }
+
+        for c in squares.iter_mut().skip(self.width - 1).step_by(self.width) {
+            *c = b'\n';
+        }
+    }
+}
+
+// The lookup table for `Board::candidates`, indexed by neighborhood. A piece
+// is a candidate if none of its squares inside the neighborhood are covered.
+fn candidate_table() -> &'static [u32] {
+    static TABLE: OnceLock<Vec<u32>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        (0..1 << 16)
+            .map(|neighborhood: usize| {
+                let mut candidates = 0;
+                for (index, shape) in piece_shapes().iter().enumerate() {
+                    let fits = shape.iter().chain(&[(0, 0)]).all(|&(row, column)| {
+                        let column = column + 1;
+                        !(0..4).contains(&column) || neighborhood & 1 << (4 * row + column) == 0
+                    });
+                    if fits {
+                        candidates |= 1 << index;
+                    }
+                }
+                candidates
+            })
+            .collect()
+    })
+}
+
+type Trace<'a> = &'a mut dyn FnMut(SearchStep, &Board);
+
+type ProgressFn<'a> = &'a mut dyn FnMut(Progress);
+
+struct Solver<'a> {
+    board: Board,
+    pieces: PieceCollection,
+    // The number of nodes visited so far
+    nodes: u64,
+    // Polled during the search; the search is abandoned once it returns true
+    stop: Option<&'a dyn Fn() -> bool>,
+    stopped: bool,
+    // Called with how far the search has got whenever `stop` is polled
+    progress: Option<ProgressFn<'a>>,
+    // Backtrack as soon as a remaining kind of piece doesn't fit anywhere
+    prune_unplaceable: bool,
+    // The search is abandoned once more nodes than this have been visited
+    max_nodes: u64,
+    // `for_each_solution` stops after finding this many solutions
+    max_solutions: u64,
+    solution_count: u64,
+    // The nodes of `for_each_solution` where no piece fit
+    backtracks: u64,
+    // Called with each step of the search and the board after it. Only
+    // `for_each_solution` traces, to keep `solve_one` fast.
+    trace: Option<Trace<'a>>,
+    // The most pieces of each kind that may be left over when the board is
+    // complete. `None` means there are exactly enough pieces to fill it.
+    leftover: Option<[u32; Piece::count()]>,
+    // The orientations `solve_one` may place, as bit sets indexed by
+    // `FixedPiece`, in the order they're tried
+    levels: [u32; FixedPiece::count()],
+    level_count: usize,
+}
+
+impl<'a> Solver<'a> {
+    fn new(board: Board, pieces: PieceCollection) -> Self {
+        Self {
+            board,
+            pieces,
+            nodes: 0,
+            stop: None,
+            stopped: false,
+            progress: None,
+            prune_unplaceable: false,
+            max_nodes: u64::MAX,
+            max_solutions: u64::MAX,
+            solution_count: 0,
+            backtracks: 0,
+            trace: None,
+            leftover: None,
+            levels: [u32::MAX; FixedPiece::count()],
+            level_count: 1,
+        }
+    }
+
+    fn with_orientations(mut self, orientations: &OrientationWeights) -> Self {
+        if !orientations.is_neutral() {
+            (self.levels, self.level_count) = orientations.levels();
+        }
+        self
+    }
+
+    fn with_leftover(mut self, leftover: [u32; Piece::count()]) -> Self {
+        self.leftover = Some(leftover);
+        self
+    }
+
+    // Whether the pieces that must still be placed fit in the open squares
+    fn leftover_fits(&self) -> bool {
+        self.leftover.is_none_or(|leftover| {
+            let required: u32 = self
+                .pieces
+                .counts
+                .iter()
+                .zip(&leftover)
+                .map(|(&count, &leftover)| count.saturating_sub(leftover))
+                .sum();
+            4 * required <= (!self.board.bits).count_ones()
+        })
+    }
+
+    fn with_stop(mut self, stop: &'a dyn Fn() -> bool) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    fn with_progress(mut self, progress: ProgressFn<'a>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn with_prune_unplaceable(mut self, prune_unplaceable: bool) -> Self {
+        self.prune_unplaceable = prune_unplaceable;
+        if prune_unplaceable {
+            self.board.track_placeable();
+        }
+        self
+    }
+
+    fn with_max_nodes(mut self, max_nodes: u64) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    fn with_max_solutions(mut self, max_solutions: u64) -> Self {
+        self.max_solutions = max_solutions;
+        self
+    }
+
+    fn with_trace(mut self, trace: Trace<'a>) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    // Push `fixed_piece` onto the board and tell the trace
+    fn push(&mut self, fixed_piece: FixedPiece) -> Result<(), ()> {
+        self.board.push(fixed_piece)?;
+        if let Some(trace) = &mut self.trace {
+            trace(SearchStep::Place(self.board.last_placement()), &self.board);
+        }
+        Ok(())
+    }
+
+    // Pop the last piece off the board and tell the trace
+    fn pop(&mut self) {
+        let placement = self.trace.is_some().then(|| self.board.last_placement());
+        self.board.pop();
+        if let (Some(trace), Some(placement)) = (&mut self.trace, placement) {
+            trace(SearchStep::Backtrack(placement), &self.board);
+        }
+    }
+
+    // Count a node and return true if the search should be abandoned, either
+    // because `max_nodes` is exceeded or `stop` returns true. `stop` is only
+    // polled, and `progress` called, every 1024 nodes to keep the overhead
+    // down.
+    fn should_stop(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            self.stopped = true;
+        } else if !self.stopped && self.nodes.is_multiple_of(0x400) {
+            if let Some(progress) = &mut self.progress {
+                progress(Progress {
+                    nodes: self.nodes,
+                    depth: self.board.stack_count as u32,
+                });
+            }
+            self.stopped = self.stop.is_some_and(|stop| stop());
+        }
+        self.stopped
+    }
+
+    pub fn solve_one(&mut self) -> Option<Position> {
+        if self.should_stop() {
+            return None;
+        }
+        if !self.leftover_fits() {
+            return None;
+        }
+        if self.board.is_complete() {
+            return Some(self.board.position());
+        }
+        if self.prune_unplaceable && !self.board.all_placeable(&self.pieces) {
+            return None;
+        }
+
+        // Only the candidates of the remaining pieces need to be tried. The
+        // set bits are visited in `FixedPiece` order, which keeps the search
+        // in canonical order. With orientation weights the candidates are
+        // tried a level at a time, the orientations with higher weights first.
+        let candidates = self.board.candidates() & self.pieces.fixed_pieces();
+        for level in 0..self.level_count {
+            let mut candidates = candidates & self.levels[level];
+            while candidates != 0 {
+                let r = FixedPiece::from(candidates.trailing_zeros() as usize);
+                candidates &= candidates - 1;
+                let t = PIECE_MAP[r as usize];
+                if self.board.push(r).is_ok() {
+                    self.pieces.remove(t);
+                    let solution = self.solve_one();
+                    if solution.is_some() {
+                        return solution;
+                    }
+                    self.board.pop();
+                    self.pieces.add(t);
+                }
+            }
+        }
+
+        None
+    }
+
+    // Calls `f` with the board of every solution, in canonical order, until
+    // `max_solutions` solutions have been found or the search is abandoned.
+    // With orientation weights the orientations with higher weights are tried
+    // first, as in `solve_one`.
+    fn for_each_solution<F: FnMut(&Board)>(&mut self, f: &mut F) {
+        if self.solution_count >= self.max_solutions || self.should_stop() {
+            return;
+        }
+        if !self.leftover_fits() {
+            return;
+        }
+        if self.board.is_complete() {
+            self.solution_count += 1;
+            f(&self.board);
+            return;
+        }
+        if self.prune_unplaceable && !self.board.all_placeable(&self.pieces) {
+            return;
+        }
+
+        let mut placed = false;
+        let candidates = self.board.candidates() & self.pieces.fixed_pieces();
+        for level in 0..self.level_count {
+            let mut candidates = candidates & self.levels[level];
+            while candidates != 0 {
+                let r = FixedPiece::from(candidates.trailing_zeros() as usize);
+                candidates &= candidates - 1;
+                let t = PIECE_MAP[r as usize];
+                if self.push(r).is_ok() {
+                    placed = true;
+                    self.pieces.remove(t);
+                    self.for_each_solution(f);
+                    self.pop();
+                    self.pieces.add(t);
+                }
+            }
+        }
+        if !placed {
+            self.backtracks += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        puzzles, solve_all, solve_first_canonical, solve_one_with_options, solve_warm_start, Board,
+        Outcome, PieceCollection, Position, PuzzleSpec, SolveOneError, SolveOptions, Solver,
+        Suggestion,
+    };
+
+    #[test]
+    fn solve_one() {
+        let board = Board::new(1, 4);
+        let mut solver = Solver::new(board, "I".parse().unwrap());
+        let solution = solver.solve_one();
+        assert!(solution.is_some());
+        assert_eq!(solution.unwrap().to_string(), "AAAA\n");
+    }
+
+    #[test]
+    fn solve_one_transposed() {
+        let pieces = || "TTLSZL".parse().unwrap();
+        let options = SolveOptions::default();
+        let solution = solve_one_with_options(6, 4, pieces(), &options).unwrap();
+        let solution = solution.into_solution().unwrap().to_string();
+        let transposed = solve_first_canonical(4, 6, pieces().transposed()).unwrap();
+        assert_eq!(solution, transposed.unwrap().transposed().to_string());
+        assert_eq!(
+            solution,
+            "AAAE\n\
+             ABEE\n\
+             BBFE\n\
+             CBFF\n\
+             CDDF\n\
+             CCDD\n"
+        );
+
+        let options = SolveOptions {
+            transpose: false,
+            ..SolveOptions::default()
+        };
+        let outcome = solve_one_with_options(6, 4, pieces(), &options).unwrap();
+        let canonical = solve_first_canonical(6, 4, pieces()).unwrap();
+        assert!(outcome == Outcome::Solved(canonical.unwrap()));
+    }
+
+    #[test]
+    fn solve_one_prune_unplaceable() {
+        let options = SolveOptions {
+            prune_unplaceable: true,
+            ..SolveOptions::default()
+        };
+        for puzzle in puzzles::all().iter().step_by(5) {
+            let pieces = puzzle.tetrominoes.parse().unwrap();
+            let outcome =
+                solve_one_with_options(puzzle.row_count, puzzle.column_count, pieces, &options);
+            let pieces = puzzle.tetrominoes.parse().unwrap();
+            let expected = crate::solve_one(puzzle.row_count, puzzle.column_count, pieces);
+            assert!(outcome.unwrap() == Outcome::Solved(expected.unwrap().unwrap()));
+        }
+
+        let outcome = solve_one_with_options(4, 4, "IITT".parse().unwrap(), &options);
+        assert!(outcome.unwrap() == Outcome::NoSolution);
+    }
+
+    #[test]
+    fn puzzle_fingerprint() {
+        let fingerprint = |rows, columns, pieces: &str| {
+            PuzzleSpec::new(rows, columns, pieces.parse().unwrap()).fingerprint()
+        };
+        assert_eq!(fingerprint(4, 4, "LLZZ"), fingerprint(4, 4, "ZLZL"));
+        assert_ne!(fingerprint(4, 4, "LLZZ"), fingerprint(4, 4, "LLSS"));
+        assert_ne!(fingerprint(2, 8, "LLZZ"), fingerprint(8, 2, "LLZZ"));
+        assert_ne!(fingerprint(4, 4, "LLZZ"), fingerprint(4, 4, "LLZ"));
+        // The hash must not change between releases
+        assert_eq!(fingerprint(4, 4, "LLZZ"), 17_848_329_765_725_156_469);
+    }
+
+    #[test]
+    fn canonicalize() {
+        let canonical = |rows, columns, pieces: &str| {
+            let spec = PuzzleSpec::new(rows, columns, pieces.parse().unwrap());
+            let (canonical, symmetry) = spec.canonicalize();
+            assert_eq!(canonical, spec.transformed(symmetry));
+            canonical
+        };
+        assert_eq!(
+            canonical(10, 4, "LLZZIIOOTT"),
+            canonical(4, 10, "LLZZIIOOTT")
+        );
+        assert_eq!(canonical(4, 4, "LLZZ"), canonical(4, 4, "JJSS"));
+        assert_ne!(canonical(4, 4, "LLZZ"), canonical(4, 4, "LLSS"));
+        let spec = canonical(8, 2, "LLZZ");
+        assert_eq!((spec.row_count, spec.column_count), (2, 8));
+        assert_eq!(spec.pieces.to_canonical_string(), "JJSS");
+
+        // Solutions of the representative turned back solve the puzzle
+        let pieces: PieceCollection = "TTLSZL".parse().unwrap();
+        let (canonical, symmetry) = PuzzleSpec::new(6, 4, pieces.clone()).canonicalize();
+        let solution = super::solve_one(
+            canonical.row_count,
+            canonical.column_count,
+            canonical.pieces,
+        )
+        .unwrap()
+        .unwrap();
+        let solution = super::board::transform::position(&solution, symmetry.inverse());
+        assert!(solution.verify(6, 4, &pieces).is_ok());
+    }
+
+    #[test]
+    fn piece_collection_canonical_string() {
+        let pieces: PieceCollection = "z, T; o t+I/o | ".parse().unwrap();
+        assert_eq!(pieces.to_canonical_string(), "IOOTTZ");
+        let pieces: PieceCollection = "IOOTTZ".parse().unwrap();
+        assert_eq!(pieces.to_canonical_string(), "IOOTTZ");
+        let pieces: PieceCollection = "".parse().unwrap();
+        assert_eq!(pieces.to_canonical_string(), "");
+        assert!("I-O".parse::<PieceCollection>().is_err());
+    }
+
+    #[test]
+    fn inconsistent_piece_count_suggestions() {
+        use Suggestion::*;
+
+        let err = crate::solve_one(6, 8, "IIIIIIOOOOO".parse().unwrap())
+            .err()
+            .unwrap();
+        match &err {
+            SolveOneError::InconsistentPieceCount {
+                square_count,
+                piece_count,
+                suggestions,
+            } => {
+                assert_eq!((*square_count, *piece_count), (48, 11));
+                assert_eq!(suggestions, &[AddPieces(1), BlockSquares(4)]);
+            }
+            _ => panic!("unexpected error"),
+        }
+        assert!(err.to_string().ends_with(
+            "There are 11 pieces for 48 squares. Try to add 1 piece or block 4 squares."
+        ));
+
+        let err = crate::solve_one(4, 4, "IIIIOO".parse().unwrap())
+            .err()
+            .unwrap();
+        match err {
+            SolveOneError::InconsistentPieceCount { suggestions, .. } => assert_eq!(
+                suggestions,
+                [
+                    RemovePieces(2),
+                    Resize {
+                        row_count: 4,
+                        column_count: 6
+                    }
+                ]
+                .to_vec()
+            ),
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn board_too_large() {
+        let pieces = |count| "I".repeat(count).parse::<PieceCollection>().unwrap();
+        let err = crate::solve_one(100_000, 100_000, pieces(12))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            SolveOneError::BoardTooLarge {
+                row_count: 100_000,
+                column_count: 100_000
+            }
+        ));
+        assert!(err.to_string().ends_with("columns at most 20."));
+        assert!(crate::board_fits(3, 16) && !crate::board_fits(16, 3));
+        assert!(!crate::board_fits(1, 21) && !crate::board_fits(u32::MAX, u32::MAX));
+
+        // Enumerated transposed when only the transposed board fits, and too
+        // large when neither does
+        let solutions = solve_all(16, 3, pieces(12)).unwrap();
+        assert!(!solutions.is_empty());
+        assert_eq!(solutions.len(), solve_all(3, 16, pieces(12)).unwrap().len());
+        for solution in &solutions {
+            assert!(solution.verify(16, 3, &pieces(12)).is_ok());
+        }
+        let compact: Vec<_> = crate::solve_all_compact(16, 3, pieces(12))
+            .unwrap()
+            .iter()
+            .map(|solution| solution.to_string())
+            .collect();
+        let expected: Vec<_> = solutions.iter().map(ToString::to_string).collect();
+        assert_eq!(compact, expected);
+        let mut steps = vec![];
+        let traced = crate::trace::solve_traced(16, 3, pieces(12), 1, &mut steps).unwrap();
+        assert_eq!(traced.len(), 1);
+        assert_eq!(traced[0].to_string(), expected[0]);
+        // The first I piece is placed across the transposed board, so down
+        // this one
+        assert!(String::from_utf8(steps)
+            .unwrap()
+            .starts_with(r#"{"step":"place","piece":"I1","row":0,"column":0,"#));
+        for (row_count, column_count) in [(24, 2), (2, 24)] {
+            assert!(matches!(
+                solve_all(row_count, column_count, pieces(12)),
+                Err(SolveOneError::BoardTooLarge { .. })
+            ));
+        }
+
+        // Solved transposed, even when transposing isn't asked for
+        let options = SolveOptions {
+            transpose: false,
+            ..SolveOptions::default()
+        };
+        for fill in [
+            crate::FillStrategy::RowMajor,
+            crate::FillStrategy::ColumnMajor,
+        ] {
+            let options = SolveOptions {
+                fill,
+                ..options.clone()
+            };
+            let outcome = solve_one_with_options(16, 3, pieces(12), &options).unwrap();
+            assert!(outcome
+                .solution()
+                .unwrap()
+                .verify(16, 3, &pieces(12))
+                .is_ok());
+        }
+        assert!(crate::smallboards::solve(48, 1, &pieces(12))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn solve_one_max_nodes() {
+        let options = |max_nodes| SolveOptions {
+            max_nodes: Some(max_nodes),
+            ..SolveOptions::default()
+        };
+        let outcome =
+            solve_one_with_options(4, 12, "IIITTTLLLJJS".parse().unwrap(), &options(1000));
+        assert!(outcome.unwrap().is_undetermined());
+
+        let outcome = solve_one_with_options(4, 4, "IITT".parse().unwrap(), &options(1000));
+        assert!(outcome.unwrap() == Outcome::NoSolution);
+
+        // Two nodes are visited: the empty board and the complete one
+        let outcome = solve_one_with_options(1, 4, "I".parse().unwrap(), &options(2));
+        assert!(outcome.unwrap().solution().is_some());
+        let outcome = solve_one_with_options(1, 4, "I".parse().unwrap(), &options(1));
+        assert!(outcome.unwrap().is_undetermined());
+    }
+
+    #[test]
+    fn solve_first_canonical_order() {
+        // Four vertical or four horizontal Is both tile the board. I1 comes
+        // before I2 so the vertical tiling is first.
+        let solution = solve_first_canonical(4, 4, "IIII".parse().unwrap()).unwrap();
+        assert_eq!(
+            solution.unwrap().to_string(),
+            "ABCD\n\
+             ABCD\n\
+             ABCD\n\
+             ABCD\n"
+        );
+    }
+
+    #[test]
+    fn solve_all_in_canonical_order() {
+        let solutions: Vec<_> = solve_all(4, 4, "IIII".parse().unwrap())
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            solutions,
+            ["ABCD\nABCD\nABCD\nABCD\n", "AAAA\nBBBB\nCCCC\nDDDD\n"]
+        );
+
+        let solutions = solve_all(4, 2, "IO".parse().unwrap()).unwrap();
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn solve_warm_start_keeps_hint() {
+        let hint = Position::from_replay(4, 4, "I2@r0c0; I2@r1c0; I2@r2c0; I2@r3c0").unwrap();
+        let solution = solve_warm_start(4, 4, "IIOO".parse().unwrap(), &hint).unwrap();
+        assert_eq!(
+            solution.unwrap().to_string(),
+            "AAAA\n\
+             BBBB\n\
+             CCDD\n\
+             CCDD\n"
+        );
+    }
+
+    #[test]
+    fn solve_warm_start_drops_hint() {
+        let hint = Position::from_replay(4, 4, "I1@r0c0; I1@r0c1; O1@r0c2; O1@r2c2").unwrap();
+        let solution = solve_warm_start(4, 4, "IOJL".parse().unwrap(), &hint).unwrap();
+        assert_eq!(
+            solution.unwrap().to_string(),
+            "ABBB\n\
+             ACCB\n\
+             ACCD\n\
+             ADDD\n"
+        );
+
+        let hint = Position::from_replay(1, 8, "I2@r0c0; I2@r0c4").unwrap();
+        let solution = solve_warm_start(4, 4, "OOOO".parse().unwrap(), &hint).unwrap();
+        assert_eq!(
+            solution.unwrap().to_string(),
+            "AABB\n\
+             AABB\n\
+             CCDD\n\
+             CCDD\n"
+        );
+    }
+
+    #[test]
+    fn equality_ignores_labels() {
+        use std::collections::HashSet;
+
+        let position = |s: &str| s.parse::<Position>().unwrap();
+        let a = position("AAAB\nCABB\nCCCB\n");
+        let b = position("BBBA\nxBAA\nxxxA\n");
+        assert!(a == b);
+        assert!(a != position("AAAB\nCABB\nCCBB\n"));
+        assert!(a != position("AAAB\nCABB\nCC.B\n"));
+        assert!(position("AAAA\n") != position("AA\nAA\n"));
+
+        let set: HashSet<Position> = [a, b].iter().cloned().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn piece_collection_equality() {
+        let pieces = |s: &str| s.parse::<PieceCollection>().unwrap();
+        assert!(pieces("ZLZL") == pieces("llzz"));
+        assert!(pieces("LLZZ") != pieces("LLZ"));
+    }
+
+    mod board {
+        use crate::Board;
+        use crate::FixedPiece::{self, *};
+        use crate::Piece::*;
+
+        #[test]
+        fn new() {
+            let board = Board::new(5, 4);
+            let position = board.position();
+
+            assert_eq!(
+                position.to_string(),
+                "....\n\
+                 ....\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn push() {
+            let mut board = Board::new(4, 4);
+            let output = board.push(I1);
+            let position = board.position();
+
+            assert!(output.is_ok());
+            assert_eq!(
+                position.to_string(),
+                "A...\n\
+                 A...\n\
+                 A...\n\
+                 A...\n"
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "L4 wraps around the edge of the board")]
+        fn audit_wrapped() {
+            // L4 at the start of the second row with its foot wrapped around
+            // to the end of the first row
+            let mut board = Board::new(3, 4);
+            let bitmap = 1 << 3 | 1 << 10 | 1 << 11 | 1 << 12;
+            board.add(bitmap, L4);
+        }
+
+        #[test]
+        #[should_panic(expected = "I2 covers a square off the board")]
+        fn audit_off_board() {
+            let mut board = Board::new(2, 4);
+            board.add(0b1111 << 1, I2);
+        }
+
+        #[test]
+        fn push_twice() {
+            let mut board = Board::new(4, 4);
+            let output1 = board.push(I1);
+            let output2 = board.push(I1);
+            let position = board.position();
+
+            assert!(output1.is_ok());
+            assert!(output2.is_ok());
+            assert_eq!(
+                position.to_string(),
+                "AB..\n\
+                 AB..\n\
+                 AB..\n\
+                 AB..\n"
+            );
+        }
+
+        #[test]
+        fn push_failure() {
+            let mut board = Board::new(1, 4);
+            let output = board.push(I1);
+            let position = board.position();
+
+            assert!(output.is_err());
+            assert_eq!(position.to_string(), "....\n");
+        }
+
+        #[test]
+        fn pop1() {
+            let mut board = Board::new(5, 4);
+            board.push(O1).unwrap();
+            let popped = board.pop();
+            let position = board.position();
+
+            assert_eq!(popped, O);
+            assert_eq!(
+                position.to_string(),
+                "....\n\
+                 ....\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn pop2() {
+            let mut board = Board::new(5, 4);
+            board.push(O1).unwrap();
+            board.push(I1).unwrap();
+            let popped = board.pop();
+            let position = board.position();
+
+            assert_eq!(popped, I);
+            assert_eq!(
+                position.to_string(),
+                "AA..\n\
+                 AA..\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn can_place() {
+            let mut board = Board::new(4, 4);
+            board.push(O1).unwrap();
+
+            assert!(board.can_place(I1, 0, 2));
+            assert!(board.can_place(L4, 2, 2));
+            assert!(!board.can_place(O1, 1, 1));
+            assert!(!board.can_place(I2, 3, 1));
+            assert!(!board.can_place(I1, 1, 3));
+            assert!(!board.can_place(J1, 2, 0));
+            assert!(!board.can_place(O1, 4, 0));
+        }
+
+        #[test]
+        fn candidates() {
+            let board = Board::new(4, 4);
+            let candidates: Vec<_> = FixedPiece::array()
+                .iter()
+                .copied()
+                .filter(|&p| board.candidates() & 1 << p as usize != 0)
+                .collect();
+            assert_eq!(
+                candidates,
+                [I1, I2, O1, T1, T4, J2, J3, J4, L1, L2, L3, S2, Z1]
+            );
+
+            // Every piece that can be pushed is a candidate
+            let placements: [&[_]; 4] = [
+                &[(I1, 0, 0)],
+                &[(O1, 0, 0), (O1, 0, 2)],
+                &[(L4, 0, 2), (O1, 0, 3)],
+                &[(I2, 0, 0)],
+            ];
+            for placements in &placements {
+                let mut board = Board::new(4, 6);
+                for &(fixed_piece, row, column) in *placements {
+                    board.place_at(fixed_piece, row, column).unwrap();
+                }
+                for &fixed_piece in &FixedPiece::array() {
+                    if board.push(fixed_piece).is_ok() {
+                        board.pop();
+                        assert!(board.candidates() & 1 << fixed_piece as usize != 0);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn all_placeable() {
+            let mut board = Board::new(4, 4);
+            board.place_at(I2, 0, 0).unwrap();
+            board.place_at(I2, 1, 0).unwrap();
+            assert!(board.all_placeable(&"IO".parse().unwrap()));
+
+            board.place_at(I2, 2, 0).unwrap();
+            assert!(board.all_placeable(&"I".parse().unwrap()));
+            assert!(!board.all_placeable(&"O".parse().unwrap()));
+
+            // Tracked as pieces are pushed and popped, and squares blocked
+            let mut board = Board::new(4, 4);
+            board.track_placeable();
+            board.push(I2).unwrap();
+            board.push(I2).unwrap();
+            assert!(board.all_placeable(&"IO".parse().unwrap()));
+            board.push(I2).unwrap();
+            assert!(board.all_placeable(&"I".parse().unwrap()));
+            assert!(!board.all_placeable(&"O".parse().unwrap()));
+            board.pop();
+            assert!(board.all_placeable(&"O".parse().unwrap()));
+            board.block(2, 1).unwrap();
+            board.block(2, 3).unwrap();
+            assert!(board.all_placeable(&"I".parse().unwrap()));
+            assert!(!board.all_placeable(&"O".parse().unwrap()));
+            let witnesses = board.placeable.as_ref().unwrap()[board.stack_count];
+            assert_eq!(witnesses[I as usize] & board.bits, 0);
+            assert_eq!(witnesses[O as usize], 0);
+        }
+
+        #[test]
+        fn block() {
+            let mut board = Board::new(2, 4);
+            board.block(1, 3).unwrap();
+            assert!(board.block(1, 3).is_err());
+            assert!(board.block(2, 0).is_err());
+            assert!(!board.can_place(I2, 1, 0));
+            board.push(I2).unwrap();
+
+            assert_eq!(board.position().to_string(), "AAAA\n...#\n");
+        }
+
+        #[test]
+        fn place_at() {
+            let mut board = Board::new(4, 4);
+            board.place_at(T3, 2, 2).unwrap();
+            assert!(board.place_at(O1, 2, 1).is_err());
+            board.push(I2).unwrap();
+            let position = board.position();
+
+            assert_eq!(
+                position.to_string(),
+                "BBBB\n\
+                 ....\n\
+                 ..A.\n\
+                 .AAA\n"
+            );
+            assert_eq!(board.last_placement().piece, I2);
+            assert_eq!(board.pop(), I);
+        }
+
+        #[test]
+        fn position_into() {
+            let mut board = Board::new(2, 4);
+            board.push(I2).unwrap();
+            let mut squares = b"left over from before".to_vec();
+            let capacity = squares.capacity();
+            board.position_into(&mut squares);
+            assert_eq!(squares, b"AAAA\n....\n");
+            assert_eq!(squares.capacity(), capacity);
+            assert_eq!(board.position().to_string().as_bytes(), &squares[..]);
+        }
+
+        #[test]
+        fn is_complete() {
+            let mut board = Board::new(1, 4);
+            assert!(!board.is_complete());
+            board.push(I2).unwrap();
+            assert!(board.is_complete());
+        }
+    }
+
+    mod shapes {
+        use crate::FixedPiece::*;
+        use crate::{Board, FixedPiece};
+
+        // Push fixed pieces onto a blank `row_count` by `col_count` board and
+        // return the display
+        fn push_output<'a, T: IntoIterator<Item = &'a FixedPiece>>(
+            row_count: u32,
+            col_count: u32,
+            pieces: T,
+        ) -> String {
+            let mut board = Board::new(row_count, col_count);
+            for r in pieces {
+                let output = board.push(*r);
+                assert!(output.is_ok());
+            }
+            board.position().to_string()
+        }
+
+        #[test]
+        fn i1() {
+            assert_eq!(
+                push_output(5, 4, &[I1]),
+                "A...\n\
+                 A...\n\
+                 A...\n\
+                 A...\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn i2() {
+            assert_eq!(
+                push_output(5, 4, &[I2]),
+                "AAAA\n\
+                 ....\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn o1() {
+            assert_eq!(
+                push_output(5, 4, &[O1]),
+                "AA..\n\
+                 AA..\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn t1() {
+            assert_eq!(
+                push_output(5, 4, &[T1]),
+                "AAA.\n\
+                 .A..\n\
+                 ....\n\
+                 ....\n\
+                 ....\n"
+            );
+        }
+
+        #[test]
+        fn t2() {
+            assert_eq!(
+                push_output(4, 5, &[I2, T2]),
+                "AAAAB\n\
+                 ...BB\n\
+                 ....B\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn t3() {
+            assert_eq!(
+                push_output(4, 6, &[I2, T3]),
+                "AAAAB.\n\
+                 ...BBB\n\
+                 ......\n\
+                 ......\n"
+            );
+        }
+
+        #[test]
+        fn t4() {
+            assert_eq!(
+                push_output(4, 5, &[T4]),
+                "A....\n\
+                 AA...\n\
+                 A....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn j1() {
+            assert_eq!(
+                push_output(4, 5, &[I2, J1]),
+                "AAAAB\n\
+                 ....B\n\
+                 ...BB\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn j2() {
+            assert_eq!(
+                push_output(4, 5, &[J2]),
+                "A....\n\
+                 AAA..\n\
+                 .....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn j3() {
+            assert_eq!(
+                push_output(4, 5, &[J3]),
+                "AA...\n\
+                 A....\n\
+                 A....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn j4() {
+            assert_eq!(
+                push_output(4, 5, &[J4]),
+                "AAA..\n\
+                 ..A..\n\
+                 .....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn l1() {
+            assert_eq!(
+                push_output(4, 5, &[L1]),
+                "A....\n\
+                 A....\n\
+                 AA...\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn l2() {
+            assert_eq!(
+                push_output(4, 5, &[L2]),
+                "AAA..\n\
+                 A....\n\
+                 .....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn l3() {
+            assert_eq!(
+                push_output(4, 5, &[L3]),
+                "AA...\n\
+                 .A...\n\
+                 .A...\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn l4() {
+            assert_eq!(
+                push_output(4, 5, &[I2, L4]),
+                "AAAAB\n\
+                 ..BBB\n\
+                 .....\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn s1() {
+            assert_eq!(
+                push_output(4, 6, &[I2, S1]),
+                "AAAABB\n\
+                 ...BB.\n\
+                 ......\n\
+                 ......\n"
+            );
+        }
+
+        #[test]
+        fn s2() {
+            assert_eq!(
+                push_output(4, 5, &[S2]),
+                "A....\n\
+                 AA...\n\
+                 .A...\n\
+                 .....\n"
+            );
+        }
+
+        #[test]
+        fn z1() {
+            assert_eq!(
+                push_output(4, 6, &[Z1]),
+                "AA....\n\
+                 .AA...\n\
+                 ......\n\
+                 ......\n"
+            );
+        }
+
+        #[test]
+        fn z2() {
+            assert_eq!(
+                push_output(4, 5, &[I2, Z2]),
+                "AAAAB\n\
+                 ...BB\n\
+                 ...B.\n\
+                 .....\n"
+            );
+        }
+    }
+
+    #[allow(clippy::uninlined_format_args)]
+    mod pretty_print {
+        use crate::Board;
+        use crate::FixedPiece::*;
+
+        #[test]
+        fn empty_board() {
+            let board = Board::new(4, 5);
+            let position = board.position();
+            let output = format!("{:#}", position);
+
+            assert_eq!(
+                output,
+                "┌─────────┐\n\
+                 │░░░░░░░░░│\n\
+                 │░░░░░░░░░│\n\
+                 │░░░░░░░░░│\n\
+                 └─────────┘\n"
+            );
+        }
+
+        #[test]
+        fn vertical_border() {
+            let mut board = Board::new(4, 5);
+            board.push(I1).unwrap();
+            let position = board.position();
+            let output = format!("{:#}", position);
+
+            assert_eq!(
+                output,
+                "┌─┬───────┐\n\
+                 │ │░░░░░░░│\n\
+                 │ │░░░░░░░│\n\
+                 │ │░░░░░░░│\n\
+                 └─┴───────┘\n"
+            );
+        }
+
+        #[test]
+        fn horizontal_border() {
+            let mut board = Board::new(5, 4);
+            board.push(I2).unwrap();
+            let position = board.position();
+            let output = format!("{:#}", position);
+
+            assert_eq!(
+                output,
+                "┌───────┐\n\
+                 ├───────┤\n\
+                 │░░░░░░░│\n\
+                 │░░░░░░░│\n\
+                 │░░░░░░░│\n\
+                 └───────┘\n"
+            );
+        }
+
+        #[test]
+        fn corners() {
+            let mut board = Board::new(4, 5);
+            board.push(Z1).unwrap();
+            let position = board.position();
+            let output = format!("{:#}", position);
+
+            assert_eq!(
+                output,
+                "┌───┬─────┐\n\
+                 ├─┐ └─┐░░░│\n\
+                 │░└───┘░░░│\n\
+                 │░░░░░░░░░│\n\
+                 └─────────┘\n"
+            );
+        }
+
+        #[test]
+        fn kind_string() {
+            let mut board = Board::new(2, 5);
+            board.push(T1).unwrap();
+            board.push(O1).unwrap();
+            let position = board.position();
+
+            assert_eq!(position.to_string(), "AAABB\n.A.BB\n");
+            assert_eq!(position.to_kind_string(), "TTTOO\n.T.OO\n");
+        }
+
+        #[test]
+        fn ascii_art() {
+            let mut board = Board::new(4, 5);
+            board.push(Z1).unwrap();
+            let position = board.position();
+
+            assert_eq!(
+                position.to_ascii_art(),
+                "+---+-----+\n\
+                 +-+ +-+:::|\n\
+                 |:+---+:::|\n\
+                 |:::::::::|\n\
+                 +---------+\n"
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// Solutions described in words, for screen readers and text-to-speech:
+//
+//     A 4 by 4 board with 4 pieces. Rows and columns are numbered from 1 at
+//     the top left, and the top-left square of a piece is its first square
+//     reading the rows from the top, left to right.
+//     Piece 1: L tetromino, rotated 90 degrees clockwise, top-left at row 1 column 1.
+//     ...
+//
+// The rotations are of the first orientation of each piece in `FixedPiece`:
+// the T pointing down, the J and the L upright and the S and the Z flat.
+
+use std::fmt::Write;
+
+use crate::{FixedPiece, Placement, Position};
+
+impl Position {
+    // Describe the placements of the position in words, one sentence per
+    // piece in the order of their labels, after a sentence about the board
+    #[must_use]
+    pub fn narrate(&self) -> String {
+        let placements = self.placements();
+        let mut narration = format!(
+            "A {} by {} board with {} {}. Rows and columns are numbered from 1 at the top \
+             left, and the top-left square of a piece is its first square reading the rows \
+             from the top, left to right.\n",
+            self.row_count(),
+            self.column_count(),
+            placements.len(),
+            if placements.len() == 1 {
+                "piece"
+            } else {
+                "pieces"
+            }
+        );
+        for (index, placement) in placements.iter().enumerate() {
+            writeln!(narration, "Piece {}: {}.", index + 1, describe(placement)).unwrap();
+        }
+        narration
+    }
+}
+
+// The placement in words, e.g. "T tetromino, rotated 180 degrees, top-left
+// at row 2 column 3"
+fn describe(placement: &Placement) -> String {
+    let piece = placement.piece.piece();
+    let orientation = orientation(placement.piece);
+    let orientation = if orientation.is_empty() {
+        String::new()
+    } else {
+        format!(", {orientation}")
+    };
+    format!(
+        "{piece:?} tetromino{orientation}, top-left at row {} column {}",
+        placement.row + 1,
+        placement.column + 1
+    )
+}
+
+// The orientation of a fixed piece in words, empty for the O
+fn orientation(fixed_piece: FixedPiece) -> &'static str {
+    use FixedPiece::*;
+    match fixed_piece {
+        I1 => "vertical",
+        I2 => "horizontal",
+        O1 => "",
+        S1 | Z1 => "flat",
+        S2 | Z2 => "upright",
+        T1 | J1 | L1 => "not rotated",
+        T2 | J2 | L2 => "rotated 90 degrees clockwise",
+        T3 | J3 | L3 => "rotated 180 degrees",
+        T4 | J4 | L4 => "rotated 90 degrees counterclockwise",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Position;
+
+    #[test]
+    fn narrate() {
+        let position = Position::from_replay(4, 4, "L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3").unwrap();
+        let narration = position.narrate();
+        let lines: Vec<_> = narration.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("A 4 by 4 board with 4 pieces. "));
+        assert_eq!(
+            lines[1],
+            "Piece 1: L tetromino, rotated 90 degrees clockwise, top-left at row 1 column 1."
+        );
+        assert_eq!(
+            lines[2],
+            "Piece 2: Z tetromino, upright, top-left at row 1 column 4."
+        );
+        assert_eq!(
+            lines[4],
+            "Piece 4: L tetromino, rotated 90 degrees counterclockwise, top-left at row 3 \
+             column 4."
+        );
+
+        let position = Position::from_replay(2, 2, "O1@r0c0").unwrap();
+        assert!(position.narrate().ends_with(
+            "1 piece. Rows and columns are numbered from 1 at the top left, and the top-left \
+             square of a piece is its first square reading the rows from the top, left to \
+             right.\nPiece 1: O tetromino, top-left at row 1 column 1.\n"
+        ));
+    }
+}
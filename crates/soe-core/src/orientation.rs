@@ -0,0 +1,194 @@
+// Preferences for the orientations of the pieces in the solution found, e.g.
+// to avoid vertical I pieces, which are awkward to drag into place on a
+// phone. Each fixed tetromino has a weight and the search tries the
+// candidates with higher weights first, so the first solution found prefers
+// them. Banned orientations aren't placed at all, so a puzzle can have no
+// solution with them.
+//
+//     let orientations: OrientationWeights = "I1=ban,T2=-1".parse()?;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::geometry::{Cell, Shape};
+use crate::FixedPiece;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct OrientationWeights {
+    weights: [i32; FixedPiece::count()],
+    // The banned orientations as a bit set indexed by `FixedPiece`
+    banned: u32,
+}
+
+#[derive(Debug)]
+pub struct ParseOrientationWeightsError;
+
+impl Display for ParseOrientationWeightsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The orientation weights must be a list like \"I1=ban,T2=-1\" of fixed \
+             tetrominoes with a whole number or \"ban\"."
+        )
+    }
+}
+
+impl Error for ParseOrientationWeightsError {}
+
+impl FromStr for OrientationWeights {
+    type Err = ParseOrientationWeightsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = OrientationWeights::default();
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (fixed_piece, weight) = item.split_once('=').ok_or(ParseOrientationWeightsError)?;
+            let fixed_piece: FixedPiece = fixed_piece
+                .trim()
+                .parse()
+                .map_err(|_| ParseOrientationWeightsError)?;
+            match weight.trim() {
+                "ban" => weights.ban(fixed_piece),
+                weight => {
+                    let weight = weight.parse().map_err(|_| ParseOrientationWeightsError)?;
+                    weights.set(fixed_piece, weight);
+                }
+            }
+        }
+        Ok(weights)
+    }
+}
+
+impl OrientationWeights {
+    // Give `fixed_piece` a weight. Higher weights are tried first and the
+    // default is 0. Lifts a ban.
+    pub fn set(&mut self, fixed_piece: FixedPiece, weight: i32) {
+        self.weights[fixed_piece as usize] = weight;
+        self.banned &= !(1 << fixed_piece as usize);
+    }
+
+    pub fn ban(&mut self, fixed_piece: FixedPiece) {
+        self.banned |= 1 << fixed_piece as usize;
+    }
+
+    // The weight of `fixed_piece`, or `None` if it's banned
+    #[must_use]
+    pub fn weight(&self, fixed_piece: FixedPiece) -> Option<i32> {
+        (self.banned & 1 << fixed_piece as usize == 0).then_some(self.weights[fixed_piece as usize])
+    }
+
+    // Whether the weights change nothing, so the search finds the canonical
+    // first solution
+    #[must_use]
+    pub fn is_neutral(&self) -> bool {
+        self.banned == 0 && self.weights.iter().all(|&weight| weight == self.weights[0])
+    }
+
+    // The weights for the transposed board. Transposing turns e.g. vertical I
+    // pieces into horizontal ones.
+    #[must_use]
+    pub(crate) fn transposed(&self) -> Self {
+        let mut transposed = OrientationWeights::default();
+        for fixed_piece in FixedPiece::array() {
+            let Shape(cells) = Shape::from(fixed_piece);
+            let image = Shape(cells.map(|Cell(row, column)| Cell(column, row)))
+                .fixed_piece()
+                .unwrap();
+            match self.weight(fixed_piece) {
+                Some(weight) => transposed.set(image, weight),
+                None => transposed.ban(image),
+            }
+        }
+        transposed
+    }
+
+    // The orientations that may be placed, as bit sets indexed by
+    // `FixedPiece`, highest weight first. Returns the sets and their number.
+    pub(crate) fn levels(&self) -> ([u32; FixedPiece::count()], usize) {
+        let mut allowed: Vec<FixedPiece> = FixedPiece::array()
+            .iter()
+            .copied()
+            .filter(|&fixed_piece| self.weight(fixed_piece).is_some())
+            .collect();
+        allowed.sort_by_key(|&fixed_piece| std::cmp::Reverse(self.weights[fixed_piece as usize]));
+        let mut levels = [0; FixedPiece::count()];
+        let mut count = 0;
+        for (i, &fixed_piece) in allowed.iter().enumerate() {
+            let weight = self.weights[fixed_piece as usize];
+            if i > 0 && weight != self.weights[allowed[i - 1] as usize] {
+                count += 1;
+            }
+            levels[count] |= 1 << fixed_piece as usize;
+        }
+        (levels, if allowed.is_empty() { 0 } else { count + 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrientationWeights;
+    use crate::FixedPiece::{self, *};
+    use crate::{solve_one_with_options, Outcome, SolveOptions};
+
+    #[test]
+    fn parse() {
+        let weights: OrientationWeights = "I1=ban, t2=-1,".parse().unwrap();
+        assert_eq!(weights.weight(I1), None);
+        assert_eq!(weights.weight(T2), Some(-1));
+        assert_eq!(weights.weight(O1), Some(0));
+        assert!(!weights.is_neutral());
+        assert!("".parse::<OrientationWeights>().unwrap().is_neutral());
+        assert!("I1".parse::<OrientationWeights>().is_err());
+        assert!("I9=1".parse::<OrientationWeights>().is_err());
+        assert!("I1=never".parse::<OrientationWeights>().is_err());
+    }
+
+    #[test]
+    fn levels() {
+        let weights: OrientationWeights = "I1=ban,I2=2,T2=-1".parse().unwrap();
+        let (levels, count) = weights.levels();
+        assert_eq!(count, 3);
+        assert_eq!(levels[0], 1 << I2 as usize);
+        assert_eq!(levels[2], 1 << T2 as usize);
+        assert_eq!(
+            (levels[0] | levels[1] | levels[2]).count_ones(),
+            FixedPiece::count() as u32 - 1
+        );
+        assert_eq!(OrientationWeights::default().levels().1, 1);
+
+        let transposed = weights.transposed();
+        assert_eq!(transposed.weight(I2), None);
+        assert_eq!(transposed.weight(I1), Some(2));
+    }
+
+    #[test]
+    fn avoid_vertical_i_pieces() {
+        let solve = |rows, columns, orientations: &str| {
+            let options = SolveOptions {
+                orientations: orientations.parse().unwrap(),
+                ..SolveOptions::default()
+            };
+            solve_one_with_options(rows, columns, "IIII".parse().unwrap(), &options).unwrap()
+        };
+        // The canonical first solution has four vertical I pieces
+        let canonical = solve(4, 4, "");
+        assert_eq!(
+            canonical.solution().unwrap().to_string(),
+            "ABCD\nABCD\nABCD\nABCD\n"
+        );
+        let solution = solve(4, 4, "I2=1");
+        assert_eq!(
+            solution.solution().unwrap().to_string(),
+            "AAAA\nBBBB\nCCCC\nDDDD\n"
+        );
+        let solution = solve(4, 4, "I1=ban");
+        assert_eq!(
+            solution.solution().unwrap().to_string(),
+            "AAAA\nBBBB\nCCCC\nDDDD\n"
+        );
+        // Transposed boards are searched with the weights transposed
+        let solution = solve(8, 2, "I1=ban");
+        assert_eq!(solution, Outcome::NoSolution);
+        assert!(solve(8, 2, "I2=ban").solution().is_some());
+    }
+}
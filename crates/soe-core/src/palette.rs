@@ -0,0 +1,242 @@
+// The colors positions are drawn in, shared by the HTML, SVG, PNG and ANSI
+// renderers. A palette can be read from a file of `name = color` lines, with
+// colors written as in CSS and comments after '#' at the start of a line or
+// after a color:
+//
+//     # Okabe-Ito colors for deuteranopia
+//     I = #56b4e9
+//     Z = #d55e00
+//     background = #fff
+//
+// The names are the letters of the pieces, `background` for empty squares,
+// `blocked`, `border` for the outlines and `unknown` for squares whose label
+// isn't a tetromino. Colors that aren't given are those of the default
+// palette.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::Piece;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Palette {
+    // The color of each kind of piece as RGB, indexed by `Piece`
+    pub pieces: [[u8; 3]; Piece::count()],
+    // The color of empty squares
+    pub background: [u8; 3],
+    // The color of blocked squares
+    pub blocked: [u8; 3],
+    // The color of the outlines of the pieces and the board
+    pub border: [u8; 3],
+    // The color of squares whose label doesn't make a tetromino
+    pub unknown: [u8; 3],
+}
+
+// The usual colors of the tetrominoes
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            pieces: [
+                [0, 255, 255],
+                [255, 255, 0],
+                [170, 0, 255],
+                [0, 0, 255],
+                [255, 170, 0],
+                [0, 255, 0],
+                [255, 0, 0],
+            ],
+            background: [255, 255, 255],
+            blocked: [136, 136, 136],
+            border: [0, 0, 0],
+            unknown: [204, 204, 204],
+        }
+    }
+}
+
+impl Palette {
+    // The Okabe-Ito colors, which can be told apart with any kind of color
+    // blindness. Unlike the usual colors, S and Z and J and L don't look
+    // alike to people with red-green color blindness.
+    #[must_use]
+    pub fn colorblind() -> Self {
+        Self {
+            pieces: [
+                [86, 180, 233],
+                [240, 228, 66],
+                [204, 121, 167],
+                [0, 114, 178],
+                [230, 159, 0],
+                [0, 158, 115],
+                [213, 94, 0],
+            ],
+            ..Self::default()
+        }
+    }
+
+    // The built-in palette `name`, "default" or "colorblind"
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "colorblind" => Some(Self::colorblind()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn piece(&self, piece: Piece) -> [u8; 3] {
+        self.pieces[piece as usize]
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParsePaletteError {
+    // The line isn't `name = color`
+    Syntax { line: usize },
+    UnknownName { line: usize },
+    InvalidColor { line: usize },
+}
+
+impl Display for ParsePaletteError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ParsePaletteError::*;
+        match self {
+            Syntax { line } => write!(f, "Line {line} of the palette is not `name = color`."),
+            UnknownName { line } => write!(
+                f,
+                "Line {line} of the palette doesn't name a piece, background, blocked, border \
+                 or unknown."
+            ),
+            InvalidColor { line } => write!(
+                f,
+                "Line {line} of the palette has an invalid color. Colors are written as #rgb \
+                 or #rrggbb."
+            ),
+        }
+    }
+}
+
+impl Error for ParsePaletteError {}
+
+impl FromStr for Palette {
+    type Err = ParsePaletteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut palette = Palette::default();
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or(ParsePaletteError::Syntax { line: line_number })?;
+            let mut words = value.split_whitespace();
+            let color = words
+                .next()
+                .and_then(parse_color)
+                .ok_or(ParsePaletteError::InvalidColor { line: line_number })?;
+            if words.next().is_some_and(|word| !word.starts_with('#')) {
+                return Err(ParsePaletteError::Syntax { line: line_number });
+            }
+            let name = name.trim();
+            let target = match name {
+                "background" => &mut palette.background,
+                "blocked" => &mut palette.blocked,
+                "border" => &mut palette.border,
+                "unknown" => &mut palette.unknown,
+                _ => match Piece::array()
+                    .iter()
+                    .find(|piece| format!("{piece:?}") == name)
+                {
+                    Some(&piece) => &mut palette.pieces[piece as usize],
+                    None => return Err(ParsePaletteError::UnknownName { line: line_number }),
+                },
+            };
+            *target = color;
+        }
+        Ok(palette)
+    }
+}
+
+// Parse a color written as #rgb or #rrggbb
+fn parse_color(s: &str) -> Option<[u8; 3]> {
+    let digits = s.strip_prefix('#')?;
+    if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| {
+        let value = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).unwrap();
+        if len == 1 {
+            value * 17
+        } else {
+            value
+        }
+    };
+    match digits.len() {
+        3 => Some([channel(0, 1), channel(1, 1), channel(2, 1)]),
+        6 => Some([channel(0, 2), channel(1, 2), channel(2, 2)]),
+        _ => None,
+    }
+}
+
+// The color as CSS, in the short form #rgb when it has one
+pub(crate) fn css(color: [u8; 3]) -> String {
+    if color.iter().all(|channel| channel % 17 == 0) {
+        let [r, g, b] = color.map(|channel| channel / 17);
+        format!("#{r:x}{g:x}{b:x}")
+    } else {
+        let [r, g, b] = color;
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{css, Palette, ParsePaletteError};
+    use crate::Piece;
+
+    #[test]
+    fn parse() {
+        let palette: Palette = "# Comment\n\
+                                I = #56b4e9\n\
+                                \n\
+                                background=#000 # black\n"
+            .parse()
+            .unwrap();
+        assert_eq!(palette.piece(Piece::I), [0x56, 0xb4, 0xe9]);
+        assert_eq!(palette.background, [0, 0, 0]);
+        assert_eq!(palette.piece(Piece::O), Palette::default().piece(Piece::O));
+        assert_eq!("".parse::<Palette>().unwrap(), Palette::default());
+        assert_eq!(Palette::named("colorblind"), Some(Palette::colorblind()));
+        assert_eq!(Palette::named("sepia"), None);
+
+        let error = |s: &str| s.parse::<Palette>().err().unwrap();
+        assert_eq!(error("I #fff"), ParsePaletteError::Syntax { line: 1 });
+        assert_eq!(
+            error("\nX = #fff"),
+            ParsePaletteError::UnknownName { line: 2 }
+        );
+        assert_eq!(
+            error("I = #ffff"),
+            ParsePaletteError::InvalidColor { line: 1 }
+        );
+        assert_eq!(
+            error("I = fff"),
+            ParsePaletteError::InvalidColor { line: 1 }
+        );
+        assert_eq!(
+            error("I = #fff black"),
+            ParsePaletteError::Syntax { line: 1 }
+        );
+    }
+
+    #[test]
+    fn css_colors() {
+        assert_eq!(css([0, 255, 255]), "#0ff");
+        assert_eq!(css([170, 0, 255]), "#a0f");
+        assert_eq!(css([0x56, 0xb4, 0xe9]), "#56b4e9");
+    }
+}
@@ -0,0 +1,241 @@
+// A multi-threaded search. The search tree is split into work units, one for
+// each valid sequence of the first `SPLIT_DEPTH` placements. The units are in
+// canonical order and the threads take them from a shared counter.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{
+    Board, FixedPiece, Outcome, PieceCollection, Position, PuzzleSpec, SolveOneError, SolveOptions,
+    Solver, PIECE_MAP,
+};
+
+// The number of placements that make up a work unit
+const SPLIT_DEPTH: usize = 2;
+
+// Search for a solution with `options.threads` threads. If
+// `options.deterministic` is true the canonical first solution is returned.
+// Otherwise the first solution found by any thread is returned. The search is
+// abandoned once `cancelled` is set.
+pub fn solve_one(
+    board: &Board,
+    pieces: &PieceCollection,
+    options: &SolveOptions,
+    cancelled: &AtomicBool,
+) -> Outcome {
+    let deterministic = options.deterministic;
+    let max_nodes = options.max_nodes.unwrap_or(u64::MAX);
+    let mut units = vec![];
+    let mut solver = Solver::new(board.clone(), pieces.clone());
+    collect_units(&mut solver, &mut vec![], &mut units);
+
+    let next_unit = AtomicUsize::new(0);
+    // The index of the first unit known to contain a solution
+    let best_unit = AtomicUsize::new(usize::MAX);
+    // The index of the first unit abandoned because of `max_nodes`
+    let first_abandoned = AtomicUsize::new(usize::MAX);
+    // The number of nodes visited by all the threads, counted every 1024
+    // nodes
+    let nodes = AtomicU64::new(0);
+    let solutions = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads {
+            scope.spawn(|| loop {
+                let index = next_unit.fetch_add(1, Ordering::Relaxed);
+                // A unit can be skipped if an earlier one has a solution or,
+                // in non-deterministic mode, if any unit has one.
+                let limit = if deterministic { index } else { usize::MAX };
+                if index >= units.len() || best_unit.load(Ordering::Relaxed) < limit {
+                    break;
+                }
+                if nodes.load(Ordering::Relaxed) >= max_nodes || cancelled.load(Ordering::Relaxed) {
+                    first_abandoned.fetch_min(index, Ordering::Relaxed);
+                    break;
+                }
+
+                let stop = || {
+                    best_unit.load(Ordering::Relaxed) < limit
+                        || nodes.fetch_add(0x400, Ordering::Relaxed) >= max_nodes
+                        || cancelled.load(Ordering::Relaxed)
+                };
+                let mut solver = Solver::new(board.clone(), pieces.clone())
+                    .with_stop(&stop)
+                    .with_prune_unplaceable(options.prune_unplaceable);
+                for &fixed_piece in &units[index] {
+                    solver.board.push(fixed_piece).unwrap();
+                    solver.pieces.remove(PIECE_MAP[fixed_piece as usize]);
+                }
+
+                if let Some(solution) = solver.solve_one() {
+                    best_unit.fetch_min(index, Ordering::Relaxed);
+                    solutions.lock().unwrap().push((index, solution));
+                } else if solver.stopped && best_unit.load(Ordering::Relaxed) >= limit {
+                    first_abandoned.fetch_min(index, Ordering::Relaxed);
+                }
+                nodes.fetch_add(solver.nodes & 0x3ff, Ordering::Relaxed);
+            });
+        }
+    });
+
+    let solutions = solutions.into_inner().unwrap();
+    let first = if deterministic {
+        solutions.into_iter().min_by_key(|&(index, _)| index)
+    } else {
+        solutions.into_iter().next()
+    };
+    let first_abandoned = first_abandoned.into_inner();
+    match first {
+        // An abandoned unit before the solution might have had an earlier one
+        Some((index, _)) if deterministic && first_abandoned < index => Outcome::Undetermined,
+        Some((_, solution)) => Outcome::Solved(solution),
+        None if first_abandoned < usize::MAX => Outcome::Undetermined,
+        None => Outcome::NoSolution,
+    }
+}
+
+// Solves many puzzles using all the available cores. The result for each
+// puzzle is the same as `solve_first_canonical` would give. Boards are set up
+// once for each board size and shared by the puzzles of that size.
+#[must_use]
+pub fn solve_batch(puzzles: &[PuzzleSpec]) -> Vec<Result<Option<Position>, SolveOneError>> {
+    let mut boards = HashMap::new();
+    for puzzle in puzzles {
+        if puzzle.validate().is_ok() {
+            boards
+                .entry((puzzle.row_count, puzzle.column_count))
+                .or_insert_with(|| Board::new(puzzle.row_count, puzzle.column_count));
+        }
+    }
+
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    let next_puzzle = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(puzzles.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(puzzles.len()) {
+            scope.spawn(|| loop {
+                let index = next_puzzle.fetch_add(1, Ordering::Relaxed);
+                let Some(puzzle) = puzzles.get(index) else {
+                    break;
+                };
+                let result = puzzle.validate().map(|()| {
+                    let board = boards[&(puzzle.row_count, puzzle.column_count)].clone();
+                    let pieces = puzzle.pieces.clone();
+                    Solver::new(board, pieces).solve_one()
+                });
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// Collect the sequences of up to `SPLIT_DEPTH` placements that can start a
+// solution, in canonical order. Shorter sequences are collected when the board
+// is complete.
+fn collect_units(
+    solver: &mut Solver,
+    prefix: &mut Vec<FixedPiece>,
+    units: &mut Vec<Vec<FixedPiece>>,
+) {
+    if prefix.len() == SPLIT_DEPTH || solver.board.is_complete() {
+        units.push(prefix.clone());
+        return;
+    }
+
+    for &fixed_piece in &FixedPiece::array() {
+        let piece = PIECE_MAP[fixed_piece as usize];
+        if solver.pieces.count(piece) == 0 || solver.board.push(fixed_piece).is_err() {
+            continue;
+        }
+        solver.pieces.remove(piece);
+        prefix.push(fixed_piece);
+        collect_units(solver, prefix, units);
+        prefix.pop();
+        solver.board.pop();
+        solver.pieces.add(piece);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve_batch;
+    use crate::puzzles;
+    use crate::{solve_one_with_options, Outcome, PuzzleSpec, SolveOptions};
+
+    #[test]
+    fn deterministic() {
+        let options = SolveOptions {
+            threads: 4,
+            deterministic: true,
+            transpose: false,
+            ..SolveOptions::default()
+        };
+        for puzzle in puzzles::all().iter().take(16) {
+            let pieces = puzzle.tetrominoes.parse().unwrap();
+            let solution =
+                solve_one_with_options(puzzle.row_count, puzzle.column_count, pieces, &options);
+            let solution = solution.unwrap().into_solution().unwrap();
+            assert_eq!(solution.to_string(), puzzle.solution);
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let mut specs: Vec<_> = puzzles::all()
+            .iter()
+            .map(|p| PuzzleSpec::new(p.row_count, p.column_count, p.tetrominoes.parse().unwrap()))
+            .collect();
+        specs.push(PuzzleSpec::new(4, 4, "IITT".parse().unwrap()));
+        specs.push(PuzzleSpec::new(3, 3, "I".parse().unwrap()));
+        let results = solve_batch(&specs);
+
+        assert_eq!(results.len(), specs.len());
+        for (puzzle, result) in puzzles::all().iter().zip(&results) {
+            let solution = result.as_ref().unwrap().as_ref().unwrap();
+            assert_eq!(solution.to_string(), puzzle.solution);
+        }
+        assert!(results[96].as_ref().unwrap().is_none());
+        assert!(results[97].is_err());
+    }
+
+    #[test]
+    fn racy() {
+        let options = SolveOptions {
+            threads: 4,
+            deterministic: false,
+            ..SolveOptions::default()
+        };
+        let outcome = solve_one_with_options(6, 6, "OTTTTLLLL".parse().unwrap(), &options);
+        assert!(outcome.unwrap().solution().is_some());
+
+        let outcome = solve_one_with_options(4, 4, "IITT".parse().unwrap(), &options);
+        assert!(outcome.unwrap() == Outcome::NoSolution);
+    }
+
+    #[test]
+    fn max_nodes() {
+        let options = SolveOptions {
+            threads: 4,
+            max_nodes: Some(5000),
+            ..SolveOptions::default()
+        };
+        let outcome = solve_one_with_options(4, 12, "IIITTTLLLJJS".parse().unwrap(), &options);
+        assert!(outcome.unwrap().is_undetermined());
+
+        let outcome = solve_one_with_options(4, 4, "IITT".parse().unwrap(), &options);
+        assert!(outcome.unwrap() == Outcome::NoSolution);
+
+        let puzzle = &puzzles::all()[0];
+        let pieces = puzzle.tetrominoes.parse().unwrap();
+        let outcome =
+            solve_one_with_options(puzzle.row_count, puzzle.column_count, pieces, &options);
+        assert!(outcome.unwrap().solution().is_some());
+    }
+}
@@ -0,0 +1,225 @@
+// Coloring arguments that show a puzzle has no solution without searching.
+// The board is colored, e.g. as a checkerboard, and each kind of piece covers
+// a known difference of squares of the two colors wherever it's placed. If the
+// pieces can't add up to the difference on the board, there's no tiling.
+//
+// The arguments assume the pieces cover as many squares as the board has,
+// which `validate` checks before any search.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{Piece, PuzzleSpec};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ParityReport {
+    // The squares of a checkerboard coloring whose row plus column is even
+    // and odd. Each T piece covers three squares of one color and one of the
+    // other and every other piece two of each.
+    pub dark_squares: u32,
+    pub light_squares: u32,
+    pub t_count: u32,
+    // The T pieces can't make up a difference of more than two each
+    pub checkerboard_rules_out: bool,
+    // Each T piece changes the difference by two, up or down, so an odd
+    // number of them can't make up a difference that's a multiple of four
+    // and an even number can't make up one that isn't
+    pub t_parity_rules_out: bool,
+    // Columns colored alternately, and rows
+    pub column_stripes: StripeCount,
+    pub row_stripes: StripeCount,
+}
+
+// The squares of each color when the lines of the board, columns or rows, are
+// colored alternately. Across the stripes a J or an L always covers three
+// squares of one color and one of the other, a T either that or two of each,
+// an I four of one color or two of each and an O, S or Z two of each.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct StripeCount {
+    // The squares in the first, third, ... line and in the others
+    pub even: u32,
+    pub odd: u32,
+    pub rules_out: bool,
+}
+
+impl ParityReport {
+    // Whether any of the arguments shows there's no solution
+    #[must_use]
+    pub fn rules_out(&self) -> bool {
+        self.checkerboard_rules_out
+            || self.t_parity_rules_out
+            || self.column_stripes.rules_out
+            || self.row_stripes.rules_out
+    }
+}
+
+// Run the coloring arguments on the puzzle
+#[must_use]
+pub fn analyze_parity(spec: &PuzzleSpec) -> ParityReport {
+    let (row_count, column_count) = (spec.row_count, spec.column_count);
+    let area = u64::from(row_count) * u64::from(column_count);
+    let dark_squares = area.div_ceil(2) as u32;
+    let light_squares = (area / 2) as u32;
+    let t_count = spec.pieces.count(Piece::T);
+    let difference = i64::from(dark_squares) - i64::from(light_squares);
+
+    ParityReport {
+        dark_squares,
+        light_squares,
+        t_count,
+        checkerboard_rules_out: difference % 2 != 0 || difference > 2 * i64::from(t_count),
+        t_parity_rules_out: difference % 2 == 0 && (difference / 2 + i64::from(t_count)) % 2 != 0,
+        column_stripes: stripes(spec, row_count, column_count),
+        row_stripes: stripes(spec, column_count, row_count),
+    }
+}
+
+// The stripe count of `line_count` lines of `line_length` squares each
+fn stripes(spec: &PuzzleSpec, line_length: u32, line_count: u32) -> StripeCount {
+    let even = line_length * line_count.div_ceil(2);
+    let odd = line_length * (line_count / 2);
+    let difference = (i64::from(even) - i64::from(odd)).abs();
+
+    let count = |piece| i64::from(spec.pieces.count(piece));
+    let (i, t, j) = (
+        count(Piece::I),
+        count(Piece::T),
+        count(Piece::J) + count(Piece::L),
+    );
+    // Each J and L changes the difference by two and each T by zero or two,
+    // so without T pieces the J and L pieces fix it modulo four
+    let rules_out = difference % 2 != 0
+        || difference > 2 * j + 2 * t + 4 * i
+        || t == 0 && (difference - 2 * j) % 4 != 0;
+    StripeCount {
+        even,
+        odd,
+        rules_out,
+    }
+}
+
+fn verdict(rules_out: bool) -> &'static str {
+    if rules_out {
+        "rules out a tiling"
+    } else {
+        "doesn't rule out a tiling"
+    }
+}
+
+// A paragraph per argument, explaining the counts and the verdict
+impl Display for ParityReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Checkerboard: the board has {} dark and {} light squares. A T piece covers three \
+             squares of one color and one of the other and the other pieces two of each, so \
+             the {} T pieces can make up a difference of at most {}. This {}.",
+            self.dark_squares,
+            self.light_squares,
+            self.t_count,
+            2 * self.t_count,
+            verdict(self.checkerboard_rules_out)
+        )?;
+        writeln!(
+            f,
+            "T parity: each T piece changes the difference between the colors by two, so {} T \
+             pieces make up a difference that is {}a multiple of four. This {}.",
+            self.t_count,
+            if self.t_count.is_multiple_of(2) {
+                ""
+            } else {
+                "not "
+            },
+            verdict(self.t_parity_rules_out)
+        )?;
+        for (name, stripes) in [("Column", self.column_stripes), ("Row", self.row_stripes)] {
+            writeln!(
+                f,
+                "{} stripes: the board has {} squares in the odd-numbered {}s and {} in the \
+                 others. A J or an L piece changes the difference by two, a T by zero or two, \
+                 an I by zero or four and the other pieces by zero. This {}.",
+                name,
+                stripes.even,
+                name.to_lowercase(),
+                stripes.odd,
+                verdict(stripes.rules_out)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_parity, StripeCount};
+    use crate::{solve_one, PuzzleSpec};
+
+    fn report(row_count: u32, column_count: u32, pieces: &str) -> super::ParityReport {
+        analyze_parity(&PuzzleSpec::new(
+            row_count,
+            column_count,
+            pieces.parse().unwrap(),
+        ))
+    }
+
+    #[test]
+    fn counts() {
+        let report = report(4, 5, "IOTTJ");
+        assert_eq!((report.dark_squares, report.light_squares), (10, 10));
+        assert_eq!(report.t_count, 2);
+        assert_eq!(
+            report.column_stripes,
+            StripeCount {
+                even: 12,
+                odd: 8,
+                rules_out: false
+            }
+        );
+        assert_eq!(
+            report.row_stripes,
+            StripeCount {
+                even: 10,
+                odd: 10,
+                rules_out: false
+            }
+        );
+        assert!(!report.rules_out());
+    }
+
+    #[test]
+    fn rules_out() {
+        // An odd number of T pieces
+        let odd_t = report(4, 4, "OTTT");
+        assert!(odd_t.t_parity_rules_out && !odd_t.checkerboard_rules_out);
+        // The five columns of a 4 by 5 board differ by four squares, which
+        // only an I, a T or a J or L can make up
+        let stripes = report(4, 5, "OOOSZ");
+        assert!(stripes.column_stripes.rules_out && !stripes.row_stripes.rules_out);
+        assert!(!stripes.t_parity_rules_out);
+        // One J and no T or I changes the difference by two, not four
+        assert!(report(4, 5, "OOOSJ").column_stripes.rules_out);
+        assert!(!report(4, 5, "OOOJJ").column_stripes.rules_out);
+    }
+
+    #[test]
+    fn sound() {
+        // Every puzzle ruled out on these boards has no solution
+        for &(row_count, column_count) in &[(4, 4), (4, 5), (2, 6), (3, 4)] {
+            let piece_count = (row_count * column_count / 4) as usize;
+            for code in 0..7usize.pow(piece_count as u32) {
+                let pieces: String = (0..piece_count)
+                    .map(|i| b"IOTJLSZ"[code / 7usize.pow(i as u32) % 7] as char)
+                    .collect();
+                if report(row_count, column_count, &pieces).rules_out() {
+                    let solution = solve_one(row_count, column_count, pieces.parse().unwrap());
+                    assert!(
+                        !matches!(solution, Ok(Some(_))),
+                        "{}x{} {}",
+                        row_count,
+                        column_count,
+                        pieces
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Manual play: the player places the pieces one at a time and the game checks
+// each move. Moves can be undone, and the solver can complete the board from
+// the pieces placed so far.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    validate_blocked, Board, BoardShape, FixedPiece, PieceCollection, PlaceAtError, Placement,
+    Position, SolveOneError, Solver, PIECE_MAP,
+};
+
+#[derive(Clone)]
+pub struct Game {
+    board: Board,
+    // The pieces not placed yet
+    remaining: PieceCollection,
+    // The placements in the order they were made
+    moves: Vec<Placement>,
+}
+
+#[derive(Debug)]
+pub enum MoveError {
+    // All the pieces of the kind have been placed
+    PieceUnavailable,
+    IllegalPlacement(PlaceAtError),
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use MoveError::*;
+        match self {
+            PieceUnavailable => write!(f, "There are no more pieces of that kind to place."),
+            IllegalPlacement(err) => write!(f, "The piece can't be placed there. {err}"),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+impl Game {
+    // Start a game on an empty `row_count` by `column_count` board
+    pub fn new(
+        row_count: u32,
+        column_count: u32,
+        pieces: PieceCollection,
+    ) -> Result<Self, SolveOneError> {
+        Self::with_board_shape(&BoardShape::rectangle(row_count, column_count), pieces)
+    }
+
+    // Start a game on a board with blocked squares
+    pub fn with_board_shape(
+        shape: &BoardShape,
+        pieces: PieceCollection,
+    ) -> Result<Self, SolveOneError> {
+        let blocked = shape.blocked_squares();
+        validate_blocked(
+            shape.row_count(),
+            shape.column_count(),
+            blocked.len() as u32,
+            &pieces,
+        )?;
+        let mut board = Board::new(shape.row_count(), shape.column_count());
+        for (row, column) in blocked {
+            board.block(row, column).unwrap();
+        }
+        Ok(Self {
+            board,
+            remaining: pieces,
+            moves: vec![],
+        })
+    }
+
+    // Place `piece` with its top-left square at `row`, `column`
+    pub fn place(&mut self, piece: FixedPiece, row: u32, column: u32) -> Result<(), MoveError> {
+        let kind = PIECE_MAP[piece as usize];
+        if self.remaining.count(kind) == 0 {
+            return Err(MoveError::PieceUnavailable);
+        }
+        self.board
+            .place_at(piece, row, column)
+            .map_err(MoveError::IllegalPlacement)?;
+        self.remaining.remove(kind);
+        self.moves.push(Placement { piece, row, column });
+        Ok(())
+    }
+
+    // Take back the last move. Returns `None` if there are no moves.
+    pub fn undo(&mut self) -> Option<Placement> {
+        let placement = self.moves.pop()?;
+        self.remaining.add(self.board.pop());
+        Some(placement)
+    }
+
+    #[must_use]
+    pub fn moves(&self) -> &[Placement] {
+        &self.moves
+    }
+
+    #[must_use]
+    pub fn remaining(&self) -> &PieceCollection {
+        &self.remaining
+    }
+
+    // The board with the pieces labelled in the order they were placed
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.board.position()
+    }
+
+    #[must_use]
+    pub fn is_solved(&self) -> bool {
+        self.board.is_complete()
+    }
+
+    // A solution that keeps the pieces placed so far, or `None` if they can't
+    // be part of a solution
+    #[must_use]
+    pub fn complete(&self) -> Option<Position> {
+        let remaining = self.remaining.clone();
+        Solver::new(self.board.clone(), remaining).solve_one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Game, MoveError};
+    use crate::FixedPiece::*;
+    use crate::{BoardShape, PlaceAtError};
+
+    #[test]
+    fn play() {
+        let mut game = Game::new(4, 4, "LLZZ".parse().unwrap()).unwrap();
+        game.place(L2, 0, 0).unwrap();
+        assert!(matches!(
+            game.place(O1, 2, 0),
+            Err(MoveError::PieceUnavailable)
+        ));
+        assert!(matches!(
+            game.place(Z2, 0, 1),
+            Err(MoveError::IllegalPlacement(PlaceAtError::Overlap))
+        ));
+        assert!(matches!(
+            game.place(Z2, 0, 4),
+            Err(MoveError::IllegalPlacement(PlaceAtError::OffBoard))
+        ));
+        game.place(Z2, 0, 3).unwrap();
+        assert_eq!(game.remaining().to_canonical_string(), "LZ");
+        assert_eq!(
+            game.complete().unwrap().to_string(),
+            "AAAB\nACBB\nCCBD\nCDDD\n"
+        );
+
+        // An L here leaves a hole that can't be filled
+        game.place(L1, 1, 1).unwrap();
+        assert!(game.complete().is_none());
+        assert_eq!(game.undo().unwrap().piece, L1);
+        game.place(Z2, 1, 1).unwrap();
+        game.place(L4, 2, 3).unwrap();
+        assert!(game.is_solved());
+        assert_eq!(game.position().to_string(), "AAAB\nACBB\nCCBD\nCDDD\n");
+        assert_eq!(game.moves().len(), 4);
+
+        while game.undo().is_some() {}
+        assert_eq!(game.remaining().to_canonical_string(), "LLZZ");
+        assert_eq!(game.position().to_string(), "....\n....\n....\n....\n");
+    }
+
+    #[test]
+    fn blocked() {
+        let shape: BoardShape = "4./2.2#/2.2#".parse().unwrap();
+        let mut game = Game::with_board_shape(&shape, "IO".parse().unwrap()).unwrap();
+        assert!(game.place(O1, 1, 2).is_err());
+        game.place(O1, 1, 0).unwrap();
+        assert_eq!(game.complete().unwrap().to_string(), "BBBB\nAA##\nAA##\n");
+        assert!(Game::new(4, 4, "LLZ".parse().unwrap()).is_err());
+    }
+}
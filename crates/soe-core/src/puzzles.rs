@@ -0,0 +1,267 @@
+// The puzzles from the game.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::{PieceCollection, Position, PuzzleSpec};
+
+mod archive;
+mod booklet;
+mod csv;
+mod file;
+mod json;
+
+pub use archive::{verify_archive, verify_toml, ArchiveFailure, ArchiveReport, SolutionError};
+pub use booklet::{booklet, BookletFormat};
+pub use csv::{import_csv, to_csv, ImportCsvError};
+pub use file::{from_toml, load, save, to_toml, LoadPuzzlesError, PuzzleRecord};
+pub use json::{from_json, to_json};
+
+// A puzzle from the game with its canonical solution. Puzzles are identified by
+// the section, the color of the level and the level number.
+pub struct Puzzle {
+    pub section: &'static str,
+    pub color: &'static str,
+    pub number: u32,
+    pub row_count: u32,
+    pub column_count: u32,
+    pub tetrominoes: &'static str,
+    pub solution: &'static str,
+}
+
+impl Puzzle {
+    const fn new(
+        section: &'static str,
+        color: &'static str,
+        number: u32,
+        row_count: u32,
+        column_count: u32,
+        tetrominoes: &'static str,
+        solution: &'static str,
+    ) -> Self {
+        Self {
+            section,
+            color,
+            number,
+            row_count,
+            column_count,
+            tetrominoes,
+            solution,
+        }
+    }
+
+    #[must_use]
+    pub fn pieces(&self) -> PieceCollection {
+        self.tetrominoes.parse().unwrap()
+    }
+
+    #[must_use]
+    pub fn solution(&self) -> Position {
+        Position {
+            squares: self.solution.as_bytes().to_vec(),
+        }
+    }
+
+    #[must_use]
+    pub fn spec(&self) -> PuzzleSpec {
+        PuzzleSpec::new(self.row_count, self.column_count, self.pieces())
+    }
+
+    // Puzzles with the same fingerprint are the same puzzle
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        self.spec().fingerprint()
+    }
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.section, self.color, self.number)
+    }
+}
+
+// All the puzzles in the order they appear in the game.
+#[must_use]
+pub fn all() -> &'static [Puzzle] {
+    &PUZZLES
+}
+
+// The puzzles of a section, e.g. "A", or of one color in a section, e.g.
+// "A yellow", in game order. Names are case insensitive.
+#[must_use]
+pub fn section(name: &str) -> Vec<&'static Puzzle> {
+    let mut words = name.split_whitespace();
+    let section = words.next().unwrap_or("");
+    let color = words.next();
+    if words.next().is_some() {
+        return vec![];
+    }
+    all()
+        .iter()
+        .filter(|puzzle| puzzle.section.eq_ignore_ascii_case(section))
+        .filter(|puzzle| color.is_none_or(|color| puzzle.color.eq_ignore_ascii_case(color)))
+        .collect()
+}
+
+// Groups of puzzles that are the same apart from the order of the
+// tetrominoes and turning the board, e.g. a 4x5 and a 5x4 puzzle with the
+// same pieces, or one with the J and L pieces and the S and Z pieces swapped.
+// Only groups with more than one puzzle are returned. Groups are ordered by
+// their first puzzle and the puzzles in each group are in game order.
+#[must_use]
+pub fn duplicates() -> Vec<Vec<&'static Puzzle>> {
+    let mut groups: Vec<Vec<&Puzzle>> = vec![];
+    let mut group_indices = HashMap::new();
+    for puzzle in all() {
+        let index = *group_indices
+            .entry(puzzle.spec().canonicalize().0)
+            .or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+        groups[index].push(puzzle);
+    }
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+#[rustfmt::skip]
+static PUZZLES: [Puzzle; 96] = [
+    Puzzle::new("A", "cyan", 1, 4, 4, "LLZZ", "AAAB\nACBB\nCCBD\nCDDD\n"),
+    Puzzle::new("A", "cyan", 2, 4, 4, "IJLZ", "ABBC\nABCC\nABCD\nADDD\n"),
+    Puzzle::new("A", "cyan", 3, 5, 4, "ITTLZ", "AAAA\nBBBC\nDBCC\nDEEC\nDDEE\n"),
+    Puzzle::new("A", "cyan", 4, 5, 4, "JLSZZ", "AABB\nABBC\nADCC\nDDCE\nDEEE\n"),
+    Puzzle::new("A", "cyan", 5, 4, 4, "ITTL", "ABBB\nACBD\nACDD\nACCD\n"),
+    Puzzle::new("A", "cyan", 6, 4, 5, "TTLSZ", "AAABB\nCABBD\nCEEDD\nCCEED\n"),
+    Puzzle::new("A", "cyan", 7, 5, 4, "TTJSZ", "AAAB\nCABB\nCCBD\nCEED\nEEDD\n"),
+    Puzzle::new("A", "cyan", 8, 6, 4, "IOTTJZ", "ABBB\nACCB\nACCD\nAEDD\nEEFD\nEFFF\n"),
+
+    Puzzle::new("A", "green", 1, 6, 4, "IOTTLZ", "ABCC\nABCC\nABBD\nAEDD\nEEFD\nEFFF\n"),
+    Puzzle::new("A", "green", 2, 4, 7, "ITTJJLZ", "ABBBCDD\nAEBCCDF\nAEGGCDF\nAEEGGFF\n"),
+    Puzzle::new("A", "green", 3, 6, 4, "IOSZJL", "AABB\nABBC\nADDC\nEDDC\nEFFC\nEEFF\n"),
+    Puzzle::new("A", "green", 4, 6, 6, "TIOTTOLTJ", "ABBCCC\nABBDEC\nADDDEE\nAFFFEG\nHHFIGG\nHHIIIG\n"),
+    Puzzle::new("A", "green", 5, 6, 6, "OTTTTLLLL", "AABBBC\nAADBCC\nEEDDFC\nGEDFFF\nGEHHHI\nGGHIII\n"),
+    Puzzle::new("A", "green", 6, 8, 5, "IIIIJJLLSZ", "ABCDD\nABCDE\nABCDE\nABCEE\nFFFFG\nHHHGG\nHIIGJ\nIIJJJ\n"),
+    Puzzle::new("A", "green", 7, 8, 5, "IITTTTJLSZ", "ABCCC\nABDCE\nABDDE\nABDEE\nFFFGG\nHFGGI\nHJJII\nHHJJI\n"),
+    Puzzle::new("A", "green", 8, 8, 6, "OOTTTTSSZZJL", "AABBCC\nAABBDC\nEEFDDC\nEFFDGG\nEFHGGI\nJHHHII\nJJKKLI\nJKKLLL\n"),
+
+    Puzzle::new("A", "yellow", 1, 6, 6, "IOOJLSSZZ", "ABBBCC\nADDBCC\nADDEEF\nAGEEFF\nGGHHFI\nGHHIII\n"),
+    Puzzle::new("A", "yellow", 2, 8, 6, "TTILLJJJOOZZ", "ABBCCD\nABBCCD\nAEEEDD\nAFEGGG\nFFHHHG\nIFHJJJ\nIKKLLJ\nIIKKLL\n"),
+    Puzzle::new("A", "yellow", 3, 4, 7, "LJZZTTI", "ABBBCCC\nADBEECF\nADGGEEF\nADDGGFF\n"),
+    Puzzle::new("A", "yellow", 4, 5, 4, "LLJTT", "AAAB\nCABB\nCCCB\nDDDE\nDEEE\n"),
+    Puzzle::new("A", "yellow", 5, 5, 4, "LZSTT", "AAAB\nACBB\nCCDB\nCEDD\nEEED\n"),
+    Puzzle::new("A", "yellow", 6, 6, 6, "IOOZZLLJJ", "ABBCCD\nABBCDD\nAEECDF\nAEEGFF\nHGGGFI\nHHHIII\n"),
+    Puzzle::new("A", "yellow", 7, 10, 4, "STTTTOOILL", "ABCC\nABBC\nABDC\nAEDD\nEEFD\nGEFF\nGGFH\nGHHH\nIIJJ\nIIJJ\n"),
+    Puzzle::new("A", "yellow", 8, 8, 5, "ZZSTTIILLO", "ABCDD\nABCDD\nABCCE\nABFEE\nGGFFE\nHGGFI\nHJJII\nHHJJI\n"),
+
+    Puzzle::new("A", "red", 1, 6, 6, "OOTTLLJIS", "ABBCCC\nABBDCE\nADDDEE\nAFFGHE\nIFFGHH\nIIIGGH\n"),
+    Puzzle::new("A", "red", 2, 6, 6, "ZZZLLJJTT", "AAABCC\nDABBBC\nDDDEEC\nFFGGEE\nHFFGGI\nHHHIII\n"),
+    Puzzle::new("A", "red", 3, 5, 4, "IOOJJ", "ABBC\nABBC\nADCC\nADEE\nDDEE\n"),
+    Puzzle::new("A", "red", 4, 6, 8, "TTOOILLJJJSS", "ABBCCDEE\nABBCCDDE\nAFFGGDHE\nAFGGHHHI\nJFKKKLLI\nJJJKLLII\n"),
+    Puzzle::new("A", "red", 5, 5, 4, "TTZLI", "AAAA\nBBBC\nDBCC\nDEEC\nDDEE\n"),
+    Puzzle::new("A", "red", 6, 10, 4, "TTTTZZSIIJ", "ABBB\nACBD\nACDD\nACED\nFCEE\nFFEG\nFHGG\nHHGI\nHJJI\nJJII\n"),
+    Puzzle::new("A", "red", 7, 4, 7, "IITTZSL", "ABCCCDD\nABECDDF\nABEGGFF\nABEEGGF\n"),
+    Puzzle::new("A", "red", 8, 6, 8, "ITTOOSZZZJJJ", "ABBCCDDD\nABBCCEDF\nAGGHEEFF\nAGHHIEFJ\nKGHIILLJ\nKKKILLJJ\n"),
+
+    Puzzle::new("B", "cyan", 1, 4, 4, "OOLL", "AABB\nAABB\nCCCD\nCDDD\n"),
+    Puzzle::new("B", "cyan", 2, 4, 5, "LLZZI", "ABBBC\nABDCC\nADDCE\nADEEE\n"),
+    Puzzle::new("B", "cyan", 3, 4, 5, "JJLLI", "ABCCC\nABBBC\nADDDE\nADEEE\n"),
+    Puzzle::new("B", "cyan", 4, 4, 6, "JLSTTI", "ABBBCC\nADBCCE\nADFFFE\nADDFEE\n"),
+    Puzzle::new("B", "cyan", 5, 4, 4, "IOLJ", "ABBB\nACCB\nACCD\nADDD\n"),
+    Puzzle::new("B", "cyan", 6, 5, 4, "TTZZL", "ABBB\nAABC\nADCC\nDDCE\nDEEE\n"),
+    Puzzle::new("B", "cyan", 7, 6, 4, "JLSOII", "ABBB\nACCB\nACCD\nAEED\nEEFD\nFFFD\n"),
+    Puzzle::new("B", "cyan", 8, 4, 4, "ILJZ", "ABBC\nABCC\nABCD\nADDD\n"),
+
+    Puzzle::new("B", "green", 1, 4, 5, "LLLJZ", "AABCC\nABBDC\nABEDC\nEEEDD\n"),
+    Puzzle::new("B", "green", 2, 4, 6, "TTSSZL", "AAABBC\nDABBCC\nDDEECF\nDEEFFF\n"),
+    Puzzle::new("B", "green", 3, 6, 6, "IOLLLLJTT", "ABBCCC\nABBDCE\nADDDEE\nAFFFGE\nHFGGGI\nHHHIII\n"),
+    Puzzle::new("B", "green", 4, 5, 8, "OOOOOOOLLI", "AAAABBCC\nDDEEBBCC\nDDEEFFGG\nHHIIFFJG\nHHIIJJJG\n"),
+    Puzzle::new("B", "green", 5, 6, 6, "LLJZTTOOI", "ABBCCC\nABBDCE\nAFFDDE\nAGFDEE\nGGFHII\nGHHHII\n"),
+    Puzzle::new("B", "green", 6, 5, 8, "OOLLLJIIIS", "ABCCCCDD\nABEEEFDD\nABEGHFFF\nABIGHHJJ\nIIIGGHJJ\n"),
+    Puzzle::new("B", "green", 7, 6, 6, "JJJJZZOOO", "AABBCC\nAABBCC\nDDDEEE\nFFDGGE\nHFFIGG\nHHHIII\n"),
+    Puzzle::new("B", "green", 8, 5, 8, "TTTTOOSZJI", "ABBCCDDE\nABBCCDEE\nAFFFGDHE\nAIFGGJHH\nIIIGJJJH\n"),
+
+    Puzzle::new("B", "yellow", 1, 4, 10, "OJTTSSZZII", "ABBBBCCDDE\nAFFFCCDDEE\nAGGFHIIJJE\nAGGHHHIIJJ\n"),
+    Puzzle::new("B", "yellow", 2, 4, 5, "JTTSZ", "AABBC\nABBCC\nADEEC\nDDDEE\n"),
+    Puzzle::new("B", "yellow", 3, 7, 4, "ITTTTZO", "AAAB\nCABB\nCDDB\nCDDE\nCFEE\nFFGE\nFGGG\n"),
+    Puzzle::new("B", "yellow", 4, 4, 10, "TTZSSLLLIO", "ABBBCCDEFF\nAGBCCDDEFF\nAGGHHDIEEJ\nAGHHIIIJJJ\n"),
+    Puzzle::new("B", "yellow", 5, 8, 6, "LJJJJIOOTTSS", "ABBCCD\nABBCCD\nAEEEDD\nAFEGGG\nFFHHHG\nIFJJHK\nIJJLLK\nIILLKK\n"),
+    Puzzle::new("B", "yellow", 6, 4, 10, "OJZZSSTTII", "ABBBBCCDDE\nAFFFCCDDEE\nAGGFHIIJJE\nAGGHHHIIJJ\n"),
+    Puzzle::new("B", "yellow", 7, 7, 4, "TOTIZTT", "AAAB\nCABB\nCDDB\nCDDE\nCFEE\nFFGE\nFGGG\n"),
+    Puzzle::new("B", "yellow", 8, 6, 8, "TTTTOOZJJLLI", "ABBCCDDD\nABBCCEDF\nAGGHEEFF\nAGHHHEIF\nJGKKIIIL\nJJJKKLLL\n"),
+
+    Puzzle::new("B", "red", 1, 4, 7, "TTTTSSI", "ABBBCCD\nAEBCCDD\nAEEFFGD\nAEFFGGG\n"),
+    Puzzle::new("B", "red", 2, 10, 4, "ZZLLLIITTS", "ABCC\nABDC\nABDC\nABDD\nEEEF\nGEFF\nGGFH\nIGHH\nIJJH\nIIJJ\n"),
+    Puzzle::new("B", "red", 3, 10, 4, "LLLSSZZZTT", "AAAB\nCABB\nCDDB\nCCDD\nEEFF\nGEEF\nGGHF\nIGHH\nIJJH\nIIJJ\n"),
+    Puzzle::new("B", "red", 4, 5, 4, "ITTLZ", "AAAA\nBBBC\nDBCC\nDEEC\nDDEE\n"),
+    Puzzle::new("B", "red", 5, 5, 8, "TTTTOOSZJI", "ABBCCDDE\nABBCCDEE\nAFFFGDHE\nAIFGGJHH\nIIIGJJJH\n"),
+    Puzzle::new("B", "red", 6, 5, 8, "TTTTLJSSZZ", "AAABCDDD\nEABBCCDF\nEEGBCHFF\nIEGGHHFJ\nIIIGHJJJ\n"),
+    Puzzle::new("B", "red", 7, 4, 10, "OJZZSSTTII", "ABBBBCCDDE\nAFFFCCDDEE\nAGGFHIIJJE\nAGGHHHIIJJ\n"),
+    Puzzle::new("B", "red", 8, 6, 8, "TTTTOOZJJLLI", "ABBCCDDD\nABBCCEDF\nAGGHEEFF\nAGHHHEIF\nJGKKIIIL\nJJJKKLLL\n"),
+
+    Puzzle::new("C", "cyan", 1, 4, 4, "SSJJ", "AABB\nABBC\nADDC\nDDCC\n"),
+    Puzzle::new("C", "cyan", 2, 4, 4, "TTLZ", "AAAB\nCABB\nCDDB\nCCDD\n"),
+    Puzzle::new("C", "cyan", 3, 5, 4, "JJLLO", "AABB\nAACB\nDDCB\nDCCE\nDEEE\n"),
+    Puzzle::new("C", "cyan", 4, 8, 5, "TTZSSIIOLJ", "ABCDD\nABCDD\nABCCE\nABFEE\nGFFHE\nGGFHH\nIGJJH\nIIIJJ\n"),
+    Puzzle::new("C", "cyan", 5, 7, 4, "IIITTJO", "ABCC\nABCC\nABDE\nABDE\nFDDE\nFFGE\nFGGG\n"),
+    Puzzle::new("C", "cyan", 6, 6, 6, "LLJJOOOOI", "ABBCCD\nABBCCD\nAEEFDD\nAEEFGG\nHHFFIG\nHHIIIG\n"),
+    Puzzle::new("C", "cyan", 7, 6, 6, "LLLLLLLLI", "ABCCCD\nABCDDD\nABBEFF\nAEEEGF\nHHHIGF\nHIIIGG\n"),
+    Puzzle::new("C", "cyan", 8, 8, 5, "TSSTTISZTS", "ABBBC\nADBCC\nADDEC\nAFDEE\nGFFHE\nGGFHH\nGIJJH\nIIIJJ\n"),
+
+    Puzzle::new("C", "green", 1, 6, 4, "SSSSJJ", "ABBB\nAACB\nDACC\nDDEC\nFDEE\nFFFE\n"),
+    Puzzle::new("C", "green", 2, 5, 4, "IOLSL", "AAAB\nACCB\nCCDB\nEEDB\nEEDD\n"),
+    Puzzle::new("C", "green", 3, 4, 5, "ZTTSJ", "AABBC\nABBCC\nADEEC\nDDDEE\n"),
+    Puzzle::new("C", "green", 4, 4, 6, "LLLJIO", "ABBBCC\nADDBEC\nADDFEC\nAFFFEE\n"),
+    Puzzle::new("C", "green", 5, 6, 6, "TILITOSIO", "ABCCDD\nABCCDD\nABEFFF\nABEEFG\nHHHEGG\nHIIIIG\n"),
+    Puzzle::new("C", "green", 6, 5, 8, "JILOIJLLTT", "ABCCDEEE\nABCCDDEF\nABGGDFFF\nABHGIJJJ\nHHHGIIIJ\n"),
+    Puzzle::new("C", "green", 7, 6, 6, "OJJOISZJJ", "ABBCCC\nABBDDC\nAEEFDD\nAEEFFG\nHIIIFG\nHHHIGG\n"),
+    Puzzle::new("C", "green", 8, 6, 6, "TSTSOSTST", "AAABBC\nDABBCC\nDDEEFC\nGDEEFF\nGGHHIF\nGHHIII\n"),
+
+    Puzzle::new("C", "yellow", 1, 10, 4, "SJTIIZTJJO", "ABBC\nABBC\nADDC\nADEC\nFDEE\nFFGE\nFGGG\nHHIJ\nHIIJ\nHIJJ\n"),
+    Puzzle::new("C", "yellow", 2, 8, 5, "OLSLLLITJT", "ABBCC\nABBDC\nAEFDC\nAEFDD\nEEFFG\nHHHGG\nHIIJG\nIIJJJ\n"),
+    Puzzle::new("C", "yellow", 3, 6, 6, "JTLZSOSJT", "AABBBC\nAADBCC\nEEDDFC\nEGHDFF\nEGHIIF\nGGHHII\n"),
+    Puzzle::new("C", "yellow", 4, 5, 4, "ZLIOL", "AAAB\nACCB\nDCCB\nDEEB\nDDEE\n"),
+    Puzzle::new("C", "yellow", 5, 6, 4, "ZLSLJJ", "AABB\nACCB\nACDB\nECDD\nEFFD\nEEFF\n"),
+    Puzzle::new("C", "yellow", 6, 6, 6, "TTJZJLTTL", "AAABBB\nCADDBE\nCCCDEE\nFFFDEG\nFHIIIG\nHHHIGG\n"),
+    Puzzle::new("C", "yellow", 7, 8, 5, "TSSOITSOZJ", "ABBCC\nABBCC\nADDDE\nAFDEE\nGFFHE\nGGFHH\nIGJJH\nIIIJJ\n"),
+    Puzzle::new("C", "yellow", 8, 7, 4, "LOITTZI", "ABBC\nABBC\nADDC\nAEDC\nEEDF\nEGFF\nGGGF\n"),
+
+    Puzzle::new("C", "red", 1, 4, 5, "LTZST", "AAABB\nCABBD\nCEEDD\nCCEED\n"),
+    Puzzle::new("C", "red", 2, 6, 6, "LLIIIIIII", "ABCCCC\nABDDDD\nABEEEF\nABEGGF\nHHHHGF\nIIIIGF\n"),
+    Puzzle::new("C", "red", 3, 5, 4, "JJJZJ", "ABBB\nAAAB\nCCDE\nCDDE\nCDEE\n"),
+    Puzzle::new("C", "red", 4, 6, 4, "TZSLTI", "ABCC\nABBC\nABDC\nAEDD\nEEFD\nEFFF\n"),
+    Puzzle::new("C", "red", 5, 6, 6, "LIOLLSZJO", "ABBCCC\nABBDEC\nAFFDEE\nAGFDDE\nGGFHII\nGHHHII\n"),
+    Puzzle::new("C", "red", 6, 8, 5, "ZTTLOIIJLI", "ABCDD\nABCDD\nABCEE\nABCFE\nGGFFE\nHGGFI\nHJJJI\nHHJII\n"),
+    Puzzle::new("C", "red", 7, 6, 6, "OSSSSLLLL", "AAABCC\nADDBBC\nDDEEBC\nFGEEHH\nFGGHHI\nFFGIII\n"),
+    Puzzle::new("C", "red", 8, 5, 8, "LJSZTTIIOO", "ABCCCDEE\nABFCDDEE\nABFFDGGH\nABIFJGGH\nIIIJJJHH\n"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::duplicates;
+
+    #[test]
+    fn duplicate_groups() {
+        let groups: Vec<String> = duplicates()
+            .iter()
+            .map(|g| {
+                let names: Vec<_> = g.iter().map(ToString::to_string).collect();
+                names.join(" = ")
+            })
+            .collect();
+
+        assert_eq!(
+            groups,
+            [
+                "A cyan 1 = C cyan 1",
+                "A cyan 2 = B cyan 8",
+                "A cyan 3 = A red 5 = B red 4",
+                "A cyan 6 = A cyan 7 = A yellow 5 = B yellow 2 = C green 3 = C red 1",
+                "B green 8 = B red 5",
+                "B yellow 1 = B yellow 6 = B red 7",
+                "B yellow 3 = B yellow 7",
+                "B yellow 8 = B red 8",
+            ]
+        );
+    }
+}
@@ -0,0 +1,372 @@
+// Checking every solution of an archive of puzzles, e.g. the community
+// archive, which is a puzzle file or a JSON export. Unlike `load`, which stops
+// at the first invalid puzzle, the check goes on and reports every puzzle that
+// can't be read and every solution that isn't a tiling of its puzzle, so
+// typos can be found and fixed all at once.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use super::file::{is_json, puzzle_record, toml_tables, Table, Value};
+use super::json::json_tables;
+use super::{LoadPuzzlesError, PuzzleRecord};
+use crate::replay::parse_placement;
+use crate::{board_fits, Board, Piece, PieceCollection, PlaceAtError, VerifyError, PIECE_MAP};
+
+// Why a solution of the archive isn't a tiling of its puzzle
+#[derive(Debug)]
+pub enum SolutionError {
+    // A placement isn't of the form "T3@r1c2"
+    InvalidPlacement {
+        placement: String,
+    },
+    // A placement doesn't fit on the board with the pieces before it
+    IllegalPlacement {
+        placement: String,
+        error: PlaceAtError,
+    },
+    // The placements leave a square uncovered or aren't the pieces of the
+    // puzzle
+    NotATiling(VerifyError),
+}
+
+impl Display for SolutionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use SolutionError::*;
+        match self {
+            InvalidPlacement { placement } => {
+                write!(f, "{placement:?} isn't a placement like \"T3@r1c2\".")
+            }
+            IllegalPlacement { placement, error } => {
+                write!(f, "The placement {placement} is illegal. {error}")
+            }
+            NotATiling(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for SolutionError {}
+
+#[derive(Debug)]
+pub enum ArchiveFailure {
+    // The puzzle can't be read, so its solutions aren't checked
+    Puzzle(LoadPuzzlesError),
+    // Puzzles and solutions are numbered from 1, in the order of the file
+    Solution {
+        puzzle: usize,
+        name: String,
+        solution: usize,
+        error: SolutionError,
+    },
+}
+
+impl Display for ArchiveFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ArchiveFailure::Puzzle(err) => write!(f, "{err}"),
+            ArchiveFailure::Solution {
+                puzzle,
+                name,
+                solution,
+                error,
+            } => write!(f, "Puzzle {puzzle} ({name}), solution {solution}: {error}"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ArchiveReport {
+    pub puzzles: usize,
+    // The solutions checked, which leaves out those of unreadable puzzles
+    pub solutions: usize,
+    pub failures: Vec<ArchiveFailure>,
+}
+
+impl ArchiveReport {
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+// A line per failure, then the totals
+impl Display for ArchiveReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for failure in &self.failures {
+            writeln!(f, "{failure}")?;
+        }
+        writeln!(
+            f,
+            "Checked {} puzzles and {} solutions: {} failure{}.",
+            self.puzzles,
+            self.solutions,
+            self.failures.len(),
+            if self.failures.len() == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Check the archive at `path`, read like `load` does. Only errors that stop
+// the file from being read at all, e.g. a syntax error, are returned as
+// errors.
+pub fn verify_archive<P: AsRef<Path>>(path: P) -> Result<ArchiveReport, LoadPuzzlesError> {
+    let s = fs::read_to_string(&path)?;
+    if is_json(path.as_ref()) {
+        Ok(verify_tables(json_tables(&s)?))
+    } else {
+        verify_toml(&s)
+    }
+}
+
+// Check the puzzles of a puzzle file
+pub fn verify_toml(s: &str) -> Result<ArchiveReport, LoadPuzzlesError> {
+    Ok(verify_tables(toml_tables(s)?.into_iter().map(Ok).collect()))
+}
+
+fn verify_tables(tables: Vec<Result<Table, LoadPuzzlesError>>) -> ArchiveReport {
+    let mut report = ArchiveReport::default();
+    for (index, table) in tables.into_iter().enumerate() {
+        let puzzle = index + 1;
+        report.puzzles += 1;
+        // The solutions are checked here rather than by `puzzle_record`
+        let (replays, table): (Table, Table) = match table {
+            Ok(table) => table
+                .into_iter()
+                .partition(|(key, value)| key == "solutions" && matches!(value, Value::Array(_))),
+            Err(err) => {
+                report.failures.push(ArchiveFailure::Puzzle(err));
+                continue;
+            }
+        };
+        let record = match puzzle_record(puzzle, table) {
+            Ok(record) => record,
+            Err(err) => {
+                report.failures.push(ArchiveFailure::Puzzle(err));
+                continue;
+            }
+        };
+        let replays: Vec<_> = replays
+            .into_iter()
+            .flat_map(|(_, value)| match value {
+                Value::Array(replays) => replays,
+                _ => vec![],
+            })
+            .collect();
+        // Like `load`, solutions can't be checked on a board that only fits
+        // the search turned over
+        if !replays.is_empty() && !board_fits(record.board.row_count(), record.board.column_count())
+        {
+            report
+                .failures
+                .push(ArchiveFailure::Puzzle(LoadPuzzlesError::InvalidValue {
+                    puzzle,
+                    key: "rows",
+                }));
+            continue;
+        }
+        for (index, replay) in replays.iter().enumerate() {
+            report.solutions += 1;
+            if let Err(error) = verify_replay(&record, replay) {
+                report.failures.push(ArchiveFailure::Solution {
+                    puzzle,
+                    name: record.to_string(),
+                    solution: index + 1,
+                    error,
+                });
+            }
+        }
+    }
+    report
+}
+
+// Check that the placements of `replay` tile the board of `record` with its
+// pieces
+fn verify_replay(record: &PuzzleRecord, replay: &str) -> Result<(), SolutionError> {
+    let mut board = Board::new(record.board.row_count(), record.board.column_count());
+    for (row, column) in record.board.blocked_squares() {
+        board.block(row, column).unwrap();
+    }
+    let mut found = PieceCollection {
+        counts: [0; Piece::count()],
+    };
+    for placement in replay.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let invalid = || SolutionError::InvalidPlacement {
+            placement: placement.to_string(),
+        };
+        let parsed = parse_placement(placement).ok_or_else(invalid)?;
+        board
+            .place_at(parsed.piece, parsed.row, parsed.column)
+            .map_err(|error| SolutionError::IllegalPlacement {
+                placement: placement.to_string(),
+                error,
+            })?;
+        found.add(PIECE_MAP[parsed.piece as usize]);
+    }
+
+    let position = board.position();
+    let width = record.board.column_count() as usize + 1;
+    if let Some(index) = position.squares.iter().position(|&square| square == b'.') {
+        return Err(SolutionError::NotATiling(VerifyError::NotCovered {
+            row: (index / width) as u32,
+            column: (index % width) as u32,
+        }));
+    }
+    if found != record.pieces {
+        return Err(SolutionError::NotATiling(VerifyError::WrongPieces {
+            expected: record.pieces.to_canonical_string(),
+            found: found.to_canonical_string(),
+        }));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_toml, ArchiveFailure, SolutionError};
+    use crate::puzzles::{self, to_toml, LoadPuzzlesError, PuzzleRecord};
+    use crate::{PlaceAtError, VerifyError};
+
+    #[test]
+    fn valid() {
+        let records: Vec<_> = puzzles::all().iter().map(PuzzleRecord::from).collect();
+        let report = verify_toml(&to_toml(&records)).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.puzzles, records.len());
+        assert_eq!(report.solutions, records.len());
+
+        let report = verify_toml(
+            "[[puzzle]]\nboard = \"4./2.2#/2.2#\"\npieces = \"OI\"\n\
+             solutions = [\"I2@r0c0; O1@r1c0\"]\n",
+        )
+        .unwrap();
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn failures() {
+        let toml = "[[puzzle]]\n\
+                    name = \"Typos\"\n\
+                    rows = 2\n\
+                    columns = 4\n\
+                    pieces = \"II\"\n\
+                    solutions = [\n\
+                        \"I2@r0c0; I2@r1c\",\n\
+                        \"I2@r0c0; I2@r0c0\",\n\
+                        \"I2@r0c0\",\n\
+                        \"O1@r0c0; O1@r0c2\",\n\
+                        \"I2@r0c0; I2@r1c0\",\n\
+                    ]\n\
+                    \n\
+                    [[puzzle]]\n\
+                    rows = 2\n\
+                    pieces = \"II\"\n\
+                    solutions = [\"I2@r0c0; I2@r1c0\"]\n\
+                    \n\
+                    [[puzzle]]\n\
+                    rows = 1\n\
+                    columns = 4\n\
+                    pieces = \"I\"\n\
+                    solutions = [\"I2@r0c0\"]\n";
+        let report = verify_toml(toml).unwrap();
+        assert_eq!((report.puzzles, report.solutions), (3, 6));
+        assert_eq!(report.failures.len(), 5);
+
+        let errors: Vec<_> = report
+            .failures
+            .iter()
+            .filter_map(|failure| match failure {
+                ArchiveFailure::Solution {
+                    puzzle: 1,
+                    solution,
+                    error,
+                    ..
+                } => Some((solution, error)),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(
+            errors[0],
+            (1, SolutionError::InvalidPlacement { placement }) if placement == "I2@r1c"
+        ));
+        assert!(matches!(
+            errors[1],
+            (
+                2,
+                SolutionError::IllegalPlacement {
+                    error: PlaceAtError::Overlap,
+                    ..
+                }
+            )
+        ));
+        assert!(matches!(
+            errors[2],
+            (
+                3,
+                SolutionError::NotATiling(VerifyError::NotCovered { row: 1, column: 0 })
+            )
+        ));
+        assert!(matches!(
+            errors[3],
+            (
+                4,
+                SolutionError::NotATiling(VerifyError::WrongPieces { .. })
+            )
+        ));
+        assert!(matches!(
+            report.failures[4],
+            ArchiveFailure::Puzzle(LoadPuzzlesError::MissingKey {
+                puzzle: 2,
+                key: "columns"
+            })
+        ));
+
+        assert_eq!(
+            report.to_string(),
+            "Puzzle 1 (Typos), solution 1: \"I2@r1c\" isn't a placement like \"T3@r1c2\".\n\
+             Puzzle 1 (Typos), solution 2: The placement I2@r0c0 is illegal. The piece \
+             overlaps another piece.\n\
+             Puzzle 1 (Typos), solution 3: The square on row 1, column 0 isn't covered.\n\
+             Puzzle 1 (Typos), solution 4: The solution has the pieces OO but the puzzle has \
+             II.\n\
+             Puzzle 2 has no columns.\n\
+             Checked 3 puzzles and 6 solutions: 5 failures.\n"
+        );
+    }
+
+    #[test]
+    fn board_too_large() {
+        let toml = "[[puzzle]]\n\
+                    rows = 30\n\
+                    columns = 30\n\
+                    pieces = \"I\"\n\
+                    solutions = [\"I1@r0c0\"]\n\
+                    \n\
+                    [[puzzle]]\n\
+                    rows = 1\n\
+                    columns = 28\n\
+                    pieces = \"IIIIIII\"\n\
+                    solutions = [\"I1@r0c0\"]\n";
+        let report = verify_toml(toml).unwrap();
+        assert_eq!((report.puzzles, report.solutions), (2, 0));
+        assert!(matches!(
+            report.failures[..],
+            [
+                ArchiveFailure::Puzzle(LoadPuzzlesError::BoardTooLarge { puzzle: 1, .. }),
+                ArchiveFailure::Puzzle(LoadPuzzlesError::InvalidValue {
+                    puzzle: 2,
+                    key: "rows"
+                })
+            ]
+        ));
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert!(matches!(
+            verify_toml("[[puzzle]]\nrows = \"4"),
+            Err(LoadPuzzlesError::Syntax { line: 2 })
+        ));
+    }
+}
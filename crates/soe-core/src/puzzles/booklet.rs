@@ -0,0 +1,93 @@
+// A printable booklet of the solutions of a group of levels, e.g. a section
+// of the game.
+
+use std::fmt::Write;
+
+use super::Puzzle;
+use crate::solve_first_canonical;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BookletFormat {
+    Markdown,
+    // A complete HTML document
+    Html,
+}
+
+// Solve `puzzles` and write a document titled `title` with a heading, the
+// board size and the pieces and the solution of each level
+#[must_use]
+pub fn booklet(title: &str, puzzles: &[&Puzzle], format: BookletFormat) -> String {
+    let mut output = String::new();
+    match format {
+        BookletFormat::Markdown => writeln!(output, "# {title}").unwrap(),
+        BookletFormat::Html => write!(
+            output,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        )
+        .unwrap(),
+    }
+
+    for puzzle in puzzles {
+        let solution =
+            solve_first_canonical(puzzle.row_count, puzzle.column_count, puzzle.pieces()).unwrap();
+        let details = format!(
+            "{}×{}, pieces {}",
+            puzzle.row_count, puzzle.column_count, puzzle.tetrominoes
+        );
+        match format {
+            BookletFormat::Markdown => {
+                write!(output, "\n## {puzzle}\n\n{details}\n\n").unwrap();
+                match solution {
+                    Some(solution) => write!(output, "```\n{solution:#}```\n").unwrap(),
+                    None => writeln!(output, "No solution").unwrap(),
+                }
+            }
+            BookletFormat::Html => {
+                write!(output, "<h2>{puzzle}</h2>\n<p>{details}</p>\n").unwrap();
+                match solution {
+                    Some(solution) => output.push_str(&solution.to_html()),
+                    None => output.push_str("<p>No solution</p>\n"),
+                }
+            }
+        }
+    }
+
+    if format == BookletFormat::Html {
+        output.push_str("</body>\n</html>\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{booklet, BookletFormat};
+    use crate::puzzles;
+
+    #[test]
+    fn markdown() {
+        let section = puzzles::section("A cyan");
+        let output = booklet("A cyan", &section[..2], BookletFormat::Markdown);
+        assert!(output.starts_with(
+            "# A cyan\n\
+             \n\
+             ## A cyan 1\n\
+             \n\
+             4×4, pieces LLZZ\n\
+             \n\
+             ```\n┌"
+        ));
+        assert!(output.contains("\n## A cyan 2\n"));
+        assert!(output.ends_with("┘\n```\n"));
+    }
+
+    #[test]
+    fn html() {
+        let section = puzzles::section("A cyan");
+        let output = booklet("A cyan", &section[..1], BookletFormat::Html);
+        assert!(
+            output.contains("<h1>A cyan</h1>\n<h2>A cyan 1</h2>\n<p>4×4, pieces LLZZ</p>\n<table")
+        );
+        assert!(output.ends_with("</table>\n</body>\n</html>\n"));
+    }
+}
@@ -0,0 +1,211 @@
+// Import of the community spreadsheet of levels exported as CSV. Each row has
+// the section, the color, the level number, the board size and the pieces:
+//
+//     Section,Color,Number,Size,Pieces
+//     A,cyan,1,4x4,LLZZ
+//
+// The header row is optional. Fields may be quoted, and the size may be
+// written as "4x4", "4 x 4" or "4×4". Each puzzle is solved while it's
+// imported and its first solution in canonical order becomes its known
+// solution, so a row with a typo is reported instead of being imported.
+//
+// Puzzles can be exported in the same form, with a header row. The form has
+// no room for blocked squares or known solutions, so they aren't exported.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter, Write as _};
+
+use super::PuzzleRecord;
+use crate::{BoardShape, PieceCollection, SolveError};
+
+#[derive(Debug)]
+pub enum ImportCsvError {
+    // The row doesn't have five fields or has an unterminated quote
+    Syntax { line: usize },
+    InvalidNumber { line: usize },
+    InvalidSize { line: usize },
+    InvalidPieces { line: usize },
+    // The solver rejected the puzzle, e.g. because of the piece count
+    InvalidPuzzle { line: usize, err: SolveError },
+    NoSolution { line: usize },
+}
+
+impl Display for ImportCsvError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ImportCsvError::*;
+        match self {
+            Syntax { line } => write!(f, "Line {line} is not a row of five fields."),
+            InvalidNumber { line } => write!(f, "Line {line} has an invalid level number."),
+            InvalidSize { line } => write!(f, "Line {line} has an invalid board size."),
+            InvalidPieces { line } => write!(f, "Line {line} has invalid pieces."),
+            InvalidPuzzle { line, err } => write!(f, "Line {line} is not a valid puzzle. {err}"),
+            NoSolution { line } => write!(f, "The puzzle on line {line} has no solution."),
+        }
+    }
+}
+
+impl Error for ImportCsvError {}
+
+// Import the puzzles in the CSV `s`, in order
+pub fn import_csv(s: &str) -> Result<Vec<PuzzleRecord>, ImportCsvError> {
+    let mut records = vec![];
+    for (index, row) in s.lines().enumerate() {
+        let line = index + 1;
+        if row.trim().is_empty() {
+            continue;
+        }
+        let fields = split_row(row).ok_or(ImportCsvError::Syntax { line })?;
+        let [section, color, number, size, pieces] = &fields[..] else {
+            return Err(ImportCsvError::Syntax { line });
+        };
+        if index == 0 && section.eq_ignore_ascii_case("section") {
+            continue;
+        }
+        let number = number
+            .parse()
+            .map_err(|_| ImportCsvError::InvalidNumber { line })?;
+        let (row_count, column_count) =
+            parse_size(size).ok_or(ImportCsvError::InvalidSize { line })?;
+        let pieces: PieceCollection = pieces
+            .parse()
+            .map_err(|_| ImportCsvError::InvalidPieces { line })?;
+        let mut record = PuzzleRecord {
+            name: None,
+            section: Some(section.clone()),
+            color: Some(color.to_lowercase()),
+            number: Some(number),
+            source: None,
+            board: BoardShape::rectangle(row_count, column_count),
+            pieces,
+            solutions: vec![],
+        };
+        let solution = record
+            .solve_first_canonical()
+            .map_err(|err| ImportCsvError::InvalidPuzzle { line, err })?
+            .ok_or(ImportCsvError::NoSolution { line })?;
+        record.solutions.push(solution);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// Export the levels of `puzzles` with a header row. Missing metadata is
+// written as empty fields.
+#[must_use]
+pub fn to_csv(puzzles: &[PuzzleRecord]) -> String {
+    let mut csv = String::from("Section,Color,Number,Size,Pieces\n");
+    for puzzle in puzzles {
+        let number = puzzle.number.map(|n| n.to_string()).unwrap_or_default();
+        writeln!(
+            csv,
+            "{},{},{},{}x{},{}",
+            quote(puzzle.section.as_deref().unwrap_or_default()),
+            quote(puzzle.color.as_deref().unwrap_or_default()),
+            number,
+            puzzle.board.row_count(),
+            puzzle.board.column_count(),
+            puzzle.pieces.to_canonical_string(),
+        )
+        .unwrap();
+    }
+    csv
+}
+
+// Quote a field if it contains a comma or a quote
+fn quote(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// The trimmed fields of a CSV row. Returns `None` if a quote isn't closed.
+fn split_row(row: &str) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = row.chars().peekable();
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push(field.trim().to_string());
+    Some(fields)
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (rows, columns) = size.split_once(['x', 'X', '×'])?;
+    let rows = rows.trim().parse().ok().filter(|&rows| rows > 0)?;
+    let columns = columns.trim().parse().ok().filter(|&columns| columns > 0)?;
+    Some((rows, columns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_csv, to_csv, ImportCsvError};
+    use crate::puzzles::{self, PuzzleRecord};
+
+    #[test]
+    fn import() {
+        let csv = "Section,Color,Number,Size,Pieces\n\
+                   A,Cyan,1,4x4,LLZZ\n\
+                   \n\
+                   \"A\",\"cyan\",2, 4 × 4 ,\"IJLZ\"\n";
+        let records = import_csv(csv).unwrap();
+        let expected: Vec<_> = puzzles::all()[..2].iter().map(PuzzleRecord::from).collect();
+        assert!(records == expected);
+    }
+
+    #[test]
+    fn export() {
+        let records: Vec<_> = puzzles::all().iter().map(PuzzleRecord::from).collect();
+        let csv = to_csv(&records);
+        assert!(csv.starts_with("Section,Color,Number,Size,Pieces\nA,cyan,1,4x4,LLZZ\n"));
+        assert!(import_csv(&csv).unwrap() == records);
+
+        let mut record = records[0].clone();
+        record.section = Some("A, \"bonus\"".to_string());
+        record.number = None;
+        assert!(to_csv(&[record]).ends_with("\n\"A, \"\"bonus\"\"\",cyan,,4x4,LLZZ\n"));
+    }
+
+    #[test]
+    fn errors() {
+        use ImportCsvError::*;
+        let error = |csv: &str| import_csv(csv).err().unwrap();
+        assert!(matches!(error("A,cyan,1,4x4"), Syntax { line: 1 }));
+        assert!(matches!(error("A,cyan,1,4x4,\"LLZZ"), Syntax { line: 1 }));
+        assert!(matches!(
+            error("A,cyan,one,4x4,LLZZ"),
+            InvalidNumber { line: 1 }
+        ));
+        assert!(matches!(
+            error("\nA,cyan,1,4by4,LLZZ"),
+            InvalidSize { line: 2 }
+        ));
+        assert!(matches!(
+            error("A,cyan,1,0x4,LLZZ"),
+            InvalidSize { line: 1 }
+        ));
+        assert!(matches!(
+            error("A,cyan,1,4x4,LLZQ"),
+            InvalidPieces { line: 1 }
+        ));
+        assert!(matches!(
+            error("A,cyan,1,4x4,LLZ"),
+            InvalidPuzzle { line: 1, .. }
+        ));
+        assert!(matches!(error("A,cyan,1,2x4,SZ"), NoSolution { line: 1 }));
+    }
+}
@@ -0,0 +1,599 @@
+// Puzzle files. A puzzle file is a subset of TOML with one `[[puzzle]]` table
+// per puzzle:
+//
+//     # Comments start with '#'
+//     version = 1
+//
+//     [[puzzle]]
+//     name = "Corner"                  # optional
+//     section = "A"                    # optional
+//     color = "cyan"                   # optional
+//     number = 1                       # optional
+//     source = "IMG_0042.png"          # optional, where the puzzle came from
+//     rows = 4                         # optional if there's a board
+//     columns = 4                      # optional if there's a board
+//     board = "3.#/4./4./4."           # optional, a `BoardShape` mask string
+//     pieces = "IOTT"
+//     solutions = ["I2@r0c0; ..."]     # optional, in the replay format
+//
+// Values are strings, non-negative integers or arrays of strings, which may
+// span several lines. Strings are written in double quotes with the escapes
+// \" \\ \n \t and \uXXXX.
+
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::json::{from_json, to_json};
+use super::Puzzle;
+use crate::replay::parse_placement;
+use crate::{
+    board_fits, Board, BoardShape, PieceCollection, Position, PuzzleSpec, Solve, SolveError,
+};
+
+pub(super) const VERSION: u64 = 1;
+
+// A puzzle read from a puzzle file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PuzzleRecord {
+    pub name: Option<String>,
+    // The level in the game
+    pub section: Option<String>,
+    pub color: Option<String>,
+    pub number: Option<u32>,
+    // Where the puzzle came from, e.g. the screenshot it was read from
+    pub source: Option<String>,
+    pub board: BoardShape,
+    pub pieces: PieceCollection,
+    // Known solutions, e.g. the canonical first solution
+    pub solutions: Vec<Position>,
+}
+
+#[derive(Debug)]
+pub enum LoadPuzzlesError {
+    Io(io::Error),
+    // The line isn't valid in a puzzle file
+    Syntax {
+        line: usize,
+    },
+    // A puzzle has no value for a required key
+    MissingKey {
+        puzzle: usize,
+        key: &'static str,
+    },
+    // A puzzle has an invalid value for a key
+    InvalidValue {
+        puzzle: usize,
+        key: &'static str,
+    },
+    // A puzzle's board is too large for the search either way round, see
+    // `board_fits`
+    BoardTooLarge {
+        puzzle: usize,
+        row_count: u32,
+        column_count: u32,
+    },
+}
+
+impl Display for LoadPuzzlesError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use LoadPuzzlesError::*;
+        match self {
+            Io(err) => write!(f, "Failed to read the puzzle file. {err}"),
+            Syntax { line } => write!(f, "Syntax error on line {line} of the puzzle file."),
+            MissingKey { puzzle, key } => write!(f, "Puzzle {puzzle} has no {key}."),
+            InvalidValue { puzzle, key } => write!(f, "Puzzle {puzzle} has an invalid {key}."),
+            BoardTooLarge {
+                puzzle,
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "Puzzle {puzzle} has a board of {row_count} by {column_count} squares, which \
+                 is too large."
+            ),
+        }
+    }
+}
+
+impl Error for LoadPuzzlesError {}
+
+impl From<io::Error> for LoadPuzzlesError {
+    fn from(err: io::Error) -> Self {
+        LoadPuzzlesError::Io(err)
+    }
+}
+
+impl PuzzleRecord {
+    #[must_use]
+    pub fn pieces(&self) -> PieceCollection {
+        self.pieces.clone()
+    }
+
+    // A search for the first solution of the puzzle, which can be changed
+    // before it's run
+    #[must_use]
+    pub fn search(&self) -> Solve {
+        Solve::board(self.board.row_count(), self.board.column_count())
+            .piece_collection(self.pieces())
+            .blocked(&self.board.blocked_squares())
+    }
+
+    // The fingerprint of the puzzle, as `Puzzle::fingerprint` gives, or `None`
+    // if the board has blocked squares
+    #[must_use]
+    pub fn fingerprint(&self) -> Option<u64> {
+        let spec = PuzzleSpec::new(
+            self.board.row_count(),
+            self.board.column_count(),
+            self.pieces(),
+        );
+        self.board
+            .blocked_squares()
+            .is_empty()
+            .then(|| spec.fingerprint())
+    }
+
+    // The first solution in canonical order
+    pub fn solve_first_canonical(&self) -> Result<Option<Position>, SolveError> {
+        let solutions = self.search().run()?;
+        Ok(solutions.solutions.into_iter().next())
+    }
+}
+
+impl From<&Puzzle> for PuzzleRecord {
+    fn from(puzzle: &Puzzle) -> Self {
+        Self {
+            name: None,
+            section: Some(puzzle.section.to_string()),
+            color: Some(puzzle.color.to_string()),
+            number: Some(puzzle.number),
+            source: None,
+            board: BoardShape::rectangle(puzzle.row_count, puzzle.column_count),
+            pieces: puzzle.pieces(),
+            solutions: vec![puzzle.solution()],
+        }
+    }
+}
+
+// Identifies the puzzle by its name or its level, like `Puzzle`
+impl Display for PuzzleRecord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (&self.name, &self.section, &self.color, self.number) {
+            (Some(name), ..) => write!(f, "{name}"),
+            (None, Some(section), Some(color), Some(number)) => {
+                write!(f, "{section} {color} {number}")
+            }
+            _ => write!(f, "{} {}", self.board, self.pieces.to_canonical_string()),
+        }
+    }
+}
+
+// Files ending in ".json" are read and written as JSON, see `to_json`, and
+// any other file as a puzzle file
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<PuzzleRecord>, LoadPuzzlesError> {
+    let s = fs::read_to_string(&path)?;
+    if is_json(path.as_ref()) {
+        from_json(&s)
+    } else {
+        from_toml(&s)
+    }
+}
+
+pub fn save<P: AsRef<Path>>(path: P, puzzles: &[PuzzleRecord]) -> io::Result<()> {
+    let s = if is_json(path.as_ref()) {
+        to_json(puzzles)
+    } else {
+        to_toml(puzzles)
+    };
+    fs::write(path, s)
+}
+
+pub(super) fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+pub(super) enum Value {
+    String(String),
+    Integer(u64),
+    Array(Vec<String>),
+}
+
+// The keys and values of a `[[puzzle]]` table, in order
+pub(super) type Table = Vec<(String, Value)>;
+
+pub fn from_toml(s: &str) -> Result<Vec<PuzzleRecord>, LoadPuzzlesError> {
+    toml_tables(s)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, table)| puzzle_record(index + 1, table))
+        .collect()
+}
+
+// The `[[puzzle]]` tables of a puzzle file, without checking their keys
+pub(super) fn toml_tables(s: &str) -> Result<Vec<Table>, LoadPuzzlesError> {
+    let mut tables: Vec<Table> = vec![];
+    let mut lines = s.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let syntax_error = LoadPuzzlesError::Syntax { line: index + 1 };
+        let mut line = strip_comment(line).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "[[puzzle]]" {
+            tables.push(vec![]);
+            continue;
+        }
+        let (key, _) = line.split_once('=').ok_or(syntax_error)?;
+        let key = key.trim().to_string();
+        // Arrays continue until the closing bracket
+        while line.ends_with(',') || line.ends_with('[') {
+            let (_, next) = lines
+                .next()
+                .ok_or(LoadPuzzlesError::Syntax { line: index + 1 })?;
+            line.push_str(strip_comment(next).trim());
+        }
+        let value = line.split_once('=').unwrap().1.trim();
+        let value = parse_value(value).ok_or(LoadPuzzlesError::Syntax { line: index + 1 })?;
+        match tables.last_mut() {
+            Some(table) => table.push((key, value)),
+            None if key == "version" && matches!(value, Value::Integer(VERSION)) => {}
+            None => return Err(LoadPuzzlesError::Syntax { line: index + 1 }),
+        }
+    }
+    Ok(tables)
+}
+
+// Remove a comment that isn't inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if let Some(items) = s.strip_prefix('[') {
+        let mut items = items.strip_suffix(']')?.trim();
+        let mut array = vec![];
+        while !items.is_empty() {
+            let (item, rest) = parse_string(items)?;
+            array.push(item);
+            let rest = rest.trim_start();
+            items = match rest.strip_prefix(',') {
+                Some(rest) => rest.trim_start(),
+                None if rest.is_empty() => rest,
+                None => return None,
+            };
+        }
+        return Some(Value::Array(array));
+    }
+    if s.starts_with('"') {
+        let (string, rest) = parse_string(s)?;
+        return rest.is_empty().then_some(Value::String(string));
+    }
+    s.parse().ok().map(Value::Integer)
+}
+
+// Parse the string at the start of `s` and return it and the rest of `s`
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut string = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((string, &s[i + 2..])),
+            '\\' => string.push(match chars.next()?.1 {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                't' => '\t',
+                'u' => {
+                    let digits: String = (0..4)
+                        .map(|_| chars.next().map(|(_, c)| c))
+                        .collect::<Option<_>>()?;
+                    char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?
+                }
+                _ => return None,
+            }),
+            c => string.push(c),
+        }
+    }
+    None
+}
+
+pub(super) fn puzzle_record(puzzle: usize, table: Table) -> Result<PuzzleRecord, LoadPuzzlesError> {
+    let invalid = |key| LoadPuzzlesError::InvalidValue { puzzle, key };
+    let (mut name, mut section, mut color, mut number) = (None, None, None, None);
+    let mut source = None;
+    let (mut rows, mut columns, mut board, mut pieces) = (None, None, None, None);
+    let mut replays = vec![];
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("name", Value::String(s)) => name = Some(s),
+            ("section", Value::String(s)) => section = Some(s),
+            ("color", Value::String(s)) => color = Some(s),
+            ("number", Value::Integer(n)) => {
+                number = Some(n.try_into().map_err(|_| invalid("number"))?);
+            }
+            ("source", Value::String(s)) => source = Some(s),
+            ("rows", Value::Integer(n)) => {
+                rows = Some(n.try_into().map_err(|_| invalid("rows"))?);
+            }
+            ("columns", Value::Integer(n)) => {
+                columns = Some(n.try_into().map_err(|_| invalid("columns"))?);
+            }
+            ("board", Value::String(s)) => {
+                board = Some(s.parse::<BoardShape>().map_err(|_| invalid("board"))?);
+            }
+            ("pieces", Value::String(s)) => {
+                pieces = Some(
+                    s.parse::<PieceCollection>()
+                        .map_err(|_| invalid("pieces"))?,
+                );
+            }
+            ("solutions", Value::Array(array)) => replays = array,
+            _ => return Err(invalid("key")),
+        }
+    }
+
+    let board = match (board, rows, columns) {
+        (Some(board), None, None) => board,
+        (Some(board), Some(rows), Some(columns))
+            if (board.row_count(), board.column_count()) == (rows, columns) =>
+        {
+            board
+        }
+        (Some(_), ..) => return Err(invalid("board")),
+        (None, Some(rows), Some(columns)) if rows > 0 && columns > 0 => {
+            BoardShape::rectangle(rows, columns)
+        }
+        (None, Some(_), Some(_)) => return Err(invalid("rows")),
+        (None, None, _) => {
+            return Err(LoadPuzzlesError::MissingKey {
+                puzzle,
+                key: "rows",
+            })
+        }
+        (None, _, None) => {
+            return Err(LoadPuzzlesError::MissingKey {
+                puzzle,
+                key: "columns",
+            })
+        }
+    };
+    let (row_count, column_count) = (board.row_count(), board.column_count());
+    if !board_fits(row_count, column_count) && !board_fits(column_count, row_count) {
+        return Err(LoadPuzzlesError::BoardTooLarge {
+            puzzle,
+            row_count,
+            column_count,
+        });
+    }
+    let pieces = pieces.ok_or(LoadPuzzlesError::MissingKey {
+        puzzle,
+        key: "pieces",
+    })?;
+    let solutions = replays
+        .iter()
+        .map(|replay| replay_on(puzzle, &board, replay))
+        .collect::<Result<_, _>>()?;
+
+    Ok(PuzzleRecord {
+        name,
+        section,
+        color,
+        number,
+        source,
+        board,
+        pieces,
+        solutions,
+    })
+}
+
+// Replay the placements of `replay` on `board`, the board of puzzle number
+// `puzzle`. It's an invalid solution unless they cover the board exactly, and
+// invalid rows if the board is too large to replay on.
+fn replay_on(
+    puzzle: usize,
+    board: &BoardShape,
+    replay: &str,
+) -> Result<Position, LoadPuzzlesError> {
+    let invalid = |key| LoadPuzzlesError::InvalidValue { puzzle, key };
+    if !board_fits(board.row_count(), board.column_count()) {
+        return Err(invalid("rows"));
+    }
+    let mut squares = Board::new(board.row_count(), board.column_count());
+    for (row, column) in board.blocked_squares() {
+        squares
+            .block(row, column)
+            .map_err(|_| invalid("solution"))?;
+    }
+    for placement in replay.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let placement = parse_placement(placement).ok_or(invalid("solution"))?;
+        squares
+            .place_at(placement.piece, placement.row, placement.column)
+            .map_err(|_| invalid("solution"))?;
+    }
+    if !squares.is_complete() {
+        return Err(invalid("solution"));
+    }
+    Ok(squares.position())
+}
+
+#[must_use]
+pub fn to_toml(puzzles: &[PuzzleRecord]) -> String {
+    let mut toml = format!("version = {VERSION}\n");
+    for puzzle in puzzles {
+        toml.push_str("\n[[puzzle]]\n");
+        if let Some(name) = &puzzle.name {
+            writeln!(toml, "name = {}", quote(name)).unwrap();
+        }
+        if let Some(section) = &puzzle.section {
+            writeln!(toml, "section = {}", quote(section)).unwrap();
+        }
+        if let Some(color) = &puzzle.color {
+            writeln!(toml, "color = {}", quote(color)).unwrap();
+        }
+        if let Some(number) = puzzle.number {
+            writeln!(toml, "number = {number}").unwrap();
+        }
+        if let Some(source) = &puzzle.source {
+            writeln!(toml, "source = {}", quote(source)).unwrap();
+        }
+        writeln!(toml, "rows = {}", puzzle.board.row_count()).unwrap();
+        writeln!(toml, "columns = {}", puzzle.board.column_count()).unwrap();
+        if !puzzle.board.blocked_squares().is_empty() {
+            writeln!(toml, "board = {}", quote(&puzzle.board.to_mask_str())).unwrap();
+        }
+        writeln!(
+            toml,
+            "pieces = {}",
+            quote(&puzzle.pieces.to_canonical_string())
+        )
+        .unwrap();
+        if !puzzle.solutions.is_empty() {
+            toml.push_str("solutions = [\n");
+            for solution in &puzzle.solutions {
+                writeln!(toml, "    {},", quote(&solution.to_replay())).unwrap();
+            }
+            toml.push_str("]\n");
+        }
+    }
+    toml
+}
+
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_toml, to_toml, LoadPuzzlesError, PuzzleRecord};
+    use crate::puzzles;
+
+    #[test]
+    fn round_trip() {
+        let records: Vec<_> = puzzles::all().iter().map(PuzzleRecord::from).collect();
+        let toml = to_toml(&records);
+        assert!(from_toml(&toml).unwrap() == records);
+        assert_eq!(
+            records[0].fingerprint(),
+            Some(puzzles::all()[0].fingerprint())
+        );
+        assert!(toml.starts_with(
+            "version = 1\n\
+             \n\
+             [[puzzle]]\n\
+             section = \"A\"\n\
+             color = \"cyan\"\n\
+             number = 1\n\
+             rows = 4\n\
+             columns = 4\n\
+             pieces = \"LLZZ\"\n\
+             solutions = [\n    \"L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3\",\n]\n"
+        ));
+    }
+
+    #[test]
+    fn board_and_comments() {
+        let toml = "# A puzzle with a hole\n\
+                    [[puzzle]]\n\
+                    name = \"Hole \\\"#1\\\"\" # the name\n\
+                    source = \"hole.png\"\n\
+                    board = \"4./2.2#/2.2#\"\n\
+                    pieces = \"OI\"\n\
+                    solutions = [\"I2@r0c0; O1@r1c0\"]\n";
+        let records = from_toml(toml).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.to_string(), "Hole \"#1\"");
+        assert_eq!(record.pieces.to_canonical_string(), "IO");
+        assert_eq!(record.source.as_deref(), Some("hole.png"));
+        assert_eq!(record.fingerprint(), None);
+        assert_eq!(record.solutions[0].to_string(), "AAAA\nBB##\nBB##\n");
+        let solution = record.solve_first_canonical().unwrap().unwrap();
+        assert!(solution == record.solutions[0]);
+        assert!(from_toml(&to_toml(&records)).unwrap() == records);
+    }
+
+    #[test]
+    fn errors() {
+        use LoadPuzzlesError::*;
+        let error = |toml: &str| from_toml(toml).err().unwrap();
+        assert!(matches!(error("rows = 4"), Syntax { line: 1 }));
+        assert!(matches!(error("version = 2"), Syntax { line: 1 }));
+        assert!(matches!(
+            error("[[puzzle]]\nrows = \"4"),
+            Syntax { line: 2 }
+        ));
+        assert!(matches!(
+            error("[[puzzle]]\nrows = 4\npieces = \"I\""),
+            MissingKey {
+                puzzle: 1,
+                key: "columns"
+            }
+        ));
+        assert!(matches!(
+            error("[[puzzle]]\nrows = 1\ncolumns = 4\npieces = \"I\"\nlevel = 1"),
+            InvalidValue { puzzle: 1, .. }
+        ));
+        assert!(matches!(
+            error("[[puzzle]]\nrows = 1\ncolumns = 4\npieces = \"I\"\nsolutions = [\"I1@r0c0\"]"),
+            InvalidValue {
+                puzzle: 1,
+                key: "solution"
+            }
+        ));
+        // A board too large for the search, and one that only fits turned
+        // over, which solutions can't be replayed on
+        assert!(matches!(
+            error("[[puzzle]]\nrows = 30\ncolumns = 30\npieces = \"I\"\nsolutions = [\"I1@r0c0\"]"),
+            BoardTooLarge {
+                puzzle: 1,
+                row_count: 30,
+                column_count: 30
+            }
+        ));
+        assert!(matches!(
+            error("[[puzzle]]\nrows = 1\ncolumns = 28\npieces = \"IIIIIII\"\nsolutions = [\"I1@r0c0\"]"),
+            InvalidValue {
+                puzzle: 1,
+                key: "rows"
+            }
+        ));
+        assert!(from_toml("[[puzzle]]\nrows = 1\ncolumns = 28\npieces = \"IIIIIII\"").is_ok());
+    }
+
+    #[test]
+    fn control_characters() {
+        let mut records = from_toml("[[puzzle]]\nrows = 1\ncolumns = 4\npieces = \"I\"").unwrap();
+        records[0].name = Some("Tab\tbell\u{7}\r".to_string());
+        let toml = to_toml(&records);
+        assert!(toml.contains("name = \"Tab\\tbell\\u0007\\u000d\"\n"));
+        assert!(from_toml(&toml).unwrap() == records);
+    }
+}
@@ -0,0 +1,334 @@
+// Export of puzzles as JSON, for tools that can't read puzzle files. The
+// object has the same keys as a puzzle file, with the puzzles in an array:
+//
+//     {"version":1,"puzzles":[{"section":"A","color":"cyan","number":1,
+//     "rows":4,"columns":4,"pieces":"LLZZ","solutions":["L2@r0c0; ..."]}]}
+//
+// Missing metadata is left out and there's one puzzle per line. `from_json`
+// reads the export back, whatever its whitespace and the order of its keys.
+
+use std::fmt::Write as _;
+
+use super::file::{puzzle_record, Table, Value, VERSION};
+use super::{LoadPuzzlesError, PuzzleRecord};
+use crate::json_string;
+
+#[must_use]
+pub fn to_json(puzzles: &[PuzzleRecord]) -> String {
+    let mut json = format!("{{\"version\":{VERSION},\"puzzles\":[");
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        json.push_str(if index == 0 { "\n" } else { ",\n" });
+        let mut fields = vec![];
+        for (key, value) in [
+            ("name", &puzzle.name),
+            ("section", &puzzle.section),
+            ("color", &puzzle.color),
+        ] {
+            if let Some(value) = value {
+                fields.push(format!("\"{key}\":{}", json_string(value)));
+            }
+        }
+        if let Some(number) = puzzle.number {
+            fields.push(format!("\"number\":{number}"));
+        }
+        if let Some(source) = &puzzle.source {
+            fields.push(format!("\"source\":{}", json_string(source)));
+        }
+        fields.push(format!("\"rows\":{}", puzzle.board.row_count()));
+        fields.push(format!("\"columns\":{}", puzzle.board.column_count()));
+        if !puzzle.board.blocked_squares().is_empty() {
+            fields.push(format!(
+                "\"board\":{}",
+                json_string(&puzzle.board.to_mask_str())
+            ));
+        }
+        fields.push(format!(
+            "\"pieces\":{}",
+            json_string(&puzzle.pieces.to_canonical_string())
+        ));
+        let solutions: Vec<_> = puzzle
+            .solutions
+            .iter()
+            .map(|solution| json_string(&solution.to_replay()))
+            .collect();
+        fields.push(format!("\"solutions\":[{}]", solutions.join(",")));
+        write!(json, "{{{}}}", fields.join(",")).unwrap();
+    }
+    json.push_str("\n]}\n");
+    json
+}
+
+// Read the puzzles of a JSON export
+pub fn from_json(s: &str) -> Result<Vec<PuzzleRecord>, LoadPuzzlesError> {
+    json_tables(s)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, table)| puzzle_record(index + 1, table?))
+        .collect()
+}
+
+// The puzzles of a JSON export as the tables of a puzzle file, without
+// checking their keys. A puzzle that isn't an object of strings, integers and
+// arrays of strings is an error of its own.
+pub(super) fn json_tables(
+    s: &str,
+) -> Result<Vec<Result<Table, LoadPuzzlesError>>, LoadPuzzlesError> {
+    let mut parser = Parser { s, position: 0 };
+    let json = parser.value().and_then(|json| {
+        parser.skip_whitespace();
+        (parser.position == s.len()).then_some(json)
+    });
+    let syntax_error = || LoadPuzzlesError::Syntax {
+        line: s[..parser.position.min(s.len())].matches('\n').count() + 1,
+    };
+    let Some(Json::Object(fields)) = json else {
+        return Err(syntax_error());
+    };
+
+    let mut puzzles = None;
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("version", Json::Integer(VERSION)) => {}
+            ("puzzles", Json::Array(array)) => puzzles = Some(array),
+            _ => return Err(LoadPuzzlesError::Syntax { line: 1 }),
+        }
+    }
+    Ok(puzzles
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(index, json)| {
+            let invalid = LoadPuzzlesError::InvalidValue {
+                puzzle: index + 1,
+                key: "key",
+            };
+            let Json::Object(fields) = json else {
+                return Err(invalid);
+            };
+            fields
+                .into_iter()
+                .map(|(key, value)| Some((key, table_value(value)?)))
+                .collect::<Option<Table>>()
+                .ok_or(invalid)
+        })
+        .collect())
+}
+
+// The value of a key of a puzzle, which is a string, an integer or an array
+// of strings like in a puzzle file
+fn table_value(json: Json) -> Option<Value> {
+    Some(match json {
+        Json::String(s) => Value::String(s),
+        Json::Integer(n) => Value::Integer(n),
+        Json::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .map(|item| match item {
+                    Json::String(s) => Some(s),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    })
+}
+
+// The JSON values that can be read. Numbers must be non-negative integers.
+enum Json {
+    Null,
+    Bool,
+    Integer(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    // The byte offset of the next character
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.s[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    // Skip `token` after any whitespace if it's next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        let c = self.rest().chars().next()?;
+        match c {
+            '{' => self
+                .sequence('{', '}', |parser| {
+                    let key = parser.string()?;
+                    parser.eat(":").then_some(())?;
+                    Some((key, parser.value()?))
+                })
+                .map(Json::Object),
+            '[' => self.sequence('[', ']', Parser::value).map(Json::Array),
+            '"' => self.string().map(Json::String),
+            '0'..='9' => {
+                let digits = self.rest().len()
+                    - self
+                        .rest()
+                        .trim_start_matches(|c: char| c.is_ascii_digit())
+                        .len();
+                let n = self.rest()[..digits].parse().ok()?;
+                self.position += digits;
+                Some(Json::Integer(n))
+            }
+            _ if self.eat("null") => Some(Json::Null),
+            _ if self.eat("true") || self.eat("false") => Some(Json::Bool),
+            _ => None,
+        }
+    }
+
+    // Items separated by commas between `open` and `close`
+    fn sequence<T>(
+        &mut self,
+        open: char,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        self.eat(&open.to_string()).then_some(())?;
+        let mut items = vec![];
+        if self.eat(&close.to_string()) {
+            return Some(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat(&close.to_string()) {
+                return Some(items);
+            }
+            self.eat(",").then_some(())?;
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.eat("\"").then_some(())?;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += i + 1;
+                    return Some(string);
+                }
+                '\\' => string.push(match chars.next()?.1 {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => {
+                        let hex: String = (0..4)
+                            .map(|_| chars.next().map(|(_, c)| c))
+                            .collect::<Option<_>>()?;
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    _ => return None,
+                }),
+                c if c.is_control() => return None,
+                c => string.push(c),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json, to_json};
+    use crate::puzzles::{self, from_toml, LoadPuzzlesError};
+
+    #[test]
+    fn json() {
+        let records: Vec<_> = puzzles::all()[..1]
+            .iter()
+            .map(puzzles::PuzzleRecord::from)
+            .collect();
+        assert_eq!(
+            to_json(&records),
+            "{\"version\":1,\"puzzles\":[\n\
+             {\"section\":\"A\",\"color\":\"cyan\",\"number\":1,\"rows\":4,\"columns\":4,\
+             \"pieces\":\"LLZZ\",\"solutions\":[\"L2@r0c0; Z2@r0c3; Z2@r1c1; L4@r2c3\"]}\n\
+             ]}\n"
+        );
+
+        let records = from_toml(
+            "[[puzzle]]\nname = \"\\\"Hole\\\"\\n\"\nboard = \"4./2.2#/2.2#\"\npieces = \"OI\"",
+        )
+        .unwrap();
+        assert_eq!(
+            to_json(&records),
+            "{\"version\":1,\"puzzles\":[\n\
+             {\"name\":\"\\\"Hole\\\"\\u000a\",\"rows\":3,\"columns\":4,\"board\":\"4./2.2#/2.2#\",\
+             \"pieces\":\"IO\",\"solutions\":[]}\n\
+             ]}\n"
+        );
+        assert_eq!(to_json(&[]), "{\"version\":1,\"puzzles\":[\n]}\n");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut records: Vec<_> = puzzles::all()
+            .iter()
+            .map(puzzles::PuzzleRecord::from)
+            .collect();
+        records[0].source = Some("C:\\Screenshots\\\"A\"\t1.png".to_string());
+        assert!(to_json(&records)
+            .contains("\"number\":1,\"source\":\"C:\\\\Screenshots\\\\\\\"A\\\"\\u00091.png\","));
+        assert!(from_json(&to_json(&records)).unwrap() == records);
+
+        let json = "{ \"puzzles\" : [ { \"pieces\" : \"I\\u0049\", \"columns\" : 8,\n\
+                    \"rows\" : 1 } ] , \"version\" : 1 }";
+        let records = from_json(json).unwrap();
+        assert_eq!(records[0].pieces.to_canonical_string(), "II");
+        assert_eq!(records[0].board.column_count(), 8);
+        assert!(from_json("{\"version\":1,\"puzzles\":[]}")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        use LoadPuzzlesError::*;
+        let error = |json: &str| from_json(json).err().unwrap();
+        assert!(matches!(error(""), Syntax { line: 1 }));
+        assert!(matches!(error("{\"version\":2}"), Syntax { line: 1 }));
+        assert!(matches!(
+            error("{\"version\":1,\n\"puzzles\":[\n{\"rows\":-1}]}"),
+            Syntax { line: 3 }
+        ));
+        assert!(matches!(error("{\"puzzles\":[]} []"), Syntax { line: 1 }));
+        assert!(matches!(
+            error("{\"puzzles\":[{\"rows\":1,\"columns\":4,\"pieces\":\"I\",\"hard\":true}]}"),
+            InvalidValue { puzzle: 1, .. }
+        ));
+        assert!(matches!(
+            error("{\"puzzles\":[{\"rows\":1,\"columns\":4}]}"),
+            MissingKey {
+                puzzle: 1,
+                key: "pieces"
+            }
+        ));
+    }
+}
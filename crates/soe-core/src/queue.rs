@@ -0,0 +1,461 @@
+// A bounded queue of solve jobs run by a fixed pool of worker threads, for
+// serving solves to untrusted clients. The queue refuses jobs when it's full
+// or when a client already has too many jobs queued or running, and every
+// search is abandoned after a timeout, so a few hostile puzzles can't keep
+// the workers busy. A job may also write each step of its search to a
+// writer, in the format of `trace::solve_traced`, or report its progress.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::trace::{self, TraceError};
+use crate::{Outcome, Progress, PuzzleSpec, Solve, SolveError};
+
+#[derive(Debug, Clone)]
+pub struct QueueOptions {
+    // The number of worker threads
+    pub workers: usize,
+    // The maximum number of jobs waiting for a worker
+    pub capacity: usize,
+    // The maximum number of jobs of one client, waiting or running
+    pub per_client: usize,
+    // Abandon each search after this long
+    pub timeout: Duration,
+    // Abandon each search after visiting this many nodes
+    pub max_nodes: Option<u64>,
+}
+
+impl Default for QueueOptions {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            capacity: 16,
+            per_client: 2,
+            timeout: Duration::from_secs(10),
+            max_nodes: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SubmitError {
+    // The queue has `capacity` jobs waiting
+    QueueFull,
+    // The client has `per_client` jobs waiting or running
+    ClientLimit,
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use SubmitError::*;
+        match self {
+            QueueFull => write!(f, "The job queue is full. Try again later."),
+            ClientLimit => write!(f, "Too many jobs are already queued for this client."),
+        }
+    }
+}
+
+impl Error for SubmitError {}
+
+#[derive(Debug)]
+pub enum JobError {
+    // The puzzle of the job is invalid
+    InvalidPuzzle(SolveError),
+    // Writing a step of the search failed
+    Io(io::Error),
+    // The search panicked. The worker carries on with the next job.
+    Panicked,
+}
+
+impl Display for JobError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use JobError::*;
+        match self {
+            InvalidPuzzle(err) => write!(f, "{err}"),
+            Io(err) => write!(f, "Failed to write the trace. {err}"),
+            Panicked => write!(f, "The search failed unexpectedly."),
+        }
+    }
+}
+
+impl Error for JobError {}
+
+pub struct JobQueue {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+// A job that has been accepted by the queue
+pub struct JobHandle {
+    receiver: Receiver<Result<Outcome, JobError>>,
+}
+
+struct Shared {
+    options: QueueOptions,
+    state: Mutex<State>,
+    // Signalled when a job is queued or the queue is closed
+    changed: Condvar,
+}
+
+struct State {
+    jobs: VecDeque<Job>,
+    // The number of jobs waiting or running for each client
+    client_jobs: HashMap<String, usize>,
+    closed: bool,
+}
+
+struct Job {
+    client: String,
+    task: Task,
+    sender: Sender<Result<Outcome, JobError>>,
+}
+
+enum Task {
+    // A search, with where its progress is reported, if anywhere
+    Solve(Solve, Option<Box<dyn FnMut(Progress) + Send>>),
+    // A search that writes each of its steps
+    Trace(PuzzleSpec, Box<dyn Write + Send>),
+}
+
+impl JobQueue {
+    #[must_use]
+    pub fn new(options: QueueOptions) -> Self {
+        let shared = Arc::new(Shared {
+            options,
+            state: Mutex::new(State {
+                jobs: VecDeque::new(),
+                client_jobs: HashMap::new(),
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        });
+        let workers = (0..shared.options.workers.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || work(&shared))
+            })
+            .collect();
+        Self { shared, workers }
+    }
+
+    // Queue a job to solve `puzzle` for `client`. Clients are identified by
+    // any string, e.g. an IP address.
+    pub fn submit(&self, client: &str, puzzle: PuzzleSpec) -> Result<JobHandle, SubmitError> {
+        let solve =
+            Solve::board(puzzle.row_count, puzzle.column_count).piece_collection(puzzle.pieces);
+        self.push(client, Task::Solve(solve, None))
+    }
+
+    // Queue a job to run `solve`, e.g. on a board with blocked squares, and
+    // call `progress` every 1024 nodes while it runs. The job searches for
+    // one solution on one thread, within the timeout and node limit of the
+    // queue as well as any that `solve` sets.
+    pub fn submit_solve(
+        &self,
+        client: &str,
+        solve: Solve,
+        progress: Box<dyn FnMut(Progress) + Send>,
+    ) -> Result<JobHandle, SubmitError> {
+        self.push(client, Task::Solve(solve, Some(progress)))
+    }
+
+    // Queue a job like `submit` that also writes each step of the search to
+    // `trace` while it runs
+    pub fn submit_traced(
+        &self,
+        client: &str,
+        puzzle: PuzzleSpec,
+        trace: Box<dyn Write + Send>,
+    ) -> Result<JobHandle, SubmitError> {
+        self.push(client, Task::Trace(puzzle, trace))
+    }
+
+    fn push(&self, client: &str, task: Task) -> Result<JobHandle, SubmitError> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.jobs.len() >= self.shared.options.capacity {
+            return Err(SubmitError::QueueFull);
+        }
+        let client_jobs = state.client_jobs.entry(client.to_string()).or_insert(0);
+        if *client_jobs >= self.shared.options.per_client {
+            return Err(SubmitError::ClientLimit);
+        }
+        *client_jobs += 1;
+        let (sender, receiver) = mpsc::channel();
+        state.jobs.push_back(Job {
+            client: client.to_string(),
+            task,
+            sender,
+        });
+        self.shared.changed.notify_one();
+        Ok(JobHandle { receiver })
+    }
+
+    // The number of jobs waiting for a worker
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().jobs.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Waits for the queued jobs to finish
+impl Drop for JobQueue {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.changed.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl JobHandle {
+    // Wait for the job to finish. The outcome is `Undetermined` if the search
+    // timed out or reached the node limit.
+    pub fn wait(self) -> Result<Outcome, JobError> {
+        self.receiver.recv().unwrap_or(Err(JobError::Panicked))
+    }
+}
+
+fn work(shared: &Shared) {
+    loop {
+        let Job {
+            client,
+            task,
+            sender,
+        } = {
+            let mut state = shared.state.lock().unwrap();
+            loop {
+                if let Some(job) = state.jobs.pop_front() {
+                    break job;
+                }
+                if state.closed {
+                    return;
+                }
+                state = shared.changed.wait(state).unwrap();
+            }
+        };
+
+        // A job that panics mustn't take the worker or the client's count of
+        // jobs with it
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run(&shared.options, task)))
+            .unwrap_or(Err(JobError::Panicked));
+
+        let mut state = shared.state.lock().unwrap();
+        let client_jobs = state.client_jobs.get_mut(&client).unwrap();
+        *client_jobs -= 1;
+        if *client_jobs == 0 {
+            state.client_jobs.remove(&client);
+        }
+        drop(state);
+        // The client may have stopped waiting
+        let _ = sender.send(result);
+    }
+}
+
+fn run(options: &QueueOptions, task: Task) -> Result<Outcome, JobError> {
+    let (solve, progress) = match task {
+        Task::Solve(solve, progress) => (solve, progress),
+        Task::Trace(puzzle, trace) => return run_traced(options, &puzzle, trace),
+    };
+    let mut progress = progress.unwrap_or_else(|| Box::new(|_| {}));
+    let solutions = solve
+        .for_job(options.timeout, options.max_nodes)
+        .run_with_progress(&mut *progress)
+        .map_err(JobError::InvalidPuzzle)?;
+    Ok(match solutions.solutions.into_iter().next() {
+        Some(solution) => Outcome::Solved(solution),
+        None if solutions.abandoned => Outcome::Undetermined,
+        None => Outcome::NoSolution,
+    })
+}
+
+fn run_traced(
+    options: &QueueOptions,
+    puzzle: &PuzzleSpec,
+    trace: Box<dyn Write + Send>,
+) -> Result<Outcome, JobError> {
+    let deadline = Instant::now() + options.timeout;
+    let (solutions, stopped) = trace::solve_traced_until(
+        puzzle.row_count,
+        puzzle.column_count,
+        puzzle.pieces.clone(),
+        1,
+        &|| Instant::now() >= deadline,
+        options.max_nodes.unwrap_or(u64::MAX),
+        trace,
+    )
+    .map_err(|err| match err {
+        TraceError::InvalidPuzzle(err) => JobError::InvalidPuzzle(err.into()),
+        TraceError::Io(err) => JobError::Io(err),
+    })?;
+    Ok(match solutions.into_iter().next() {
+        Some(solution) => Outcome::Solved(solution),
+        None if stopped => Outcome::Undetermined,
+        None => Outcome::NoSolution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::{JobError, JobHandle, JobQueue, QueueOptions, SubmitError};
+    use crate::{Outcome, PuzzleSpec, Solve};
+
+    fn puzzle(row_count: u32, column_count: u32, pieces: &str) -> PuzzleSpec {
+        PuzzleSpec::new(row_count, column_count, pieces.parse().unwrap())
+    }
+
+    #[test]
+    fn solve() {
+        let queue = JobQueue::new(QueueOptions::default());
+        let solved = queue.submit("a", puzzle(4, 4, "LLZZ")).unwrap();
+        let none = queue.submit("b", puzzle(2, 4, "SZ")).unwrap();
+        let invalid = queue.submit("c", puzzle(4, 4, "LLZ")).unwrap();
+
+        let solution = solved.wait().ok().and_then(Outcome::into_solution).unwrap();
+        assert_eq!(solution.to_string(), "AAAB\nACBB\nCCBD\nCDDD\n");
+        assert!(none.wait().ok() == Some(Outcome::NoSolution));
+        assert!(matches!(invalid.wait(), Err(JobError::InvalidPuzzle(_))));
+    }
+
+    #[test]
+    fn limits() {
+        // A puzzle with no solution that takes longer than the timeout
+        let hard = || puzzle(4, 12, "IITTTLLLJJSZ");
+        let queue = JobQueue::new(QueueOptions {
+            workers: 1,
+            per_client: 2,
+            timeout: Duration::from_millis(200),
+            ..QueueOptions::default()
+        });
+        let start = Instant::now();
+        let first = queue.submit("a", hard()).unwrap();
+        let second = queue.submit("a", hard()).unwrap();
+        assert_eq!(
+            queue.submit("a", hard()).err(),
+            Some(SubmitError::ClientLimit)
+        );
+        let third = queue.submit("b", hard()).unwrap();
+        for job in [first, second, third] {
+            assert!(job.wait().ok() == Some(Outcome::Undetermined));
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        // The client's jobs are done, so it can submit again
+        assert!(queue.submit("a", hard()).is_ok());
+
+        let queue = JobQueue::new(QueueOptions {
+            capacity: 0,
+            ..QueueOptions::default()
+        });
+        assert_eq!(
+            queue.submit("a", hard()).err(),
+            Some(SubmitError::QueueFull)
+        );
+    }
+
+    // A trace that can still be read after the job has dropped it
+    #[derive(Clone, Default)]
+    struct SharedTrace(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedTrace {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn traced() {
+        let queue = JobQueue::new(QueueOptions {
+            timeout: Duration::from_millis(200),
+            ..QueueOptions::default()
+        });
+        let trace = SharedTrace::default();
+        let job = queue
+            .submit_traced("a", puzzle(2, 4, "OO"), Box::new(trace.clone()))
+            .unwrap();
+        let solution = job.wait().ok().and_then(Outcome::into_solution).unwrap();
+        assert_eq!(solution.to_string(), "AABB\nAABB\n");
+        let output = String::from_utf8(trace.0.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with("{\"step\":\"place\",\"piece\":\"O1\",\"row\":0,\"column\":0,"));
+        assert_eq!(output.lines().count(), 5);
+
+        let job = queue
+            .submit_traced("a", puzzle(4, 12, "IITTTLLLJJSZ"), Box::new(io::sink()))
+            .unwrap();
+        assert!(job.wait().ok() == Some(Outcome::Undetermined));
+        let job = queue
+            .submit_traced("a", puzzle(4, 4, "LLZ"), Box::new(io::sink()))
+            .unwrap();
+        assert!(matches!(job.wait(), Err(JobError::InvalidPuzzle(_))));
+    }
+
+    #[test]
+    fn solve_with_progress() {
+        let queue = JobQueue::new(QueueOptions {
+            timeout: Duration::from_millis(200),
+            ..QueueOptions::default()
+        });
+        let job = queue
+            .submit_solve(
+                "a",
+                Solve::board(3, 4)
+                    .blocked(&[(1, 0), (1, 1), (1, 2), (1, 3)])
+                    .pieces("II"),
+                Box::new(|_| {}),
+            )
+            .unwrap();
+        let solution = job.wait().ok().and_then(Outcome::into_solution).unwrap();
+        assert_eq!(solution.to_string(), "AAAA\n####\nBBBB\n");
+
+        // The queue's timeout applies when the search has a longer one
+        let (sender, receiver) = mpsc::channel();
+        let job = queue
+            .submit_solve(
+                "a",
+                Solve::board(4, 12)
+                    .pieces("IITTTLLLJJSZ")
+                    .timeout(Duration::from_mins(1)),
+                Box::new(move |progress| {
+                    let _ = sender.send(progress);
+                }),
+            )
+            .unwrap();
+        assert!(job.wait().ok() == Some(Outcome::Undetermined));
+        let reports: Vec<_> = receiver.iter().collect();
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|pair| pair[0].nodes < pair[1].nodes));
+
+        let job = queue
+            .submit_solve("a", Solve::board(4, 4).pieces("LLZ"), Box::new(|_| {}))
+            .unwrap();
+        assert!(matches!(job.wait(), Err(JobError::InvalidPuzzle(_))));
+    }
+
+    #[test]
+    fn worker_gone() {
+        // The worker dropped the job without an outcome
+        let (sender, receiver) = mpsc::channel();
+        drop(sender);
+        let handle = JobHandle { receiver };
+        assert!(matches!(handle.wait(), Err(JobError::Panicked)));
+    }
+}
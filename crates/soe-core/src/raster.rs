@@ -0,0 +1,198 @@
+// Rendering of positions as raster images, for places that don't take
+// vector graphics or HTML.
+
+use image::{Rgb, RgbImage};
+
+use crate::{AnnotatedPosition, Annotation, Palette, Position};
+
+// The digits in a 3 by 5 pixel font, a row of three bits per line from the
+// top, the most significant bit on the left
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+#[derive(Clone, Debug)]
+pub struct RasterOptions {
+    // The width and height of a square in pixels
+    pub cell_size: u32,
+    pub palette: Palette,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 32,
+            palette: Palette::default(),
+        }
+    }
+}
+
+impl Position {
+    // Render the position as an image with `options.cell_size` pixels per
+    // square. Squares are colored by the kind of piece that covers them and
+    // pieces are outlined.
+    #[must_use]
+    pub fn to_image(&self, options: &RasterOptions) -> RgbImage {
+        let (row_count, column_count) = (self.row_count(), self.column_count());
+        let width = column_count as usize + 1;
+        let pieces = self.label_pieces();
+        let cell_size = options.cell_size.max(1);
+        let border_width = (cell_size / 16).max(1);
+        let label = |row: u32, column: u32| self.squares[row as usize * width + column as usize];
+        let palette = &options.palette;
+        let color = |label: u8| match label {
+            b'.' => palette.background,
+            b'#' => palette.blocked,
+            label => pieces
+                .get(&label)
+                .map_or(palette.unknown, |&piece| palette.piece(piece)),
+        };
+
+        RgbImage::from_fn(column_count * cell_size, row_count * cell_size, |x, y| {
+            let (row, column) = (y / cell_size, x / cell_size);
+            let (dy, dx) = (y % cell_size, x % cell_size);
+            let this = label(row, column);
+            // Whether the square differs from its neighbor at the given
+            // offset. Squares off the board differ from all squares.
+            let differs = |row: Option<u32>, column: Option<u32>| match (row, column) {
+                (Some(row), Some(column)) if row < row_count && column < column_count => {
+                    label(row, column) != this
+                }
+                _ => true,
+            };
+            let on_border = (dy < border_width && differs(row.checked_sub(1), Some(column)))
+                || (dy >= cell_size - border_width && differs(Some(row + 1), Some(column)))
+                || (dx < border_width && differs(Some(row), column.checked_sub(1)))
+                || (dx >= cell_size - border_width && differs(Some(row), Some(column + 1)));
+            Rgb(if on_border {
+                palette.border
+            } else {
+                color(this)
+            })
+        })
+    }
+}
+
+impl AnnotatedPosition {
+    // Render the position as `Position::to_image` does, with the annotations
+    // drawn over their squares in the border color
+    #[must_use]
+    pub fn to_image(&self, options: &RasterOptions) -> RgbImage {
+        let mut image = self.position.to_image(options);
+        let cell_size = options.cell_size.max(1) as i32;
+        let color = Rgb(options.palette.border);
+        for &(row, column, annotation) in self.annotations() {
+            let (x0, y0) = (column as i32 * cell_size, row as i32 * cell_size);
+            // Whether the pixel at `x`, `y` from the center of the square is
+            // part of the annotation
+            let inside: Box<dyn Fn(i32, i32) -> bool> = match annotation {
+                Annotation::Number(number) => {
+                    let digits: Vec<usize> = number
+                        .to_string()
+                        .bytes()
+                        .map(|digit| (digit - b'0') as usize)
+                        .collect();
+                    // Digits are three pixels of the font wide with a gap of
+                    // one, scaled to fit half the square
+                    let font_width = 4 * digits.len() as i32 - 1;
+                    let scale = (cell_size / 2 / font_width.max(5)).max(1);
+                    let (width, height) = (font_width * scale, 5 * scale);
+                    Box::new(move |x, y| {
+                        let (x, y) = (x + width / 2, y + height / 2);
+                        if x < 0 || y < 0 || x >= width || y >= height {
+                            return false;
+                        }
+                        let (font_x, font_y) = (x / scale, y / scale);
+                        font_x % 4 != 3
+                            && DIGITS[digits[(font_x / 4) as usize]][font_y as usize]
+                                & (0b100 >> (font_x % 4))
+                                != 0
+                    })
+                }
+                Annotation::Arrow(arrow) => {
+                    // A triangle pointing in the direction of the arrow, with
+                    // its tip a quarter of a square from the center
+                    let (dy, dx) = arrow.offsets();
+                    let size = cell_size / 4;
+                    Box::new(move |x, y| {
+                        // The distance from the base towards the tip, and
+                        // from the axis of the arrow
+                        let along = x * dx + y * dy + size;
+                        let across = (x * dy - y * dx).abs();
+                        (0..=2 * size).contains(&along) && 2 * across <= 2 * size - along
+                    })
+                }
+                Annotation::Marker => {
+                    let radius = cell_size / 6;
+                    Box::new(move |x, y| x * x + y * y <= radius * radius)
+                }
+            };
+            for y in 0..cell_size {
+                for x in 0..cell_size {
+                    if inside(x - cell_size / 2, y - cell_size / 2) {
+                        image.put_pixel((x0 + x) as u32, (y0 + y) as u32, color);
+                    }
+                }
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RasterOptions;
+    use crate::{AnnotatedPosition, Annotation, Arrow, Position};
+
+    #[test]
+    fn to_image() {
+        let position = Position::from_replay(2, 4, "I2@r0c0").unwrap();
+        let options = RasterOptions {
+            cell_size: 16,
+            ..RasterOptions::default()
+        };
+        let image = position.to_image(&options);
+
+        assert_eq!(image.dimensions(), (64, 32));
+        assert_eq!(image.get_pixel(0, 0).data, options.palette.border);
+        assert_eq!(image.get_pixel(8, 8).data, options.palette.pieces[0]);
+        // No border between squares of the same piece
+        assert_eq!(image.get_pixel(16, 8).data, options.palette.pieces[0]);
+        assert_eq!(image.get_pixel(8, 15).data, options.palette.border);
+        assert_eq!(image.get_pixel(8, 16).data, options.palette.border);
+        assert_eq!(image.get_pixel(8, 24).data, options.palette.background);
+    }
+
+    #[test]
+    fn annotations() {
+        let position = Position::from_replay(2, 4, "I2@r0c0").unwrap();
+        let options = RasterOptions {
+            cell_size: 16,
+            ..RasterOptions::default()
+        };
+        let annotated = AnnotatedPosition::from(position)
+            .with(1, 0, Annotation::Number(1))
+            .with(1, 1, Annotation::Arrow(Arrow::Right))
+            .with(1, 2, Annotation::Marker);
+        let image = annotated.to_image(&options);
+        let is_border = |x, y| image.get_pixel(x, y).data == options.palette.border;
+
+        // The 1 is three pixels wide at the bottom and one at the top
+        assert!(is_border(8, 22) && !is_border(7, 22));
+        assert!(is_border(7, 26) && is_border(8, 26) && is_border(9, 26));
+        // The tip of the arrow is on the right and its base on the left
+        assert!(is_border(28, 24) && !is_border(29, 24));
+        assert!(is_border(20, 20) && is_border(20, 28) && !is_border(20, 19));
+        assert!(is_border(40, 24) && !is_border(40, 27));
+        assert!(!is_border(8, 8));
+    }
+}
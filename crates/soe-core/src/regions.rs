@@ -0,0 +1,353 @@
+// Boards whose open squares fall into separate regions, e.g. when a wall of
+// blocked squares cuts the board in two. No piece can cross from one region
+// to another, so each region can be tiled on its own once the pieces are
+// shared out between the regions. Each region is searched once per share of
+// the pieces it could get, rather than once for every tiling of the regions
+// searched before it, which makes the search about as fast as that of the
+// largest region instead of the product of all of them.
+
+use std::collections::HashMap;
+
+use crate::{Board, BoardShape, LabelOrder, Piece, PieceCollection, Position, Solver};
+
+// The open squares of each region as (row, column), in row-major order. The
+// regions are ordered by their first square. Squares are connected through
+// their edges.
+pub(crate) fn regions(
+    row_count: u32,
+    column_count: u32,
+    blocked: &[(u32, u32)],
+) -> Vec<Vec<(u32, u32)>> {
+    let index = |row: u32, column: u32| (row * column_count + column) as usize;
+    let mut seen = vec![false; (row_count * column_count) as usize];
+    for &(row, column) in blocked {
+        seen[index(row, column)] = true;
+    }
+
+    let mut regions = vec![];
+    for row in 0..row_count {
+        for column in 0..column_count {
+            if seen[index(row, column)] {
+                continue;
+            }
+            seen[index(row, column)] = true;
+            let mut region = vec![];
+            let mut stack = vec![(row, column)];
+            while let Some((row, column)) = stack.pop() {
+                region.push((row, column));
+                let neighbors = [
+                    (row.wrapping_sub(1), column),
+                    (row + 1, column),
+                    (row, column.wrapping_sub(1)),
+                    (row, column + 1),
+                ];
+                for (row, column) in neighbors {
+                    if row < row_count && column < column_count && !seen[index(row, column)] {
+                        seen[index(row, column)] = true;
+                        stack.push((row, column));
+                    }
+                }
+            }
+            region.sort_unstable();
+            regions.push(region);
+        }
+    }
+    regions
+}
+
+impl BoardShape {
+    // The open squares of each separate region of the board as (row, column),
+    // in row-major order. The regions are ordered by their first square.
+    #[must_use]
+    pub fn regions(&self) -> Vec<Vec<(u32, u32)>> {
+        regions(
+            self.row_count(),
+            self.column_count(),
+            &self.blocked_squares(),
+        )
+    }
+}
+
+// The result of `solve`
+pub(crate) struct SplitSolutions {
+    pub(crate) solutions: Vec<Position>,
+    pub(crate) nodes: u64,
+    pub(crate) backtracks: u64,
+    // Whether the search was abandoned
+    pub(crate) stopped: bool,
+}
+
+// A search of the regions of a board, with the solutions of each region for
+// each share of the pieces
+struct Split<'a> {
+    row_count: u32,
+    column_count: u32,
+    regions: &'a [Vec<(u32, u32)>],
+    stop: &'a dyn Fn() -> bool,
+    max_nodes: u64,
+    limit: u64,
+    // The solutions of a region, given by index, with the counts of its
+    // pieces. The solutions are of the rectangle around the region.
+    memo: HashMap<(usize, [u32; Piece::count()]), Vec<Position>>,
+    solutions: Vec<Position>,
+    nodes: u64,
+    backtracks: u64,
+    stopped: bool,
+}
+
+// Find at most `limit` solutions of the board with `blocked` squares by
+// tiling each of `regions` separately. The puzzle must be valid. The
+// solutions are grouped by how the pieces are shared out, so they're not in
+// canonical order.
+pub(crate) fn solve(
+    row_count: u32,
+    column_count: u32,
+    regions: &[Vec<(u32, u32)>],
+    pieces: &PieceCollection,
+    limit: u64,
+    max_nodes: u64,
+    stop: &dyn Fn() -> bool,
+) -> SplitSolutions {
+    let mut split = Split {
+        row_count,
+        column_count,
+        regions,
+        stop,
+        max_nodes,
+        limit,
+        memo: HashMap::new(),
+        solutions: vec![],
+        nodes: 0,
+        backtracks: 0,
+        stopped: false,
+    };
+    if regions.iter().all(|region| region.len() % 4 == 0) {
+        split.share(&mut vec![], pieces.counts);
+    }
+    SplitSolutions {
+        solutions: split.solutions,
+        nodes: split.nodes,
+        backtracks: split.backtracks,
+        stopped: split.stopped,
+    }
+}
+
+impl Split<'_> {
+    // Share the `remaining` pieces out between the regions after those in
+    // `shares`, and combine the solutions of the regions for each way
+    fn share(&mut self, shares: &mut Vec<[u32; Piece::count()]>, remaining: [u32; Piece::count()]) {
+        if self.solutions.len() as u64 >= self.limit || self.stopped {
+            return;
+        }
+        let region = shares.len();
+        if region + 1 == self.regions.len() {
+            shares.push(remaining);
+            self.combine(shares);
+            shares.pop();
+            return;
+        }
+        let size = self.regions[region].len() as u32 / 4;
+        for share in sub_collections(remaining, size) {
+            let mut rest = remaining;
+            for (rest, count) in rest.iter_mut().zip(&share) {
+                *rest -= count;
+            }
+            shares.push(share);
+            self.share(shares, rest);
+            shares.pop();
+        }
+    }
+
+    // Add the solutions of the whole board with each region given the pieces
+    // of its share
+    fn combine(&mut self, shares: &[[u32; Piece::count()]]) {
+        for (region, &share) in shares.iter().enumerate() {
+            if self.region_solutions(region, share).is_empty() {
+                return;
+            }
+        }
+        let solutions: Vec<&[Position]> = shares
+            .iter()
+            .enumerate()
+            .map(|(region, &share)| self.memo[&(region, share)].as_slice())
+            .collect();
+
+        // The solution of each region to combine, counting like an odometer
+        let mut chosen = vec![0; solutions.len()];
+        let mut combined = vec![];
+        loop {
+            combined.push(self.combined(&solutions, &chosen));
+            if (self.solutions.len() + combined.len()) as u64 >= self.limit {
+                break;
+            }
+            let Some(region) = (0..chosen.len())
+                .rev()
+                .find(|&region| chosen[region] + 1 < solutions[region].len())
+            else {
+                break;
+            };
+            chosen[region] += 1;
+            for chosen in &mut chosen[region + 1..] {
+                *chosen = 0;
+            }
+        }
+        self.solutions.extend(combined);
+    }
+
+    // The solutions of `region` with the pieces `counts`, searched for on
+    // the rectangle around the region the first time
+    fn region_solutions(&mut self, region: usize, counts: [u32; Piece::count()]) -> &[Position] {
+        if !self.memo.contains_key(&(region, counts)) {
+            let solutions = self.solve_region(region, counts);
+            self.memo.insert((region, counts), solutions);
+        }
+        &self.memo[&(region, counts)]
+    }
+
+    fn solve_region(&mut self, region: usize, counts: [u32; Piece::count()]) -> Vec<Position> {
+        if self.stopped {
+            return vec![];
+        }
+        let squares = &self.regions[region];
+        let (top, left, bottom, right) = bounds(squares);
+        let mut board = Board::new(bottom - top + 1, right - left + 1);
+        for row in top..=bottom {
+            for column in left..=right {
+                if squares.binary_search(&(row, column)).is_err() {
+                    board.block(row - top, column - left).unwrap();
+                }
+            }
+        }
+        let mut solver = Solver::new(board, PieceCollection { counts })
+            .with_stop(self.stop)
+            .with_max_nodes(self.max_nodes.saturating_sub(self.nodes))
+            .with_max_solutions(self.limit);
+        let mut solutions = vec![];
+        solver.for_each_solution(&mut |board| solutions.push(board.position()));
+        self.nodes += solver.nodes;
+        self.backtracks += solver.backtracks;
+        self.stopped |= solver.stopped;
+        solutions
+    }
+
+    // The whole board with the `chosen` solution of each region
+    fn combined(&self, solutions: &[&[Position]], chosen: &[usize]) -> Position {
+        let width = self.column_count as usize + 1;
+        let mut squares = vec![b'#'; self.row_count as usize * width];
+        for row in 0..self.row_count as usize {
+            squares[row * width + width - 1] = b'\n';
+        }
+        // Labels are made distinct by counting on from those of the regions
+        // before
+        let mut offset = 0;
+        for (region, (solutions, &chosen)) in solutions.iter().zip(chosen).enumerate() {
+            let solution = &solutions[chosen];
+            let (top, left, _, right) = bounds(&self.regions[region]);
+            let region_width = (right - left + 2) as usize;
+            let mut label_count = 0;
+            for &(row, column) in &self.regions[region] {
+                let label = solution.squares
+                    [(row - top) as usize * region_width + (column - left) as usize]
+                    - b'A';
+                label_count = label_count.max(label + 1);
+                squares[row as usize * width + column as usize] = b'A' + offset + label;
+            }
+            offset += label_count;
+        }
+        Position { squares }.relabel(LabelOrder::ByPosition)
+    }
+}
+
+// The top, left, bottom and right of the rectangle around `squares`
+fn bounds(squares: &[(u32, u32)]) -> (u32, u32, u32, u32) {
+    let top = squares.iter().map(|&(row, _)| row).min().unwrap();
+    let bottom = squares.iter().map(|&(row, _)| row).max().unwrap();
+    let left = squares.iter().map(|&(_, column)| column).min().unwrap();
+    let right = squares.iter().map(|&(_, column)| column).max().unwrap();
+    (top, left, bottom, right)
+}
+
+// The collections of `size` pieces taken from `counts`, as counts
+fn sub_collections(counts: [u32; Piece::count()], size: u32) -> Vec<[u32; Piece::count()]> {
+    fn add(
+        counts: &[u32; Piece::count()],
+        kind: usize,
+        left: u32,
+        current: &mut [u32; Piece::count()],
+        result: &mut Vec<[u32; Piece::count()]>,
+    ) {
+        if kind == Piece::count() {
+            if left == 0 {
+                result.push(*current);
+            }
+            return;
+        }
+        for count in (0..=counts[kind].min(left)).rev() {
+            current[kind] = count;
+            add(counts, kind + 1, left - count, current, result);
+        }
+        current[kind] = 0;
+    }
+
+    let mut result = vec![];
+    add(&counts, 0, size, &mut [0; Piece::count()], &mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sub_collections;
+    use crate::{BoardShape, Solve};
+
+    #[test]
+    fn regions() {
+        let shape: BoardShape = "2.#2./2.#2./5#/5.".parse().unwrap();
+        let regions = shape.regions();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0], [(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(regions[1], [(0, 3), (0, 4), (1, 3), (1, 4)]);
+        assert_eq!(regions[2].len(), 5);
+        assert!(BoardShape::rectangle(0, 0).regions().is_empty());
+    }
+
+    #[test]
+    fn shares() {
+        let shares = sub_collections([2, 1, 0, 0, 0, 0, 1], 2);
+        assert_eq!(
+            shares,
+            [
+                [2, 0, 0, 0, 0, 0, 0],
+                [1, 1, 0, 0, 0, 0, 0],
+                [1, 0, 0, 0, 0, 0, 1],
+                [0, 1, 0, 0, 0, 0, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_regions() {
+        // Two 4 by 4 regions either side of a blocked column
+        let blocked: Vec<_> = (0..4).map(|row| (row, 4)).collect();
+        let solve = || Solve::board(4, 9).pieces("IIOOLLJJ").blocked(&blocked);
+        let split = solve().all().split_regions(true).run().unwrap();
+        let whole = solve().all().run().unwrap();
+        let mut expected: Vec<_> = whole.solutions.iter().map(ToString::to_string).collect();
+        let mut found: Vec<_> = split.solutions.iter().map(ToString::to_string).collect();
+        expected.sort();
+        found.sort();
+        assert_eq!(found, expected);
+        assert!(split.stats.nodes < whole.stats.nodes);
+
+        let first = solve().split_regions(true).run().unwrap();
+        assert_eq!(first.solutions, split.solutions[..1]);
+
+        // A region that isn't a multiple of four squares
+        let result = Solve::board(2, 7)
+            .pieces("IIO")
+            .blocked(&[(0, 1), (1, 1)])
+            .split_regions(true)
+            .run()
+            .unwrap();
+        assert!(result.solutions.is_empty() && !result.abandoned);
+    }
+}
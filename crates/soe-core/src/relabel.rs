@@ -0,0 +1,135 @@
+// Relabelling the pieces of a position. The labels a search gives depend on
+// the order it placed the pieces in, so two searches can label the same
+// tiling differently. Relabelling both by position makes them read the same.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{Piece, Position};
+
+// The order in which pieces are labelled 'A', 'B', ...
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LabelOrder {
+    // The order of the current labels, the order the search placed the pieces
+    // in. Only closes the gaps between labels.
+    ByPlacement,
+    // The kind of the piece in the order IOTJLSZ, then the position
+    ByKind,
+    // The first square of the piece in row-major order, i.e. top-left first.
+    // The canonical first solution is labelled in this order.
+    #[default]
+    ByPosition,
+}
+
+#[derive(Debug)]
+pub struct ParseLabelOrderError;
+
+impl Display for ParseLabelOrderError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The label order must be placement, kind or position.")
+    }
+}
+
+impl Error for ParseLabelOrderError {}
+
+impl FromStr for LabelOrder {
+    type Err = ParseLabelOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "placement" => Ok(LabelOrder::ByPlacement),
+            "kind" => Ok(LabelOrder::ByKind),
+            "position" => Ok(LabelOrder::ByPosition),
+            _ => Err(ParseLabelOrderError),
+        }
+    }
+}
+
+impl Position {
+    // The position with its pieces labelled 'A', 'B', ... in `order`. Labels
+    // whose squares don't form a tetromino are put after the pieces when
+    // labelling by kind. Positions with the same tiling get the same labels,
+    // except by placement, which keeps the order of the current labels.
+    #[must_use]
+    pub fn relabel(&self, order: LabelOrder) -> Self {
+        // The labels in order of their first square
+        let mut labels: Vec<u8> = vec![];
+        for &square in &self.squares {
+            if square.is_ascii_alphabetic() && !labels.contains(&square) {
+                labels.push(square);
+            }
+        }
+        match order {
+            LabelOrder::ByPlacement => labels.sort_unstable(),
+            LabelOrder::ByKind => {
+                let label_pieces = self.label_pieces();
+                let kind = |label: &u8| {
+                    label_pieces
+                        .get(label)
+                        .map_or(Piece::count(), |&p| p as usize)
+                };
+                labels.sort_by_key(kind);
+            }
+            LabelOrder::ByPosition => {}
+        }
+
+        let mut new_labels = [0; 256];
+        for (&label, new_label) in labels.iter().zip(LABELS) {
+            new_labels[label as usize] = *new_label;
+        }
+        let squares = self
+            .squares
+            .iter()
+            .map(|&square| match new_labels[square as usize] {
+                0 => square,
+                new_label => new_label,
+            })
+            .collect();
+        Position { squares }
+    }
+}
+
+// The labels in the order they are given out
+const LABELS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+#[cfg(test)]
+mod tests {
+    use super::LabelOrder::*;
+    use crate::{solve_first_canonical, Position};
+
+    fn relabel(position: &str, order: super::LabelOrder) -> String {
+        position
+            .parse::<Position>()
+            .unwrap()
+            .relabel(order)
+            .to_string()
+    }
+
+    #[test]
+    fn orders() {
+        // An I, a J and an O
+        let position = "BBBA\nBCCA\n.CCA\n...A\n";
+        assert_eq!(relabel(position, ByPlacement), position);
+        assert_eq!(relabel(position, ByPosition), "AAAB\nACCB\n.CCB\n...B\n");
+        assert_eq!(relabel(position, ByKind), "CCCA\nCBBA\n.BBA\n...A\n");
+        // Gaps are closed
+        assert_eq!(relabel("XXXX\nQQQQ\n", ByPlacement), "BBBB\nAAAA\n");
+    }
+
+    #[test]
+    fn canonical_solution_is_labelled_by_position() {
+        let solution = solve_first_canonical(4, 6, "TTLSZL".parse().unwrap())
+            .unwrap()
+            .unwrap();
+        let relabelled = solution.relabel(ByPosition);
+        assert_eq!(relabelled.to_string(), solution.to_string());
+        assert_eq!(relabelled.relabel(ByKind), solution.relabel(ByKind));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("kind".parse::<super::LabelOrder>().ok(), Some(ByKind));
+        assert!("size".parse::<super::LabelOrder>().is_err());
+    }
+}
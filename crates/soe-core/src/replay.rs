@@ -0,0 +1,174 @@
+// The replay format lists the placements of a position in order, separated by
+// "; ". Each placement is written as the fixed tetromino followed by the row
+// and column of its top-left square, e.g. "L1@r0c0; T3@r1c2". A replay doesn't
+// include the size of the board.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{board_fits, Board, BoardShape, FixedPiece, PlaceAtError, Placement, Position};
+
+#[derive(Debug)]
+pub enum ParseReplayError {
+    // A placement isn't of the form "T3@r1c2"
+    InvalidPlacement,
+    // A placement doesn't fit on the board
+    IllegalPlacement(PlaceAtError),
+    // The board is too large for the search, see `board_fits`
+    BoardTooLarge { row_count: u32, column_count: u32 },
+}
+
+impl Display for ParseReplayError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ParseReplayError::*;
+        match self {
+            InvalidPlacement => write!(f, "The replay contains an invalid placement."),
+            IllegalPlacement(err) => write!(f, "The replay contains an illegal placement. {err}"),
+            BoardTooLarge {
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "A board of {row_count} by {column_count} squares is too large to replay on."
+            ),
+        }
+    }
+}
+
+impl Error for ParseReplayError {}
+
+impl Position {
+    // Write the placements of the position in the replay format.
+    #[must_use]
+    pub fn to_replay(&self) -> String {
+        self.placements()
+            .iter()
+            .map(|p| format!("{}@r{}c{}", p.piece, p.row, p.column))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    // Replay the placements in `replay` on an empty `row_count` by
+    // `column_count` board. The pieces are labelled in the order in which they
+    // are placed.
+    pub fn from_replay(
+        row_count: u32,
+        column_count: u32,
+        replay: &str,
+    ) -> Result<Self, ParseReplayError> {
+        Self::from_replay_on(&BoardShape::rectangle(row_count, column_count), replay)
+    }
+
+    // Replay the placements in `replay` on an empty board of `shape`. Pieces
+    // may not cover its blocked squares, which are '#' in the position.
+    pub fn from_replay_on(shape: &BoardShape, replay: &str) -> Result<Self, ParseReplayError> {
+        let (row_count, column_count) = (shape.row_count(), shape.column_count());
+        if !board_fits(row_count, column_count) {
+            return Err(ParseReplayError::BoardTooLarge {
+                row_count,
+                column_count,
+            });
+        }
+        let mut board = Board::new(shape.row_count(), shape.column_count());
+        for (row, column) in shape.blocked_squares() {
+            board
+                .block(row, column)
+                .map_err(ParseReplayError::IllegalPlacement)?;
+        }
+        for placement in replay.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let placement = parse_placement(placement).ok_or(ParseReplayError::InvalidPlacement)?;
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .map_err(ParseReplayError::IllegalPlacement)?;
+        }
+        Ok(board.position())
+    }
+}
+
+// A single placement in the replay format, e.g. "T3@r1c2"
+impl FromStr for Placement {
+    type Err = ParseReplayError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_placement(s.trim()).ok_or(ParseReplayError::InvalidPlacement)
+    }
+}
+
+pub(crate) fn parse_placement(s: &str) -> Option<Placement> {
+    let (piece, square) = s.split_once('@')?;
+    let square = square.strip_prefix(['r', 'R'])?;
+    let (row, column) = square.split_once(['c', 'C'])?;
+    Some(Placement {
+        piece: piece.trim().parse::<FixedPiece>().ok()?,
+        row: row.parse().ok()?,
+        column: column.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseReplayError;
+    use crate::FixedPiece::T3;
+    use crate::{BoardShape, Placement, Position};
+
+    #[test]
+    fn round_trip() {
+        let replay = "L2@r0c0; J4@r0c3; O1@r1c1; S1@r2c4";
+        let position = Position::from_replay(4, 6, replay).unwrap();
+
+        assert_eq!(
+            position.to_string(),
+            "AAABBB\n\
+             ACC..B\n\
+             .CC.DD\n\
+             ...DD.\n"
+        );
+        assert_eq!(position.to_replay(), replay);
+
+        let placement: Placement = " T3@r1c2 ".parse().unwrap();
+        assert_eq!(
+            (placement.piece, placement.row, placement.column),
+            (T3, 1, 2)
+        );
+        assert!("T3@1c2".parse::<Placement>().is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let position = Position::from_replay(1, 4, "").unwrap();
+
+        assert_eq!(position.to_string(), "....\n");
+        assert_eq!(position.to_replay(), "");
+    }
+
+    #[test]
+    fn shape() {
+        let shape: BoardShape = "4./4#/4.".parse().unwrap();
+        let position = Position::from_replay_on(&shape, "I2@r0c0; I2@r2c0").unwrap();
+        assert_eq!(position.to_string(), "AAAA\n####\nBBBB\n");
+        assert!(Position::from_replay_on(&shape, "I2@r1c0").is_err());
+    }
+
+    #[test]
+    fn invalid_placement() {
+        assert!(Position::from_replay(4, 4, "X1@r0c0").is_err());
+        assert!(Position::from_replay(4, 4, "I1@0,0").is_err());
+    }
+
+    #[test]
+    fn illegal_placement() {
+        assert!(Position::from_replay(4, 4, "I2@r0c1").is_err());
+        assert!(Position::from_replay(4, 4, "O1@r0c0; I1@r1c1").is_err());
+    }
+
+    #[test]
+    fn board_too_large() {
+        assert!(matches!(
+            Position::from_replay(30, 30, "I1@r0c0"),
+            Err(ParseReplayError::BoardTooLarge {
+                row_count: 30,
+                column_count: 30
+            })
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+// The pseudo-random number generator used by every randomized feature, so
+// that a seed reproduces a result everywhere. It's SplitMix64, which is small
+// and fast and good enough for choosing solutions and orderings. It implements
+// `rand_core::RngCore` and `SeedableRng`, so it can be used with the `rand`
+// crates, and a `u64` seed is the state itself.
+
+use rand_core::{RngCore, SeedableRng};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    // A number less than `bound`, which must not be zero. The modulo bias is
+    // negligible for the small bounds used in this crate.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "empty range");
+        self.next_u64() % bound
+    }
+
+    // Shuffle `items` uniformly with the Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+impl SeedableRng for Rng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::seed_from_u64(u64::from_le_bytes(seed))
+    }
+
+    // The default mixes the seed with another generator first, which would
+    // change every seeded result
+    fn seed_from_u64(state: u64) -> Self {
+        Self { state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{RngCore, SeedableRng};
+
+    use super::Rng;
+
+    #[test]
+    fn reproducible() {
+        // The first outputs of SplitMix64 seeded with 1234567
+        let mut rng = Rng::seed_from_u64(1_234_567);
+        assert_eq!(rng.next_u64(), 6_457_827_717_110_365_317);
+        assert_eq!(rng.next_u64(), 3_203_168_211_198_807_973);
+
+        let mut a = Rng::seed_from_u64(42);
+        let mut b = a.clone();
+        let mut items: Vec<_> = (0..10).collect();
+        a.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+        let mut again: Vec<_> = (0..10).collect();
+        b.shuffle(&mut again);
+        assert_eq!(items, again);
+
+        let mut bytes = [0; 12];
+        Rng::seed_from_u64(7).fill_bytes(&mut bytes);
+        assert_eq!(bytes[..8], Rng::seed_from_u64(7).next_u64().to_le_bytes());
+        assert_eq!(Rng::from_seed(7u64.to_le_bytes()), Rng::seed_from_u64(7));
+    }
+}
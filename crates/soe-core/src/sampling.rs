@@ -0,0 +1,151 @@
+// Sampling the solutions of puzzles with too many to enumerate. Each probe
+// walks down the search tree from the empty board, placing a piece chosen at
+// random from those that fit, until the board is full or no piece fits. A
+// probe reaches a solution with the probability of the choices on its path,
+// so weighting each solution by the product of the number of choices along
+// it and drawing in proportion to the weights gives about uniform samples.
+
+use std::collections::HashMap;
+
+use crate::{
+    validate, Board, FixedPiece, PieceCollection, Position, Rng, RngCore, SeedableRng,
+    SolveOneError, PIECE_MAP,
+};
+
+// The successful probes made for each solution asked for. More probes make
+// the samples closer to uniform.
+const PROBES_PER_SAMPLE: usize = 64;
+
+// The most probes made for each solution asked for, for puzzles where most
+// probes end without a solution
+const MAX_PROBES_PER_SAMPLE: usize = 10_000;
+
+// Up to `count` distinct solutions of the puzzle, about uniformly at random,
+// without enumerating them all. Fewer are returned if the probes found fewer,
+// e.g. because the puzzle has fewer solutions or hardly any probe reaches one.
+// The same seed gives the same solutions.
+pub fn sample_solutions(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<Position>, SolveOneError> {
+    validate(row_count, column_count, pieces)?;
+
+    let mut rng = Rng::seed_from_u64(seed);
+    // The total weight of each solution found, in the order found
+    let mut weights: HashMap<Position, f64> = HashMap::new();
+    let mut found = vec![];
+    let mut successes = 0;
+    for _ in 0..count * MAX_PROBES_PER_SAMPLE {
+        if successes >= count * PROBES_PER_SAMPLE {
+            break;
+        }
+        let mut board = Board::new(row_count, column_count);
+        if let Some(weight) = probe(&mut board, pieces.clone(), &mut rng) {
+            successes += 1;
+            let position = board.position();
+            let total = weights.entry(position.clone()).or_insert_with(|| {
+                found.push(position);
+                0.0
+            });
+            *total += weight;
+        }
+    }
+
+    // Draw without replacement in proportion to the weights
+    let mut solutions: Vec<(Position, f64)> = found
+        .into_iter()
+        .map(|position| {
+            let weight = weights[&position];
+            (position, weight)
+        })
+        .collect();
+    let mut samples = vec![];
+    while samples.len() < count && !solutions.is_empty() {
+        let total: f64 = solutions.iter().map(|&(_, weight)| weight).sum();
+        let mut target = total * f64::from(rng.next_u32()) / 2f64.powi(32);
+        let index = solutions
+            .iter()
+            .position(|&(_, weight)| {
+                target -= weight;
+                target < 0.0
+            })
+            .unwrap_or(solutions.len() - 1);
+        samples.push(solutions.swap_remove(index).0);
+    }
+    Ok(samples)
+}
+
+// Fill `board` with random choices. Returns the product of the number of
+// choices at each step if the board is filled, or `None` at a dead end.
+fn probe(board: &mut Board, mut pieces: PieceCollection, rng: &mut Rng) -> Option<f64> {
+    let mut weight = 1.0;
+    while !board.is_complete() {
+        let mut choices = vec![];
+        let mut candidates = board.candidates() & pieces.fixed_pieces();
+        while candidates != 0 {
+            let fixed_piece = FixedPiece::from(candidates.trailing_zeros() as usize);
+            candidates &= candidates - 1;
+            if board.push(fixed_piece).is_ok() {
+                board.pop();
+                choices.push(fixed_piece);
+            }
+        }
+        if choices.is_empty() {
+            return None;
+        }
+        let fixed_piece = choices[rng.below(choices.len() as u64) as usize];
+        board.push(fixed_piece).unwrap();
+        pieces.remove(PIECE_MAP[fixed_piece as usize]);
+        weight *= f64::from(choices.len() as u32);
+    }
+    Some(weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::sample_solutions;
+    use crate::{solve_all, PieceCollection, SolveOneError};
+
+    #[test]
+    fn all_solutions_of_small_puzzles() {
+        let pieces: PieceCollection = "IIOO".parse().unwrap();
+        let mut samples = sample_solutions(4, 4, &pieces, 10, 1).unwrap();
+        let mut solutions = solve_all(4, 4, pieces).unwrap();
+        samples.sort_by_key(ToString::to_string);
+        solutions.sort_by_key(ToString::to_string);
+        assert_eq!(samples, solutions);
+
+        assert_eq!(
+            sample_solutions(4, 4, &"LLZZ".parse().unwrap(), 3, 1).unwrap(),
+            sample_solutions(4, 4, &"LLZZ".parse().unwrap(), 3, 1).unwrap()
+        );
+        assert!(matches!(
+            sample_solutions(3, 4, &"LL".parse().unwrap(), 3, 1),
+            Err(SolveOneError::InconsistentPieceCount { .. })
+        ));
+    }
+
+    #[test]
+    fn about_uniform() {
+        // Each of the two I pieces on the top-left square leaves two
+        // solutions and each of the other pieces one, so choosing uniformly
+        // at each step would find the solutions with an I half as often
+        let pieces: PieceCollection = "IJLZ".parse().unwrap();
+        let solutions = solve_all(4, 4, pieces.clone()).unwrap();
+        assert_eq!(solutions.len(), 8);
+        let mut counts = HashMap::new();
+        for seed in 0..320 {
+            let sample = sample_solutions(4, 4, &pieces, 1, seed).unwrap();
+            *counts.entry(sample[0].clone()).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 8);
+        for count in counts.values() {
+            assert!((22..=58).contains(count), "{:?}", counts);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Serde support, with the `serde` feature. Fixed pieces, piece collections and
+// positions are written in the text formats they're parsed from, e.g. "L1",
+// "LLZZ" and "AAAB\nACBB\nCCBD\nCDDD\n", so that they read well in JSON and
+// can't be deserialized into values the solver would reject.
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{FixedPiece, PieceCollection, Position};
+
+impl Serialize for FixedPiece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FixedPiece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for PieceCollection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_canonical_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for PieceCollection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Outcome, Placement, PuzzleSpec};
+
+    #[test]
+    fn round_trip() {
+        let puzzle = PuzzleSpec::new(4, 4, "ZZLL".parse().unwrap());
+        let json = serde_json::to_string(&puzzle).unwrap();
+        assert_eq!(json, r#"{"row_count":4,"column_count":4,"pieces":"LLZZ"}"#);
+        let puzzle: PuzzleSpec = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&puzzle).unwrap(), json);
+
+        let outcome = Outcome::Solved("AAAB\nACBB\nCCBD\nCDDD\n".parse().unwrap());
+        let json = serde_json::to_string(&outcome).unwrap();
+        assert_eq!(json, r#"{"Solved":"AAAB\nACBB\nCCBD\nCDDD\n"}"#);
+        assert_eq!(serde_json::from_str::<Outcome>(&json).unwrap(), outcome);
+
+        let placement: Placement =
+            serde_json::from_str(r#"{"piece":"t3","row":1,"column":2}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&placement).unwrap(),
+            r#"{"piece":"T3","row":1,"column":2}"#
+        );
+
+        assert!(serde_json::from_str::<PuzzleSpec>(
+            r#"{"row_count":4,"column_count":4,"pieces":"LLXZ"}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,183 @@
+// A solving session for interactive editors. The puzzle is edited one piece
+// or one blocked square at a time and re-solved after each edit. Results are
+// cached by puzzle, so undoing an edit doesn't search again, and the last
+// solution is used as a warm start for the next search.
+
+use std::collections::HashMap;
+
+use crate::{
+    board_fits, validate_blocked, warm_start, Board, Piece, PieceCollection, Position,
+    SolveOneError, Solver,
+};
+
+pub struct Session {
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    // Bit `row * column_count + column` is set if the square is blocked
+    blocked: u64,
+    // The last solution found, the warm start for the next search
+    last_solution: Option<Position>,
+    // The results of the puzzles solved so far, by piece counts and blocked
+    // squares
+    results: HashMap<([u32; Piece::count()], u64), Option<Position>>,
+}
+
+impl Session {
+    // Fails with `BoardTooLarge` if the search can't handle the board, whose
+    // squares must also fit in `blocked`
+    pub fn new(
+        row_count: u32,
+        column_count: u32,
+        pieces: PieceCollection,
+    ) -> Result<Self, SolveOneError> {
+        if !board_fits(row_count, column_count) {
+            return Err(SolveOneError::BoardTooLarge {
+                row_count,
+                column_count,
+            });
+        }
+        Ok(Self {
+            row_count,
+            column_count,
+            pieces,
+            blocked: 0,
+            last_solution: None,
+            results: HashMap::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn pieces(&self) -> &PieceCollection {
+        &self.pieces
+    }
+
+    pub fn add_piece(&mut self, piece: Piece) {
+        self.pieces.add(piece);
+    }
+
+    // Remove one `piece` from the collection. Returns false if there is none.
+    pub fn remove_piece(&mut self, piece: Piece) -> bool {
+        if self.pieces.count(piece) == 0 {
+            return false;
+        }
+        self.pieces.remove(piece);
+        true
+    }
+
+    #[must_use]
+    pub fn is_blocked(&self, row: u32, column: u32) -> bool {
+        self.blocked & self.square_bit(row, column) != 0
+    }
+
+    // Block the square at `row`, `column` if it's open and open it if it's
+    // blocked. Panics if the square is off the board.
+    pub fn toggle_blocked(&mut self, row: u32, column: u32) {
+        self.blocked ^= self.square_bit(row, column);
+    }
+
+    fn square_bit(&self, row: u32, column: u32) -> u64 {
+        assert!(
+            row < self.row_count && column < self.column_count,
+            "square off the board"
+        );
+        1 << (row * self.column_count + column)
+    }
+
+    // Solve the puzzle as it is now. A puzzle that was solved before in this
+    // session isn't searched again.
+    pub fn solve(&mut self) -> Result<Option<Position>, SolveOneError> {
+        validate_blocked(
+            self.row_count,
+            self.column_count,
+            self.blocked.count_ones(),
+            &self.pieces,
+        )?;
+
+        let key = (self.pieces.counts, self.blocked);
+        if let Some(result) = self.results.get(&key) {
+            return Ok(result.clone());
+        }
+
+        let mut board = Board::new(self.row_count, self.column_count);
+        for row in 0..self.row_count {
+            for column in 0..self.column_count {
+                if self.is_blocked(row, column) {
+                    board.block(row, column).unwrap();
+                }
+            }
+        }
+        let pieces = self.pieces.clone();
+        let result = match &self.last_solution {
+            Some(hint) => warm_start(&board, &pieces, hint),
+            None => Solver::new(board, pieces).solve_one(),
+        };
+
+        if result.is_some() {
+            self.last_solution.clone_from(&result);
+        }
+        self.results.insert(key, result.clone());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::Piece::*;
+    use crate::SolveOneError;
+
+    #[test]
+    fn edit_pieces() {
+        let mut session = Session::new(4, 4, "IIOO".parse().unwrap()).unwrap();
+        let solution = session.solve().unwrap().unwrap();
+        assert_eq!(solution.to_string(), "ABCC\nABCC\nABDD\nABDD\n");
+
+        assert!(session.remove_piece(O));
+        session.add_piece(T);
+        session.add_piece(T);
+        assert!(session.solve().is_err());
+        assert!(session.remove_piece(O));
+        assert!(session.solve().unwrap().is_none());
+
+        session.add_piece(O);
+        session.add_piece(O);
+        assert!(session.remove_piece(T));
+        assert!(session.remove_piece(T));
+        assert!(!session.remove_piece(T));
+        // Solved before, so the same solution is returned
+        let again = session.solve().unwrap().unwrap();
+        assert!(again == solution);
+    }
+
+    #[test]
+    fn toggle_blocked() {
+        let mut session = Session::new(3, 4, "IO".parse().unwrap()).unwrap();
+        session.toggle_blocked(1, 2);
+        session.toggle_blocked(1, 3);
+        session.toggle_blocked(2, 2);
+        session.toggle_blocked(2, 3);
+        assert!(session.is_blocked(2, 2));
+        let solution = session.solve().unwrap().unwrap();
+        assert_eq!(solution.to_string(), "AAAA\nBB##\nBB##\n");
+
+        session.toggle_blocked(2, 2);
+        assert!(!session.is_blocked(2, 2));
+        assert!(session.solve().is_err());
+    }
+
+    #[test]
+    fn board_too_large() {
+        assert!(matches!(
+            Session::new(10, 10, "I".parse().unwrap()),
+            Err(SolveOneError::BoardTooLarge {
+                row_count: 10,
+                column_count: 10
+            })
+        ));
+        // The largest boards have a bit for every square
+        let mut session = Session::new(7, 8, "I".parse().unwrap()).unwrap();
+        session.toggle_blocked(6, 7);
+        assert!(session.is_blocked(6, 7));
+    }
+}
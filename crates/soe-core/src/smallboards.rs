@@ -0,0 +1,337 @@
+// Boards at most three squares wide, like the bonus levels of the mobile game
+// and many generated daily puzzles, are solved column by column instead of
+// with the general search. The first empty square in column-major order is
+// covered next, and the result for the pieces left with the squares covered
+// so far is remembered. Only the few columns after the first empty square can
+// be partly covered, so there are few such states and counting or finding
+// tilings takes time linear in the length of the board rather than
+// exponential.
+//
+//     if smallboards::is_small(3, 16) {
+//         let count = smallboards::count(3, 16, &pieces)?;
+//     }
+
+use std::collections::{HashMap, HashSet};
+
+use crate::geometry::Shape;
+use crate::{
+    validate_squares, FixedPiece, Piece, PieceCollection, Position, SolveOneError, PIECE_MAP,
+};
+
+// The widest board solved here
+const MAX_WIDTH: u32 = 3;
+
+// Whether the board is narrow enough to be solved here
+#[must_use]
+pub fn is_small(row_count: u32, column_count: u32) -> bool {
+    row_count.min(column_count) <= MAX_WIDTH
+}
+
+// Solve the puzzle on a small board. The solution is not the same as
+// `solve_first_canonical` gives.
+//
+// Panics if the board isn't small.
+pub fn solve(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+) -> Result<Option<Position>, SolveOneError> {
+    assert!(is_small(row_count, column_count));
+    validate_squares(row_count, column_count, 0, pieces)?;
+    if row_count > column_count {
+        let solution = solve(column_count, row_count, &pieces.transposed())?;
+        return Ok(solution.map(|solution| solution.transposed()));
+    }
+
+    let mut strip = Strip::new(row_count, column_count);
+    let mut placements = vec![];
+    if !strip.search(0, pieces.counts, &mut placements) {
+        return Ok(None);
+    }
+    Ok(Some(strip.position(&placements)))
+}
+
+// The number of solutions of the puzzle on a small board, the length of what
+// `solve_all` gives
+//
+// Panics if the board isn't small.
+pub fn count(
+    row_count: u32,
+    column_count: u32,
+    pieces: &PieceCollection,
+) -> Result<u64, SolveOneError> {
+    assert!(is_small(row_count, column_count));
+    validate_squares(row_count, column_count, 0, pieces)?;
+    if row_count > column_count {
+        return count(column_count, row_count, &pieces.transposed());
+    }
+
+    let mut strip = Strip::new(row_count, column_count);
+    Ok(strip.count(0, pieces.counts))
+}
+
+// A board `row_count` squares high. Square (row, column) is bit
+// `column * row_count + row` of the covered squares.
+struct Strip {
+    row_count: u32,
+    column_count: u32,
+    // Each fixed piece that fits in the height of the board with its squares
+    // relative to its first square in column-major order
+    shapes: Vec<(FixedPiece, Vec<(i32, i32)>)>,
+    // The covered squares and the piece counts that lead to no solution
+    dead_ends: HashSet<(u64, [u32; Piece::count()])>,
+    // The number of ways to cover the rest of the board from the covered
+    // squares with the piece counts
+    counts: HashMap<(u64, [u32; Piece::count()]), u64>,
+}
+
+impl Strip {
+    fn new(row_count: u32, column_count: u32) -> Self {
+        let shapes = FixedPiece::array()
+            .iter()
+            .filter_map(|&fixed_piece| {
+                let Shape(mut cells) = Shape::from(fixed_piece);
+                cells.sort_unstable_by_key(|cell| (cell.1, cell.0));
+                let first = cells[0];
+                let offsets: Vec<_> = cells
+                    .iter()
+                    .map(|cell| (cell.0 - first.0, cell.1 - first.1))
+                    .collect();
+                let top = offsets.iter().map(|offset| offset.0).min().unwrap();
+                let bottom = offsets.iter().map(|offset| offset.0).max().unwrap();
+                (bottom - top < row_count as i32).then_some((fixed_piece, offsets))
+            })
+            .collect();
+        Self {
+            row_count,
+            column_count,
+            shapes,
+            dead_ends: HashSet::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    // Cover the rest of the board with the pieces in `counts`, adding the
+    // squares covered by each piece to `placements`. Returns false if there's
+    // no way.
+    fn search(
+        &mut self,
+        covered: u64,
+        counts: [u32; Piece::count()],
+        placements: &mut Vec<u64>,
+    ) -> bool {
+        let square_count = self.row_count * self.column_count;
+        let first = (!covered).trailing_zeros();
+        if first >= square_count {
+            return true;
+        }
+        if self.dead_ends.contains(&(covered, counts)) {
+            return false;
+        }
+
+        for (fixed_piece, bits) in self.moves(covered, first, counts) {
+            let piece = PIECE_MAP[fixed_piece as usize];
+            let mut remaining = counts;
+            remaining[piece as usize] -= 1;
+            placements.push(bits);
+            if self.search(covered | bits, remaining, placements) {
+                return true;
+            }
+            placements.pop();
+        }
+
+        self.dead_ends.insert((covered, counts));
+        false
+    }
+
+    // The number of ways to cover the rest of the board with the pieces in
+    // `counts`
+    fn count(&mut self, covered: u64, counts: [u32; Piece::count()]) -> u64 {
+        let square_count = self.row_count * self.column_count;
+        let first = (!covered).trailing_zeros();
+        if first >= square_count {
+            return 1;
+        }
+        if let Some(&count) = self.counts.get(&(covered, counts)) {
+            return count;
+        }
+
+        let mut total = 0;
+        for (fixed_piece, bits) in self.moves(covered, first, counts) {
+            let piece = PIECE_MAP[fixed_piece as usize];
+            let mut remaining = counts;
+            remaining[piece as usize] -= 1;
+            total += self.count(covered | bits, remaining);
+        }
+
+        self.counts.insert((covered, counts), total);
+        total
+    }
+
+    // The pieces in `counts` that can cover the empty square `first` with
+    // the squares they cover
+    fn moves(
+        &self,
+        covered: u64,
+        first: u32,
+        counts: [u32; Piece::count()],
+    ) -> Vec<(FixedPiece, u64)> {
+        let (row, column) = (
+            (first % self.row_count) as i32,
+            (first / self.row_count) as i32,
+        );
+        self.shapes
+            .iter()
+            .filter(|(fixed_piece, _)| counts[PIECE_MAP[*fixed_piece as usize] as usize] > 0)
+            .filter_map(|(fixed_piece, offsets)| {
+                let bits = self.bits(offsets, row, column)?;
+                (covered & bits == 0).then_some((*fixed_piece, bits))
+            })
+            .collect()
+    }
+
+    // The squares covered by a piece with `offsets` from (row, column), or
+    // `None` if the piece doesn't fit on the board there
+    fn bits(&self, offsets: &[(i32, i32)], row: i32, column: i32) -> Option<u64> {
+        let mut bits = 0;
+        for &(row_offset, column_offset) in offsets {
+            let (row, column) = (row + row_offset, column + column_offset);
+            if row < 0 || row >= self.row_count as i32 || column >= self.column_count as i32 {
+                return None;
+            }
+            bits |= 1 << (column as u32 * self.row_count + row as u32);
+        }
+        Some(bits)
+    }
+
+    // The tiling with the pieces covering `placements`, labelled 'A', 'B',
+    // ... in order. `Board` isn't used since its bitboard doesn't fit long
+    // boards lying on their side.
+    fn position(&self, placements: &[u64]) -> Position {
+        let width = self.column_count as usize + 1;
+        let mut squares = vec![b'\n'; self.row_count as usize * width];
+        for (label, bits) in (b'A'..).zip(placements) {
+            for bit in 0..self.row_count * self.column_count {
+                if bits & 1 << bit != 0 {
+                    let (row, column) = (bit % self.row_count, bit / self.row_count);
+                    squares[row as usize * width + column as usize] = label;
+                }
+            }
+        }
+        Position { squares }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, is_small, solve};
+    use crate::{solve_all, solve_first_canonical, Piece, PieceCollection, SolveOneError};
+
+    // Every collection of `piece_count` pieces
+    fn collections(piece_count: u32) -> Vec<PieceCollection> {
+        let mut collections = vec![];
+        let mut counts = [0; Piece::count()];
+        add_collections(0, piece_count, &mut counts, &mut collections);
+        collections
+    }
+
+    // Add the collections with the counts of the kinds before `kind` in
+    // `counts` and `left` more pieces
+    fn add_collections(
+        kind: usize,
+        left: u32,
+        counts: &mut [u32; Piece::count()],
+        collections: &mut Vec<PieceCollection>,
+    ) {
+        if kind == Piece::count() - 1 {
+            counts[kind] = left;
+            collections.push(PieceCollection { counts: *counts });
+            return;
+        }
+        for count in 0..=left {
+            counts[kind] = count;
+            add_collections(kind + 1, left - count, counts, collections);
+        }
+    }
+
+    #[test]
+    fn same_as_the_general_search() {
+        let sizes = [(2, 8), (8, 2), (2, 10), (1, 12), (12, 1), (3, 8), (8, 3)];
+        for &(row_count, column_count) in &sizes {
+            for pieces in collections(row_count * column_count / 4) {
+                let Ok(expected) = solve_first_canonical(row_count, column_count, pieces.clone())
+                else {
+                    continue;
+                };
+                let solution = solve(row_count, column_count, &pieces).unwrap();
+                assert_eq!(
+                    solution.is_some(),
+                    expected.is_some(),
+                    "{row_count}x{column_count} {}",
+                    pieces.to_canonical_string()
+                );
+                if let Some(solution) = solution {
+                    assert_eq!(solution.verify(row_count, column_count, &pieces), Ok(()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_board_without_solution() {
+        // T pieces can't cover the corners of a board two squares wide
+        let pieces: PieceCollection = "TTTTTTTTTTTT".parse().unwrap();
+        assert!(solve(2, 24, &pieces).unwrap().is_none());
+        assert_eq!(count(2, 24, &pieces).unwrap(), 0);
+    }
+
+    #[test]
+    fn counts() {
+        for &(row_count, column_count) in &[(2, 8), (1, 8), (3, 8), (8, 3)] {
+            for pieces in collections(row_count * column_count / 4) {
+                let Ok(expected) = solve_all(row_count, column_count, pieces.clone()) else {
+                    continue;
+                };
+                assert_eq!(
+                    count(row_count, column_count, &pieces).unwrap(),
+                    expected.len() as u64,
+                    "{row_count}x{column_count} {}",
+                    pieces.to_canonical_string()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn long_board() {
+        // Two L pieces, two J pieces or two I pieces cover each 2x4 block
+        let pieces: PieceCollection = "IIIIIILLLLJJ".parse().unwrap();
+        assert!(solve(2, 24, &pieces).unwrap().is_some());
+        assert!(count(2, 24, &pieces).unwrap() > 1);
+        // The I pieces can only lie along the rows of a board three squares
+        // high
+        let pieces: PieceCollection = "IIIIIIIIIIII".parse().unwrap();
+        assert_eq!(count(3, 16, &pieces).unwrap(), 1);
+    }
+
+    #[test]
+    fn invalid_puzzle() {
+        assert!(matches!(
+            count(3, 4, &"LL".parse().unwrap()),
+            Err(SolveOneError::InconsistentPieceCount { .. })
+        ));
+        assert!(matches!(
+            solve(1, 8, &"IO".parse().unwrap()),
+            Err(SolveOneError::PieceDoesntFit {
+                piece: Piece::O,
+                width: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn small() {
+        assert!(is_small(1, 8) && is_small(2, 4) && is_small(12, 2) && is_small(3, 8));
+        assert!(!is_small(4, 4) && !is_small(5, 8));
+    }
+}
@@ -0,0 +1,357 @@
+// A solution database holds all the solutions of a puzzle together with an
+// index of the solutions in which each square is covered by each kind of
+// piece. It can be written to a file once and queried without searching again.
+//
+// The file starts with the header "SOLDB", a version byte, one byte each for
+// the row count, the column count and the number of pieces and the number of
+// solutions as a little-endian u32. The solutions follow in canonical order as
+// fixed-size records of two bytes per placement, in the same format as
+// `Position::encode`. The index comes last: for each square in row-major order
+// and each `Piece`, the number of solutions followed by their numbers, all as
+// little-endian u32s.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+
+use crate::analysis::Heatmap;
+use crate::solutions::{pack, unpack};
+use crate::{
+    board_fits, validate, Board, FixedPiece, Piece, PieceCollection, Placement, Position, Rng,
+    SeedableRng, SolveOneError, Solver,
+};
+
+const MAGIC: &[u8; 5] = b"SOLDB";
+const VERSION: u8 = 1;
+
+pub struct SolutionDb {
+    row_count: u32,
+    column_count: u32,
+    piece_count: usize,
+    // `piece_count` packed placements per solution
+    records: Vec<u16>,
+    // The solution numbers indexed by square in row-major order and then by
+    // `Piece`
+    index: Vec<Vec<u32>>,
+}
+
+#[derive(Debug)]
+pub enum ReadSolutionDbError {
+    Io(io::Error),
+    // The data isn't a solution database or is corrupt
+    InvalidFormat,
+}
+
+impl Display for ReadSolutionDbError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ReadSolutionDbError::*;
+        match self {
+            Io(err) => write!(f, "Failed to read the solution database. {err}"),
+            InvalidFormat => write!(f, "The file is not a valid solution database."),
+        }
+    }
+}
+
+impl Error for ReadSolutionDbError {}
+
+impl From<io::Error> for ReadSolutionDbError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            ReadSolutionDbError::InvalidFormat
+        } else {
+            ReadSolutionDbError::Io(err)
+        }
+    }
+}
+
+impl SolutionDb {
+    // Enumerate all the solutions of the puzzle and index them. Boards must be
+    // smaller than 256 squares.
+    pub fn build(
+        row_count: u32,
+        column_count: u32,
+        pieces: PieceCollection,
+    ) -> Result<Self, SolveOneError> {
+        validate(row_count, column_count, &pieces)?;
+
+        let mut db = SolutionDb {
+            row_count,
+            column_count,
+            piece_count: pieces.count_all() as usize,
+            records: vec![],
+            index: vec![vec![]; (row_count * column_count) as usize * Piece::count()],
+        };
+        let mut solution = 0;
+        let mut solver = Solver::new(Board::new(row_count, column_count), pieces);
+        solver.for_each_solution(&mut |board| {
+            for &entry in &board.stack[..board.stack_count] {
+                db.records
+                    .push(pack(board.placement_of(entry), column_count));
+            }
+            for (row, col, piece) in board.covered_squares() {
+                let square = row * column_count as usize + col;
+                db.index[square * Piece::count() + piece as usize].push(solution);
+            }
+            solution += 1;
+        });
+        Ok(db)
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[
+            VERSION,
+            self.row_count as u8,
+            self.column_count as u8,
+            self.piece_count as u8,
+        ])?;
+        writer.write_all(&(self.len() as u32).to_le_bytes())?;
+        for &record in &self.records {
+            writer.write_all(&record.to_be_bytes())?;
+        }
+        for solutions in &self.index {
+            writer.write_all(&(solutions.len() as u32).to_le_bytes())?;
+            for &solution in solutions {
+                writer.write_all(&solution.to_le_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, ReadSolutionDbError> {
+        let mut header = [0; 9];
+        reader.read_exact(&mut header)?;
+        if header[..5] != MAGIC[..] || header[5] != VERSION {
+            return Err(ReadSolutionDbError::InvalidFormat);
+        }
+        let row_count = u32::from(header[6]);
+        let column_count = u32::from(header[7]);
+        let piece_count = usize::from(header[8]);
+        let solution_count = read_u32(&mut reader)? as usize;
+        let square_count = (row_count * column_count) as usize;
+        if !board_fits(row_count, column_count) || square_count != piece_count * 4 {
+            return Err(ReadSolutionDbError::InvalidFormat);
+        }
+
+        // The counts come from the file, so nothing is allocated for them
+        // before the data is there
+        let mut records = vec![];
+        for _ in 0..solution_count * piece_count {
+            let mut bytes = [0; 2];
+            reader.read_exact(&mut bytes)?;
+            records.push(u16::from_be_bytes(bytes));
+        }
+        let mut index = Vec::with_capacity(square_count * Piece::count());
+        for _ in 0..square_count * Piece::count() {
+            let count = read_u32(&mut reader)? as usize;
+            if count > solution_count {
+                return Err(ReadSolutionDbError::InvalidFormat);
+            }
+            let mut solutions = vec![];
+            for _ in 0..count {
+                solutions.push(read_u32(&mut reader)?);
+            }
+            index.push(solutions);
+        }
+
+        let db = SolutionDb {
+            row_count,
+            column_count,
+            piece_count,
+            records,
+            index,
+        };
+        if (0..db.len()).any(|solution| db.get(solution).is_none()) {
+            return Err(ReadSolutionDbError::InvalidFormat);
+        }
+        Ok(db)
+    }
+
+    #[must_use]
+    pub fn row_count(&self) -> u32 {
+        self.row_count
+    }
+
+    #[must_use]
+    pub fn column_count(&self) -> u32 {
+        self.column_count
+    }
+
+    // The number of solutions
+    #[must_use]
+    pub fn len(&self) -> usize {
+        if self.piece_count == 0 {
+            return 0;
+        }
+        self.records.len() / self.piece_count
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Solution number `solution` in canonical order
+    #[must_use]
+    pub fn get(&self, solution: usize) -> Option<Position> {
+        let start = solution.checked_mul(self.piece_count)?;
+        let records = self.records.get(start..start + self.piece_count)?;
+        let mut board = Board::new(self.row_count, self.column_count);
+        for &record in records {
+            if usize::from(record >> 8) >= FixedPiece::count() {
+                return None;
+            }
+            let placement = unpack(record, self.column_count);
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .ok()?;
+        }
+        Some(board.position())
+    }
+
+    // A solution chosen pseudo-randomly from `seed`. The same seed always
+    // gives the same solution.
+    #[must_use]
+    pub fn random(&self, seed: u64) -> Option<Position> {
+        if self.is_empty() {
+            return None;
+        }
+        let solution = Rng::seed_from_u64(seed).below(self.len() as u64);
+        self.get(solution as usize)
+    }
+
+    // The numbers of the solutions in which the square at `row`, `column` is
+    // covered by `piece`, in ascending order.
+    #[must_use]
+    pub fn containing(&self, piece: Piece, row: u32, column: u32) -> &[u32] {
+        let square = (row * self.column_count + column) as usize;
+        &self.index[square * Piece::count() + piece as usize]
+    }
+
+    // The heatmap of the solutions, like `analysis::heatmap`
+    #[must_use]
+    pub fn heatmap(&self) -> Heatmap {
+        let counts = self
+            .index
+            .chunks(Piece::count())
+            .map(|square| {
+                let mut counts = [0; Piece::count()];
+                for (count, solutions) in counts.iter_mut().zip(square) {
+                    *count = solutions.len() as u64;
+                }
+                counts
+            })
+            .collect();
+        Heatmap::from_counts(self.row_count, self.column_count, self.len() as u64, counts)
+    }
+
+    // The placements that appear in every solution, like
+    // `hints::forced_cells`. Returns `None` if there are no solutions.
+    #[must_use]
+    pub fn forced_cells(&self) -> Option<Vec<Placement>> {
+        let mut solutions = self.records.chunks(self.piece_count.max(1));
+        let mut forced = solutions.next()?.to_vec();
+        for solution in solutions {
+            forced.retain(|record| solution.contains(record));
+        }
+        Some(
+            forced
+                .iter()
+                .map(|&record| unpack(record, self.column_count))
+                .collect(),
+        )
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadSolutionDbError, SolutionDb};
+    use crate::analysis::heatmap;
+    use crate::{hints, solve_all, Piece, PuzzleSpec};
+
+    #[test]
+    fn queries() {
+        let db = SolutionDb::build(4, 4, "IIOO".parse().unwrap()).unwrap();
+        let solutions = solve_all(4, 4, "IIOO".parse().unwrap()).unwrap();
+
+        assert_eq!(db.len(), solutions.len());
+        for (k, solution) in solutions.iter().enumerate() {
+            assert_eq!(db.get(k).unwrap().to_string(), solution.to_string());
+        }
+        assert!(db.get(db.len()).is_none());
+        assert_eq!(
+            db.random(7).unwrap().to_string(),
+            db.random(7).unwrap().to_string()
+        );
+        assert_eq!(db.containing(Piece::O, 1, 1).len(), 4);
+        assert_eq!(db.containing(Piece::I, 0, 0), [0, 1, 2, 3]);
+
+        let heatmap = heatmap(4, 4, "IIOO".parse().unwrap()).unwrap();
+        for row in 0..4 {
+            for column in 0..4 {
+                for &piece in &Piece::array() {
+                    let count = db.containing(piece, row, column).len() as u64;
+                    assert_eq!(count, heatmap.count(row, column, piece));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn write_read() {
+        let db = SolutionDb::build(4, 6, "TTLSZL".parse().unwrap()).unwrap();
+        let mut file = vec![];
+        db.write(&mut file).unwrap();
+        let read = SolutionDb::read(&file[..]).unwrap();
+
+        assert_eq!(read.len(), db.len());
+        assert_eq!(read.records, db.records);
+        assert_eq!(read.index, db.index);
+        assert!(SolutionDb::read(&file[..file.len() - 1]).is_err());
+        assert!(SolutionDb::read(&b"SOLDA"[..]).is_err());
+    }
+
+    #[test]
+    fn read_invalid_header() {
+        // A board too large for the search
+        let file = b"SOLDB\x01\x10\x10\x40\x01\x00\x00\x00";
+        assert!(matches!(
+            SolutionDb::read(&file[..]),
+            Err(ReadSolutionDbError::InvalidFormat)
+        ));
+        // A solution count far beyond the data
+        let file = b"SOLDB\x01\x04\x04\x04\xff\xff\xff\xff\x00\x01";
+        assert!(matches!(
+            SolutionDb::read(&file[..]),
+            Err(ReadSolutionDbError::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn forced_cells() {
+        let db = SolutionDb::build(6, 4, "IOSZJL".parse().unwrap()).unwrap();
+        let puzzle = PuzzleSpec::new(6, 4, "IOSZJL".parse().unwrap());
+        let forced = hints::forced_cells(&puzzle).unwrap();
+
+        assert_eq!(db.forced_cells(), forced);
+        assert_eq!(
+            db.heatmap().to_string(),
+            heatmap(6, 4, puzzle.pieces).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn no_solutions() {
+        let db = SolutionDb::build(4, 4, "IITT".parse().unwrap()).unwrap();
+
+        assert!(db.is_empty());
+        assert!(db.random(0).is_none());
+        assert!(db.forced_cells().is_none());
+    }
+}
@@ -0,0 +1,392 @@
+// Compact storage for large numbers of solutions.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::board::transform;
+use crate::geometry::Symmetry;
+use crate::{
+    board_fits, fits_only_transposed, validate, Board, FixedPiece, PieceCollection, Placement,
+    Position, SolveOneError, Solver, MAX_PIECE_COUNT,
+};
+
+// A set of solutions of one puzzle. Instead of a grid, each solution is stored
+// as its placements, two bytes each, in a single buffer shared by all the
+// solutions. Solutions are expanded to a `Position` on demand.
+pub struct SolutionSet {
+    row_count: u32,
+    column_count: u32,
+    piece_count: usize,
+    // `piece_count` packed placements per solution. See `pack`.
+    placements: Vec<u16>,
+    // Whether the placements are on the transposed board, which is the board
+    // that was searched. `row_count` and `column_count` are then those of the
+    // transposed board.
+    transposed: bool,
+}
+
+// Finds all the solutions of the puzzle in canonical order, like `solve_all`,
+// but stores them compactly. Boards that only fit the bitboard transposed are
+// searched transposed, like in `solve_all`.
+pub fn solve_all_compact(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+) -> Result<SolutionSet, SolveOneError> {
+    if fits_only_transposed(row_count, column_count) {
+        let mut solutions = solve_all_compact(column_count, row_count, pieces.transposed())?;
+        solutions.transposed = true;
+        return Ok(solutions);
+    }
+    validate(row_count, column_count, &pieces)?;
+
+    let mut solutions = SolutionSet {
+        row_count,
+        column_count,
+        piece_count: pieces.count_all() as usize,
+        placements: vec![],
+        transposed: false,
+    };
+    let mut solver = Solver::new(Board::new(row_count, column_count), pieces);
+    solver.for_each_solution(&mut |board| {
+        for &entry in &board.stack[..board.stack_count] {
+            let placement = board.placement_of(entry);
+            solutions.placements.push(pack(placement, column_count));
+        }
+    });
+    Ok(solutions)
+}
+
+// Set in the number of placements of an encoded position when a list of
+// blocked squares follows the placements
+const BLOCKED_FLAG: u8 = 0x80;
+
+// Pack a placement into 16 bits: the fixed tetromino in the high byte and the
+// row-major index of its top-left square in the low byte.
+pub(crate) fn pack(placement: Placement, column_count: u32) -> u16 {
+    let square = placement.row * column_count + placement.column;
+    (placement.piece as u16) << 8 | square as u16
+}
+
+pub(crate) fn unpack(packed: u16, column_count: u32) -> Placement {
+    let square = u32::from(packed & 0xff);
+    Placement {
+        piece: FixedPiece::from(usize::from(packed >> 8)),
+        row: square / column_count,
+        column: square % column_count,
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodePositionError {
+    // The input ends in the middle of a position
+    UnexpectedEnd,
+    // A placement is not a valid fixed tetromino or doesn't fit on the board
+    InvalidPlacement,
+    // There are more than `MAX_PIECE_COUNT` placements
+    TooManyPlacements,
+    // A blocked square is off the board
+    InvalidBlockedSquare,
+    // The board has no rows or no columns
+    EmptyBoard,
+    // The board is too large for the search, see `board_fits`
+    BoardTooLarge { row_count: u32, column_count: u32 },
+}
+
+impl Display for DecodePositionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use DecodePositionError::*;
+        match self {
+            UnexpectedEnd => write!(f, "The encoded position is truncated."),
+            InvalidPlacement => write!(f, "The encoded position contains an invalid placement."),
+            TooManyPlacements => write!(
+                f,
+                "The encoded position has more than {MAX_PIECE_COUNT} placements."
+            ),
+            InvalidBlockedSquare => {
+                write!(
+                    f,
+                    "The encoded position contains an invalid blocked square."
+                )
+            }
+            EmptyBoard => write!(f, "The encoded position has no rows or no columns."),
+            BoardTooLarge {
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "The encoded position's board of {row_count} by {column_count} squares is \
+                 too large."
+            ),
+        }
+    }
+}
+
+impl Error for DecodePositionError {}
+
+// The binary encoding of a position is one byte each for the row count, the
+// column count and the number of placements, followed by two bytes for each
+// placement: the index of the fixed tetromino in `FixedPiece` and the
+// row-major index of its top-left square. If the board has blocked squares,
+// the high bit of the number of placements is set and the placements are
+// followed by a byte with the number of blocked squares and a byte with the
+// row-major index of each. Encoded positions can be concatenated and decoded
+// one after the other.
+impl Position {
+    // Encode the position in the binary format. Panics if the board has more
+    // than 255 rows or columns.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let column_count = self.column_count();
+        let placements = self.placements();
+        let blocked: Vec<u8> = self
+            .squares
+            .iter()
+            .filter(|&&square| square != b'\n')
+            .enumerate()
+            .filter(|&(_, &square)| square == b'#')
+            .map(|(index, _)| index as u8)
+            .collect();
+        let mut bytes = Vec::with_capacity(4 + 2 * placements.len() + blocked.len());
+        bytes.push(u8::try_from(self.row_count()).unwrap());
+        bytes.push(u8::try_from(column_count).unwrap());
+        let placement_count = placements.len() as u8;
+        bytes.push(if blocked.is_empty() {
+            placement_count
+        } else {
+            placement_count | BLOCKED_FLAG
+        });
+        for placement in placements {
+            bytes.extend_from_slice(&pack(placement, column_count).to_be_bytes());
+        }
+        if !blocked.is_empty() {
+            bytes.push(blocked.len() as u8);
+            bytes.extend(blocked);
+        }
+        bytes
+    }
+
+    // Decode a position from the start of `input` and advance `input` past it.
+    pub fn decode(input: &mut &[u8]) -> Result<Self, DecodePositionError> {
+        let (&[row_count, column_count, count], rest) = input
+            .split_first_chunk::<3>()
+            .ok_or(DecodePositionError::UnexpectedEnd)?;
+        let (row_count, column_count) = (u32::from(row_count), u32::from(column_count));
+        if row_count == 0 || column_count == 0 {
+            return Err(DecodePositionError::EmptyBoard);
+        }
+        if !board_fits(row_count, column_count) {
+            return Err(DecodePositionError::BoardTooLarge {
+                row_count,
+                column_count,
+            });
+        }
+        let placement_count = usize::from(count & !BLOCKED_FLAG);
+        if placement_count > MAX_PIECE_COUNT {
+            return Err(DecodePositionError::TooManyPlacements);
+        }
+        let length = 2 * placement_count;
+        if rest.len() < length {
+            return Err(DecodePositionError::UnexpectedEnd);
+        }
+        let (placements, mut rest) = rest.split_at(length);
+        let mut blocked: &[u8] = &[];
+        if count & BLOCKED_FLAG != 0 {
+            let (&blocked_count, tail) = rest
+                .split_first()
+                .ok_or(DecodePositionError::UnexpectedEnd)?;
+            if tail.len() < usize::from(blocked_count) {
+                return Err(DecodePositionError::UnexpectedEnd);
+            }
+            (blocked, rest) = tail.split_at(usize::from(blocked_count));
+        }
+
+        let mut board = Board::new(row_count, column_count);
+        for &square in blocked {
+            let square = u32::from(square);
+            board
+                .block(square / column_count, square % column_count)
+                .map_err(|_| DecodePositionError::InvalidBlockedSquare)?;
+        }
+        for pair in placements.chunks(2) {
+            let packed = u16::from_be_bytes([pair[0], pair[1]]);
+            if usize::from(packed >> 8) >= FixedPiece::count() {
+                return Err(DecodePositionError::InvalidPlacement);
+            }
+            let placement = unpack(packed, column_count);
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .map_err(|_| DecodePositionError::InvalidPlacement)?;
+        }
+        *input = rest;
+        Ok(board.position())
+    }
+}
+
+impl SolutionSet {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        if self.piece_count == 0 {
+            return 0;
+        }
+        self.placements.len() / self.piece_count
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    // The placements of solution `index` in the order they were placed.
+    #[must_use]
+    pub fn placements(&self, index: usize) -> Option<Vec<Placement>> {
+        let start = index.checked_mul(self.piece_count)?;
+        let packed = self.placements.get(start..start + self.piece_count)?;
+        Some(
+            packed
+                .iter()
+                .map(|&p| {
+                    let placement = unpack(p, self.column_count);
+                    if self.transposed {
+                        transform::placement(
+                            placement,
+                            Symmetry::Transpose,
+                            self.row_count,
+                            self.column_count,
+                        )
+                    } else {
+                        placement
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    // Expand solution `index` to a position.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Position> {
+        let start = index.checked_mul(self.piece_count)?;
+        let packed = self.placements.get(start..start + self.piece_count)?;
+        let mut board = Board::new(self.row_count, self.column_count);
+        for &p in packed {
+            let placement = unpack(p, self.column_count);
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .ok()?;
+        }
+        let position = board.position();
+        Some(if self.transposed {
+            position.transposed()
+        } else {
+            position
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_all_compact, DecodePositionError};
+    use crate::FixedPiece::*;
+    use crate::{solve_all, Board, Position};
+
+    #[test]
+    fn same_as_solve_all() {
+        let expected: Vec<_> = solve_all(4, 6, "TTLSZL".parse().unwrap())
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let solutions = solve_all_compact(4, 6, "TTLSZL".parse().unwrap()).unwrap();
+        let actual: Vec<_> = solutions.iter().map(|p| p.to_string()).collect();
+
+        assert!(!expected.is_empty());
+        assert_eq!(solutions.len(), expected.len());
+        assert_eq!(actual, expected);
+        assert!(solutions.get(expected.len()).is_none());
+    }
+
+    #[test]
+    fn encode_decode() {
+        let solutions = solve_all(4, 4, "IIOO".parse().unwrap()).unwrap();
+        let mut bytes = vec![];
+        for solution in &solutions {
+            bytes.extend(solution.encode());
+        }
+        assert_eq!(bytes.len(), solutions.len() * 11);
+        assert_eq!(bytes[..11], [4, 4, 4, 0, 0, 0, 1, 2, 2, 2, 10]);
+
+        let mut input = &bytes[..];
+        for solution in &solutions {
+            let decoded = Position::decode(&mut input).unwrap();
+            assert_eq!(decoded.to_string(), solution.to_string());
+        }
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(Position::decode(&mut &[4, 4][..]).is_err());
+        assert!(Position::decode(&mut &[4, 4, 1, 0][..]).is_err());
+        assert!(Position::decode(&mut &[4, 4, 1, 19, 0][..]).is_err());
+        assert!(Position::decode(&mut &[4, 4, 2, 0, 0, 0, 0][..]).is_err());
+        assert!(matches!(
+            Position::decode(&mut &[4, 4, 13][..]),
+            Err(DecodePositionError::TooManyPlacements)
+        ));
+        // The list of blocked squares is truncated or off the board
+        assert!(matches!(
+            Position::decode(&mut &[1, 4, 0x80][..]),
+            Err(DecodePositionError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Position::decode(&mut &[1, 4, 0x80, 1, 4][..]),
+            Err(DecodePositionError::InvalidBlockedSquare)
+        ));
+        // The board has no squares or doesn't fit in the bitboard
+        assert!(matches!(
+            Position::decode(&mut &[0, 0, 0][..]),
+            Err(DecodePositionError::EmptyBoard)
+        ));
+        assert!(matches!(
+            Position::decode(&mut &[1, 0, 0][..]),
+            Err(DecodePositionError::EmptyBoard)
+        ));
+        assert!(matches!(
+            Position::decode(&mut &[8, 8, 0][..]),
+            Err(DecodePositionError::BoardTooLarge {
+                row_count: 8,
+                column_count: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn blocked_squares() {
+        let mut board = Board::new(3, 4);
+        for (row, column) in [(1, 2), (1, 3), (2, 2), (2, 3)] {
+            board.block(row, column).unwrap();
+        }
+        board.push(I2).unwrap();
+        board.push(O1).unwrap();
+        let bytes = board.position().encode();
+        assert_eq!(bytes, [3, 4, 0x82, 1, 0, 2, 4, 4, 6, 7, 10, 11]);
+        let mut input = &bytes[..];
+        let decoded = Position::decode(&mut input).unwrap();
+        assert_eq!(decoded.to_string(), "AAAA\nBB##\nBB##\n");
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn no_solutions() {
+        let solutions = solve_all_compact(4, 4, "IITT".parse().unwrap()).unwrap();
+
+        assert!(solutions.is_empty());
+        assert_eq!(solutions.len(), 0);
+        assert!(solutions.get(0).is_none());
+    }
+}
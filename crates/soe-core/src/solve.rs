@@ -0,0 +1,715 @@
+// A builder for solving puzzles. This is the general entry point; the
+// `solve_*` functions cover the common cases.
+//
+//     let solutions = Solve::board(4, 6).pieces("TTLSZL").limit(5).run()?;
+//
+// The search takes the options of `solve_one_with_options` too, set one by
+// one or all at once with `options`.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    board_fits, regions, solve_cancellable, validate_blocked, Board, FillStrategy,
+    OrientationWeights, Outcome, ParsePieceCollectionError, PieceCollection, PlaceAtError,
+    Position, SolveOneError, SolveOptions, Solver,
+};
+
+#[derive(Clone, Debug)]
+pub struct Solve {
+    row_count: u32,
+    column_count: u32,
+    pieces: Result<PieceCollection, ParsePieceCollectionError>,
+    // (row, column) of each blocked square, sorted and without duplicates
+    blocked: Vec<(u32, u32)>,
+    limit: Option<u64>,
+    timeout: Option<Duration>,
+    options: SolveOptions,
+    split_regions: bool,
+}
+
+#[derive(Debug)]
+pub enum SolveError {
+    // The pieces string can't be parsed
+    InvalidPieces(ParsePieceCollectionError),
+    // A blocked square is off the board
+    InvalidBlockedSquare(PlaceAtError),
+    // The pieces can't tile the board
+    InvalidPuzzle(SolveOneError),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use SolveError::*;
+        match self {
+            InvalidPieces(err) => write!(f, "Invalid pieces. {err}"),
+            InvalidBlockedSquare(err) => write!(f, "Invalid blocked square. {err}"),
+            InvalidPuzzle(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+impl From<SolveOneError> for SolveError {
+    fn from(err: SolveOneError) -> Self {
+        SolveError::InvalidPuzzle(err)
+    }
+}
+
+// The solutions found by `Solve::run`, in canonical order unless the regions
+// were split
+#[derive(Clone, Debug)]
+pub struct Solutions {
+    pub solutions: Vec<Position>,
+    // True if the search was abandoned because of the timeout or the node
+    // limit. There may be solutions that weren't found, even if the search
+    // found none.
+    pub abandoned: bool,
+    pub stats: SearchStats,
+}
+
+// How much searching a solve took, which doesn't depend on the speed of the
+// machine
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SearchStats {
+    // The boards visited, including the empty board
+    pub nodes: u64,
+    // The boards visited where no piece fit on the first empty square
+    pub backtracks: u64,
+    // The number of pieces in a solution, the depth of the search tree
+    pub depth: u32,
+}
+
+impl SearchStats {
+    // The branching factor b of a tree of uniform depth `depth` with as many
+    // nodes as were visited: b + b^2 + ... + b^depth = nodes - 1. It's 1 when
+    // the search went straight to a solution and grows with the backtracking.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn effective_branching_factor(&self) -> f64 {
+        if self.depth == 0 || self.nodes <= 1 {
+            return 0.0;
+        }
+        let target = (self.nodes - 1) as f64;
+        let tree_size = |b: f64| (1..=self.depth).map(|d| b.powi(d as i32)).sum::<f64>();
+        let (mut low, mut high) = (0.0, target.max(1.0));
+        for _ in 0..100 {
+            let middle = f64::midpoint(low, high);
+            if tree_size(middle) < target {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        f64::midpoint(low, high)
+    }
+}
+
+// How far a running search has got, see `Solve::run_with_progress`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Progress {
+    // The boards visited so far
+    pub nodes: u64,
+    // The number of pieces on the board
+    pub depth: u32,
+}
+
+impl Solve {
+    // Start building a search on an empty `row_count` by `column_count` board
+    // with no pieces. By default the first solution is searched for, with the
+    // default `SolveOptions` except that the board isn't transposed, so that
+    // the solutions are in canonical order.
+    #[must_use]
+    pub fn board(row_count: u32, column_count: u32) -> Self {
+        Self {
+            row_count,
+            column_count,
+            pieces: Ok(PieceCollection {
+                counts: [0; crate::Piece::count()],
+            }),
+            blocked: vec![],
+            limit: Some(1),
+            timeout: None,
+            options: SolveOptions {
+                transpose: false,
+                ..SolveOptions::default()
+            },
+            split_regions: false,
+        }
+    }
+
+    // The pieces to tile the board with, in the format of `PieceCollection`.
+    // A parse error is reported by `run`.
+    #[must_use]
+    pub fn pieces(mut self, pieces: &str) -> Self {
+        self.pieces = pieces.parse();
+        self
+    }
+
+    #[must_use]
+    pub fn piece_collection(mut self, pieces: PieceCollection) -> Self {
+        self.pieces = Ok(pieces);
+        self
+    }
+
+    // Squares, given as (row, column), that no piece may cover
+    #[must_use]
+    pub fn blocked(mut self, squares: &[(u32, u32)]) -> Self {
+        self.blocked.extend_from_slice(squares);
+        self.blocked.sort_unstable();
+        self.blocked.dedup();
+        self
+    }
+
+    // Find at most `limit` solutions
+    #[must_use]
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    // Find all the solutions
+    #[must_use]
+    pub fn all(mut self) -> Self {
+        self.limit = None;
+        self
+    }
+
+    // Abandon the search after `timeout`
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // Abandon the search after visiting `max_nodes` nodes
+    #[must_use]
+    pub fn max_nodes(mut self, max_nodes: u64) -> Self {
+        self.options.max_nodes = Some(max_nodes);
+        self
+    }
+
+    // Replace all the options of the search, including the node limit. See
+    // `SolveOptions` for what each does and the setters below for how `Solve`
+    // applies them.
+    #[must_use]
+    pub fn options(mut self, options: SolveOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    // Search with `threads` threads. Only a search for one solution on a board
+    // without blocked squares uses more than one.
+    #[must_use]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = threads;
+        self
+    }
+
+    // With several threads, whether to find the canonical first solution
+    #[must_use]
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.options.deterministic = deterministic;
+        self
+    }
+
+    // Search boards with more rows than columns transposed. The solutions are
+    // then in the canonical order of the transposed board.
+    #[must_use]
+    pub fn transpose(mut self, transpose: bool) -> Self {
+        self.options.transpose = transpose;
+        self
+    }
+
+    // The order in which the board is filled. `ColumnMajor` searches the
+    // transposed board when it fits. The other orders than `RowMajor` and
+    // `ColumnMajor` are only used by a search for one solution on a board
+    // without blocked squares.
+    #[must_use]
+    pub fn fill(mut self, fill: FillStrategy) -> Self {
+        self.options.fill = fill;
+        self
+    }
+
+    // Orientations of the pieces to try first, or not to place at all. The
+    // solutions are then no longer in canonical order.
+    #[must_use]
+    pub fn orientations(mut self, orientations: OrientationWeights) -> Self {
+        self.options.orientations = orientations;
+        self
+    }
+
+    // Break the symmetries of the board at the root of the search. Only a
+    // search for one solution on a board without blocked squares breaks
+    // them.
+    #[must_use]
+    pub fn break_symmetry(mut self, break_symmetry: bool) -> Self {
+        self.options.break_symmetry = break_symmetry;
+        self
+    }
+
+    // Backtrack as soon as a remaining kind of piece fits nowhere
+    #[must_use]
+    pub fn prune_unplaceable(mut self, prune_unplaceable: bool) -> Self {
+        self.options.prune_unplaceable = prune_unplaceable;
+        self
+    }
+
+    // The search for one solution that a `JobQueue` runs: on one thread and
+    // within the limits of the queue as well as its own
+    pub(crate) fn for_job(mut self, timeout: Duration, max_nodes: Option<u64>) -> Self {
+        self.limit = Some(1);
+        self.timeout = Some(self.timeout.map_or(timeout, |own| own.min(timeout)));
+        self.options.max_nodes = match (self.options.max_nodes, max_nodes) {
+            (Some(own), Some(max_nodes)) => Some(own.min(max_nodes)),
+            (own, max_nodes) => own.or(max_nodes),
+        };
+        self.options.threads = 1;
+        self
+    }
+
+    // Tile each region of open squares on its own when the blocked squares cut
+    // the board into several, which is much faster than searching the whole
+    // board. The solutions are then no longer in canonical order.
+    #[must_use]
+    pub fn split_regions(mut self, split_regions: bool) -> Self {
+        self.split_regions = split_regions;
+        self
+    }
+
+    // Run the search. With the default options and unless the regions are
+    // split, solutions are found in canonical order, so with a limit of one
+    // this is the solution `solve_first_canonical` gives. Boards that only fit
+    // the bitboard transposed are searched transposed, like `solve_one` does,
+    // and their solutions are in the canonical order of the transposed board.
+    //
+    // The search statistics are zero when the search uses several threads, a
+    // fill order other than `RowMajor` and `ColumnMajor` or breaks symmetry.
+    pub fn run(self) -> Result<Solutions, SolveError> {
+        self.run_with_progress(&mut |_| {})
+    }
+
+    // Like `run`, calling `progress` every 1024 nodes while the search runs.
+    // Only the searches with statistics report progress, and not when the
+    // regions are split.
+    pub fn run_with_progress(
+        self,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result<Solutions, SolveError> {
+        let (row_count, column_count) = (self.row_count, self.column_count);
+        let fits_transposed = board_fits(column_count, row_count);
+        let transpose = if board_fits(row_count, column_count) {
+            fits_transposed
+                && (self.options.transpose && row_count > column_count
+                    || self.options.fill == FillStrategy::ColumnMajor)
+        } else {
+            fits_transposed
+        };
+        if transpose {
+            return self.run_transposed(progress);
+        }
+
+        let Solve {
+            row_count,
+            column_count,
+            pieces,
+            blocked,
+            limit,
+            timeout,
+            options,
+            split_regions,
+        } = self;
+        let pieces = pieces.map_err(SolveError::InvalidPieces)?;
+        let pieces_count = pieces.count_all();
+        if blocked
+            .iter()
+            .any(|&(row, column)| row >= row_count || column >= column_count)
+        {
+            return Err(SolveError::InvalidBlockedSquare(PlaceAtError::OffBoard));
+        }
+        validate_blocked(row_count, column_count, blocked.len() as u32, &pieces)?;
+
+        let searches_alone = options.threads > 1
+            || !matches!(
+                options.fill,
+                FillStrategy::RowMajor | FillStrategy::ColumnMajor
+            )
+            || options.break_symmetry;
+        if searches_alone && limit == Some(1) && blocked.is_empty() && !split_regions {
+            let outcome = solve_within(row_count, column_count, pieces, &options, timeout)?;
+            return Ok(Solutions {
+                abandoned: outcome.is_undetermined(),
+                solutions: outcome.into_solution().into_iter().collect(),
+                stats: SearchStats {
+                    depth: pieces_count,
+                    ..SearchStats::default()
+                },
+            });
+        }
+        let max_nodes = options.max_nodes;
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let stop = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let regions = if split_regions {
+            regions::regions(row_count, column_count, &blocked)
+        } else {
+            vec![]
+        };
+        if regions.len() > 1 {
+            let split = regions::solve(
+                row_count,
+                column_count,
+                &regions,
+                &pieces,
+                limit.unwrap_or(u64::MAX),
+                max_nodes.unwrap_or(u64::MAX),
+                &stop,
+            );
+            return Ok(Solutions {
+                solutions: split.solutions,
+                abandoned: split.stopped,
+                stats: SearchStats {
+                    nodes: split.nodes,
+                    backtracks: split.backtracks,
+                    depth: pieces_count,
+                },
+            });
+        }
+
+        let mut board = Board::new(row_count, column_count);
+        for &(row, column) in &blocked {
+            board.block(row, column).unwrap();
+        }
+
+        let mut solver = Solver::new(board, pieces)
+            .with_stop(&stop)
+            .with_progress(progress)
+            .with_prune_unplaceable(options.prune_unplaceable)
+            .with_orientations(&options.orientations)
+            .with_max_nodes(max_nodes.unwrap_or(u64::MAX))
+            .with_max_solutions(limit.unwrap_or(u64::MAX));
+        let mut solutions = vec![];
+        solver.for_each_solution(&mut |board| solutions.push(board.position()));
+
+        Ok(Solutions {
+            solutions,
+            abandoned: solver.stopped,
+            stats: SearchStats {
+                nodes: solver.nodes,
+                backtracks: solver.backtracks,
+                depth: pieces_count,
+            },
+        })
+    }
+
+    // Search the transposed board and transpose the solutions back
+    fn run_transposed(self, progress: &mut dyn FnMut(Progress)) -> Result<Solutions, SolveError> {
+        // `blocked` sorts the transposed squares again
+        let blocked: Vec<_> = self
+            .blocked
+            .iter()
+            .map(|&(row, column)| (column, row))
+            .collect();
+        // The column-major order of the board is the row-major order of
+        // the transposed board
+        let options = SolveOptions {
+            transpose: false,
+            orientations: self.options.orientations.transposed(),
+            fill: match self.options.fill {
+                FillStrategy::ColumnMajor => FillStrategy::RowMajor,
+                fill => fill,
+            },
+            ..self.options.clone()
+        };
+        let transposed = Solve {
+            row_count: self.column_count,
+            column_count: self.row_count,
+            pieces: self.pieces.map(|pieces| pieces.transposed()),
+            blocked: vec![],
+            options,
+            ..self
+        }
+        .blocked(&blocked);
+        let mut solutions = transposed.run_with_progress(progress)?;
+        for solution in &mut solutions.solutions {
+            *solution = solution.transposed();
+        }
+        Ok(solutions)
+    }
+}
+
+// `solve_one_with_options`, abandoned with the outcome `Undetermined` after
+// `timeout`
+fn solve_within(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    options: &SolveOptions,
+    timeout: Option<Duration>,
+) -> Result<Outcome, SolveOneError> {
+    let cancelled = AtomicBool::new(false);
+    let Some(timeout) = timeout else {
+        return solve_cancellable(row_count, column_count, pieces, options, &cancelled);
+    };
+    let (done, finished) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        let cancelled = &cancelled;
+        scope.spawn(move || {
+            if finished.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+        let outcome = solve_cancellable(row_count, column_count, pieces, options, cancelled);
+        drop(done);
+        outcome
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{SearchStats, Solve, SolveError};
+    use crate::{
+        solve_all, solve_first_canonical, solve_one, solve_one_with_options, FillStrategy,
+        FixedPiece, OrientationWeights, SolveOptions,
+    };
+
+    #[test]
+    fn first() {
+        let result = Solve::board(4, 6).pieces("TTLSZL").run().unwrap();
+        let expected = solve_first_canonical(4, 6, "TTLSZL".parse().unwrap()).unwrap();
+
+        assert!(!result.abandoned);
+        assert_eq!(result.solutions.len(), 1);
+        assert!(result.solutions[0] == expected.unwrap());
+    }
+
+    #[test]
+    fn limit_and_all() {
+        let all = solve_all(4, 4, "IIOO".parse().unwrap()).unwrap();
+
+        let result = Solve::board(4, 4).pieces("IIOO").limit(4).run().unwrap();
+        assert!(result.solutions[..] == all[..4]);
+        let result = Solve::board(4, 4).pieces("IIOO").all().run().unwrap();
+        assert!(result.solutions == all);
+    }
+
+    #[test]
+    fn options() {
+        let pieces = || "TTLSZL".parse().unwrap();
+        let solve = || Solve::board(4, 6).pieces("TTLSZL");
+        let same_as_options = |solve: Solve, options: SolveOptions| {
+            let result = solve.run().unwrap();
+            let expected = solve_one_with_options(4, 6, pieces(), &options).unwrap();
+            assert!(result.solutions[0] == expected.into_solution().unwrap());
+        };
+
+        let options = SolveOptions {
+            threads: 2,
+            fill: FillStrategy::Spiral,
+            break_symmetry: true,
+            prune_unplaceable: true,
+            ..SolveOptions::default()
+        };
+        same_as_options(solve().options(options.clone()), options);
+        for fill in [
+            FillStrategy::ColumnMajor,
+            FillStrategy::Boustrophedon,
+            FillStrategy::Spiral,
+        ] {
+            let options = SolveOptions {
+                fill,
+                ..SolveOptions::default()
+            };
+            same_as_options(solve().fill(fill), options);
+        }
+        let options = SolveOptions {
+            threads: 2,
+            ..SolveOptions::default()
+        };
+        same_as_options(solve().threads(2), options);
+        let options = SolveOptions {
+            break_symmetry: true,
+            ..SolveOptions::default()
+        };
+        same_as_options(solve().break_symmetry(true), options);
+
+        // Options that the single threaded search takes apply to every
+        // search
+        let result = solve().prune_unplaceable(true).all().run().unwrap();
+        let all = solve_all(4, 6, pieces()).unwrap();
+        assert!(result.solutions == all);
+        let mut orientations = OrientationWeights::default();
+        orientations.ban(FixedPiece::T1);
+        let result = solve()
+            .orientations(orientations)
+            .prune_unplaceable(true)
+            .all()
+            .run()
+            .unwrap();
+        assert!(!result.solutions.is_empty());
+        for solution in &result.solutions {
+            assert!(!solution
+                .placements()
+                .iter()
+                .any(|placement| placement.piece == FixedPiece::T1));
+        }
+
+        let result = Solve::board(6, 4).pieces("TTLSZL").transpose(true).run();
+        let expected = solve_one(6, 4, pieces()).unwrap();
+        assert!(result.unwrap().solutions[0] == expected.unwrap());
+        let result = Solve::board(4, 3)
+            .pieces("IO")
+            .blocked(&[(2, 1), (2, 2), (3, 1), (3, 2)])
+            .transpose(true)
+            .all()
+            .run()
+            .unwrap();
+        assert_eq!(result.solutions.len(), 1);
+        assert_eq!(result.solutions[0].to_string(), "ABB\nABB\nA##\nA##\n");
+
+        let result = Solve::board(4, 12)
+            .pieces("IITTTLLLJJSZ")
+            .threads(2)
+            .timeout(Duration::from_millis(100))
+            .run()
+            .unwrap();
+        assert!(result.solutions.is_empty() && result.abandoned);
+    }
+
+    #[test]
+    fn blocked() {
+        let result = Solve::board(3, 4)
+            .pieces("IO")
+            .blocked(&[(1, 2), (1, 3), (2, 2), (2, 3), (2, 3)])
+            .all()
+            .run()
+            .unwrap();
+        assert_eq!(result.solutions.len(), 1);
+        assert_eq!(result.solutions[0].to_string(), "AAAA\nBB##\nBB##\n");
+    }
+
+    #[test]
+    fn abandoned() {
+        let result = Solve::board(4, 12)
+            .pieces("IIITTTLLLJJS")
+            .max_nodes(1000)
+            .run()
+            .unwrap();
+        assert!(result.abandoned);
+        assert!(result.solutions.is_empty());
+
+        let result = Solve::board(4, 12)
+            .pieces("IIITTTLLLJJS")
+            .timeout(Duration::from_millis(0))
+            .run()
+            .unwrap();
+        assert!(result.abandoned);
+    }
+
+    #[test]
+    fn stats() {
+        let result = Solve::board(4, 6).pieces("TTLSZL").run().unwrap();
+        let stats = result.stats;
+        assert_eq!(stats.depth, 6);
+        assert!(stats.nodes > 6 && stats.backtracks > 0);
+        assert!(stats.effective_branching_factor() > 1.0);
+
+        // One node per piece and the empty board
+        let result = Solve::board(1, 8).pieces("II").run().unwrap();
+        assert_eq!(result.stats.nodes, 3);
+        assert_eq!(result.stats.backtracks, 0);
+        assert!((result.stats.effective_branching_factor() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn progress() {
+        let mut reports = vec![];
+        let result = Solve::board(4, 8)
+            .pieces("TTLLSZOI")
+            .all()
+            .run_with_progress(&mut |progress| reports.push(progress))
+            .unwrap();
+        assert_eq!(reports.len() as u64, result.stats.nodes / 1024);
+        assert!(reports.len() > 1);
+        assert_eq!(reports[1].nodes, 2048);
+        assert!(reports.iter().all(|progress| progress.depth <= 8));
+
+        // Transposed boards report too
+        let mut count = 0;
+        let result = Solve::board(8, 4)
+            .pieces("TTLLSZOI")
+            .all()
+            .transpose(true)
+            .run_with_progress(&mut |_| count += 1)
+            .unwrap();
+        assert_eq!(count, result.stats.nodes / 1024);
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn effective_branching_factor() {
+        let stats = SearchStats {
+            nodes: 1 + 2 + 4 + 8,
+            backtracks: 0,
+            depth: 3,
+        };
+        assert!((stats.effective_branching_factor() - 2.0).abs() < 1e-9);
+        assert!(SearchStats::default().effective_branching_factor().abs() < 1e-9);
+    }
+
+    #[test]
+    fn errors() {
+        let result = Solve::board(4, 4).pieces("IIOX").run();
+        assert!(matches!(result, Err(SolveError::InvalidPieces(_))));
+        let result = Solve::board(4, 4).pieces("IIO").blocked(&[(4, 0)]).run();
+        assert!(matches!(result, Err(SolveError::InvalidBlockedSquare(_))));
+        let result = Solve::board(4, 4).pieces("IIO").run();
+        assert!(matches!(result, Err(SolveError::InvalidPuzzle(_))));
+        let result = Solve::board(8, 8).pieces("IIIIIIIIIIII").run();
+        assert!(matches!(result, Err(SolveError::InvalidPuzzle(_))));
+        let result = Solve::board(3, 21).pieces("IIIIIIIIIIII").run();
+        assert!(matches!(result, Err(SolveError::InvalidPuzzle(_))));
+    }
+
+    #[test]
+    fn transposed() {
+        // Only fits the bitboard transposed, like for `solve_one`
+        let pieces = "IIIIIIIIIIII".parse().unwrap();
+        let result = Solve::board(16, 3).piece_collection(pieces).run().unwrap();
+        let expected = solve_one(16, 3, "IIIIIIIIIIII".parse().unwrap()).unwrap();
+        assert!(expected.is_some());
+        assert_eq!(result.solutions.len(), 1);
+        let solution = &result.solutions[0];
+        assert_eq!((solution.row_count(), solution.column_count()), (16, 3));
+        assert!(solution
+            .verify(16, 3, &"IIIIIIIIIIII".parse().unwrap())
+            .is_ok());
+
+        let result = Solve::board(16, 3)
+            .pieces("IIIIIIIIIII")
+            .blocked(&[(12, 2), (13, 2), (14, 2), (15, 2)])
+            .all()
+            .run()
+            .unwrap();
+        assert!(!result.solutions.is_empty());
+        for solution in &result.solutions {
+            let text = solution.to_string();
+            let blocked: Vec<_> = text
+                .lines()
+                .enumerate()
+                .flat_map(|(row, line)| {
+                    line.match_indices('#')
+                        .map(move |(column, _)| (row, column))
+                })
+                .collect();
+            assert_eq!(blocked, [(12, 2), (13, 2), (14, 2), (15, 2)]);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Rendering of positions as SVG images, colored like the HTML tables, with
+// any annotations drawn on top in the border color.
+
+use std::fmt::Write;
+
+use crate::annotation::{AnnotatedPosition, Annotation};
+use crate::palette::css;
+use crate::{Palette, Position};
+
+// The width and height of a square
+const CELL_SIZE: i32 = 32;
+
+impl Position {
+    // Render the position as an SVG image. Squares are colored by the kind of
+    // piece that covers them and pieces are outlined.
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with_palette(&Palette::default())
+    }
+
+    // Render the position as `to_svg` does, in the colors of `palette`
+    #[must_use]
+    pub fn to_svg_with_palette(&self, palette: &Palette) -> String {
+        svg(self, &[], palette)
+    }
+}
+
+impl AnnotatedPosition {
+    // Render the position as `Position::to_svg` does, with the annotations
+    // drawn in the border color over their squares
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with_palette(&Palette::default())
+    }
+
+    #[must_use]
+    pub fn to_svg_with_palette(&self, palette: &Palette) -> String {
+        svg(&self.position, self.annotations(), palette)
+    }
+}
+
+fn svg(position: &Position, annotations: &[(u32, u32, Annotation)], palette: &Palette) -> String {
+    let (row_count, column_count) = (position.row_count() as i32, position.column_count() as i32);
+    let width = column_count as usize + 1;
+    let pieces = position.label_pieces();
+    let label = |row: i32, column: i32| {
+        ((0..row_count).contains(&row) && (0..column_count).contains(&column))
+            .then(|| position.squares[row as usize * width + column as usize])
+    };
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"-1 -1 {0} {1}\">",
+        column_count * CELL_SIZE + 2,
+        row_count * CELL_SIZE + 2
+    )
+    .unwrap();
+    for row in 0..row_count {
+        for column in 0..column_count {
+            let fill = css(match label(row, column).unwrap() {
+                b'.' => palette.background,
+                b'#' => palette.blocked,
+                label => pieces
+                    .get(&label)
+                    .map_or(palette.unknown, |&piece| palette.piece(piece)),
+            });
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{CELL_SIZE}\" height=\"{CELL_SIZE}\" \
+                 fill=\"{fill}\"/>",
+                column * CELL_SIZE,
+                row * CELL_SIZE
+            )
+            .unwrap();
+        }
+    }
+
+    // The edges between squares with different labels, and of the board. Each
+    // square draws its top and left edges, and the squares off the board past
+    // the last row and column draw the rest.
+    let stroke = css(palette.border);
+    for row in 0..=row_count {
+        for column in 0..=column_count {
+            let this = label(row, column);
+            let (x, y) = (column * CELL_SIZE, row * CELL_SIZE);
+            if column < column_count && this != label(row - 1, column) {
+                line(&mut svg, (x, y), (x + CELL_SIZE, y), &stroke);
+            }
+            if row < row_count && this != label(row, column - 1) {
+                line(&mut svg, (x, y), (x, y + CELL_SIZE), &stroke);
+            }
+        }
+    }
+
+    if !annotations.is_empty() {
+        writeln!(svg, "<g fill=\"{stroke}\">").unwrap();
+    }
+    for &(row, column, annotation) in annotations {
+        let (x, y) = (
+            column as i32 * CELL_SIZE + CELL_SIZE / 2,
+            row as i32 * CELL_SIZE + CELL_SIZE / 2,
+        );
+        match annotation {
+            Annotation::Number(number) => writeln!(
+                svg,
+                "<text x=\"{x}\" y=\"{y}\" font-family=\"sans-serif\" font-size=\"{}\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\">{number}</text>",
+                CELL_SIZE * 5 / 8
+            ),
+            Annotation::Arrow(arrow) => {
+                // A triangle pointing in the direction of the arrow
+                let (dy, dx) = arrow.offsets();
+                let size = CELL_SIZE / 4;
+                let points = [
+                    (x + dx * size, y + dy * size),
+                    (x - dx * size - dy * size, y - dy * size + dx * size),
+                    (x - dx * size + dy * size, y - dy * size - dx * size),
+                ];
+                let points: Vec<String> = points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+                writeln!(svg, "<polygon points=\"{}\"/>", points.join(" "))
+            }
+            Annotation::Marker => writeln!(
+                svg,
+                "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\"/>",
+                CELL_SIZE / 6
+            ),
+        }
+        .unwrap();
+    }
+    if !annotations.is_empty() {
+        svg.push_str("</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn line(svg: &mut String, (x1, y1): (i32, i32), (x2, y2): (i32, i32), stroke: &str) {
+    writeln!(
+        svg,
+        "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"{stroke}\" \
+         stroke-width=\"2\" stroke-linecap=\"square\"/>"
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AnnotatedPosition, Annotation, Arrow, Palette, Position};
+
+    #[test]
+    fn to_svg() {
+        let position = Position::from_replay(2, 4, "I2@r0c0").unwrap();
+        let svg = position.to_svg();
+        let lines: Vec<_> = svg.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"130\" height=\"66\" \
+             viewBox=\"-1 -1 130 66\">"
+        );
+        assert_eq!(lines.last(), Some(&"</svg>"));
+        assert_eq!(svg.matches("fill=\"#0ff\"").count(), 4);
+        assert_eq!(svg.matches("fill=\"#fff\"").count(), 4);
+        // The outline of the board and the edge under the I piece
+        assert_eq!(svg.matches("<line").count(), 12 + 4);
+
+        let annotated = AnnotatedPosition::from(position)
+            .with(1, 0, Annotation::Number(1))
+            .with(1, 1, Annotation::Arrow(Arrow::Right))
+            .with(1, 2, Annotation::Marker);
+        let svg = annotated.to_svg();
+        assert!(svg.contains(
+            "<text x=\"16\" y=\"48\" font-family=\"sans-serif\" font-size=\"20\" \
+             text-anchor=\"middle\" dominant-baseline=\"central\">1</text>"
+        ));
+        assert!(svg.contains("<polygon points=\"56,48 40,56 40,40\"/>"));
+        assert!(svg.contains("<circle cx=\"80\" cy=\"48\" r=\"5\"/>"));
+
+        let palette = Palette {
+            border: [0x12, 0x34, 0x56],
+            ..Palette::colorblind()
+        };
+        let svg = annotated.to_svg_with_palette(&palette);
+        assert_eq!(svg.matches("fill=\"#56b4e9\"").count(), 4);
+        assert_eq!(svg.matches("stroke=\"#123456\"").count(), 16);
+        assert!(svg.contains("<g fill=\"#123456\">"));
+    }
+}
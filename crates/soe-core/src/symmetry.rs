@@ -0,0 +1,105 @@
+// Detection of the symmetries of boards and tilings.
+
+use std::collections::HashMap;
+
+use crate::board::transform;
+use crate::geometry::{Cell, Symmetry};
+use crate::{Board, Position};
+
+impl Board {
+    // The symmetries that map the covered squares of the board onto
+    // themselves. An empty rectangular board has four symmetries, or eight if
+    // it's square.
+    #[must_use]
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        let (row_count, column_count) = (self.height as u32, self.width as u32 - 1);
+        let covered = transform::squares(self.bits, Symmetry::Identity, row_count, column_count);
+        symmetries(row_count as i32, column_count as i32, |symmetry| {
+            transform::squares(self.bits, symmetry, row_count, column_count) == covered
+        })
+    }
+}
+
+impl Position {
+    // The symmetries that map the tiling onto itself: each piece is mapped
+    // onto a piece and empty squares onto empty squares. Labels are ignored.
+    #[must_use]
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        let (row_count, column_count) = (self.row_count() as i32, self.column_count() as i32);
+        let width = column_count as usize + 1;
+        let label = |Cell(row, column): Cell| self.squares[row as usize * width + column as usize];
+        symmetries(row_count, column_count, |symmetry| {
+            // The label of the image of each label, and the reverse
+            let mut images = HashMap::new();
+            let mut preimages = HashMap::new();
+            cells(row_count, column_count).all(|cell| {
+                let from = label(cell);
+                let to = label(symmetry.apply(cell, row_count, column_count));
+                *images.entry(from).or_insert(to) == to
+                    && *preimages.entry(to).or_insert(from) == from
+                    && (from == b'.') == (to == b'.')
+            })
+        })
+    }
+}
+
+// The symmetries of a `row_count` by `column_count` board for which
+// `is_symmetry` returns true. Transforms that swap the dimensions are only
+// considered for square boards.
+fn symmetries<F: Fn(Symmetry) -> bool>(
+    row_count: i32,
+    column_count: i32,
+    is_symmetry: F,
+) -> Vec<Symmetry> {
+    Symmetry::array()
+        .iter()
+        .copied()
+        .filter(|s| !s.swaps_dimensions() || row_count == column_count)
+        .filter(|&s| is_symmetry(s))
+        .collect()
+}
+
+fn cells(row_count: i32, column_count: i32) -> impl Iterator<Item = Cell> {
+    (0..row_count).flat_map(move |row| (0..column_count).map(move |column| Cell(row, column)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Symmetry::*;
+    use crate::FixedPiece::*;
+    use crate::{Board, Position};
+
+    #[test]
+    fn board() {
+        assert_eq!(
+            Board::new(2, 3).symmetries(),
+            [Identity, Rotate180, FlipVertical, FlipHorizontal]
+        );
+        assert_eq!(Board::new(4, 4).symmetries().len(), 8);
+
+        let mut board = Board::new(4, 4);
+        board.place_at(I2, 0, 0).unwrap();
+        assert_eq!(board.symmetries(), [Identity, FlipHorizontal]);
+    }
+
+    #[test]
+    fn position() {
+        let position = Position::from_replay(4, 4, "I2@r0c0; I2@r1c0; I2@r2c0; I2@r3c0").unwrap();
+        assert_eq!(
+            position.symmetries(),
+            [Identity, Rotate180, FlipVertical, FlipHorizontal]
+        );
+
+        let position = Position::from_replay(4, 4, "O1@r0c0; O1@r0c2; O1@r2c0; O1@r2c2").unwrap();
+        assert_eq!(position.symmetries().len(), 8);
+
+        let position = Position::from_replay(4, 4, "I1@r0c0; I1@r0c1; O1@r0c2; O1@r2c2").unwrap();
+        assert_eq!(position.symmetries(), [Identity, FlipVertical]);
+
+        let position = Position::from_replay(2, 4, "L2@r0c0; L4@r0c3").unwrap();
+        assert_eq!(position.symmetries(), [Identity, Rotate180]);
+
+        let position = Position::from_replay(2, 4, "L2@r0c0").unwrap();
+        assert_eq!(position.symmetries(), [Identity]);
+    }
+}
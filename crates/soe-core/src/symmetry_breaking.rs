@@ -0,0 +1,241 @@
+// Symmetry breaking at the root of the search. When a symmetry of the board
+// maps the puzzle onto itself, it maps every solution onto a solution. Of the
+// placements of one chosen kind of piece that the symmetries map onto each
+// other, only the first in canonical order is searched, with the piece placed
+// there before the search starts. The other solutions are the images of those
+// found.
+//
+// Reflections turn J pieces into L pieces and S pieces into Z pieces, so when
+// the puzzle has a reflection symmetry the chosen kind is I, O or T. Of the
+// kinds that can be chosen it's the one with the fewest pieces. With k pieces
+// of that kind each solution is found up to k times, so symmetry is only
+// broken when there are more than k symmetries.
+
+use std::collections::HashSet;
+
+use crate::board::transform;
+use crate::geometry::Symmetry;
+use crate::{
+    Board, FixedPiece, LabelOrder, Outcome, Piece, PieceCollection, Placement, Position,
+    SolveOptions, Solver, PIECE_MAP,
+};
+
+pub(crate) struct RootSymmetry {
+    row_count: u32,
+    column_count: u32,
+    // The symmetries of the board that map the pieces onto themselves,
+    // including the identity
+    symmetries: Vec<Symmetry>,
+    // The first placement of each class of placements of the chosen kind
+    placements: Vec<Placement>,
+}
+
+impl RootSymmetry {
+    // `None` if breaking the symmetry of the puzzle wouldn't save any search
+    pub(crate) fn new(row_count: u32, column_count: u32, pieces: &PieceCollection) -> Option<Self> {
+        let symmetries: Vec<_> = Board::new(row_count, column_count)
+            .symmetries()
+            .into_iter()
+            .filter(|symmetry| !symmetry.is_reflection() || pieces.transposed() == *pieces)
+            .collect();
+        let reflects = symmetries.iter().any(|symmetry| symmetry.is_reflection());
+        let piece = Piece::array()
+            .iter()
+            .copied()
+            .filter(|&piece| pieces.count(piece) > 0)
+            .filter(|&piece| !reflects || matches!(piece, Piece::I | Piece::O | Piece::T))
+            .min_by_key(|&piece| pieces.count(piece))?;
+        if pieces.count(piece) as usize >= symmetries.len() {
+            return None;
+        }
+
+        // The placements in canonical order, keeping those that aren't the
+        // image of an earlier one
+        let board = Board::new(row_count, column_count);
+        let mut seen = HashSet::new();
+        let mut placements = vec![];
+        for row in 0..row_count {
+            for column in 0..column_count {
+                for (index, &kind) in PIECE_MAP.iter().enumerate() {
+                    let fixed_piece = FixedPiece::from(index);
+                    if kind != piece || !board.can_place(fixed_piece, row, column) {
+                        continue;
+                    }
+                    let placement = Placement {
+                        piece: fixed_piece,
+                        row,
+                        column,
+                    };
+                    if seen.insert(placement) {
+                        placements.push(placement);
+                        seen.extend(symmetries.iter().map(|&symmetry| {
+                            transform::placement(placement, symmetry, row_count, column_count)
+                        }));
+                    }
+                }
+            }
+        }
+
+        Some(Self {
+            row_count,
+            column_count,
+            symmetries,
+            placements,
+        })
+    }
+
+    // A solver for each searched placement, with the piece placed
+    fn solvers<'a>(&'a self, pieces: &'a PieceCollection) -> impl Iterator<Item = Solver<'a>> + 'a {
+        self.placements.iter().map(move |placement| {
+            let mut board = Board::new(self.row_count, self.column_count);
+            board
+                .place_at(placement.piece, placement.row, placement.column)
+                .unwrap();
+            let mut pieces = pieces.clone();
+            pieces.remove(PIECE_MAP[placement.piece as usize]);
+            Solver::new(board, pieces)
+        })
+    }
+
+    // All the solutions in canonical order, labelled like those of `solve_all`
+    pub(crate) fn solve_all(&self, pieces: &PieceCollection) -> Vec<Position> {
+        let mut found = vec![];
+        for mut solver in self.solvers(pieces) {
+            solver.for_each_solution(&mut |board| found.push(board.position()));
+        }
+
+        let mut solutions: Vec<_> = found
+            .iter()
+            .flat_map(|solution| {
+                self.symmetries
+                    .iter()
+                    .map(move |&symmetry| transform::position(solution, symmetry))
+            })
+            .map(|solution| solution.relabel(LabelOrder::ByPosition))
+            .collect();
+        solutions.sort_by_cached_key(canonical_key);
+        solutions.dedup();
+        solutions
+    }
+
+    // A solution, which isn't necessarily the canonical first, with the
+    // node limit and stop condition of `options` shared by the searches
+    pub(crate) fn solve_one(
+        &self,
+        pieces: &PieceCollection,
+        options: &SolveOptions,
+        stop: &dyn Fn() -> bool,
+    ) -> Outcome {
+        let max_nodes = options.max_nodes.unwrap_or(u64::MAX);
+        let mut nodes = 0;
+        for solver in self.solvers(pieces) {
+            let mut solver = solver
+                .with_stop(stop)
+                .with_prune_unplaceable(options.prune_unplaceable)
+                .with_max_nodes(max_nodes - nodes);
+            let solution = solver.solve_one();
+            nodes = (nodes + solver.nodes).min(max_nodes);
+            if let Some(solution) = solution {
+                return Outcome::Solved(solution.relabel(LabelOrder::ByPosition));
+            }
+            if solver.stopped {
+                return Outcome::Undetermined;
+            }
+        }
+        Outcome::NoSolution
+    }
+}
+
+// The placements of a solution labelled by position, in label order, as
+// (row, column, orientation). Sorting by this key sorts in canonical order.
+fn canonical_key(solution: &Position) -> Vec<(u32, u32, usize)> {
+    solution
+        .placements()
+        .iter()
+        .map(|placement| (placement.row, placement.column, placement.piece as usize))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RootSymmetry;
+    use crate::{
+        solve_one_with_options, Board, Outcome, PieceCollection, Position, SolveOptions, Solver,
+    };
+
+    // The solutions found without breaking symmetry
+    fn plain_solve_all(row_count: u32, column_count: u32, pieces: &str) -> Vec<Position> {
+        let mut solutions = vec![];
+        let mut solver = Solver::new(Board::new(row_count, column_count), pieces.parse().unwrap());
+        solver.for_each_solution(&mut |board| solutions.push(board.position()));
+        solutions
+    }
+
+    #[test]
+    fn same_solutions() {
+        for &(row_count, column_count, pieces) in &[
+            (4, 4, "IIOO"),
+            (4, 4, "TTTT"),
+            (4, 4, "IOTT"),
+            (4, 5, "IOTJL"),
+            (4, 6, "TTLSZL"),
+            (4, 6, "IIOOTT"),
+            (5, 4, "OTTJL"),
+        ] {
+            let collection: PieceCollection = pieces.parse().unwrap();
+            let expected: Vec<_> = plain_solve_all(row_count, column_count, pieces)
+                .iter()
+                .map(Position::to_string)
+                .collect();
+            let root = RootSymmetry::new(row_count, column_count, &collection).unwrap();
+            let solutions: Vec<_> = root
+                .solve_all(&collection)
+                .iter()
+                .map(Position::to_string)
+                .collect();
+            assert_eq!(solutions, expected, "{row_count}x{column_count} {pieces}");
+        }
+    }
+
+    #[test]
+    fn chosen_piece() {
+        let root = |row_count, column_count, pieces: &str| {
+            RootSymmetry::new(row_count, column_count, &pieces.parse().unwrap())
+        };
+        // The 4 by 4 board has 8 symmetries. The I is either at the edge or
+        // not, and no symmetry maps any of the 24 placements of the T onto
+        // itself.
+        assert_eq!(root(4, 4, "IOTT").unwrap().placements.len(), 2);
+        assert_eq!(root(4, 4, "TTTT").unwrap().placements.len(), 3);
+        // The reflections would turn a J into an L, which leaves the rotations
+        assert_eq!(root(4, 4, "JJLT").unwrap().symmetries.len(), 4);
+        assert!(root(4, 4, "JJLL").is_none());
+        // Too many pieces of every kind to save anything
+        assert!(root(4, 2, "JL").is_none());
+        assert!(root(4, 8, "IIIIIIII").is_none());
+    }
+
+    #[test]
+    fn no_solution() {
+        let options = SolveOptions {
+            break_symmetry: true,
+            ..SolveOptions::default()
+        };
+        let outcome = |row_count, column_count, pieces: &str| {
+            solve_one_with_options(row_count, column_count, pieces.parse().unwrap(), &options)
+                .unwrap()
+        };
+        assert_eq!(outcome(4, 4, "OTTT"), Outcome::NoSolution);
+        assert_eq!(outcome(4, 6, "TTTTTT"), Outcome::NoSolution);
+        let solution = outcome(4, 6, "TTLSZL").into_solution().unwrap();
+        assert!(solution.verify(4, 6, &"TTLSZL".parse().unwrap()).is_ok());
+        let options = SolveOptions {
+            max_nodes: Some(1),
+            ..options
+        };
+        assert_eq!(
+            solve_one_with_options(4, 4, "OTTT".parse().unwrap(), &options).unwrap(),
+            Outcome::Undetermined
+        );
+    }
+}
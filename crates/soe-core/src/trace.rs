@@ -0,0 +1,169 @@
+// Export of the steps of the search as JSON lines, for tools that visualize
+// the search tree. Each line is an object with a "step" of "place",
+// "backtrack" or "solution" and the time in microseconds since the start of
+// the search. Placements and backtracks also have the fixed "piece", its
+// "row" and "column", and the "depth", the number of pieces on the board after
+// the step:
+//
+//     {"step":"place","piece":"L1","row":0,"column":0,"depth":1,"time_us":3}
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crate::board::transform;
+use crate::geometry::Symmetry;
+use crate::{
+    candidate_table, fits_only_transposed, validate, Board, PieceCollection, Position, SearchStep,
+    SolveOneError, Solver,
+};
+
+#[derive(Debug)]
+pub enum TraceError {
+    InvalidPuzzle(SolveOneError),
+    Io(io::Error),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use TraceError::*;
+        match self {
+            InvalidPuzzle(err) => write!(f, "{err}"),
+            Io(err) => write!(f, "Failed to write the trace. {err}"),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+// Search for up to `max_solutions` solutions in canonical order, writing each
+// step of the search to `writer`. Returns the solutions found. Boards that
+// only fit the bitboard transposed are searched transposed, like in
+// `solve_all`, and the steps are written with the pieces and squares of the
+// board as given.
+pub fn solve_traced<W: Write>(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    max_solutions: u64,
+    writer: W,
+) -> Result<Vec<Position>, TraceError> {
+    let (solutions, _) = solve_traced_until(
+        row_count,
+        column_count,
+        pieces,
+        max_solutions,
+        &|| false,
+        u64::MAX,
+        writer,
+    )?;
+    Ok(solutions)
+}
+
+// Like `solve_traced`, but the search is abandoned once `stop` returns true or
+// more than `max_nodes` nodes have been visited. Also returns whether the
+// search was abandoned.
+pub(crate) fn solve_traced_until<W: Write>(
+    row_count: u32,
+    column_count: u32,
+    pieces: PieceCollection,
+    max_solutions: u64,
+    stop: &dyn Fn() -> bool,
+    max_nodes: u64,
+    mut writer: W,
+) -> Result<(Vec<Position>, bool), TraceError> {
+    let transpose = fits_only_transposed(row_count, column_count);
+    let (searched_row_count, searched_column_count, pieces) = if transpose {
+        (column_count, row_count, pieces.transposed())
+    } else {
+        (row_count, column_count, pieces)
+    };
+    validate(searched_row_count, searched_column_count, &pieces)
+        .map_err(TraceError::InvalidPuzzle)?;
+    // Build the lookup table first so that it isn't counted in the times
+    candidate_table();
+    let start = Instant::now();
+    // The first write error. Nothing more is written after it.
+    let mut result = Ok(());
+    let mut solutions = vec![];
+
+    let mut trace = |step: SearchStep, board: &Board| {
+        if result.is_err() {
+            return;
+        }
+        let (name, mut placement) = match step {
+            SearchStep::Place(placement) => ("place", placement),
+            SearchStep::Backtrack(placement) => ("backtrack", placement),
+        };
+        if transpose {
+            placement = transform::placement(
+                placement,
+                Symmetry::Transpose,
+                searched_row_count,
+                searched_column_count,
+            );
+        }
+        let time = start.elapsed().as_micros();
+        result = writeln!(
+            writer,
+            "{{\"step\":\"{}\",\"piece\":\"{}\",\"row\":{},\"column\":{},\"depth\":{},\
+             \"time_us\":{}}}",
+            name, placement.piece, placement.row, placement.column, board.stack_count, time,
+        );
+        if result.is_ok() && board.is_complete() {
+            result = writeln!(writer, "{{\"step\":\"solution\",\"time_us\":{time}}}");
+        }
+    };
+    let mut solver = Solver::new(
+        Board::new(searched_row_count, searched_column_count),
+        pieces,
+    )
+    .with_stop(stop)
+    .with_max_nodes(max_nodes)
+    .with_max_solutions(max_solutions)
+    .with_trace(&mut trace);
+    solver.for_each_solution(&mut |board| {
+        let solution = board.position();
+        solutions.push(if transpose {
+            solution.transposed()
+        } else {
+            solution
+        });
+    });
+    let stopped = solver.stopped;
+
+    result
+        .and_then(|()| writer.flush())
+        .map_err(TraceError::Io)?;
+    Ok((solutions, stopped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve_traced;
+
+    #[test]
+    fn trace() {
+        let mut output = vec![];
+        let solutions = solve_traced(2, 4, "OO".parse().unwrap(), u64::MAX, &mut output).unwrap();
+        assert_eq!(solutions.len(), 1);
+
+        // Strip the times
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output
+            .lines()
+            .map(|line| &line[..line.find(",\"time_us\"").unwrap()])
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"step":"place","piece":"O1","row":0,"column":0,"depth":1"#,
+                r#"{"step":"place","piece":"O1","row":0,"column":2,"depth":2"#,
+                r#"{"step":"solution""#,
+                r#"{"step":"backtrack","piece":"O1","row":0,"column":2,"depth":1"#,
+                r#"{"step":"backtrack","piece":"O1","row":0,"column":0,"depth":0"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,294 @@
+// Checking tilings written by hand, e.g. by students, in the plain format of
+// `Position`:
+//
+//     AAAB
+//     CABB
+//     CCCB
+//
+// Each piece is labelled with a letter of its own. Empty squares are '.'.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{BoardShape, Piece, PieceCollection, Position};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParsePositionError {
+    Empty,
+    // A row isn't as long as the first row. Rows are numbered from 0.
+    RaggedRow { row: u32 },
+    // A square isn't a letter, '.' or '#'
+    InvalidCharacter { row: u32, character: char },
+}
+
+impl Display for ParsePositionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ParsePositionError::*;
+        match self {
+            Empty => write!(f, "The solution has no squares."),
+            RaggedRow { row } => write!(f, "Row {row} isn't as long as the first row."),
+            InvalidCharacter { row, character } => write!(
+                f,
+                "Row {row} has the character {character:?}. Squares are letters, '.' or '#'."
+            ),
+        }
+    }
+}
+
+impl Error for ParsePositionError {}
+
+// Reads the plain format. Blank lines and whitespace around rows are ignored.
+impl FromStr for Position {
+    type Err = ParsePositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let Some(first) = rows.first() else {
+            return Err(ParsePositionError::Empty);
+        };
+
+        let mut squares = Vec::with_capacity(rows.len() * (first.len() + 1));
+        for (row, line) in rows.iter().enumerate() {
+            let row = row as u32;
+            if line.chars().count() != first.chars().count() {
+                return Err(ParsePositionError::RaggedRow { row });
+            }
+            if let Some(character) = line
+                .chars()
+                .find(|&c| !(c.is_ascii_alphabetic() || c == '.' || c == '#'))
+            {
+                return Err(ParsePositionError::InvalidCharacter { row, character });
+            }
+            squares.extend_from_slice(line.as_bytes());
+            squares.push(b'\n');
+        }
+        Ok(Position { squares })
+    }
+}
+
+// The first rule a tiling breaks
+#[derive(Debug, Eq, PartialEq)]
+pub enum VerifyError {
+    WrongSize { row_count: u32, column_count: u32 },
+    // A square that no piece covers. Rows and columns are numbered from 0.
+    NotCovered { row: u32, column: u32 },
+    // A piece covers a square that the board shape blocks
+    CoversBlocked { row: u32, column: u32 },
+    // The squares with the label aren't the four squares of a tetromino
+    NotATetromino { label: char },
+    // The pieces aren't the pieces of the puzzle. Both are in canonical
+    // order.
+    WrongPieces { expected: String, found: String },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use VerifyError::*;
+        match self {
+            WrongSize {
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "The solution has {row_count} rows and {column_count} columns, which isn't the \
+                 size of the board."
+            ),
+            NotCovered { row, column } => {
+                write!(f, "The square on row {row}, column {column} isn't covered.")
+            }
+            CoversBlocked { row, column } => {
+                write!(
+                    f,
+                    "The square on row {row}, column {column} is blocked but covered."
+                )
+            }
+            NotATetromino { label } => {
+                write!(f, "The squares labelled '{label}' aren't a tetromino.")
+            }
+            WrongPieces { expected, found } => write!(
+                f,
+                "The solution has the pieces {found} but the puzzle has {expected}."
+            ),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+impl Position {
+    // Check that the position is a tiling of a `row_count` by `column_count`
+    // board with exactly `pieces`
+    pub fn verify(
+        &self,
+        row_count: u32,
+        column_count: u32,
+        pieces: &PieceCollection,
+    ) -> Result<(), VerifyError> {
+        self.verify_shape(&BoardShape::rectangle(row_count, column_count), pieces)
+    }
+
+    // Check that the position is a tiling of the open squares of `shape` with
+    // exactly `pieces`. Its blocked squares may be '#' or '.'.
+    pub fn verify_shape(
+        &self,
+        shape: &BoardShape,
+        pieces: &PieceCollection,
+    ) -> Result<(), VerifyError> {
+        if (self.row_count(), self.column_count()) != (shape.row_count(), shape.column_count()) {
+            return Err(VerifyError::WrongSize {
+                row_count: self.row_count(),
+                column_count: self.column_count(),
+            });
+        }
+
+        let width = shape.column_count() as usize + 1;
+        for (index, &square) in self.squares.iter().enumerate() {
+            let (row, column) = ((index / width) as u32, (index % width) as u32);
+            if square == b'\n' {
+                continue;
+            }
+            let covered = square.is_ascii_alphabetic();
+            if shape.is_blocked(row, column) {
+                if covered {
+                    return Err(VerifyError::CoversBlocked { row, column });
+                }
+            } else if !covered {
+                return Err(VerifyError::NotCovered { row, column });
+            }
+        }
+
+        let label_pieces = self.label_pieces();
+        let mut labels: Vec<u8> = self
+            .squares
+            .iter()
+            .copied()
+            .filter(u8::is_ascii_alphabetic)
+            .collect();
+        labels.sort_unstable();
+        labels.dedup();
+        if let Some(&label) = labels
+            .iter()
+            .find(|label| !label_pieces.contains_key(label))
+        {
+            return Err(VerifyError::NotATetromino {
+                label: char::from(label),
+            });
+        }
+
+        let mut found = PieceCollection {
+            counts: [0; Piece::count()],
+        };
+        for label in &labels {
+            found.add(label_pieces[label]);
+        }
+        let found = found.to_canonical_string();
+        let expected = pieces.to_canonical_string();
+        if found != expected {
+            return Err(VerifyError::WrongPieces { expected, found });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParsePositionError, VerifyError};
+    use crate::{solve_first_canonical, BoardShape, Position};
+
+    fn verify(
+        solution: &str,
+        row_count: u32,
+        column_count: u32,
+        pieces: &str,
+    ) -> Result<(), VerifyError> {
+        let position: Position = solution.parse().unwrap();
+        position.verify(row_count, column_count, &pieces.parse().unwrap())
+    }
+
+    #[test]
+    fn parse() {
+        let solution = solve_first_canonical(4, 6, "TTLSZL".parse().unwrap())
+            .unwrap()
+            .unwrap();
+        let parsed: Position = format!("\n  {solution}  \n").parse().unwrap();
+        assert!(parsed == solution);
+
+        assert_eq!(
+            "".parse::<Position>().err(),
+            Some(ParsePositionError::Empty)
+        );
+        assert_eq!(
+            "AAAA\nBBB\n".parse::<Position>().err(),
+            Some(ParsePositionError::RaggedRow { row: 1 })
+        );
+        assert_eq!(
+            "AAAA\nBB1B\n".parse::<Position>().err(),
+            Some(ParsePositionError::InvalidCharacter {
+                row: 1,
+                character: '1'
+            })
+        );
+    }
+
+    #[test]
+    fn valid() {
+        let solution = solve_first_canonical(4, 6, "TTLSZL".parse().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(solution.verify(4, 6, &"LLSTTZ".parse().unwrap()), Ok(()));
+        assert_eq!(verify("AAAA\nBBBB\n", 2, 4, "II"), Ok(()));
+        assert_eq!(verify("AABB\nAABB\n", 2, 4, "OO"), Ok(()));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            verify("AAAA\nBBBB\n", 4, 2, "II"),
+            Err(VerifyError::WrongSize {
+                row_count: 2,
+                column_count: 4
+            })
+        );
+        assert_eq!(
+            verify("AAAA\nBBB.\n", 2, 4, "II"),
+            Err(VerifyError::NotCovered { row: 1, column: 3 })
+        );
+        assert_eq!(
+            verify("AABB\nABBA\n", 2, 4, "SS"),
+            Err(VerifyError::NotATetromino { label: 'A' })
+        );
+        assert_eq!(
+            verify("AABB\nAABB\n", 2, 4, "II"),
+            Err(VerifyError::WrongPieces {
+                expected: "II".to_string(),
+                found: "OO".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn shape() {
+        let shape: BoardShape = "4./4#/4.".parse().unwrap();
+        let pieces = "II".parse().unwrap();
+        let solution: Position = "AAAA\n####\nBBBB\n".parse().unwrap();
+        assert_eq!(solution.verify_shape(&shape, &pieces), Ok(()));
+        let solution: Position = "AAAA\n....\nBBBB\n".parse().unwrap();
+        assert_eq!(solution.verify_shape(&shape, &pieces), Ok(()));
+
+        let solution: Position = "AAAA\nBBBB\n####\n".parse().unwrap();
+        assert_eq!(
+            solution.verify_shape(&shape, &pieces),
+            Err(VerifyError::CoversBlocked { row: 1, column: 0 })
+        );
+        let solution: Position = "AAAA\n####\n#BBB\n".parse().unwrap();
+        assert_eq!(
+            solution.verify_shape(&shape, &pieces),
+            Err(VerifyError::NotCovered { row: 2, column: 0 })
+        );
+    }
+}
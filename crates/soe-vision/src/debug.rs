@@ -0,0 +1,305 @@
+// What the reader saw in a screenshot, for working out why it reads one
+// wrongly. The log has a line for each decision the reader made and the
+// images show what it looked at:
+//
+//  * `mask-COLOR`: white where the pixels have each color the reader looks
+//    for, after the colors are corrected
+//  * `objects`: the board squares in magenta, the sprites of the pieces in
+//    blue, the tray in orange and the progress dots in green
+//  * `grid`: the rows and columns of the board read, over the screenshot
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use image::{Rgb, RgbImage};
+
+use crate::{
+    enclosing, is_color, read_scan, scan, Bounds, ReadError, Reading, Rect, Scan, CYAN, GOLD,
+    GREEN, RED, WHITE, YELLOW,
+};
+
+const SQUARE_OUTLINE: [u8; 3] = [255, 0, 255];
+const SPRITE_OUTLINE: [u8; 3] = [0, 90, 255];
+const TRAY_OUTLINE: [u8; 3] = [255, 140, 0];
+const DOT_OUTLINE: [u8; 3] = [0, 255, 0];
+const GRID_LINE: [u8; 3] = [255, 0, 0];
+
+// The log and the images of a reading
+#[derive(Clone, Debug, Default)]
+pub struct DebugOutput {
+    pub log: Vec<LogEntry>,
+    // The images by name, e.g. "objects"
+    pub images: Vec<(String, RgbImage)>,
+}
+
+// A decision of the reader, e.g. the size of the board, with the stage it was
+// made in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub stage: &'static str,
+    pub message: String,
+}
+
+impl Display for LogEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.stage, self.message)
+    }
+}
+
+impl DebugOutput {
+    fn log(&mut self, stage: &'static str, message: String) {
+        self.log.push(LogEntry { stage, message });
+    }
+
+    // Write each image as NAME.png and the log as log.txt in `dir`, which is
+    // created if it doesn't exist
+    pub fn write<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        for (name, image) in &self.images {
+            image.save(dir.join(format!("{name}.png")))?;
+        }
+        let mut log = fs::File::create(dir.join("log.txt"))?;
+        for entry in &self.log {
+            writeln!(log, "{entry}")?;
+        }
+        Ok(())
+    }
+
+    fn add_masks(&mut self, image: &RgbImage) {
+        for color in [&WHITE, &GOLD, &CYAN, &GREEN, &YELLOW, &RED] {
+            let mask = RgbImage::from_fn(image.width(), image.height(), |x, y| {
+                if is_color(*image.get_pixel(x, y), color) {
+                    Rgb([255; 3])
+                } else {
+                    Rgb([0; 3])
+                }
+            });
+            self.images.push((format!("mask-{}", color.name), mask));
+        }
+    }
+
+    fn log_scan(&mut self, scan: &Scan) {
+        match scan.white {
+            Some([r, g, b]) => self.log(
+                "colors",
+                format!("corrected for a white of ({r:.0}, {g:.0}, {b:.0})"),
+            ),
+            None => self.log("colors", "not corrected".to_string()),
+        }
+        let sizes = scan.min_sizes;
+        self.log(
+            "sizes",
+            format!(
+                "least sizes: square {}, sprite {}, dot {}, icon {}",
+                sizes.square, sizes.sprite, sizes.dot, sizes.icon
+            ),
+        );
+        if scan.white_squares.is_empty() {
+            self.log("board", "no white squares found".to_string());
+        } else {
+            self.log(
+                "board",
+                format!(
+                    "{} white squares in {}",
+                    scan.white_squares.len(),
+                    describe(&enclosing(&scan.white_squares))
+                ),
+            );
+        }
+        match &scan.tray {
+            Some(tray) => self.log("tray", format!("pieces looked for in {}", describe(tray))),
+            None => self.log(
+                "tray",
+                "not found, pieces looked for on the whole screen".to_string(),
+            ),
+        }
+        for sprite in &scan.tetrominoes {
+            let alternatives: Vec<String> = sprite
+                .alternatives
+                .iter()
+                .map(|(fixed_piece, extra)| format!(", or {fixed_piece} ({extra:+.2})"))
+                .collect();
+            self.log(
+                "sprite",
+                format!(
+                    "{} {} in {}{}",
+                    sprite.color,
+                    sprite.fixed_piece,
+                    describe(&sprite.rect),
+                    alternatives.concat()
+                ),
+            );
+        }
+        self.log(
+            "dots",
+            format!(
+                "{} of {} gold objects counted as progress dots",
+                scan.progress_dot_count,
+                scan.dots.len()
+            ),
+        );
+    }
+}
+
+// Read the level from a screenshot as `read` does, and keep what the reader
+// saw
+pub fn read_debug(image: &RgbImage) -> (Result<Reading, ReadError>, DebugOutput) {
+    let mut debug = DebugOutput::default();
+    let scan = match scan(image, true) {
+        Ok(scan) => scan,
+        Err(err) => {
+            debug.log("scan", err.to_string());
+            debug.add_masks(image);
+            return (Err(err), debug);
+        }
+    };
+    debug.log_scan(&scan);
+    debug.add_masks(&scan.image);
+
+    let mut objects = scan.image.clone();
+    for square in &scan.white_squares {
+        outline(&mut objects, square, SQUARE_OUTLINE);
+    }
+    for sprite in &scan.tetrominoes {
+        outline(&mut objects, &sprite.rect, SPRITE_OUTLINE);
+    }
+    if let Some(tray) = &scan.tray {
+        outline(&mut objects, tray, TRAY_OUTLINE);
+    }
+    for dot in &scan.dots {
+        outline(&mut objects, dot, DOT_OUTLINE);
+    }
+    debug.images.push(("objects".to_string(), objects));
+
+    let mut grid = scan.image.clone();
+    let result = read_scan(scan);
+    match &result {
+        Ok(reading) => {
+            draw_grid(
+                &mut grid,
+                reading.board,
+                reading.row_count,
+                reading.column_count,
+            );
+            debug.images.push(("grid".to_string(), grid));
+            debug.log(
+                "reading",
+                format!(
+                    "{} level {}, {} by {}, pieces {}, confidence {:.2}",
+                    reading.color,
+                    reading
+                        .number
+                        .map_or_else(|| "?".to_string(), |number| number.to_string()),
+                    reading.row_count,
+                    reading.column_count,
+                    reading.pieces,
+                    reading.confidence
+                ),
+            );
+        }
+        Err(err) => debug.log("reading", err.to_string()),
+    }
+    (result, debug)
+}
+
+// The position and size of a rectangle, e.g. "(10, 20) 30x40"
+fn describe(rect: &Rect) -> String {
+    format!(
+        "({}, {}) {}x{}",
+        rect.x1,
+        rect.y1,
+        rect.width(),
+        rect.height()
+    )
+}
+
+// Draw the edges of `rect`
+fn outline(image: &mut RgbImage, rect: &Rect, color: [u8; 3]) {
+    let color = Rgb(color);
+    for x in rect.x1..=rect.x2 {
+        image.put_pixel(x, rect.y1, color);
+        image.put_pixel(x, rect.y2, color);
+    }
+    for y in rect.y1..=rect.y2 {
+        image.put_pixel(rect.x1, y, color);
+        image.put_pixel(rect.x2, y, color);
+    }
+}
+
+// Draw the lines between the rows and columns of the board in `bounds`
+fn draw_grid(image: &mut RgbImage, bounds: Bounds, row_count: u32, column_count: u32) {
+    let rect = Rect::from(bounds);
+    for column in 0..=column_count.max(1) {
+        let x = (rect.x1 + column * bounds.width / column_count.max(1)).min(rect.x2);
+        for y in rect.y1..=rect.y2 {
+            image.put_pixel(x, y, Rgb(GRID_LINE));
+        }
+    }
+    for row in 0..=row_count.max(1) {
+        let y = (rect.y1 + row * bounds.height / row_count.max(1)).min(rect.y2);
+        for x in rect.x1..=rect.x2 {
+            image.put_pixel(x, y, Rgb(GRID_LINE));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_debug;
+    use crate::synthetic::{render, Spec};
+    use image::RgbImage;
+    use soe_core::FixedPiece;
+
+    #[test]
+    fn debug_images_and_log() {
+        let spec = Spec {
+            color: "cyan",
+            number: 2,
+            row_count: 2,
+            column_count: 4,
+            sprites: vec![FixedPiece::I2, FixedPiece::I2],
+            square_size: 24,
+            gap: 2,
+            sprite_square_size: 10,
+        };
+        let (reading, debug) = read_debug(&render(&spec));
+        assert_eq!(reading.unwrap().pieces, "II");
+        let names: Vec<_> = debug.images.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mask-white",
+                "mask-gold",
+                "mask-cyan",
+                "mask-green",
+                "mask-yellow",
+                "mask-red",
+                "objects",
+                "grid"
+            ]
+        );
+        let stages: Vec<_> = debug.log.iter().map(|entry| entry.stage).collect();
+        assert_eq!(stages.iter().filter(|&&stage| stage == "sprite").count(), 2);
+        assert!(debug
+            .log
+            .last()
+            .unwrap()
+            .message
+            .starts_with("cyan level 2, 2 by 4"));
+
+        let dir = std::env::temp_dir().join(format!("soe_debug_{}", std::process::id()));
+        debug.write(&dir).unwrap();
+        assert!(dir.join("grid.png").exists());
+        let log = std::fs::read_to_string(dir.join("log.txt")).unwrap();
+        assert!(log.starts_with("colors: "));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (reading, debug) = read_debug(&RgbImage::new(50, 50));
+        assert!(reading.is_err());
+        assert_eq!(debug.images.len(), 6);
+        assert_eq!(debug.log.len(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+// Decoders for the formats the image crate can't read: WebP, which its decoder
+// only reads the brightness of, and HEIC, the format of iPhone photos. Each is
+// behind a feature and the file is rejected without it.
+
+use image::RgbImage;
+
+use crate::ReadError;
+
+pub fn is_webp(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP")
+}
+
+pub fn is_heic(bytes: &[u8]) -> bool {
+    bytes.get(4..8) == Some(b"ftyp")
+        && matches!(
+            bytes.get(8..12),
+            Some(b"heic" | b"heix" | b"hevc" | b"heif" | b"mif1" | b"msf1")
+        )
+}
+
+// The width and height of the image, read from its header without decoding
+// the pixels
+pub fn dimensions(bytes: &[u8]) -> Result<(u64, u64), ReadError> {
+    use image::{bmp, gif, hdr, ico, jpeg, png, pnm, tga, tiff, webp, ImageDecoder, ImageFormat};
+    use std::io::{BufReader, Cursor};
+
+    if is_webp(bytes) {
+        return webp_dimensions(bytes);
+    }
+    if is_heic(bytes) {
+        return heic_dimensions(bytes);
+    }
+    let reader = Cursor::new(bytes);
+    let format = image::guess_format(bytes).map_err(ReadError::Image)?;
+    let dimensions = match format {
+        ImageFormat::PNG => png::PNGDecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::JPEG => jpeg::JPEGDecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::GIF => gif::Decoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::TIFF => tiff::TIFFDecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::TGA => tga::TGADecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::BMP => bmp::BMPDecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::ICO => ico::ICODecoder::new(reader).map(|d| d.dimensions()),
+        ImageFormat::HDR => hdr::HDRAdapter::new(BufReader::new(reader)).map(|d| d.dimensions()),
+        ImageFormat::PNM => pnm::PNMDecoder::new(BufReader::new(reader)).map(|d| d.dimensions()),
+        ImageFormat::WEBP => webp::WebpDecoder::new(reader).map(|d| d.dimensions()),
+    };
+    dimensions.map_err(ReadError::Image)
+}
+
+#[cfg(feature = "webp")]
+pub fn webp(bytes: &[u8]) -> Result<RgbImage, ReadError> {
+    use image::{DynamicImage, ImageError, RgbaImage};
+    use image_webp::WebPDecoder;
+
+    let error =
+        |err: image_webp::DecodingError| ReadError::Image(ImageError::FormatError(err.to_string()));
+    let mut decoder = WebPDecoder::new(std::io::Cursor::new(bytes)).map_err(error)?;
+    let (width, height) = decoder.dimensions();
+    let size = decoder
+        .output_buffer_size()
+        .ok_or(ReadError::Image(ImageError::InsufficientMemory))?;
+    let mut data = vec![0; size];
+    // The first frame of an animation
+    decoder.read_image(&mut data).map_err(error)?;
+    let image = if decoder.has_alpha() {
+        RgbaImage::from_raw(width, height, data)
+            .map(|image| DynamicImage::ImageRgba8(image).to_rgb())
+    } else {
+        RgbImage::from_raw(width, height, data)
+    };
+    image.ok_or(ReadError::Image(ImageError::DimensionError))
+}
+
+#[cfg(feature = "webp")]
+fn webp_dimensions(bytes: &[u8]) -> Result<(u64, u64), ReadError> {
+    use image::ImageError;
+    use image_webp::WebPDecoder;
+
+    let decoder = WebPDecoder::new(std::io::Cursor::new(bytes))
+        .map_err(|err| ReadError::Image(ImageError::FormatError(err.to_string())))?;
+    let (width, height) = decoder.dimensions();
+    Ok((u64::from(width), u64::from(height)))
+}
+
+#[cfg(not(feature = "webp"))]
+pub fn webp(_bytes: &[u8]) -> Result<RgbImage, ReadError> {
+    Err(ReadError::UnsupportedFormat("WebP"))
+}
+
+#[cfg(not(feature = "webp"))]
+fn webp_dimensions(_bytes: &[u8]) -> Result<(u64, u64), ReadError> {
+    Err(ReadError::UnsupportedFormat("WebP"))
+}
+
+// libheif applies the rotation and mirroring stored in the file, so the image
+// is upright
+#[cfg(feature = "heic")]
+pub fn heic(bytes: &[u8]) -> Result<RgbImage, ReadError> {
+    use image::ImageError;
+    use libheif_rs::{ColorSpace, HeifContext, HeifError, LibHeif, RgbChroma};
+
+    let error = |err: HeifError| ReadError::Image(ImageError::FormatError(err.to_string()));
+    let context = HeifContext::read_from_bytes(bytes).map_err(error)?;
+    let handle = context.primary_image_handle().map_err(error)?;
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .map_err(error)?;
+    let plane = image
+        .planes()
+        .interleaved
+        .ok_or(ReadError::Image(ImageError::DimensionError))?;
+    // Rows are padded to the stride
+    let row = plane.width as usize * 3;
+    let data = plane
+        .data
+        .chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|line| &line[..row])
+        .copied()
+        .collect();
+    RgbImage::from_raw(plane.width, plane.height, data)
+        .ok_or(ReadError::Image(ImageError::DimensionError))
+}
+
+#[cfg(feature = "heic")]
+fn heic_dimensions(bytes: &[u8]) -> Result<(u64, u64), ReadError> {
+    use image::ImageError;
+    use libheif_rs::{HeifContext, HeifError};
+
+    let error = |err: HeifError| ReadError::Image(ImageError::FormatError(err.to_string()));
+    let context = HeifContext::read_from_bytes(bytes).map_err(error)?;
+    let handle = context.primary_image_handle().map_err(error)?;
+    Ok((u64::from(handle.width()), u64::from(handle.height())))
+}
+
+#[cfg(not(feature = "heic"))]
+pub fn heic(_bytes: &[u8]) -> Result<RgbImage, ReadError> {
+    Err(ReadError::UnsupportedFormat("HEIC"))
+}
+
+#[cfg(not(feature = "heic"))]
+fn heic_dimensions(_bytes: &[u8]) -> Result<(u64, u64), ReadError> {
+    Err(ReadError::UnsupportedFormat("HEIC"))
+}
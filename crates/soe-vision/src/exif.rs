@@ -0,0 +1,198 @@
+// The EXIF orientation of photos, which phones save upright and tag with the
+// rotation needed to display them.
+
+use image::imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90};
+use image::RgbImage;
+
+const ORIENTATION_TAG: u16 = 0x0112;
+
+// The orientation in the EXIF data of a JPEG, PNG or WebP file, from 1 to 8.
+// HEIC files are turned upright by libheif.
+pub fn orientation(bytes: &[u8]) -> Option<u16> {
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg_exif(bytes).and_then(tiff_orientation)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_exif(bytes).and_then(tiff_orientation)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        webp_exif(bytes).and_then(tiff_orientation)
+    } else {
+        None
+    }
+}
+
+// Turn an image as stored into the image as displayed
+pub fn orient(image: RgbImage, orientation: u16) -> RgbImage {
+    match orientation {
+        2 => flip_horizontal(&image),
+        3 => rotate180(&image),
+        4 => flip_vertical(&image),
+        // Transposed
+        5 => flip_horizontal(&rotate90(&image)),
+        6 => rotate90(&image),
+        // Transposed across the other diagonal
+        7 => flip_horizontal(&rotate270(&image)),
+        8 => rotate270(&image),
+        _ => image,
+    }
+}
+
+// The APP1 segment with the EXIF data, which comes before the image data
+fn jpeg_exif(bytes: &[u8]) -> Option<&[u8]> {
+    let mut i = 2;
+    while bytes.get(i) == Some(&0xFF) {
+        let marker = *bytes.get(i + 1)?;
+        // Start of scan and end of image
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = usize::from(read_u16(bytes, i + 2, true)?);
+        let segment = bytes.get(i + 4..i + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(&segment[6..]);
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+// The eXIf chunk
+fn png_exif(bytes: &[u8]) -> Option<&[u8]> {
+    let mut i = 8;
+    while i + 8 <= bytes.len() {
+        let length = read_u32(bytes, i, true)? as usize;
+        let data = bytes.get(i + 8..i + 8 + length)?;
+        match &bytes[i + 4..i + 8] {
+            b"eXIf" => return Some(data),
+            b"IDAT" | b"IEND" => return None,
+            _ => i += 12 + length,
+        }
+    }
+    None
+}
+
+// The EXIF chunk of the RIFF file, which some writers start with the JPEG
+// marker
+fn webp_exif(bytes: &[u8]) -> Option<&[u8]> {
+    let mut i = 12;
+    while i + 8 <= bytes.len() {
+        let length = read_u32(bytes, i + 4, false)? as usize;
+        let data = bytes.get(i + 8..i + 8 + length)?;
+        if &bytes[i..i + 4] == b"EXIF" {
+            return Some(data.strip_prefix(b"Exif\0\0").unwrap_or(data));
+        }
+        // Chunks are padded to an even length
+        i += 8 + length + length % 2;
+    }
+    None
+}
+
+// The orientation tag of the first image file directory
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let directory = read_u32(tiff, 4, big_endian)? as usize;
+    let entry_count = usize::from(read_u16(tiff, directory, big_endian)?);
+    (0..entry_count)
+        .map(|i| directory + 2 + 12 * i)
+        .find(|&entry| read_u16(tiff, entry, big_endian) == Some(ORIENTATION_TAG))
+        .and_then(|entry| read_u16(tiff, entry + 8, big_endian))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+fn read_u16(bytes: &[u8], i: usize, big_endian: bool) -> Option<u16> {
+    let b = bytes.get(i..i + 2)?;
+    let b = [b[0], b[1]];
+    Some(if big_endian {
+        u16::from_be_bytes(b)
+    } else {
+        u16::from_le_bytes(b)
+    })
+}
+
+fn read_u32(bytes: &[u8], i: usize, big_endian: bool) -> Option<u32> {
+    let b = bytes.get(i..i + 4)?;
+    let b = [b[0], b[1], b[2], b[3]];
+    Some(if big_endian {
+        u32::from_be_bytes(b)
+    } else {
+        u32::from_le_bytes(b)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{orient, orientation};
+    use image::{Rgb, RgbImage};
+
+    // An EXIF block with only the orientation
+    fn tiff(orientation: u16, big_endian: bool) -> Vec<u8> {
+        let mut tiff = vec![];
+        if big_endian {
+            tiff.extend(b"MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01");
+            tiff.extend(&orientation.to_be_bytes());
+        } else {
+            tiff.extend(b"II\x2a\0\x08\0\0\0\x01\0\x12\x01\x03\0\x01\0\0\0");
+            tiff.extend(&orientation.to_le_bytes());
+        }
+        tiff.extend(&[0; 6]);
+        tiff
+    }
+
+    #[test]
+    fn read_orientation() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        // A segment before the EXIF data
+        jpeg.extend(&[0xFF, 0xE0, 0, 4, 0, 0]);
+        let exif = [&b"Exif\0\0"[..], &tiff(6, true)].concat();
+        jpeg.extend(&[0xFF, 0xE1]);
+        jpeg.extend(&(exif.len() as u16 + 2).to_be_bytes());
+        jpeg.extend(&exif);
+        jpeg.extend(&[0xFF, 0xDA]);
+        assert_eq!(orientation(&jpeg), Some(6));
+
+        let exif = tiff(8, false);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(&[0, 0, 0, 0]);
+        png.extend(b"IHDR\0\0\0\0");
+        png.extend(&(exif.len() as u32).to_be_bytes());
+        png.extend(b"eXIf");
+        png.extend(&exif);
+        assert_eq!(orientation(&png), Some(8));
+
+        let exif = [&b"Exif\0\0"[..], &tiff(3, false)].concat();
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        // A chunk of odd length with a pad byte
+        webp.extend(b"VP8X\x01\0\0\0\0\0");
+        webp.extend(b"EXIF");
+        webp.extend(&(exif.len() as u32).to_le_bytes());
+        webp.extend(&exif);
+        assert_eq!(orientation(&webp), Some(3));
+
+        assert_eq!(orientation(&[0xFF, 0xD8, 0xFF, 0xDA]), None);
+        assert_eq!(orientation(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn orient_image() {
+        // 2 by 1 with a white pixel on the left
+        let mut image = RgbImage::new(2, 1);
+        image.put_pixel(0, 0, Rgb([255; 3]));
+        let white = |image: &RgbImage| {
+            let (x, y, _) = image
+                .enumerate_pixels()
+                .find(|(_, _, p)| p[0] == 255)
+                .unwrap();
+            (image.dimensions(), (x, y))
+        };
+        assert_eq!(white(&orient(image.clone(), 1)), ((2, 1), (0, 0)));
+        assert_eq!(white(&orient(image.clone(), 2)), ((2, 1), (1, 0)));
+        assert_eq!(white(&orient(image.clone(), 3)), ((2, 1), (1, 0)));
+        assert_eq!(white(&orient(image.clone(), 5)), ((1, 2), (0, 0)));
+        assert_eq!(white(&orient(image.clone(), 6)), ((1, 2), (0, 0)));
+        assert_eq!(white(&orient(image.clone(), 7)), ((1, 2), (0, 1)));
+        assert_eq!(white(&orient(image, 8)), ((1, 2), (0, 1)));
+    }
+}
@@ -0,0 +1,1802 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::enum_glob_use)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+
+// Reading levels from screenshots of the game: the board size, the color of
+// the level, its number from the progress dots and the pieces.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use image::{ImageError, Rgb, RgbImage};
+
+use soe_core::geometry::{Cell, Shape};
+use soe_core::{puzzles, CellIndex, ColCount, FixedPiece, RowCount};
+
+mod debug;
+mod decode;
+mod exif;
+mod split;
+mod sprite;
+
+pub use debug::{read_debug, DebugOutput, LogEntry};
+pub use sprite::{classify_sprite, SpriteClass};
+#[cfg(test)]
+pub(crate) mod synthetic;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+// A rectangle with top-left point (x1, y1) and bottom-right point (x2, y2).
+struct Rect {
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+}
+
+struct Color {
+    name: &'static str,
+    range: [RangeInclusive<u8>; 3],
+}
+
+const WHITE: Color = Color {
+    name: "white",
+    range: [170..=255, 160..=255, 145..=255],
+};
+
+const GOLD: Color = Color {
+    name: "gold",
+    range: [110..=155, 95..=255, 55..=100],
+};
+
+const CYAN: Color = Color {
+    name: "cyan",
+    range: [0..=35, 80..=255, 100..=255],
+};
+
+const GREEN: Color = Color {
+    name: "green",
+    range: [0..=60, 45..=255, 0..=40],
+};
+
+const YELLOW: Color = Color {
+    name: "yellow",
+    range: [100..=255, 50..=255, 0..=90],
+};
+
+const RED: Color = Color {
+    name: "red",
+    range: [70..=255, 0..=30, 0..=30],
+};
+
+// Light enough to be a board square under a blue light filter, which makes
+// the white squares orange
+const BRIGHT: Color = Color {
+    name: "bright",
+    range: [120..=255, 100..=255, 60..=255],
+};
+
+const TETROMINO_COLORS: [&Color; 4] = [&CYAN, &GREEN, &YELLOW, &RED];
+
+impl Rect {
+    fn width(&self) -> u32 {
+        self.x2 - self.x1 + 1
+    }
+
+    fn height(&self) -> u32 {
+        self.y2 - self.y1 + 1
+    }
+
+    fn pixel_count(&self) -> u32 {
+        self.width() * self.height()
+    }
+}
+
+// A rectangle of the screenshot in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<Rect> for Bounds {
+    fn from(rect: Rect) -> Self {
+        Self {
+            x: rect.x1,
+            y: rect.y1,
+            width: rect.width(),
+            height: rect.height(),
+        }
+    }
+}
+
+impl From<Bounds> for Rect {
+    fn from(bounds: Bounds) -> Self {
+        Self {
+            x1: bounds.x,
+            y1: bounds.y,
+            x2: bounds.x + bounds.width - 1,
+            y2: bounds.y + bounds.height - 1,
+        }
+    }
+}
+
+// What was read from a screenshot of a level
+#[derive(Debug)]
+pub struct Reading {
+    // The color of the level
+    pub color: &'static str,
+    // The number of gold dots at the bottom of the screen
+    pub progress_dot_count: u32,
+    // The number of the level in its color, the number of dots, or `None` if
+    // no level of the color has that number
+    pub number: Option<u32>,
+    pub row_count: u32,
+    pub column_count: u32,
+    // The pieces in the order they are shown, top to bottom and left to right
+    pub pieces: String,
+    // How sure the reader is that the screenshot is of a level, from 0 to 1.
+    // Readings under `MIN_CONFIDENCE` are rejected.
+    pub confidence: f64,
+    // Where the board is on the screen
+    pub board: Bounds,
+    // The part of the screen with the pieces, below or right of the board.
+    // `None` if no pieces were found there, and the whole screen was searched.
+    pub tray: Option<Bounds>,
+    // The width of a square of the board in pixels
+    pub square_size: f64,
+    // Where each square of the board is on the screen, row by row
+    pub cells: Vec<Bounds>,
+    // The orientation and position of each piece in the order of `pieces`.
+    // Pieces read from counts share the bounds of their icon.
+    pub sprites: Vec<(FixedPiece, Bounds)>,
+    // The piece lists the pieces may be, most likely first, when sprites
+    // could be more than one shape, e.g. an S or a Z with a smudged square.
+    // The first is `pieces` and there are at most `MAX_CANDIDATES`.
+    pub candidates: Vec<String>,
+}
+
+impl Reading {
+    // The size of the board read
+    #[must_use]
+    pub fn dimensions(&self) -> (RowCount, ColCount) {
+        (
+            RowCount::new(self.row_count).unwrap(),
+            ColCount::new(self.column_count).unwrap(),
+        )
+    }
+
+    // Where the square is on the screen, `None` if it's off the board
+    #[must_use]
+    pub fn cell(&self, index: CellIndex) -> Option<Bounds> {
+        self.cells.get(index.get() as usize).copied()
+    }
+
+    // The square at the point `x`, `y` of the screen, `None` if the point
+    // isn't on a square. `x` is across the columns and `y` down the rows.
+    #[must_use]
+    pub fn cell_at(&self, x: u32, y: u32) -> Option<CellIndex> {
+        let (row_count, column_count) = self.dimensions();
+        let index = self.cells.iter().position(|cell| {
+            (cell.x..cell.x + cell.width).contains(&x)
+                && (cell.y..cell.y + cell.height).contains(&y)
+        })? as u32;
+        let (row, column) = (index / column_count.get(), index % column_count.get());
+        CellIndex::new(row, column, row_count, column_count)
+    }
+
+    // The reading as a JSON object, e.g.
+    //
+    //     {"color":"cyan","number":1,"rows":2,"columns":4,"pieces":"II",
+    //     "confidence":0.98,"board":{"x":50,"y":50,"width":86,"height":42},
+    //     "tray":{"x":50,"y":120,"width":140,"height":10},
+    //     "square_size":20.0,"cells":[[{"x":50,"y":50,"width":20,...},...],...],
+    //     "candidates":["II"]}
+    //
+    // `cells` has a row of rectangles for each row of the board and `number`
+    // is null when the dots were misread.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let rows: Vec<_> = self
+            .cells
+            .chunks(self.column_count.max(1) as usize)
+            .map(|row| {
+                let row: Vec<_> = row.iter().map(|bounds| bounds.to_json()).collect();
+                format!("[{}]", row.join(","))
+            })
+            .collect();
+        let candidates: Vec<_> = self
+            .candidates
+            .iter()
+            .map(|pieces| format!("\"{pieces}\""))
+            .collect();
+        format!(
+            "{{\"color\":\"{}\",\"number\":{},\"rows\":{},\"columns\":{},\"pieces\":\"{}\",\
+             \"confidence\":{:.2},\"board\":{},\"tray\":{},\"square_size\":{:.1},\
+             \"cells\":[{}],\"candidates\":[{}]}}",
+            self.color,
+            self.number
+                .map_or_else(|| "null".to_string(), |number| number.to_string()),
+            self.row_count,
+            self.column_count,
+            self.pieces,
+            self.confidence,
+            self.board.to_json(),
+            self.tray
+                .map_or_else(|| "null".to_string(), Bounds::to_json),
+            self.square_size,
+            rows.join(","),
+            candidates.join(",")
+        )
+    }
+}
+
+impl Bounds {
+    fn to_json(self) -> String {
+        format!(
+            "{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}}",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+// One of the boards of a screenshot with several, like the level select
+// screen
+#[derive(Debug)]
+pub struct BoardReading {
+    pub color: &'static str,
+    pub row_count: u32,
+    pub column_count: u32,
+    // The pieces nearer to this board than to any other
+    pub pieces: String,
+    pub confidence: f64,
+    pub board: Bounds,
+    pub square_size: f64,
+    pub cells: Vec<Bounds>,
+    pub sprites: Vec<(FixedPiece, Bounds)>,
+    pub candidates: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    // No board squares were found
+    NoBoard,
+    // The pieces aren't all of one color
+    UnknownColor,
+    // Something like a board was found but it doesn't look enough like a
+    // level
+    NotAPuzzle { confidence: f64 },
+    // The file can't be read
+    Io(io::Error),
+    Image(ImageError),
+    // The file is in a format the reader can't decode, e.g. "WebP"
+    UnsupportedFormat(&'static str),
+    // No sprite of the color of a piece was found
+    NoSprite,
+    // The image has more pixels than the reader was allowed to decode
+    ImageTooLarge { width: u64, height: u64 },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ReadError::*;
+        match self {
+            NoBoard => write!(f, "Unable to find the board."),
+            UnknownColor => write!(f, "Unable to determine the level color."),
+            NotAPuzzle { confidence } => write!(
+                f,
+                "The image doesn't look like a level (confidence {confidence:.2})."
+            ),
+            Io(err) => write!(f, "Unable to read the file. {err}"),
+            Image(err) => write!(f, "Unable to decode the image. {err}"),
+            UnsupportedFormat(format) => write!(
+                f,
+                "{format} images aren't supported. Convert the screenshot to PNG or JPEG."
+            ),
+            NoSprite => write!(f, "Unable to find a piece in the image."),
+            ImageTooLarge { width, height } => {
+                write!(f, "The image of {width} by {height} pixels is too large.")
+            }
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Image(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// The least confidence of an accepted reading
+pub const MIN_CONFIDENCE: f64 = 0.6;
+
+// The most candidate piece lists kept for a reading
+pub const MAX_CANDIDATES: usize = 8;
+
+// The height of the title bar and of the progress dots at the bottom, which
+// aren't scanned for the board and the pieces
+const TITLE_HEIGHT: u32 = 40;
+const DOTS_HEIGHT: u32 = 60;
+
+// The smallest objects looked for, in pixels wide and high
+const MIN_OBJECT_SIZE: u32 = 3;
+
+// The least width and height of the objects of each kind. They're in
+// proportion to the board squares, so that downscaled screenshots and large
+// captures are read alike.
+#[derive(Clone, Copy, Debug)]
+struct MinSizes {
+    square: u32,
+    sprite: u32,
+    dot: u32,
+    icon: u32,
+}
+
+impl MinSizes {
+    // The sizes for squares of about 26 pixels, used when no board squares
+    // are found
+    const DEFAULT: MinSizes = MinSizes {
+        square: 13,
+        sprite: 10,
+        dot: 5,
+        icon: 4,
+    };
+
+    fn for_square_size(square_size: f64) -> Self {
+        let scaled = |ratio: f64| ((square_size * ratio) as u32).max(MIN_OBJECT_SIZE);
+        MinSizes {
+            square: scaled(0.5),
+            sprite: scaled(0.3),
+            dot: scaled(0.15),
+            icon: scaled(0.12),
+        }
+    }
+}
+
+// Open a screenshot, see `load_from_memory`
+pub fn open<P: AsRef<Path>>(path: P) -> Result<RgbImage, ReadError> {
+    let bytes = std::fs::read(path).map_err(ReadError::Io)?;
+    load_from_memory(&bytes)
+}
+
+// Decode the bytes of a screenshot file and turn it upright if it is a photo
+// with an EXIF orientation. WebP needs the webp feature and HEIC the heic
+// feature.
+pub fn load_from_memory(bytes: &[u8]) -> Result<RgbImage, ReadError> {
+    let image = if decode::is_webp(bytes) {
+        decode::webp(bytes)?
+    } else if decode::is_heic(bytes) {
+        decode::heic(bytes)?
+    } else {
+        image::load_from_memory(bytes)
+            .map_err(ReadError::Image)?
+            .to_rgb()
+    };
+    Ok(match exif::orientation(bytes) {
+        Some(orientation) => exif::orient(image, orientation),
+        None => image,
+    })
+}
+
+// Like `load_from_memory`, but refuse an image of more than `max_pixels`
+// pixels from the size in its header, before any of it is decoded
+pub fn load_from_memory_within(bytes: &[u8], max_pixels: u64) -> Result<RgbImage, ReadError> {
+    let (width, height) = decode::dimensions(bytes)?;
+    if width.saturating_mul(height) > max_pixels {
+        return Err(ReadError::ImageTooLarge { width, height });
+    }
+    load_from_memory(bytes)
+}
+
+// The board squares, the pieces and the progress dots found in a screenshot
+struct Scan {
+    white_squares: Vec<Rect>,
+    tetrominoes: Vec<Sprite>,
+    // The part of the screen searched for the pieces, when it was found
+    tray: Option<Rect>,
+    progress_dot_count: u32,
+    // The gold objects the dots were counted from
+    dots: Vec<Rect>,
+    // The screenshot with its colors corrected, the white they were corrected
+    // for and the least sizes of the objects looked for, for debugging
+    image: RgbImage,
+    white: Option<[f64; 3]>,
+    min_sizes: MinSizes,
+}
+
+// A piece found in a screenshot
+#[derive(Clone, Debug)]
+struct Sprite {
+    fixed_piece: FixedPiece,
+    rect: Rect,
+    // The name of its color
+    color: &'static str,
+    // Other shapes that match the sprite as well as `fixed_piece`, with how
+    // much worse they match. Empty when the sprite matches a shape exactly.
+    alternatives: Vec<(FixedPiece, f64)>,
+}
+
+// Scan a screenshot. With `find_tray` the pieces are only looked for in the
+// tray of the board, which only works for screenshots with one board.
+fn scan(image: &RgbImage, find_tray: bool) -> Result<Scan, ReadError> {
+    let mut img = image.clone();
+    let (width, height) = img.dimensions();
+    if height <= TITLE_HEIGHT + DOTS_HEIGHT {
+        return Err(ReadError::NoBoard);
+    }
+    let (start_y, end_y) = (TITLE_HEIGHT, height - DOTS_HEIGHT);
+
+    // Correct the colors of screenshots taken with a blue light filter
+    let board_squares = find_board_squares(&img, start_y, end_y);
+    let white = board_squares
+        .as_deref()
+        .and_then(|squares| estimate_white(&img, squares));
+    if let Some(white) = white {
+        white_balance(&mut img, white);
+    }
+    let min_sizes = board_squares.map_or(MinSizes::DEFAULT, |squares| {
+        let mut widths: Vec<u32> = squares.iter().map(Rect::width).collect();
+        widths.sort_unstable();
+        MinSizes::for_square_size(f64::from(widths[widths.len() / 2]))
+    });
+
+    let mut white_squares = vec![];
+
+    // Point that have already been processed
+    let mut checked_points = HashSet::new();
+
+    for y in start_y..end_y {
+        for x in 0..width {
+            if let Some(white_square) = get_bounds(
+                &img,
+                &mut checked_points,
+                x,
+                y,
+                &WHITE,
+                min_sizes.square,
+                0.1,
+            ) {
+                white_squares.push(white_square);
+            }
+        }
+    }
+
+    let tray = if find_tray && !white_squares.is_empty() {
+        find_piece_tray(&img, &enclosing(&white_squares), start_y, end_y)
+    } else {
+        None
+    };
+    let area = tray.clone().unwrap_or(Rect {
+        x1: 0,
+        y1: start_y,
+        x2: width - 1,
+        y2: end_y - 1,
+    });
+    let mut tetrominoes = find_sprites(&img, &mut checked_points, &area, min_sizes.sprite);
+
+    // Some screenshots show small icons with the piece counts next to them
+    // instead of a sprite for every piece
+    if tetrominoes.is_empty() {
+        tetrominoes = read_panel(&img, &area, min_sizes.icon);
+    }
+
+    // Search for level dots at the bottom of the screen
+    let mut dots = vec![];
+    for y in end_y..height {
+        for x in 0..width {
+            if let Some(dot) =
+                get_bounds(&img, &mut checked_points, x, y, &GOLD, min_sizes.dot, 0.1)
+            {
+                dots.push(dot);
+            }
+        }
+    }
+
+    Ok(Scan {
+        white_squares,
+        tetrominoes,
+        tray,
+        progress_dot_count: count_dots(&dots),
+        dots,
+        image: img,
+        white,
+        min_sizes,
+    })
+}
+
+// Find the tray of pieces, the part of the screen below or right of the board
+// with the most pixels of the colors of the pieces, trimmed to those pixels.
+// Only the tray is searched for pieces, so colored art elsewhere isn't taken
+// for them. `None` if neither side has any.
+fn find_piece_tray(image: &RgbImage, board: &Rect, start_y: u32, end_y: u32) -> Option<Rect> {
+    let (width, _) = image.dimensions();
+    let below = Rect {
+        x1: 0,
+        y1: board.y2 + 1,
+        x2: width - 1,
+        y2: end_y - 1,
+    };
+    let right = Rect {
+        x1: board.x2 + 1,
+        y1: start_y,
+        x2: width - 1,
+        y2: end_y - 1,
+    };
+    [below, right]
+        .iter()
+        .filter(|side| side.x1 <= side.x2 && side.y1 <= side.y2)
+        .filter_map(|side| {
+            let mut count = 0;
+            let mut tray: Option<Rect> = None;
+            for y in side.y1..=side.y2 {
+                for x in side.x1..=side.x2 {
+                    let pixel = *image.get_pixel(x, y);
+                    if !TETROMINO_COLORS.iter().any(|color| is_color(pixel, color)) {
+                        continue;
+                    }
+                    count += 1;
+                    let tray = tray.get_or_insert(Rect {
+                        x1: x,
+                        y1: y,
+                        x2: x,
+                        y2: y,
+                    });
+                    tray.x1 = tray.x1.min(x);
+                    tray.x2 = tray.x2.max(x);
+                    tray.y2 = y;
+                }
+            }
+            Some((count, tray?))
+        })
+        .max_by_key(|&(count, _)| count)
+        .map(|(_, tray)| tray)
+}
+
+// Find the sprites of the pieces that start in `area`
+fn find_sprites(
+    image: &RgbImage,
+    checked_points: &mut HashSet<(u32, u32)>,
+    area: &Rect,
+    min_size: u32,
+) -> Vec<Sprite> {
+    // The objects of the colors of the pieces, with their number of pixels
+    let mut objects = vec![];
+    for y in area.y1..=area.y2 {
+        for x in area.x1..=area.x2 {
+            for color in &TETROMINO_COLORS {
+                if let Some((rect, pixel_count)) =
+                    get_object(image, checked_points, x, y, color, min_size, 0.5)
+                {
+                    objects.push((rect, pixel_count, *color));
+                }
+            }
+        }
+    }
+
+    let mut tetrominoes = vec![];
+    // Sprites that touch are found as one object, which is split into the
+    // sprites by the size of the others
+    let pixel_counts: Vec<u32> = objects
+        .iter()
+        .map(|&(_, pixel_count, _)| pixel_count)
+        .collect();
+    let square_size = split::square_size(&pixel_counts);
+    for (rect, pixel_count, color) in objects {
+        let sprites = square_size
+            .and_then(|square_size| split::split(image, &rect, pixel_count, color, square_size));
+        if let Some(sprites) = sprites {
+            tetrominoes.extend(sprites);
+            continue;
+        }
+        let (fixed_piece, alternatives) = classify(image, &rect, color);
+        tetrominoes.push(Sprite {
+            fixed_piece,
+            rect,
+            color: color.name,
+            alternatives,
+        });
+    }
+    tetrominoes
+}
+
+// The number of progress dots among gold objects. The dots are all the same
+// size and in a row, so objects of another size or off the row, e.g. parts of
+// the interface, aren't counted. The lower median is taken as the size of a
+// dot, since stray objects are usually larger.
+fn count_dots(dots: &[Rect]) -> u32 {
+    let median = |values: Vec<u32>| {
+        let mut values = values;
+        values.sort_unstable();
+        values[(values.len() - 1) / 2]
+    };
+    if dots.is_empty() {
+        return 0;
+    }
+    let size = median(
+        dots.iter()
+            .map(|dot| dot.width().max(dot.height()))
+            .collect(),
+    );
+    let row = median(dots.iter().map(|dot| dot.y1 + dot.y2).collect());
+    dots.iter()
+        .filter(|dot| {
+            let dot_size = dot.width().max(dot.height());
+            2 * dot_size >= size && dot_size <= 2 * size && (dot.y1 + dot.y2).abs_diff(row) <= size
+        })
+        .count() as u32
+}
+
+// The number of the level from its progress dots, or `None` if no level of
+// `color` has that number, which means the dots were misread
+#[must_use]
+pub fn level_number(color: &str, progress_dot_count: u32) -> Option<u32> {
+    let level_count = puzzles::all()
+        .iter()
+        .filter(|puzzle| puzzle.color == color)
+        .map(|puzzle| puzzle.number)
+        .max()?;
+    (1..=level_count)
+        .contains(&progress_dot_count)
+        .then_some(progress_dot_count)
+}
+
+// Read the level from a screenshot
+pub fn read(image: &RgbImage) -> Result<Reading, ReadError> {
+    read_scan(scan(image, true)?)
+}
+
+// Read the level from the scan of a screenshot with one board
+fn read_scan(scan: Scan) -> Result<Reading, ReadError> {
+    let Scan {
+        white_squares,
+        tetrominoes,
+        tray,
+        progress_dot_count,
+        ..
+    } = scan;
+    let board = read_board(&white_squares, tetrominoes)?;
+    Ok(Reading {
+        color: board.color,
+        progress_dot_count,
+        number: level_number(board.color, progress_dot_count),
+        row_count: board.row_count,
+        column_count: board.column_count,
+        pieces: board.pieces,
+        confidence: board.confidence,
+        board: board.board,
+        tray: tray.map(Bounds::from),
+        square_size: board.square_size,
+        cells: board.cells,
+        sprites: board.sprites,
+        candidates: board.candidates,
+    })
+}
+
+// Read every board of a screenshot, top to bottom and left to right. Each
+// piece goes with the board nearest to it. Boards that don't look like levels
+// are left out.
+pub fn read_all(image: &RgbImage) -> Result<Vec<BoardReading>, ReadError> {
+    let Scan {
+        white_squares,
+        tetrominoes,
+        ..
+    } = scan(image, false)?;
+    let boards = group_squares(white_squares);
+    if boards.is_empty() {
+        return Err(ReadError::NoBoard);
+    }
+
+    let bounds: Vec<Rect> = boards.iter().map(|squares| enclosing(squares)).collect();
+    let mut pieces = vec![vec![]; boards.len()];
+    for tetromino in tetrominoes {
+        let nearest = (0..boards.len())
+            .min_by_key(|&i| distance(&bounds[i], &tetromino.rect))
+            .unwrap();
+        pieces[nearest].push(tetromino);
+    }
+
+    let mut readings = vec![];
+    let mut first_error = None;
+    for (squares, pieces) in boards.iter().zip(pieces) {
+        match read_board(squares, pieces) {
+            Ok(reading) => readings.push(reading),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) if readings.is_empty() => Err(err),
+        _ => {
+            readings.sort_by(|a, b| reading_order(&a.board.into(), &b.board.into()));
+            Ok(readings)
+        }
+    }
+}
+
+// Read the size of the board made of `white_squares` and its pieces
+fn read_board(
+    white_squares: &[Rect],
+    mut tetrominoes: Vec<Sprite>,
+) -> Result<BoardReading, ReadError> {
+    if white_squares.is_empty() {
+        return Err(ReadError::NoBoard);
+    }
+
+    tetrominoes.sort_by(|a, b| reading_order(&a.rect, &b.rect));
+
+    // Estimate the width of a square
+    let sample_total: u32 = white_squares.iter().map(Rect::width).sum::<u32>()
+        + white_squares.iter().map(Rect::height).sum::<u32>();
+    let sample_count = 2 * white_squares.len() as u32;
+    let square_width: f64 = f64::from(sample_total) / f64::from(sample_count);
+
+    let board = enclosing(white_squares);
+    let column_count = (f64::from(board.x2 - board.x1) / square_width).round() as u32;
+    let row_count = (f64::from(board.y2 - board.y1) / square_width).round() as u32;
+
+    let colors: HashSet<_> = tetrominoes.iter().map(|sprite| sprite.color).collect();
+    if colors.len() != 1 {
+        return Err(ReadError::UnknownColor);
+    }
+
+    let pieces: String = tetrominoes
+        .iter()
+        .map(|sprite| letter(sprite.fixed_piece))
+        .collect();
+    let confidence = confidence(white_squares, row_count, column_count, pieces.len());
+    if confidence < MIN_CONFIDENCE {
+        return Err(ReadError::NotAPuzzle { confidence });
+    }
+
+    let cells = cells(white_squares, &board, row_count, column_count, square_width);
+    Ok(BoardReading {
+        color: colors.into_iter().next().unwrap(),
+        row_count,
+        column_count,
+        pieces,
+        confidence,
+        board: board.into(),
+        square_size: square_width,
+        cells,
+        candidates: candidates(&tetrominoes),
+        sprites: tetrominoes
+            .into_iter()
+            .map(|sprite| (sprite.fixed_piece, sprite.rect.into()))
+            .collect(),
+    })
+}
+
+// The piece lists of the sprites, most likely first. The lists are built a
+// sprite at a time, keeping the `MAX_CANDIDATES` best so far, and lists with
+// the same pieces in another order are dropped.
+fn candidates(sprites: &[Sprite]) -> Vec<String> {
+    let mut lists = vec![(0.0, String::new())];
+    for sprite in sprites {
+        let mut next = vec![];
+        for (penalty, pieces) in &lists {
+            next.push((*penalty, pieces.clone() + &letter(sprite.fixed_piece)));
+            for &(fixed_piece, extra) in &sprite.alternatives {
+                next.push((penalty + extra, pieces.clone() + &letter(fixed_piece)));
+            }
+        }
+        next.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        let mut seen = HashSet::new();
+        next.retain(|(_, pieces)| {
+            let mut letters: Vec<char> = pieces.chars().collect();
+            letters.sort_unstable();
+            seen.insert(letters)
+        });
+        next.truncate(MAX_CANDIDATES);
+        lists = next;
+    }
+    lists.into_iter().map(|(_, pieces)| pieces).collect()
+}
+
+// The squares of a board spread evenly over its bounds. A square found on the
+// screen is used as is, which keeps the small differences in the gaps between
+// squares.
+fn cells(
+    white_squares: &[Rect],
+    board: &Rect,
+    row_count: u32,
+    column_count: u32,
+    square_width: f64,
+) -> Vec<Bounds> {
+    let size = square_width.round() as u32;
+    let pitch = |span: u32, count: u32| {
+        if count > 1 {
+            f64::from(span.saturating_sub(size)) / f64::from(count - 1)
+        } else {
+            0.0
+        }
+    };
+    let (pitch_x, pitch_y) = (
+        pitch(board.width(), column_count),
+        pitch(board.height(), row_count),
+    );
+
+    let mut cells = vec![];
+    for row in 0..row_count {
+        for column in 0..column_count {
+            let x = board.x1 + (f64::from(column) * pitch_x).round() as u32;
+            let y = board.y1 + (f64::from(row) * pitch_y).round() as u32;
+            let cell = Rect {
+                x1: x,
+                y1: y,
+                x2: x + size - 1,
+                y2: y + size - 1,
+            };
+            let found = white_squares.iter().find(|square| {
+                let center_x = square.x1.midpoint(square.x2);
+                let center_y = square.y1.midpoint(square.y2);
+                (cell.x1..=cell.x2).contains(&center_x) && (cell.y1..=cell.y2).contains(&center_y)
+            });
+            cells.push(found.cloned().unwrap_or(cell).into());
+        }
+    }
+    cells
+}
+
+// Top to bottom and then left to right for rectangles on the same line
+fn reading_order(a: &Rect, b: &Rect) -> Ordering {
+    if a.y2 < b.y1 {
+        Ordering::Less
+    } else if a.y1 > b.y2 {
+        Ordering::Greater
+    } else {
+        a.x1.cmp(&b.x1)
+    }
+}
+
+// Group the squares into boards. Squares of the same board are closer than
+// half a square apart.
+fn group_squares(squares: Vec<Rect>) -> Vec<Vec<Rect>> {
+    // The squares are numbered and each is linked to another of its group
+    // until the root of the group, which is linked to itself
+    let mut groups: Vec<usize> = (0..squares.len()).collect();
+    for i in 0..squares.len() {
+        for j in i + 1..squares.len() {
+            let (a, b) = (&squares[i], &squares[j]);
+            let margin = i64::from(a.width().min(b.width()) / 2);
+            let gap_x = i64::from(a.x1.max(b.x1)) - i64::from(a.x2.min(b.x2));
+            let gap_y = i64::from(a.y1.max(b.y1)) - i64::from(a.y2.min(b.y2));
+            if gap_x <= margin && gap_y <= margin {
+                let (root_i, root_j) = (root(&mut groups, i), root(&mut groups, j));
+                groups[root_i] = root_j;
+            }
+        }
+    }
+
+    let mut boards: Vec<(usize, Vec<Rect>)> = vec![];
+    for (i, square) in squares.into_iter().enumerate() {
+        let group = root(&mut groups, i);
+        match boards.iter_mut().find(|(root, _)| *root == group) {
+            Some((_, board)) => board.push(square),
+            None => boards.push((group, vec![square])),
+        }
+    }
+    boards.into_iter().map(|(_, board)| board).collect()
+}
+
+fn root(groups: &mut [usize], mut i: usize) -> usize {
+    while groups[i] != i {
+        groups[i] = groups[groups[i]];
+        i = groups[i];
+    }
+    i
+}
+
+// The smallest rectangle containing all of `rects`
+fn enclosing(rects: &[Rect]) -> Rect {
+    Rect {
+        x1: rects.iter().map(|r| r.x1).min().unwrap(),
+        y1: rects.iter().map(|r| r.y1).min().unwrap(),
+        x2: rects.iter().map(|r| r.x2).max().unwrap(),
+        y2: rects.iter().map(|r| r.y2).max().unwrap(),
+    }
+}
+
+// The distance between the centers of two rectangles, doubled
+fn distance(a: &Rect, b: &Rect) -> u64 {
+    let dx = i64::from(a.x1 + a.x2) - i64::from(b.x1 + b.x2);
+    let dy = i64::from(a.y1 + a.y2) - i64::from(b.y1 + b.y2);
+    (dx * dx + dy * dy) as u64
+}
+
+// How much the detected board and pieces look like a level. The board
+// squares should fill the grid and have the same size, and the pieces should
+// cover the board.
+fn confidence(
+    white_squares: &[Rect],
+    row_count: u32,
+    column_count: u32,
+    piece_count: usize,
+) -> f64 {
+    let square_count = f64::from(row_count * column_count);
+    if square_count == 0.0 {
+        return 0.0;
+    }
+    let grid_fill = (f64::from(white_squares.len() as u32) / square_count).min(1.0);
+
+    let widths: Vec<f64> = white_squares
+        .iter()
+        .map(|square| f64::from(square.width()))
+        .collect();
+    let count = f64::from(widths.len() as u32);
+    let mean = widths.iter().sum::<f64>() / count;
+    let variance = widths
+        .iter()
+        .map(|width| (width - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    let uniformity = 1.0 - (variance.sqrt() / mean).min(1.0);
+
+    // Blocked squares and misread pieces make small differences
+    let coverage = if 4 * piece_count as u32 == row_count * column_count {
+        1.0
+    } else if piece_count > 0 {
+        0.7
+    } else {
+        0.0
+    };
+    grid_fill * uniformity * coverage
+}
+
+// Classify the tetromino sprite in `rect`. The sprite is divided into a grid
+// of squares from the shape of `rect`, 2 by 3 for most pieces, and the
+// squares that are mostly of `color` are matched against the shapes of the
+// fixed tetrominoes with that grid. If none matches exactly, the shape that
+// differs in the fewest squares is used, with the other shapes that differ in
+// as few as alternatives. Ties are ranked by how much of each square is
+// colored.
+fn classify(image: &RgbImage, rect: &Rect, color: &Color) -> (FixedPiece, Vec<(FixedPiece, f64)>) {
+    let (row_count, column_count, fill) = grid_fill(image, rect, color);
+
+    let mut matches: Vec<_> = FixedPiece::array()
+        .iter()
+        .copied()
+        .filter_map(|fixed_piece| {
+            let cells = grid_cells(fixed_piece);
+            let (rows, columns) = cells.iter().fold((0, 0), |(rows, columns), cell| {
+                (rows.max(cell.0 + 1), columns.max(cell.1 + 1))
+            });
+            if (rows, columns) != (row_count as i32, column_count as i32) {
+                return None;
+            }
+            let mut difference = 0;
+            let mut cost = 0.0;
+            for &(cell, fill) in &fill {
+                let expected = if cells.contains(&cell) { 1.0 } else { 0.0 };
+                if (fill > 0.5) != (expected > 0.5) {
+                    difference += 1;
+                }
+                cost += f64::abs(fill - expected);
+            }
+            Some((difference, cost, fixed_piece))
+        })
+        .collect();
+    matches.sort_by(|a, b| (a.0, a.1).partial_cmp(&(b.0, b.1)).unwrap());
+
+    let (difference, cost, fixed_piece) = matches[0];
+    let alternatives = if difference == 0 {
+        vec![]
+    } else {
+        matches[1..]
+            .iter()
+            .filter(|m| m.0 == difference)
+            .map(|m| (m.2, m.1 - cost))
+            .collect()
+    };
+    (fixed_piece, alternatives)
+}
+
+// The grid a sprite in `rect` is divided into, from its shape, and how much of
+// each square of the grid is of `color`, from 0 to 1
+fn grid_fill(image: &RgbImage, rect: &Rect, color: &Color) -> (u32, u32, Vec<(Cell, f64)>) {
+    let ratio = f64::from(rect.width()) / f64::from(rect.height());
+    let (row_count, column_count) = if ratio >= 2.5 {
+        (1, 4)
+    } else if ratio <= 0.4 {
+        (4, 1)
+    } else if ratio > 1.25 {
+        (2, 3)
+    } else if ratio < 0.8 {
+        (3, 2)
+    } else {
+        (2, 2)
+    };
+
+    let mut fill = vec![];
+    for row in 0..row_count {
+        for column in 0..column_count {
+            let square = Rect {
+                x1: rect.x1 + column * rect.width() / column_count,
+                y1: rect.y1 + row * rect.height() / row_count,
+                x2: rect.x1 + (column + 1) * rect.width() / column_count - 1,
+                y2: rect.y1 + (row + 1) * rect.height() / row_count - 1,
+            };
+            let colored = count_pixels(image, &square, color);
+            fill.push((
+                Cell(row as i32, column as i32),
+                f64::from(colored) / f64::from(square.pixel_count()),
+            ));
+        }
+    }
+    (row_count, column_count, fill)
+}
+
+// The squares of a fixed tetromino in the grid of its bounding box
+fn grid_cells(fixed_piece: FixedPiece) -> [Cell; 4] {
+    let Shape(cells) = Shape::from(fixed_piece);
+    let min_column = cells.iter().map(|cell| cell.1).min().unwrap();
+    cells.map(|cell| cell.translate(0, -min_column))
+}
+
+// The letter of a fixed tetromino's piece
+fn letter(fixed_piece: FixedPiece) -> String {
+    format!("{:?}", fixed_piece.piece())
+}
+
+// Read the side panel layout, where each kind of piece is shown once as a
+// small icon followed by the number of pieces of the kind. Each piece is
+// returned with the bounds of its icon and the name of its color.
+fn read_panel(image: &RgbImage, area: &Rect, min_size: u32) -> Vec<Sprite> {
+    let mut checked_points = HashSet::new();
+    let mut icons = vec![];
+    for y in area.y1..=area.y2 {
+        for x in area.x1..=area.x2 {
+            for color in &TETROMINO_COLORS {
+                if let Some(icon) =
+                    get_bounds(image, &mut checked_points, x, y, color, min_size, 0.5)
+                {
+                    icons.push((icon, *color));
+                }
+            }
+        }
+    }
+
+    let mut pieces = vec![];
+    for (icon, color) in icons {
+        let Some(count) = read_count(image, &icon) else {
+            continue;
+        };
+        let (fixed_piece, alternatives) = classify(image, &icon, color);
+        for _ in 0..count {
+            pieces.push(Sprite {
+                fixed_piece,
+                rect: icon.clone(),
+                color: color.name,
+                alternatives: alternatives.clone(),
+            });
+        }
+    }
+    pieces
+}
+
+// The digits of the piece counts, 5 by 7 pixels
+#[rustfmt::skip]
+const DIGITS: [[&str; 7]; 10] = [
+    [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."],
+    ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+    ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+    ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+    ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+    ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+    ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+    [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+    [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+];
+
+// Read the number right of an icon in the side panel. The digits must be
+// at least half as tall as the icon and start within an icon's width of it.
+fn read_count(image: &RgbImage, icon: &Rect) -> Option<u32> {
+    let (width, height) = image.dimensions();
+    let search = Rect {
+        x1: icon.x2 + 1,
+        y1: icon.y1.saturating_sub(icon.height() / 2),
+        x2: (icon.x2 + 4 * icon.width()).min(width - 1),
+        y2: (icon.y2 + icon.height() / 2).min(height - 1),
+    };
+    if search.x1 > search.x2 {
+        return None;
+    }
+
+    let mut checked_points = HashSet::new();
+    let mut parts = vec![];
+    for y in search.y1..=search.y2 {
+        for x in search.x1..=search.x2 {
+            if let Some(part) = get_bounds(image, &mut checked_points, x, y, &WHITE, 1, 0.1) {
+                parts.push(part);
+            }
+        }
+    }
+    // Strokes that only touch diagonally are separate parts, so parts that
+    // overlap horizontally are merged into one digit
+    parts.sort_by_key(|part| part.x1);
+    let mut digits: Vec<Rect> = vec![];
+    for part in parts {
+        match digits.last_mut() {
+            Some(digit) if part.x1 <= digit.x2 + 1 => {
+                digit.x2 = digit.x2.max(part.x2);
+                digit.y1 = digit.y1.min(part.y1);
+                digit.y2 = digit.y2.max(part.y2);
+            }
+            _ => digits.push(part),
+        }
+    }
+    digits.retain(|digit| digit.height() >= icon.height() / 2);
+    if digits.first()?.x1 > icon.x2 + icon.width() {
+        return None;
+    }
+
+    digits
+        .iter()
+        .try_fold(0, |count, digit| {
+            Some(10 * count + read_digit(image, digit)?)
+        })
+        .filter(|&count| count > 0)
+}
+
+// Match the digit in `rect` against the templates by sampling the middle of
+// each template pixel
+fn read_digit(image: &RgbImage, rect: &Rect) -> Option<u32> {
+    // A "1" is much narrower than the template, so it's padded to the
+    // template's proportions around its middle
+    let template_width = (rect.height() * 5 / 7).max(rect.width());
+    let x1 = (rect.x1 + rect.width() / 2).saturating_sub(template_width / 2);
+    let samples: Vec<bool> = (0..7)
+        .flat_map(|row| (0..5).map(move |column| (row, column)))
+        .map(|(row, column)| {
+            let x = x1 + (2 * column + 1) * template_width / 10;
+            let y = rect.y1 + (2 * row + 1) * rect.height() / 14;
+            x <= rect.x2 && x >= rect.x1 && is_color(*image.get_pixel(x, y), &WHITE)
+        })
+        .collect();
+
+    let (digit, score) = DIGITS
+        .iter()
+        .map(|template| {
+            template
+                .iter()
+                .flat_map(|row| row.bytes())
+                .zip(&samples)
+                .filter(|&(pixel, &sample)| (pixel == b'#') == sample)
+                .count()
+        })
+        .enumerate()
+        .max_by_key(|&(_, score)| score)?;
+    // Most of the 35 pixels must agree
+    (score >= 28).then_some(digit as u32)
+}
+
+// Find the board squares before the colors are corrected. Bright, solid,
+// square objects are grouped by size and the largest group is taken to be the
+// board squares, since there are more of them than of anything else of that
+// shape.
+fn find_board_squares(image: &RgbImage, start_y: u32, end_y: u32) -> Option<Vec<Rect>> {
+    let (width, _) = image.dimensions();
+    let mut checked_points = HashSet::new();
+    let mut squares = vec![];
+    for y in start_y..end_y {
+        for x in 0..width {
+            if let Some(square) = get_bounds(
+                image,
+                &mut checked_points,
+                x,
+                y,
+                &BRIGHT,
+                MIN_OBJECT_SIZE,
+                0.8,
+            ) {
+                let aspect = f64::from(square.width()) / f64::from(square.height());
+                if (0.8..=1.25).contains(&aspect) {
+                    squares.push(square);
+                }
+            }
+        }
+    }
+
+    // The squares within 10% of the width of each square
+    let similar = |square: &Rect| {
+        squares
+            .iter()
+            .filter(|other| {
+                let ratio = f64::from(other.width()) / f64::from(square.width());
+                (0.9..=1.1).contains(&ratio)
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    squares
+        .iter()
+        .map(similar)
+        .max_by_key(Vec::len)
+        .filter(|group| group.len() >= 4)
+}
+
+// Estimate the color of the white board squares, the average color of their
+// bright pixels
+fn estimate_white(image: &RgbImage, board_squares: &[Rect]) -> Option<[f64; 3]> {
+    let mut total = [0.0; 3];
+    let mut count = 0.0;
+    for square in board_squares {
+        for y in square.y1..=square.y2 {
+            for x in square.x1..=square.x2 {
+                let pixel = *image.get_pixel(x, y);
+                if is_color(pixel, &BRIGHT) {
+                    for (total, &channel) in total.iter_mut().zip(&pixel.data) {
+                        *total += f64::from(channel);
+                    }
+                    count += 1.0;
+                }
+            }
+        }
+    }
+    (count > 0.0).then(|| total.map(|total| total / count))
+}
+
+// Scale the channels so that `white` becomes neutral, leaving its brightest
+// channel unchanged. Images without a noticeable color cast are left alone.
+// The gains are positive, so the scaled channels are too.
+fn white_balance(image: &mut RgbImage, white: [f64; 3]) {
+    let brightest = white.iter().copied().fold(0.0, f64::max);
+    let darkest = white.iter().copied().fold(f64::MAX, f64::min);
+    if darkest <= 0.0 || brightest / darkest < 1.15 {
+        return;
+    }
+    let gains = white.map(|channel| brightest / channel);
+    for pixel in image.pixels_mut() {
+        for (channel, gain) in pixel.data.iter_mut().zip(&gains) {
+            *channel = (f64::from(*channel) * gain).round().min(255.0) as u8;
+        }
+    }
+}
+
+fn is_color(pixel: Rgb<u8>, color: &Color) -> bool {
+    pixel
+        .data
+        .iter()
+        .zip(&color.range)
+        .all(|(c, r)| r.start() <= c && c <= r.end())
+}
+
+fn count_pixels(image: &RgbImage, rect: &Rect, color: &Color) -> u32 {
+    let mut count = 0;
+    for y in rect.y1..=rect.y2 {
+        for x in rect.x1..=rect.x2 {
+            if is_color(*image.get_pixel(x, y), color) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// Get the bounds of object based on filter, bounding box size and the proportion
+// of the bounding box occupied by the object.
+fn get_bounds(
+    image: &RgbImage,
+    checked_points: &mut HashSet<(u32, u32)>,
+    x: u32,
+    y: u32,
+    color: &Color,
+    min_size: u32,
+    min_proportion: f64,
+) -> Option<Rect> {
+    get_object(image, checked_points, x, y, color, min_size, min_proportion)
+        .map(|(bounds, _)| bounds)
+}
+
+// The bounds of the object as `get_bounds` and its number of pixels
+fn get_object(
+    image: &RgbImage,
+    checked_points: &mut HashSet<(u32, u32)>,
+    x: u32,
+    y: u32,
+    color: &Color,
+    min_size: u32,
+    min_proportion: f64,
+) -> Option<(Rect, u32)> {
+    let pixel = *image.get_pixel(x, y);
+    if !is_color(pixel, color) {
+        return None;
+    }
+
+    let (width, height) = image.dimensions();
+    let mut frontier = vec![];
+
+    let mut bounds = Rect {
+        x1: x,
+        y1: y,
+        x2: x,
+        y2: y,
+    };
+
+    let mut object_pixel_count = 0;
+    // Points are checked as they're pushed so that each is only counted once
+    frontier.push((x, y));
+    checked_points.insert((x, y));
+    while let Some((x, y)) = frontier.pop() {
+        if is_color(*image.get_pixel(x, y), color) {
+            object_pixel_count += 1;
+
+            bounds.x1 = bounds.x1.min(x);
+            bounds.y1 = bounds.y1.min(y);
+            bounds.x2 = bounds.x2.max(x);
+            bounds.y2 = bounds.y2.max(y);
+
+            if x > 0 {
+                let p = (x - 1, y);
+                if checked_points.insert(p) {
+                    frontier.push(p);
+                }
+            }
+            if x < width - 1 {
+                let p = (x + 1, y);
+                if checked_points.insert(p) {
+                    frontier.push(p);
+                }
+            }
+            if y > 0 {
+                let p = (x, y - 1);
+                if checked_points.insert(p) {
+                    frontier.push(p);
+                }
+            }
+            if y < height - 1 {
+                let p = (x, y + 1);
+                if checked_points.insert(p) {
+                    frontier.push(p);
+                }
+            }
+        }
+    }
+
+    if bounds.width() < min_size || bounds.height() < min_size {
+        return None;
+    }
+
+    if f64::from(object_pixel_count) / f64::from(bounds.pixel_count()) < min_proportion {
+        return None;
+    }
+
+    Some((bounds, object_pixel_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        candidates, classify, grid_cells, level_number, load_from_memory_within, open, read,
+        read_all, Bounds, ReadError, Rect, Sprite, CYAN,
+    };
+    use image::{Rgb, RgbImage};
+    use soe_core::geometry::Cell;
+    use soe_core::FixedPiece;
+
+    fn fill(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+        for y in y..y + height {
+            for x in x..x + width {
+                image.put_pixel(x, y, Rgb(color));
+            }
+        }
+    }
+
+    // A 2 by 4 board, two I pieces and a progress dot
+    fn level() -> RgbImage {
+        let mut image = RgbImage::new(300, 250);
+        for row in 0..2 {
+            for column in 0..4 {
+                fill(
+                    &mut image,
+                    50 + 22 * column,
+                    50 + 22 * row,
+                    20,
+                    20,
+                    [255; 3],
+                );
+            }
+        }
+        fill(&mut image, 50, 120, 40, 10, [0, 200, 220]);
+        fill(&mut image, 150, 120, 40, 10, [0, 200, 220]);
+        fill(&mut image, 100, 210, 6, 6, [130, 120, 80]);
+        image
+    }
+
+    #[test]
+    fn read_level() {
+        let reading = read(&level()).unwrap();
+        assert_eq!(reading.color, "cyan");
+        assert_eq!(reading.progress_dot_count, 1);
+        assert_eq!(reading.number, Some(1));
+        assert_eq!((reading.row_count, reading.column_count), (2, 4));
+        assert_eq!(reading.pieces, "II");
+        assert_eq!(reading.sprites[0].0, FixedPiece::I2);
+        assert!(reading.confidence > 0.9);
+        assert!((reading.square_size - 20.0).abs() < 1e-9);
+        assert_eq!(reading.cells.len(), 8);
+        assert_eq!(
+            reading.cells[5],
+            Bounds {
+                x: 72,
+                y: 72,
+                width: 20,
+                height: 20
+            }
+        );
+        assert!(reading.to_json().starts_with(
+            "{\"color\":\"cyan\",\"number\":1,\"rows\":2,\"columns\":4,\"pieces\":\"II\",\
+             \"confidence\":1.00,\"board\":{\"x\":50,\"y\":50,\"width\":86,\"height\":42},\
+             \"tray\":{\"x\":50,\"y\":120,\"width\":140,\"height\":10},\"square_size\":20.0,\"cells\":[[{\"x\":50,\"y\":50,\"width\":20,\"height\":20},"
+        ));
+        assert!(reading.to_json().ends_with(
+            "{\"x\":116,\"y\":72,\"width\":20,\"height\":20}]],\"candidates\":[\"II\"]}"
+        ));
+
+        // x is across the columns: the square at x 75, y 55 is in row 0,
+        // column 1
+        let (row_count, column_count) = reading.dimensions();
+        assert_eq!((row_count.get(), column_count.get()), (2, 4));
+        let index = reading.cell_at(75, 55).unwrap();
+        assert_eq!(
+            (index.row(column_count), index.column(column_count)),
+            (0, 1)
+        );
+        assert_eq!(reading.cell(index), Some(reading.cells[1]));
+        assert_eq!(reading.cell_at(10, 10), None);
+
+        // A covered square is placed between the others
+        let mut image = level();
+        fill(&mut image, 72, 50, 20, 20, [0; 3]);
+        let reading = read(&image).unwrap();
+        assert_eq!(
+            reading.cells[1],
+            Bounds {
+                x: 72,
+                y: 50,
+                width: 20,
+                height: 20
+            }
+        );
+    }
+
+    #[test]
+    fn read_at_any_scale() {
+        // A 2 by 4 board with squares `size` wide, two I pieces half as wide
+        // and a progress dot
+        let level = |size: u32| {
+            let mut image = RgbImage::new(300, 320);
+            for row in 0..2 {
+                for column in 0..4 {
+                    let pitch = size + size / 10;
+                    fill(
+                        &mut image,
+                        10 + pitch * column,
+                        50 + pitch * row,
+                        size,
+                        size,
+                        [255; 3],
+                    );
+                }
+            }
+            fill(&mut image, 10, 180, 2 * size, size / 2, [0, 200, 220]);
+            fill(&mut image, 150, 180, 2 * size, size / 2, [0, 200, 220]);
+            fill(
+                &mut image,
+                100,
+                290,
+                4 + size / 10,
+                4 + size / 10,
+                [130, 120, 80],
+            );
+            image
+        };
+
+        // Sprites smaller than the least size for squares of the usual size
+        let reading = read(&level(10)).unwrap();
+        assert_eq!((reading.row_count, reading.column_count), (2, 4));
+        assert_eq!(reading.pieces, "II");
+        assert_eq!(reading.progress_dot_count, 1);
+
+        // A speck right of the board as large as a small square
+        let mut image = level(50);
+        fill(&mut image, 270, 60, 14, 14, [255; 3]);
+        let reading = read(&image).unwrap();
+        assert_eq!((reading.row_count, reading.column_count), (2, 4));
+        assert_eq!(reading.pieces, "II");
+        assert_eq!(reading.progress_dot_count, 1);
+    }
+
+    #[test]
+    fn read_progress_dots() {
+        // A gold bar of the interface below the dots isn't a dot
+        let mut image = level();
+        fill(&mut image, 100, 235, 60, 10, [130, 120, 80]);
+        let reading = read(&image).unwrap();
+        assert_eq!(reading.progress_dot_count, 1);
+        assert_eq!(reading.number, Some(1));
+
+        // There are only eight levels of each color
+        let mut image = level();
+        for dot in 1..12 {
+            fill(&mut image, 100 + 12 * dot, 210, 6, 6, [130, 120, 80]);
+        }
+        let reading = read(&image).unwrap();
+        assert_eq!(reading.progress_dot_count, 12);
+        assert_eq!(reading.number, None);
+        assert!(reading.to_json().contains("\"number\":null,"));
+    }
+
+    #[test]
+    fn read_only_the_tray() {
+        // Red art left of the board would be a piece of another color
+        let mut image = level();
+        fill(&mut image, 5, 50, 30, 30, [200, 20, 20]);
+        let reading = read(&image).unwrap();
+        assert_eq!(reading.color, "cyan");
+        assert_eq!(reading.pieces, "II");
+        assert_eq!(
+            reading.tray,
+            Some(Bounds {
+                x: 50,
+                y: 120,
+                width: 140,
+                height: 10
+            })
+        );
+    }
+
+    #[test]
+    fn read_touching_sprites() {
+        // An O touching the right end of the first I
+        let mut image = level();
+        fill(&mut image, 90, 120, 20, 20, [0, 200, 220]);
+        let reading = read(&image).unwrap();
+        assert_eq!(reading.pieces, "IOI");
+        assert_eq!(
+            reading.sprites[1],
+            (
+                FixedPiece::O1,
+                Bounds {
+                    x: 90,
+                    y: 120,
+                    width: 20,
+                    height: 20
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn level_numbers() {
+        assert_eq!(level_number("cyan", 8), Some(8));
+        assert_eq!(level_number("cyan", 0), None);
+        assert_eq!(level_number("cyan", 9), None);
+        assert_eq!(level_number("white", 1), None);
+    }
+
+    #[test]
+    fn classify_sprites() {
+        for &fixed_piece in &FixedPiece::array() {
+            let mut image = RgbImage::new(60, 60);
+            let cells = grid_cells(fixed_piece);
+            for cell in &cells {
+                fill(
+                    &mut image,
+                    5 + 12 * cell.1 as u32,
+                    5 + 12 * cell.0 as u32,
+                    12,
+                    12,
+                    [0, 200, 220],
+                );
+            }
+            let rect = Rect {
+                x1: 5,
+                y1: 5,
+                x2: 4 + 12 * (cells.iter().map(|cell| cell.1).max().unwrap() as u32 + 1),
+                y2: 4 + 12 * (cells.iter().map(|cell| cell.0).max().unwrap() as u32 + 1),
+            };
+            assert_eq!(classify(&image, &rect, &CYAN), (fixed_piece, vec![]));
+        }
+    }
+
+    #[test]
+    fn classify_smudged_sprite() {
+        // An S with most of one of the other squares of its grid colored
+        // could be several shapes
+        let fixed_piece = FixedPiece::S1;
+        let cells = grid_cells(fixed_piece);
+        let mut image = RgbImage::new(60, 60);
+        for cell in &cells {
+            fill(
+                &mut image,
+                5 + 12 * cell.1 as u32,
+                5 + 12 * cell.0 as u32,
+                12,
+                12,
+                [0, 200, 220],
+            );
+        }
+        let smudge = (0..2)
+            .flat_map(|row| (0..3).map(move |column| Cell(row, column)))
+            .find(|cell| !cells.contains(cell))
+            .unwrap();
+        fill(
+            &mut image,
+            5 + 12 * smudge.1 as u32,
+            5 + 12 * smudge.0 as u32,
+            12,
+            8,
+            [0, 200, 220],
+        );
+        let rect = Rect {
+            x1: 5,
+            y1: 5,
+            x2: 40,
+            y2: 28,
+        };
+
+        let (best, alternatives) = classify(&image, &rect, &CYAN);
+        assert_eq!(best, fixed_piece);
+        assert!(!alternatives.is_empty());
+        assert!(alternatives.iter().all(|&(_, extra)| extra > 0.0));
+    }
+
+    #[test]
+    fn candidates_are_ranked_and_distinct() {
+        let sprite = |fixed_piece, alternatives| Sprite {
+            fixed_piece,
+            rect: Rect {
+                x1: 0,
+                y1: 0,
+                x2: 10,
+                y2: 10,
+            },
+            color: "cyan",
+            alternatives,
+        };
+        let sprites = [
+            sprite(FixedPiece::S1, vec![(FixedPiece::Z1, 0.5)]),
+            sprite(FixedPiece::Z1, vec![(FixedPiece::S1, 0.8)]),
+            sprite(FixedPiece::O1, vec![]),
+        ];
+        // "ZSO" has the same pieces as "SZO"
+        assert_eq!(candidates(&sprites), ["SZO", "ZZO", "SSO"]);
+        assert_eq!(candidates(&sprites[2..]), ["O"]);
+    }
+
+    #[test]
+    fn read_boards() {
+        // A 2 by 2 board with an O piece below it and a 1 by 4 board with an
+        // I piece
+        let mut image = RgbImage::new(300, 250);
+        for row in 0..2 {
+            for column in 0..2 {
+                fill(
+                    &mut image,
+                    20 + 22 * column,
+                    50 + 22 * row,
+                    20,
+                    20,
+                    [255; 3],
+                );
+            }
+        }
+        fill(&mut image, 20, 110, 20, 20, [0, 200, 220]);
+        for column in 0..4 {
+            fill(&mut image, 150 + 22 * column, 50, 20, 20, [255; 3]);
+        }
+        fill(&mut image, 160, 110, 40, 10, [0, 200, 220]);
+
+        let readings = read_all(&image).unwrap();
+        assert_eq!(readings.len(), 2);
+        assert_eq!((readings[0].row_count, readings[0].column_count), (2, 2));
+        assert_eq!(readings[0].pieces, "O");
+        assert_eq!(
+            readings[0].board,
+            Bounds {
+                x: 20,
+                y: 50,
+                width: 42,
+                height: 42
+            }
+        );
+        assert_eq!((readings[1].row_count, readings[1].column_count), (1, 4));
+        assert_eq!(readings[1].pieces, "I");
+        assert_eq!(readings[1].board.x, 150);
+
+        // One board reads the same as with `read`
+        let readings = read_all(&level()).unwrap();
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].pieces, "II");
+    }
+
+    #[test]
+    fn read_non_level() {
+        assert!(matches!(
+            read(&RgbImage::new(300, 250)),
+            Err(ReadError::NoBoard)
+        ));
+        assert!(matches!(
+            read(&RgbImage::new(40, 40)),
+            Err(ReadError::NoBoard)
+        ));
+
+        // Two white squares far apart aren't a board
+        let mut image = RgbImage::new(300, 250);
+        fill(&mut image, 10, 50, 20, 20, [255; 3]);
+        fill(&mut image, 250, 50, 20, 20, [255; 3]);
+        fill(&mut image, 50, 120, 40, 10, [0, 200, 220]);
+        assert!(matches!(read(&image), Err(ReadError::NotAPuzzle { .. })));
+    }
+
+    // WebP and HEIC are decoded with their features and rejected without them
+    #[test]
+    fn open_formats() {
+        let path = std::env::temp_dir().join(format!("soe_open_{}", std::process::id()));
+        let open_bytes = |bytes: &[u8]| {
+            std::fs::write(&path, bytes).unwrap();
+            let image = open(&path);
+            std::fs::remove_file(&path).unwrap();
+            image
+        };
+
+        let image = level();
+        let mut webp = vec![];
+        #[cfg(feature = "webp")]
+        image_webp::WebPEncoder::new(&mut webp)
+            .encode(
+                &image,
+                image.width(),
+                image.height(),
+                image_webp::ColorType::Rgb8,
+            )
+            .unwrap();
+        #[cfg(not(feature = "webp"))]
+        webp.extend(b"RIFF\0\0\0\0WEBPVP8L");
+        let opened = open_bytes(&webp);
+        if cfg!(feature = "webp") {
+            assert_eq!(opened.unwrap().into_raw(), image.into_raw());
+        } else {
+            assert!(matches!(opened, Err(ReadError::UnsupportedFormat("WebP"))));
+        }
+
+        let heic = open_bytes(b"\0\0\0\x18ftypheic\0\0\0\0");
+        if cfg!(feature = "heic") {
+            assert!(matches!(heic, Err(ReadError::Image(_))));
+        } else {
+            assert!(matches!(heic, Err(ReadError::UnsupportedFormat("HEIC"))));
+        }
+    }
+
+    #[test]
+    fn pixel_budget() {
+        let mut png = vec![];
+        image::DynamicImage::ImageRgb8(level())
+            .write_to(&mut png, image::ImageOutputFormat::PNG)
+            .unwrap();
+        assert!(load_from_memory_within(&png, 300 * 250).is_ok());
+        assert!(matches!(
+            load_from_memory_within(&png, 300 * 250 - 1),
+            Err(ReadError::ImageTooLarge {
+                width: 300,
+                height: 250
+            })
+        ));
+    }
+}
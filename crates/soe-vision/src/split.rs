@@ -0,0 +1,268 @@
+// Splitting sprites that touch. On a crowded tray two sprites of the same
+// color can touch, and the flood fill then finds them as one sprite with a
+// shape no tetromino has. A sprite much larger than the others is cut into a
+// grid of squares the size of the squares of the others, and the grid is
+// tiled with tetrominoes.
+
+use std::cmp::Reverse;
+
+use image::RgbImage;
+
+use soe_core::geometry::Cell;
+use soe_core::FixedPiece;
+
+use crate::{count_pixels, grid_cells, Color, Rect, Sprite};
+
+// The most tilings of a merged sprite that are compared
+const MAX_TILINGS: usize = 64;
+
+// The width in pixels of a square of a sprite, estimated from the number of
+// pixels of each sprite found. The lower median is used so that a few merged
+// sprites don't throw it off.
+pub(crate) fn square_size(pixel_counts: &[u32]) -> Option<f64> {
+    let mut sizes: Vec<f64> = pixel_counts
+        .iter()
+        .map(|&count| (f64::from(count) / 4.0).sqrt())
+        .collect();
+    sizes.sort_by(f64::total_cmp);
+    sizes.get(sizes.len().saturating_sub(1) / 2).copied()
+}
+
+// The sprites of the merged sprite in `rect`, or `None` if it has about the
+// pixels of a single sprite or can't be tiled with tetrominoes. The sprites
+// of a tray are laid out side by side along their tops, so of the ways to tile
+// it the one with the fewest pieces sharing columns and then the most pieces
+// touching its top is used.
+pub(crate) fn split(
+    image: &RgbImage,
+    rect: &Rect,
+    pixel_count: u32,
+    color: &Color,
+    square_size: f64,
+) -> Option<Vec<Sprite>> {
+    let piece_count = (f64::from(pixel_count) / (4.0 * square_size * square_size)).round() as usize;
+    if piece_count < 2 {
+        return None;
+    }
+
+    let column_count = (f64::from(rect.width()) / square_size).round().max(1.0) as u32;
+    let row_count = (f64::from(rect.height()) / square_size).round().max(1.0) as u32;
+    let squares: Vec<Rect> = (0..row_count)
+        .flat_map(|row| (0..column_count).map(move |column| (row, column)))
+        .map(|(row, column)| Rect {
+            x1: rect.x1 + column * rect.width() / column_count,
+            y1: rect.y1 + row * rect.height() / row_count,
+            x2: rect.x1 + (column + 1) * rect.width() / column_count - 1,
+            y2: rect.y1 + (row + 1) * rect.height() / row_count - 1,
+        })
+        .collect();
+    let colored: Vec<bool> = squares
+        .iter()
+        .map(|square| 2 * count_pixels(image, square, color) > square.pixel_count())
+        .collect();
+    if colored.iter().filter(|&&colored| colored).count() != 4 * piece_count {
+        return None;
+    }
+
+    let mut grid = Grid {
+        row_count,
+        column_count,
+        colored,
+        covered: vec![false; squares.len()],
+        placed: vec![],
+        tilings: vec![],
+    };
+    grid.tile();
+    let tiling = grid
+        .tilings
+        .into_iter()
+        .min_by_key(|tiling| (overlap_count(tiling), Reverse(top_count(tiling))))?;
+
+    let sprites = tiling
+        .into_iter()
+        .map(|(fixed_piece, top_left)| {
+            let cells = grid_cells(fixed_piece).map(|cell| cell.translate(top_left.0, top_left.1));
+            let index = |cell: &Cell| cell.0 as usize * column_count as usize + cell.1 as usize;
+            Sprite {
+                fixed_piece,
+                rect: Rect {
+                    x1: cells
+                        .iter()
+                        .map(|cell| squares[index(cell)].x1)
+                        .min()
+                        .unwrap(),
+                    y1: cells
+                        .iter()
+                        .map(|cell| squares[index(cell)].y1)
+                        .min()
+                        .unwrap(),
+                    x2: cells
+                        .iter()
+                        .map(|cell| squares[index(cell)].x2)
+                        .max()
+                        .unwrap(),
+                    y2: cells
+                        .iter()
+                        .map(|cell| squares[index(cell)].y2)
+                        .max()
+                        .unwrap(),
+                },
+                color: color.name,
+                alternatives: vec![],
+            }
+        })
+        .collect();
+    Some(sprites)
+}
+
+// The number of pairs of pieces of a tiling that share a column
+fn overlap_count(tiling: &[(FixedPiece, Cell)]) -> usize {
+    let columns = |&(fixed_piece, top_left): &(FixedPiece, Cell)| {
+        let width = grid_cells(fixed_piece)
+            .iter()
+            .map(|cell| cell.1)
+            .max()
+            .unwrap();
+        top_left.1..=top_left.1 + width
+    };
+    let mut count = 0;
+    for (i, a) in tiling.iter().enumerate() {
+        for b in &tiling[i + 1..] {
+            let (a, b) = (columns(a), columns(b));
+            if a.start() <= b.end() && b.start() <= a.end() {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// The number of pieces of a tiling on the top row of the grid
+fn top_count(tiling: &[(FixedPiece, Cell)]) -> usize {
+    tiling
+        .iter()
+        .filter(|(_, top_left)| top_left.0 == 0)
+        .count()
+}
+
+// The squares of a merged sprite, row by row
+struct Grid {
+    row_count: u32,
+    column_count: u32,
+    colored: Vec<bool>,
+    covered: Vec<bool>,
+    // The pieces placed so far with the top-left corner of their grid
+    placed: Vec<(FixedPiece, Cell)>,
+    tilings: Vec<Vec<(FixedPiece, Cell)>>,
+}
+
+impl Grid {
+    fn index(&self, cell: Cell) -> Option<usize> {
+        let in_grid = (0..self.row_count as i32).contains(&cell.0)
+            && (0..self.column_count as i32).contains(&cell.1);
+        in_grid.then(|| cell.0 as usize * self.column_count as usize + cell.1 as usize)
+    }
+
+    // Cover the first colored square that isn't covered with each piece that
+    // fits, and so on until all are covered
+    fn tile(&mut self) {
+        if self.tilings.len() == MAX_TILINGS {
+            return;
+        }
+        let Some(first) = (0..self.colored.len()).find(|&i| self.colored[i] && !self.covered[i])
+        else {
+            self.tilings.push(self.placed.clone());
+            return;
+        };
+        let (row, column) = (
+            (first / self.column_count as usize) as i32,
+            (first % self.column_count as usize) as i32,
+        );
+        for &fixed_piece in &FixedPiece::array() {
+            let cells = grid_cells(fixed_piece);
+            // The first square of the piece in row-major order goes on `first`
+            let anchor = cells.iter().min_by_key(|cell| (cell.0, cell.1)).unwrap();
+            let top_left = Cell(row - anchor.0, column - anchor.1);
+            let indices: Option<Vec<usize>> = cells
+                .iter()
+                .map(|cell| {
+                    self.index(cell.translate(top_left.0, top_left.1))
+                        .filter(|&i| self.colored[i] && !self.covered[i])
+                })
+                .collect();
+            let Some(indices) = indices else {
+                continue;
+            };
+            for &i in &indices {
+                self.covered[i] = true;
+            }
+            self.placed.push((fixed_piece, top_left));
+            self.tile();
+            self.placed.pop();
+            for &i in &indices {
+                self.covered[i] = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, square_size};
+    use crate::{grid_cells, Rect, CYAN};
+    use image::{Rgb, RgbImage};
+    use soe_core::FixedPiece;
+
+    // Draw the sprites of `fixed_pieces` left to right with no gap between
+    // them, 10 pixels to a square, and return the rectangle they cover
+    fn draw(image: &mut RgbImage, fixed_pieces: &[FixedPiece]) -> Rect {
+        let mut x = 5;
+        let mut height = 0;
+        for &fixed_piece in fixed_pieces {
+            let cells = grid_cells(fixed_piece);
+            for cell in &cells {
+                for dy in 0..10 {
+                    for dx in 0..10 {
+                        let (px, py) = (x + 10 * cell.1 as u32 + dx, 5 + 10 * cell.0 as u32 + dy);
+                        image.put_pixel(px, py, Rgb([0, 200, 220]));
+                    }
+                }
+            }
+            x += 10 * (cells.iter().map(|cell| cell.1).max().unwrap() as u32 + 1);
+            height = height.max(10 * (cells.iter().map(|cell| cell.0).max().unwrap() as u32 + 1));
+        }
+        Rect {
+            x1: 5,
+            y1: 5,
+            x2: x - 1,
+            y2: 4 + height,
+        }
+    }
+
+    #[test]
+    fn split_touching_sprites() {
+        for fixed_pieces in [
+            [FixedPiece::I2, FixedPiece::O1],
+            [FixedPiece::L1, FixedPiece::T3],
+            [FixedPiece::O1, FixedPiece::O1],
+        ] {
+            let mut image = RgbImage::new(120, 60);
+            let rect = draw(&mut image, &fixed_pieces);
+            let sprites = split(&image, &rect, 800, &CYAN, 10.0).unwrap();
+            let found: Vec<_> = sprites.iter().map(|sprite| sprite.fixed_piece).collect();
+            assert_eq!(found, fixed_pieces);
+            assert_eq!(sprites[0].rect.x1, 5);
+            assert_eq!(sprites[1].rect.x2, rect.x2);
+        }
+    }
+
+    #[test]
+    fn single_sprites_are_kept() {
+        let mut image = RgbImage::new(120, 60);
+        let rect = draw(&mut image, &[FixedPiece::S1]);
+        assert!(split(&image, &rect, 400, &CYAN, 10.0).is_none());
+        assert_eq!(square_size(&[400, 400, 800]), Some(10.0));
+        assert_eq!(square_size(&[800, 400]), Some(10.0));
+        assert_eq!(square_size(&[]), None);
+    }
+}
@@ -0,0 +1,164 @@
+// Classifying a single sprite, cropped from a screenshot, without reading a
+// board. For tuning the colors and writing test fixtures for the classifier.
+
+use std::collections::HashSet;
+
+use image::RgbImage;
+
+use soe_core::FixedPiece;
+
+use crate::{
+    classify, get_object, grid_cells, grid_fill, letter, Bounds, ReadError, MIN_OBJECT_SIZE,
+    TETROMINO_COLORS,
+};
+
+// What a sprite was classified as
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteClass {
+    // The piece in the orientation it's drawn in
+    pub fixed_piece: FixedPiece,
+    pub color: &'static str,
+    pub bounds: Bounds,
+    // How well the sprite matches the shape, from 0 to 1
+    pub confidence: f64,
+    // Other shapes that match as well apart from how much of each square is
+    // colored, with how much worse they match
+    pub alternatives: Vec<(FixedPiece, f64)>,
+}
+
+impl SpriteClass {
+    // The orientation of the piece, counting from 1 as in "T3"
+    #[must_use]
+    pub fn orientation(&self) -> u32 {
+        let piece = self.fixed_piece.piece();
+        let index = FixedPiece::array()
+            .iter()
+            .filter(|fixed_piece| fixed_piece.piece() == piece)
+            .position(|&fixed_piece| fixed_piece == self.fixed_piece)
+            .unwrap();
+        index as u32 + 1
+    }
+
+    // The classification as a JSON object, e.g.
+    //
+    //     {"kind":"T","orientation":3,"color":"cyan","confidence":0.97,
+    //     "bounds":{"x":2,"y":3,"width":30,"height":20},"alternatives":[]}
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let alternatives: Vec<_> = self
+            .alternatives
+            .iter()
+            .map(|(fixed_piece, extra)| {
+                format!("{{\"fixed_piece\":\"{fixed_piece}\",\"extra\":{extra:.2}}}")
+            })
+            .collect();
+        format!(
+            "{{\"kind\":\"{}\",\"orientation\":{},\"color\":\"{}\",\"confidence\":{:.2},\
+             \"bounds\":{},\"alternatives\":[{}]}}",
+            letter(self.fixed_piece),
+            self.orientation(),
+            self.color,
+            self.confidence,
+            self.bounds.to_json(),
+            alternatives.join(",")
+        )
+    }
+}
+
+// Classify the largest sprite of the color of a piece in `image`, which
+// should be cropped to the sprite
+pub fn classify_sprite(image: &RgbImage) -> Result<SpriteClass, ReadError> {
+    let (width, height) = image.dimensions();
+    let mut checked_points = HashSet::new();
+    let mut largest = None;
+    for y in 0..height {
+        for x in 0..width {
+            for color in &TETROMINO_COLORS {
+                if let Some((rect, pixel_count)) = get_object(
+                    image,
+                    &mut checked_points,
+                    x,
+                    y,
+                    color,
+                    MIN_OBJECT_SIZE,
+                    0.5,
+                ) {
+                    if largest
+                        .as_ref()
+                        .is_none_or(|&(_, largest_count, _)| pixel_count > largest_count)
+                    {
+                        largest = Some((rect, pixel_count, *color));
+                    }
+                }
+            }
+        }
+    }
+
+    let (rect, _, color) = largest.ok_or(ReadError::NoSprite)?;
+    let (fixed_piece, alternatives) = classify(image, &rect, color);
+    // The mean difference from the shape of how much of each square is
+    // colored
+    let (_, _, fill) = grid_fill(image, &rect, color);
+    let cells = grid_cells(fixed_piece);
+    let difference: f64 = fill
+        .iter()
+        .map(|(cell, fill)| {
+            let expected = if cells.contains(cell) { 1.0 } else { 0.0 };
+            (fill - expected).abs()
+        })
+        .sum();
+    let confidence = 1.0 - difference / f64::from(fill.len() as u32);
+    Ok(SpriteClass {
+        fixed_piece,
+        color: color.name,
+        bounds: rect.into(),
+        confidence,
+        alternatives,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::classify_sprite;
+    use crate::{grid_cells, ReadError};
+    use image::{Rgb, RgbImage};
+    use soe_core::FixedPiece;
+
+    // A sprite of `fixed_piece` with squares of 10 pixels, with a border
+    fn sprite(fixed_piece: FixedPiece, color: [u8; 3]) -> RgbImage {
+        let mut image = RgbImage::new(50, 50);
+        for cell in &grid_cells(fixed_piece) {
+            for dy in 0..10 {
+                for dx in 0..10 {
+                    let (x, y) = (5 + 10 * cell.1 as u32 + dx, 5 + 10 * cell.0 as u32 + dy);
+                    image.put_pixel(x, y, Rgb(color));
+                }
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn classify() {
+        for fixed_piece in FixedPiece::array() {
+            let class = classify_sprite(&sprite(fixed_piece, [200, 30, 10])).unwrap();
+            assert_eq!(class.fixed_piece, fixed_piece);
+            assert_eq!(class.color, "red");
+            assert!(class.confidence > 0.99, "{:?}", class);
+            assert_eq!((class.bounds.x, class.bounds.y), (5, 5));
+        }
+
+        let class = classify_sprite(&sprite(FixedPiece::T3, [0, 200, 220])).unwrap();
+        assert_eq!(class.orientation(), 3);
+        assert_eq!(
+            class.to_json(),
+            "{\"kind\":\"T\",\"orientation\":3,\"color\":\"cyan\",\"confidence\":1.00,\
+             \"bounds\":{\"x\":5,\"y\":5,\"width\":30,\"height\":20},\"alternatives\":[]}"
+        );
+
+        assert!(matches!(
+            classify_sprite(&RgbImage::new(20, 20)),
+            Err(ReadError::NoSprite)
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+// Synthetic screenshots for testing the reader: a board of white squares,
+// piece sprites in the color of the level below it and progress dots at the
+// bottom, laid out like the game's level screen.
+
+use image::{Rgb, RgbImage};
+
+use soe_core::geometry::Cell;
+use soe_core::{FixedPiece, Rng};
+
+use crate::{grid_cells, DOTS_HEIGHT, TITLE_HEIGHT};
+
+const BACKGROUND: [u8; 3] = [20, 20, 30];
+const SQUARE: [u8; 3] = [250, 248, 240];
+const DOT: [u8; 3] = [130, 120, 80];
+const DOT_SIZE: u32 = 6;
+const MARGIN: u32 = 20;
+
+// The level shown in a screenshot and how it is drawn
+#[derive(Clone, Debug)]
+pub struct Spec {
+    // One of "cyan", "green", "yellow" and "red"
+    pub color: &'static str,
+    // The number of progress dots
+    pub number: u32,
+    pub row_count: u32,
+    pub column_count: u32,
+    // The sprites in reading order
+    pub sprites: Vec<FixedPiece>,
+    // The width of a board square and of the gap between squares in pixels
+    pub square_size: u32,
+    pub gap: u32,
+    // The width of a square of a sprite
+    pub sprite_square_size: u32,
+}
+
+impl Spec {
+    // A level with a board of 2 to 6 rows and 2 to 8 columns and enough
+    // random pieces to cover it, drawn at a random scale
+    pub fn random(rng: &mut Rng) -> Self {
+        let (row_count, column_count) = loop {
+            let row_count = 2 + rng.below(5) as u32;
+            let column_count = 2 + rng.below(7) as u32;
+            if (row_count * column_count).is_multiple_of(4) {
+                break (row_count, column_count);
+            }
+        };
+        let sprites = (0..row_count * column_count / 4)
+            .map(|_| FixedPiece::from(rng.below(FixedPiece::count() as u64) as usize))
+            .collect();
+        let square_size = 16 + rng.below(17) as u32;
+        // Wider gaps would add up to more than half a square across the board
+        let max_gap = (square_size / 2 / column_count.max(row_count)).max(1);
+        Spec {
+            color: ["cyan", "green", "yellow", "red"][rng.below(4) as usize],
+            number: 1 + rng.below(10) as u32,
+            row_count,
+            column_count,
+            sprites,
+            square_size,
+            gap: 1 + rng.below(u64::from(max_gap)) as u32,
+            sprite_square_size: 10 + rng.below(5) as u32,
+        }
+    }
+}
+
+// Draw the screenshot of `spec`
+pub fn render(spec: &Spec) -> RgbImage {
+    let pitch = spec.square_size + spec.gap;
+    let board_width = spec.column_count * pitch - spec.gap;
+    let board_height = spec.row_count * pitch - spec.gap;
+    let width = (board_width + 2 * MARGIN).max(300);
+
+    // Lay the sprites out in lines, wrapping at the right margin
+    let size = spec.sprite_square_size;
+    let mut sprites = vec![];
+    let (mut x, mut y) = (MARGIN, TITLE_HEIGHT + MARGIN + board_height + MARGIN);
+    let mut line_height = 0;
+    for &fixed_piece in &spec.sprites {
+        let cells = grid_cells(fixed_piece);
+        let columns = cells.iter().map(|cell| cell.1 as u32 + 1).max().unwrap();
+        let rows = cells.iter().map(|cell| cell.0 as u32 + 1).max().unwrap();
+        if x + columns * size > width - MARGIN {
+            x = MARGIN;
+            y += line_height + size;
+            line_height = 0;
+        }
+        sprites.push((cells, x, y));
+        x += columns * size + size;
+        line_height = line_height.max(rows * size);
+    }
+    let height = y + line_height + MARGIN + DOTS_HEIGHT;
+
+    let mut image = RgbImage::from_pixel(width, height, Rgb(BACKGROUND));
+    for row in 0..spec.row_count {
+        for column in 0..spec.column_count {
+            let x = MARGIN + column * pitch;
+            let y = TITLE_HEIGHT + MARGIN + row * pitch;
+            fill(&mut image, x, y, spec.square_size, spec.square_size, SQUARE);
+        }
+    }
+
+    let color = piece_color(spec.color);
+    for (cells, x, y) in sprites {
+        for &Cell(row, column) in &cells {
+            let (row, column) = (row as u32, column as u32);
+            fill(
+                &mut image,
+                x + column * size,
+                y + row * size,
+                size,
+                size,
+                color,
+            );
+        }
+    }
+
+    let dots_y = height - DOTS_HEIGHT / 2;
+    for dot in 0..spec.number {
+        fill(
+            &mut image,
+            MARGIN + dot * 2 * DOT_SIZE,
+            dots_y,
+            DOT_SIZE,
+            DOT_SIZE,
+            DOT,
+        );
+    }
+    image
+}
+
+fn piece_color(color: &str) -> [u8; 3] {
+    match color {
+        "cyan" => [0, 200, 220],
+        "green" => [30, 160, 20],
+        "yellow" => [230, 200, 40],
+        "red" => [200, 20, 20],
+        _ => panic!("unknown color {}", color),
+    }
+}
+
+fn fill(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+    for y in y..y + height {
+        for x in x..x + width {
+            image.put_pixel(x, y, Rgb(color));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Spec};
+    use crate::read;
+    use soe_core::{Rng, SeedableRng};
+
+    // Raise the count to check the reader against many more layouts. Each
+    // screenshot takes a fraction of a second to read in a debug build.
+    const ROUND_TRIP_COUNT: usize = 20;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng::seed_from_u64(1936);
+        for _ in 0..ROUND_TRIP_COUNT {
+            let spec = Spec::random(&mut rng);
+            let reading = read(&render(&spec)).unwrap();
+            assert_eq!(reading.color, spec.color, "{spec:?}");
+            assert_eq!(reading.progress_dot_count, spec.number, "{spec:?}");
+            assert_eq!(
+                (reading.row_count, reading.column_count),
+                (spec.row_count, spec.column_count),
+                "{spec:?}"
+            );
+            let sprites: Vec<_> = reading.sprites.iter().map(|&(sprite, _)| sprite).collect();
+            assert_eq!(sprites, spec.sprites, "{spec:?}");
+            assert!(reading.confidence > 0.9, "{:?}", spec);
+        }
+    }
+}